@@ -1,23 +1,71 @@
 #![forbid(unsafe_code)]
 use itertools::Itertools;
+use std::collections::HashMap;
 use structopt::StructOpt;
 use thiserror::Error;
+use wwc_core::Date;
 use wwc_core::error::WwcError;
 use wwc_core::game::GameId;
-use wwc_core::group::{Group, GroupId};
+use wwc_core::group::order::RuleSet;
+use wwc_core::group::{Group, GroupId, Groups};
+use wwc_core::leaderboard::LeaderboardRules;
+use wwc_core::player::{PlayerId, PlayerPredictions, Prediction};
+use wwc_core::pred_score::scheme::ScoringConfig;
 use wwc_core::team::Team;
+use wwc_core::validation::PredStatus;
 use wwc_data::lsv::LsvParseError;
 use wwc_data::lsv::get_data;
-use wwc_data::lsv::{Fifa2018Data, LsvData};
-
-type Tournament = Fifa2018Data;
-const DATA_PATH: &str = "data/lsv_data/blank-fifa-2018.json";
+use wwc_data::lsv::{Euro2020Data, Fifa2018Data, LsvData, TeamSources};
+use wwc_db::{MigrationState, SqliteStore, TournamentId, TournamentScoring};
 
 #[tokio::main]
 async fn main() -> Result<(), CliError> {
+    let Cli {
+        tournament: tournament_name,
+        data,
+        cmd: opt,
+    } = Cli::from_args();
+    // Migrations are inspected before any is applied
+    if let Opt::Migrations(cmd) = opt {
+        let pool = wwc_db::connect_pool().await?;
+        return match cmd {
+            MigrationsCommand::Status => migration_status(&pool).await,
+            MigrationsCommand::Run => {
+                wwc_db::run_migrations(&pool).await?;
+                migration_status(&pool).await
+            }
+        };
+    }
     let pool = wwc_db::create_pool().await?;
-    let opt = Opt::from_args();
+    if let Opt::Add(table) = opt {
+        let source = match data {
+            Some(source) => source,
+            None => tournament_name.parse()?,
+        };
+        let tournament = wwc_db::insert_tournament(&pool, &tournament_name).await?;
+        wwc_db::set_group_rules(&pool, tournament, source.group_rules()).await?;
+        return match table {
+            Table::Players => Ok(()),
+            Table::Teams => add_teams(&pool, tournament, source).await,
+            Table::Games => add_games(&pool, tournament, source).await,
+            Table::GroupGameMaps => add_groups(&pool, tournament, source).await,
+            Table::PlayoffTeamSources => add_playoff_team_sources(&pool, tournament, source).await,
+            Table::ScoringConfig => add_scoring_config(&pool, tournament).await,
+            Table::All => {
+                add_teams(&pool, tournament, source).await?;
+                add_games(&pool, tournament, source).await?;
+                add_groups(&pool, tournament, source).await?;
+                add_playoff_team_sources(&pool, tournament, source).await?;
+                add_scoring_config(&pool, tournament).await
+            }
+        };
+    }
     match opt {
+        Opt::Tournaments => list_tournaments(&pool).await,
+        Opt::Check => check_consistency(&pool).await,
+        Opt::Leaderboard => print_leaderboard(&pool, &tournament_name).await,
+        Opt::Export { file } => export_snapshot(&pool, file).await,
+        Opt::Import { file, replace } => import_snapshot(&pool, file, replace).await,
         Opt::Register(new_instance) => match new_instance {
             Register::Player { name } => register_player(&pool, name).await,
         },
@@ -42,97 +90,290 @@ async fn main() -> Result<(), CliError> {
                 delete_bot(&pool, username, bot_name).await
             }
         },
-        Opt::Add(table) => match table {
-            Table::Players => Ok(()),
-            Table::Teams => add_teams(&pool).await,
-            Table::Games => add_games(&pool).await,
-            Table::GroupGameMaps => add_groups(&pool).await,
-            Table::PlayoffTeamSources => add_playoff_team_sources(&pool).await,
-            Table::All => {
-                add_teams(&pool).await?;
-                add_games(&pool).await?;
-                add_groups(&pool).await?;
-                add_playoff_team_sources(&pool).await
+        Opt::Preds(cmd) => match cmd {
+            PredsCommand::Import {
+                player_id,
+                file,
+                bot,
+            } => {
+                let tournament = tournament_id(&pool, &tournament_name).await?;
+                import_preds(&pool, tournament, player_id, file, bot).await
             }
         },
-        Opt::List(table) => match table {
-            Table::Players => list_players(&pool).await,
-            Table::Teams => list_teams(&pool).await,
-            Table::Games => list_games(&pool).await,
-            Table::GroupGameMaps => list_group_maps(&pool).await,
-            Table::PlayoffTeamSources => list_team_sources(&pool).await,
-            Table::All => {
-                list_players(&pool).await?;
-                list_teams(&pool).await?;
-                list_games(&pool).await?;
-                list_group_maps(&pool).await?;
-                list_team_sources(&pool).await
+        Opt::Migrations(_) | Opt::Add(_) => Ok(()),
+        Opt::List(Table::Players) => list_players(&pool).await,
+        Opt::List(table) => {
+            let tournament = tournament_id(&pool, &tournament_name).await?;
+            match table {
+                Table::Players => Ok(()),
+                Table::Teams => list_teams(&pool, tournament).await,
+                Table::Games => list_games(&pool, tournament).await,
+                Table::GroupGameMaps => list_group_maps(&pool, tournament).await,
+                Table::PlayoffTeamSources => list_team_sources(&pool, tournament).await,
+                Table::ScoringConfig => list_scoring_config(&pool, tournament).await,
+                Table::All => {
+                    list_players(&pool).await?;
+                    list_teams(&pool, tournament).await?;
+                    list_games(&pool, tournament).await?;
+                    list_group_maps(&pool, tournament).await?;
+                    list_team_sources(&pool, tournament).await?;
+                    list_scoring_config(&pool, tournament).await
+                }
             }
-        },
-        Opt::Clear(table) => match table {
-            Table::Players => Ok(wwc_db::clear_players(&pool).await?),
-            Table::Teams => Ok(wwc_db::clear_teams(&pool).await?),
-            Table::Games => Ok(wwc_db::clear_games(&pool).await?),
-            Table::GroupGameMaps => Ok(wwc_db::clear_group_game_maps(&pool).await?),
-            Table::PlayoffTeamSources => Ok(wwc_db::clear_playoff_team_sources(&pool).await?),
-            Table::All => {
-                // Clear child tables first to avoid foreign key constraints
-                wwc_db::clear_preds(&pool).await?;
-                wwc_db::clear_group_game_maps(&pool).await?;
-                wwc_db::clear_playoff_team_sources(&pool).await?;
-                wwc_db::clear_playoff_games(&pool).await?;
-                wwc_db::clear_games(&pool).await?;
-                wwc_db::clear_teams(&pool).await?;
-                Ok(wwc_db::clear_players(&pool).await?)
+        }
+        // Players take part in every tournament
+        Opt::Clear(Table::Players) => Ok(wwc_db::clear_players(&pool).await?),
+        Opt::Clear(table) => {
+            let tournament = tournament_id(&pool, &tournament_name).await?;
+            match table {
+                Table::Players => Ok(()),
+                Table::Teams => Ok(wwc_db::clear_teams(&pool, tournament).await?),
+                Table::Games => Ok(wwc_db::clear_games(&pool, tournament).await?),
+                Table::GroupGameMaps => Ok(wwc_db::clear_group_game_maps(&pool, tournament).await?),
+                Table::PlayoffTeamSources => {
+                    Ok(wwc_db::clear_playoff_team_sources(&pool, tournament).await?)
+                }
+                Table::ScoringConfig => Ok(wwc_db::clear_scoring_config(&pool, tournament).await?),
+                Table::All => Ok(wwc_db::clear_tournament_data(&pool, tournament).await?),
             }
-        },
+        }
+    }
+}
+
+async fn tournament_id(pool: &sqlx::SqlitePool, name: &str) -> Result<TournamentId, CliError> {
+    wwc_db::get_tournament_id(pool, name)
+        .await?
+        .ok_or_else(|| CliError::UnknownTournament(name.to_string()))
+}
+
+async fn list_tournaments(pool: &sqlx::SqlitePool) -> Result<(), CliError> {
+    println!("TOURNAMENTS:");
+    for tournament in wwc_db::get_tournaments(pool).await? {
+        println!("{}: {}", tournament.id, tournament.name);
+    }
+    println!();
+    Ok(())
+}
+
+async fn check_consistency(pool: &sqlx::SqlitePool) -> Result<(), CliError> {
+    let corrupt = wwc_db::check_consistency(pool).await?;
+    for row in &corrupt {
+        println!("{}: {}", row.tournament.name, row.error);
+    }
+    println!("{} corrupt rows", corrupt.len());
+    Ok(())
+}
+
+/// Tournament data which can be imported with `add`
+#[derive(Debug, Clone, Copy)]
+pub enum DataSource {
+    Fifa2018,
+    Euro2020,
+}
+
+impl std::str::FromStr for DataSource {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fifa-2018" => Ok(DataSource::Fifa2018),
+            "euro-2020" => Ok(DataSource::Euro2020),
+            _ => Err(CliError::UnknownData(s.to_string())),
+        }
+    }
+}
+
+impl DataSource {
+    fn group_rules(self) -> RuleSet {
+        match self {
+            DataSource::Fifa2018 => RuleSet::Fifa2018,
+            DataSource::Euro2020 => RuleSet::Euro2020,
+        }
     }
 }
 
+/// Teams, groups and playoff team sources of a tournament
+struct ImportData {
+    teams: Vec<Team>,
+    groups: Groups,
+    team_sources: TeamSources,
+}
+
+impl ImportData {
+    fn try_from_lsv<T: LsvData>(data_path: &str) -> Result<Self, CliError> {
+        let data = get_data::<T>(data_path)?;
+        Ok(ImportData {
+            teams: data.try_teams()?.values().cloned().collect(),
+            groups: data.try_groups()?,
+            team_sources: data.try_team_sources()?,
+        })
+    }
+
+    fn load(source: DataSource) -> Result<Self, CliError> {
+        match source {
+            DataSource::Fifa2018 => {
+                Self::try_from_lsv::<Fifa2018Data>("data/lsv_data/blank-fifa-2018.json")
+            }
+            DataSource::Euro2020 => {
+                Self::try_from_lsv::<Euro2020Data>("data/lsv_data/blank-euro-2020.json")
+            }
+        }
+    }
+}
+
+/// Print the leaderboard of a tournament, scored with its scoring config
+async fn print_leaderboard(pool: &sqlx::SqlitePool, name: &str) -> Result<(), CliError> {
+    let tournament = tournament_id(pool, name).await?;
+    let config = wwc_db::get_scoring_config(pool, tournament)
+        .await?
+        .ok_or_else(|| CliError::NoScoringConfig(name.to_string()))?;
+    let preds = wwc_db::get_all_competitor_preds(pool, tournament).await?;
+    let store = SqliteStore::new(pool.clone());
+    let scoring = TournamentScoring::load(&store, tournament, &config, preds).await?;
+    let leaderboard =
+        LeaderboardRules::from_config(&config).leaderboard(scoring.preds(), &scoring.truth());
+
+    let names: HashMap<(i32, Option<String>), String> = wwc_db::get_all_display_names(pool)
+        .await?
+        .into_iter()
+        .map(|(player_id, bot_name, name)| ((player_id, bot_name), name))
+        .collect();
+    println!("Leaderboard:");
+    for row in leaderboard.rows() {
+        let competitor = &row.competitor;
+        let key = (i32::from(competitor.player_id), competitor.bot_name.clone());
+        let name = names
+            .get(&key)
+            .cloned()
+            .unwrap_or_else(|| format!("Player {}", key.0));
+        println!(
+            "{:>3}. {:<24} {:>6} ({} exact, {} outcome)",
+            row.rank, name, row.total, row.exact_hits, row.outcome_hits
+        );
+    }
+    Ok(())
+}
+
 async fn register_player(pool: &sqlx::SqlitePool, name: String) -> Result<(), CliError> {
     Ok(wwc_db::register_player(pool, &name).await?)
 }
 
-async fn add_teams(pool: &sqlx::SqlitePool) -> Result<(), CliError> {
-    let teams = get_data::<Tournament>(DATA_PATH)?
-        .try_teams()?
-        .values()
-        .cloned()
-        .collect::<Vec<Team>>();
-    Ok(wwc_db::insert_teams(pool, &teams).await?)
+async fn export_snapshot(pool: &sqlx::SqlitePool, file: String) -> Result<(), CliError> {
+    let snapshot = wwc_db::export_snapshot(pool).await?;
+    std::fs::write(&file, serde_json::to_string_pretty(&snapshot)?)?;
+
+    let rows: usize = snapshot.row_counts().map(|(_, count)| count).sum();
+    println!(
+        "Exported {} rows of {} tables (schema version {}) to {}",
+        rows,
+        snapshot.tables.len(),
+        snapshot.schema_version,
+        file
+    );
+    Ok(())
 }
 
-async fn add_games(pool: &sqlx::SqlitePool) -> Result<(), CliError> {
+async fn import_snapshot(
+    pool: &sqlx::SqlitePool,
+    file: String,
+    replace: bool,
+) -> Result<(), CliError> {
+    let snapshot: wwc_db::Snapshot = serde_json::from_str(&std::fs::read_to_string(&file)?)?;
+    wwc_db::import_snapshot(pool, &snapshot, replace).await?;
+
+    snapshot
+        .row_counts()
+        .filter(|(_, count)| *count > 0)
+        .for_each(|(table, count)| println!("{}: {}", table, count));
+    println!("Imported snapshot from {} ({})", file, snapshot.exported_at);
+    Ok(())
+}
+
+async fn import_preds(
+    pool: &sqlx::SqlitePool,
+    tournament: TournamentId,
+    player_id: i32,
+    file: String,
+    bot: Option<String>,
+) -> Result<(), CliError> {
+    let preds: Vec<Prediction> = serde_json::from_str(&std::fs::read_to_string(file)?)?;
+    let player_preds = PlayerPredictions::new(PlayerId::from(player_id), preds);
+    let now = Date::from(chrono::Utc::now().fixed_offset());
+    let report =
+        wwc_db::insert_validated_preds(pool, tournament, &player_preds, bot.as_deref(), now)
+            .await?;
+
+    report
+        .checks
+        .iter()
+        .filter(|check| check.status != PredStatus::Accepted)
+        .for_each(|check| println!("{}: {:?}", check.pred, check.status));
+    println!(
+        "Imported {} of {} predictions for player {} (bot: {:?})",
+        report.accepted().count(),
+        report.checks.len(),
+        player_id,
+        bot
+    );
+    Ok(())
+}
+
+async fn add_teams(
+    pool: &sqlx::SqlitePool,
+    tournament: TournamentId,
+    source: DataSource,
+) -> Result<(), CliError> {
+    let teams = ImportData::load(source)?.teams;
+    Ok(wwc_db::insert_teams(pool, tournament, &teams).await?)
+}
+
+async fn add_games(
+    pool: &sqlx::SqlitePool,
+    tournament: TournamentId,
+    source: DataSource,
+) -> Result<(), CliError> {
+    let data = ImportData::load(source)?;
     // Add group games
-    let groups = get_data::<Tournament>(DATA_PATH)?
-        .try_groups()?
-        .values()
-        .cloned()
-        .collect::<Vec<Group>>();
+    let groups = data.groups.values().cloned().collect::<Vec<Group>>();
 
     let unplayed_games: Vec<_> = groups
         .iter()
         .flat_map(|group| group.unplayed_games())
         .cloned()
         .collect();
-    wwc_db::insert_unplayed_games(pool, &unplayed_games).await?;
+    wwc_db::insert_unplayed_games(pool, tournament, &unplayed_games).await?;
     let played_games: Vec<_> = groups
         .iter()
         .flat_map(|group| group.played_games())
         .cloned()
         .collect();
-    wwc_db::insert_played_games(pool, &played_games).await?;
+    wwc_db::insert_played_games(pool, tournament, &played_games).await?;
+
+    // Kickoff times lock the jokers
+    let kickoffs: Vec<_> = unplayed_games
+        .iter()
+        .map(|game| (game.id, game.date()))
+        .chain(played_games.iter().map(|game| (game.id, game.date())))
+        .collect();
+    wwc_db::insert_game_kickoffs(pool, tournament, &kickoffs).await?;
 
     // Add playoff game IDs (no teams/results yet - just the IDs)
     // The actual teams will be determined by BracketStructure from team_sources
-    let team_sources = get_data::<Tournament>(DATA_PATH)?.team_sources;
-    let playoff_game_ids: Vec<_> = team_sources.iter().map(|(game_id, _)| *game_id).collect();
-    wwc_db::insert_playoff_games(pool, &playoff_game_ids).await?;
+    let playoff_game_ids: Vec<_> = data
+        .team_sources
+        .iter()
+        .map(|(game_id, _)| *game_id)
+        .collect();
+    wwc_db::insert_playoff_games(pool, tournament, &playoff_game_ids).await?;
     Ok(())
 }
 
-async fn add_groups(pool: &sqlx::SqlitePool) -> Result<(), CliError> {
-    let groups = get_data::<Tournament>(DATA_PATH)?.try_groups()?;
+async fn add_groups(
+    pool: &sqlx::SqlitePool,
+    tournament: TournamentId,
+    source: DataSource,
+) -> Result<(), CliError> {
+    let groups = ImportData::load(source)?.groups;
 
     let group_games: Vec<(GroupId, GameId)> = groups
         .iter()
@@ -143,16 +384,28 @@ async fn add_groups(pool: &sqlx::SqlitePool) -> Result<(), CliError> {
                 .chain(group.unplayed_games().map(move |game| (*id, game.id)))
         })
         .collect();
-    wwc_db::insert_group_game_mappings(pool, &group_games).await?;
+    wwc_db::insert_group_game_mappings(pool, tournament, &group_games).await?;
     Ok(())
 }
 
-async fn add_playoff_team_sources(pool: &sqlx::SqlitePool) -> Result<(), CliError> {
-    let team_sources = get_data::<Tournament>(DATA_PATH)?.team_sources;
-    wwc_db::insert_playoff_team_sources(pool, &team_sources).await?;
+async fn add_playoff_team_sources(
+    pool: &sqlx::SqlitePool,
+    tournament: TournamentId,
+    source: DataSource,
+) -> Result<(), CliError> {
+    let team_sources = ImportData::load(source)?.team_sources;
+    wwc_db::insert_playoff_team_sources(pool, tournament, &team_sources).await?;
     Ok(())
 }
 
+/// Add the default (Kicktipp 4/3/2) scoring config for the tournament
+async fn add_scoring_config(
+    pool: &sqlx::SqlitePool,
+    tournament: TournamentId,
+) -> Result<(), CliError> {
+    Ok(wwc_db::insert_scoring_config(pool, tournament, &ScoringConfig::default()).await?)
+}
+
 async fn list_players(pool: &sqlx::SqlitePool) -> Result<(), CliError> {
     println!("PLAYERS:");
     let players = wwc_db::get_players(pool).await?;
@@ -348,24 +601,27 @@ fn generate_jwt_token(player_id: i32, bot_name: Option<String>) -> String {
     .expect("Failed to generate JWT token")
 }
 
-async fn list_teams(pool: &sqlx::SqlitePool) -> Result<(), CliError> {
-    let teams = wwc_db::get_teams(pool).await?;
+async fn list_teams(pool: &sqlx::SqlitePool, tournament: TournamentId) -> Result<(), CliError> {
+    let teams = wwc_db::get_teams(pool, tournament).await?;
     println!("TEAMS:");
     teams.iter().for_each(|team| println!("{}", team));
     println!();
     Ok(())
 }
 
-async fn list_games(pool: &sqlx::SqlitePool) -> Result<(), CliError> {
-    let games = wwc_db::get_games(pool).await?;
+async fn list_games(pool: &sqlx::SqlitePool, tournament: TournamentId) -> Result<(), CliError> {
+    let games = wwc_db::get_games(pool, tournament).await?;
     println!("GAMES:");
     games.iter().for_each(|game| println!("{:?}", game));
     println!();
     Ok(())
 }
 
-async fn list_group_maps(pool: &sqlx::SqlitePool) -> Result<(), CliError> {
-    let group_game_maps = wwc_db::get_group_game_maps(pool).await?;
+async fn list_group_maps(
+    pool: &sqlx::SqlitePool,
+    tournament: TournamentId,
+) -> Result<(), CliError> {
+    let group_game_maps = wwc_db::get_group_game_maps(pool, tournament).await?;
     println!("Group: Game mapping:");
     group_game_maps
         .into_iter()
@@ -384,8 +640,42 @@ async fn list_group_maps(pool: &sqlx::SqlitePool) -> Result<(), CliError> {
     Ok(())
 }
 
-async fn list_team_sources(pool: &sqlx::SqlitePool) -> Result<(), CliError> {
-    let team_sources = wwc_db::get_playoff_team_sources(pool).await?;
+async fn list_scoring_config(
+    pool: &sqlx::SqlitePool,
+    tournament: TournamentId,
+) -> Result<(), CliError> {
+    println!("Scoring config:");
+    match wwc_db::get_scoring_config(pool, tournament).await? {
+        Some(config) => println!("{:?}", config),
+        None => println!("Not set"),
+    }
+    println!();
+    Ok(())
+}
+
+async fn migration_status(pool: &sqlx::SqlitePool) -> Result<(), CliError> {
+    println!("Migrations:");
+    for status in wwc_db::migration_status(pool).await? {
+        let state = match status.state {
+            MigrationState::Applied { applied_at } => format!("applied {}", applied_at),
+            MigrationState::Pending => "pending".to_string(),
+            MigrationState::ChecksumMismatch { applied_at } => {
+                format!("CHANGED since applied {}", applied_at)
+            }
+            MigrationState::Unknown { applied_at } => {
+                format!("UNKNOWN to this version, applied {}", applied_at)
+            }
+        };
+        println!("{:03} {}: {}", status.version, status.name, state);
+    }
+    Ok(())
+}
+
+async fn list_team_sources(
+    pool: &sqlx::SqlitePool,
+    tournament: TournamentId,
+) -> Result<(), CliError> {
+    let team_sources = wwc_db::get_playoff_team_sources(pool, tournament).await?;
     println!("Team sources");
     team_sources
         .into_iter()
@@ -397,7 +687,39 @@ async fn list_team_sources(pool: &sqlx::SqlitePool) -> Result<(), CliError> {
 
 #[derive(Debug, StructOpt)]
 #[structopt(name = "wwc-cli", about = "cli usage")]
+pub struct Cli {
+    /// Tournament to work on, `add` creates it if it doesn't exist
+    #[structopt(long, global = true, default_value = "fifa-2018")]
+    tournament: String,
+    /// Data imported by `add`: fifa-2018 or euro-2020, defaults to the tournament name
+    #[structopt(long, global = true)]
+    data: Option<DataSource>,
+    #[structopt(subcommand)]
+    cmd: Opt,
+}
+
+#[derive(Debug, StructOpt)]
 pub enum Opt {
+    /// List all tournaments
+    #[structopt(name = "tournaments")]
+    Tournaments,
+    /// Report all rows of the database which do not decode
+    #[structopt(name = "check")]
+    Check,
+    /// Rank all competitors, scored like the server's leaderboard
+    #[structopt(name = "leaderboard")]
+    Leaderboard,
+    /// Export the whole database to a JSON snapshot
+    #[structopt(name = "export")]
+    Export { file: String },
+    /// Import a JSON snapshot into an empty database
+    #[structopt(name = "import")]
+    Import {
+        file: String,
+        /// Delete all existing data first
+        #[structopt(long)]
+        replace: bool,
+    },
     #[structopt(name = "register")]
     Register(Register),
     #[structopt(name = "add")]
@@ -410,6 +732,10 @@ pub enum Opt {
     User(UserCommand),
     #[structopt(name = "bot")]
     Bot(BotCommand),
+    #[structopt(name = "preds")]
+    Preds(PredsCommand),
+    #[structopt(name = "migrations")]
+    Migrations(MigrationsCommand),
 }
 
 #[derive(Debug, StructOpt)]
@@ -432,6 +758,8 @@ pub enum Table {
     GroupGameMaps,
     #[structopt(name = "playoff-team-sources")]
     PlayoffTeamSources,
+    #[structopt(name = "scoring-config")]
+    ScoringConfig,
     #[structopt(name = "all")]
     All,
 }
@@ -467,6 +795,30 @@ pub enum BotCommand {
     Delete { username: String, bot_name: String },
 }
 
+#[derive(Debug, StructOpt)]
+#[structopt(name = "preds", about = "Prediction commands")]
+pub enum PredsCommand {
+    /// Import group predictions from a JSON file, validated like the server does
+    #[structopt(name = "import")]
+    Import {
+        player_id: i32,
+        file: String,
+        #[structopt(long)]
+        bot: Option<String>,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "migrations", about = "Schema migration commands")]
+pub enum MigrationsCommand {
+    /// Show applied, pending and changed migrations without applying any
+    #[structopt(name = "status")]
+    Status,
+    /// Apply pending migrations
+    #[structopt(name = "run")]
+    Run,
+}
+
 #[derive(Error, Debug)]
 pub enum CliError {
     #[error("Cli: {0}")]
@@ -475,4 +827,14 @@ pub enum CliError {
     WwcCore(#[from] WwcError),
     #[error("Parse: {0}")]
     Parse(#[from] LsvParseError),
+    #[error("Io: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Json: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("No scoring config for '{0}', add it first")]
+    NoScoringConfig(String),
+    #[error("No tournament '{0}', add it first")]
+    UnknownTournament(String),
+    #[error("No data for '{0}', choose fifa-2018 or euro-2020 with --data")]
+    UnknownData(String),
 }
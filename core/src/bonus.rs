@@ -0,0 +1,345 @@
+//! Bonus questions
+//!
+//! Pre-tournament bets besides the game predictions, e.g. the champion, the top scorer or the
+//! total number of goals. Every [`BonusQuestion`] has a [`BonusKind`] which decides what an
+//! answer looks like and how it is scored, and a deadline after which answers are locked.
+//!
+//! A question is resolved by setting its solution, a list of accepted answers (several players
+//! can share the top scorer award). Unresolved questions give no points.
+//! Bonus points are part of the leaderboard total, as the
+//! [`Stage::Bonus`](crate::pred_score::scheme::Stage::Bonus) stage.
+use crate::Date;
+use crate::group::GroupId;
+use crate::pred_score::PredScore;
+use crate::team::TeamId;
+use derive_more::{Display, From, Into};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use thiserror::Error;
+
+/// Numeric bonus question id for db.
+#[derive(
+    Display,
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    From,
+    Into,
+    Serialize,
+    Deserialize,
+)]
+pub struct BonusQuestionId(u32);
+
+/// Type of a bonus question and its scoring
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BonusKind {
+    /// A single team, e.g. the tournament winner
+    TeamPick { points: f32 },
+    /// A single footballer, e.g. the top scorer. Names are compared case-insensitively.
+    PlayerPick { points: f32 },
+    /// A number, e.g. total tournament goals
+    ///
+    /// The exact number gives full points, decreasing linearly to zero at `tolerance + 1` off.
+    Numeric { points: f32, tolerance: u16 },
+    /// The final order of a group, `points` per team in the correct position
+    GroupOrder { group: GroupId, points: f32 },
+}
+
+/// An answer to a bonus question, or an accepted answer in a solution
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum BonusAnswer {
+    Team(TeamId),
+    Player(String),
+    Number(u32),
+    /// Best team first
+    TeamOrder(Vec<TeamId>),
+}
+
+/// A bonus question
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BonusQuestion {
+    pub id: BonusQuestionId,
+    pub text: String,
+    pub kind: BonusKind,
+    /// Answers are locked after this
+    pub deadline: Date,
+    /// Accepted answers, `None` until the question is resolved
+    pub solution: Option<Vec<BonusAnswer>>,
+}
+
+impl BonusQuestion {
+    pub fn new(
+        id: BonusQuestionId,
+        text: impl Into<String>,
+        kind: BonusKind,
+        deadline: Date,
+    ) -> Self {
+        BonusQuestion {
+            id,
+            text: text.into(),
+            kind,
+            deadline,
+            solution: None,
+        }
+    }
+
+    pub fn is_open(&self, now: Date) -> bool {
+        now < self.deadline
+    }
+
+    pub fn is_resolved(&self) -> bool {
+        self.solution.is_some()
+    }
+
+    /// Check that an answer has the right type for the question
+    pub fn validate_answer(&self, answer: &BonusAnswer) -> Result<(), BonusError> {
+        match (&self.kind, answer) {
+            (BonusKind::TeamPick { .. }, BonusAnswer::Team(_))
+            | (BonusKind::Numeric { .. }, BonusAnswer::Number(_)) => Ok(()),
+            (BonusKind::PlayerPick { .. }, BonusAnswer::Player(name)) => {
+                if name.trim().is_empty() {
+                    Err(BonusError::EmptyPlayerName)
+                } else {
+                    Ok(())
+                }
+            }
+            (BonusKind::GroupOrder { .. }, BonusAnswer::TeamOrder(order)) => {
+                let unique: HashSet<_> = order.iter().collect();
+                if order.is_empty() || unique.len() != order.len() {
+                    Err(BonusError::InvalidTeamOrder)
+                } else {
+                    Ok(())
+                }
+            }
+            _ => Err(BonusError::WrongAnswerType(self.id)),
+        }
+    }
+
+    /// Check an answer submitted at `now`
+    pub fn validate_submission(&self, answer: &BonusAnswer, now: Date) -> Result<(), BonusError> {
+        if !self.is_open(now) {
+            return Err(BonusError::DeadlinePassed(self.id));
+        }
+        self.validate_answer(answer)
+    }
+
+    /// Score of an answer, the best score over all accepted answers
+    ///
+    /// Unresolved questions and answers of the wrong type give no points.
+    pub fn score(&self, answer: &BonusAnswer) -> PredScore {
+        let best = self
+            .solution
+            .iter()
+            .flatten()
+            .map(|truth| self.kind.score(answer, truth))
+            .fold(0.0, f32::max);
+        PredScore::from(best)
+    }
+}
+
+impl BonusKind {
+    fn score(&self, answer: &BonusAnswer, truth: &BonusAnswer) -> f32 {
+        match (self, answer, truth) {
+            (
+                BonusKind::TeamPick { points },
+                BonusAnswer::Team(answer),
+                BonusAnswer::Team(truth),
+            ) => (answer == truth) as u8 as f32 * points,
+            (
+                BonusKind::PlayerPick { points },
+                BonusAnswer::Player(answer),
+                BonusAnswer::Player(truth),
+            ) => {
+                (answer.trim().to_lowercase() == truth.trim().to_lowercase()) as u8 as f32 * points
+            }
+            (
+                BonusKind::Numeric { points, tolerance },
+                BonusAnswer::Number(answer),
+                BonusAnswer::Number(truth),
+            ) => match u16::try_from(answer.abs_diff(*truth)) {
+                Ok(off) if off <= *tolerance => {
+                    points * (1.0 - f32::from(off) / (f32::from(*tolerance) + 1.0))
+                }
+                _ => 0.0,
+            },
+            (
+                BonusKind::GroupOrder { points, .. },
+                BonusAnswer::TeamOrder(answer),
+                BonusAnswer::TeamOrder(truth),
+            ) => {
+                let correct = answer.iter().zip(truth).filter(|(a, t)| a == t).count();
+                f32::from(u8::try_from(correct).unwrap_or(u8::MAX)) * points
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+/// A competitor's answer to a bonus question
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BonusPrediction(pub BonusQuestionId, pub BonusAnswer);
+
+/// Total bonus score of a competitor's answers
+pub fn bonus_score(questions: &[BonusQuestion], preds: &[BonusPrediction]) -> PredScore {
+    preds
+        .iter()
+        .filter_map(|BonusPrediction(id, answer)| {
+            questions
+                .iter()
+                .find(|question| question.id == *id)
+                .map(|question| question.score(answer))
+        })
+        .sum()
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BonusError {
+    #[error("Wrong answer type for bonus question {0}")]
+    WrongAnswerType(BonusQuestionId),
+    #[error("Deadline of bonus question {0} has passed")]
+    DeadlinePassed(BonusQuestionId),
+    #[error("No bonus question with id {0}")]
+    UnknownQuestion(BonusQuestionId),
+    #[error("Player name must not be empty")]
+    EmptyPlayerName,
+    #[error("Team order must be non-empty and without duplicates")]
+    InvalidTeamOrder,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    fn question(id: u32, kind: BonusKind, solution: Vec<BonusAnswer>) -> BonusQuestion {
+        BonusQuestion {
+            solution: Some(solution),
+            ..BonusQuestion::new(BonusQuestionId::from(id), "", kind, Date::mock())
+        }
+    }
+
+    fn points(question: &BonusQuestion, answer: BonusAnswer) -> f32 {
+        question.score(&answer).into()
+    }
+
+    #[test]
+    fn team_and_player_picks() {
+        let champion = question(
+            1,
+            BonusKind::TeamPick { points: 10.0 },
+            vec![BonusAnswer::Team(TeamId(3))],
+        );
+        assert_approx_eq!(points(&champion, BonusAnswer::Team(TeamId(3))), 10.0);
+        assert_approx_eq!(points(&champion, BonusAnswer::Team(TeamId(1))), 0.0);
+
+        // Shared top scorer award
+        let top_scorer = question(
+            2,
+            BonusKind::PlayerPick { points: 5.0 },
+            vec![
+                BonusAnswer::Player("Harry Kane".into()),
+                BonusAnswer::Player("Romelu Lukaku".into()),
+            ],
+        );
+        assert_approx_eq!(
+            points(&top_scorer, BonusAnswer::Player(" romelu lukaku".into())),
+            5.0
+        );
+        assert_approx_eq!(points(&top_scorer, BonusAnswer::Player("Kane".into())), 0.0);
+    }
+
+    #[test]
+    fn numeric_closeness() {
+        let goals = question(
+            1,
+            BonusKind::Numeric {
+                points: 8.0,
+                tolerance: 3,
+            },
+            vec![BonusAnswer::Number(169)],
+        );
+        assert_approx_eq!(points(&goals, BonusAnswer::Number(169)), 8.0);
+        assert_approx_eq!(points(&goals, BonusAnswer::Number(171)), 4.0);
+        assert_approx_eq!(points(&goals, BonusAnswer::Number(166)), 2.0);
+        assert_approx_eq!(points(&goals, BonusAnswer::Number(165)), 0.0);
+    }
+
+    #[test]
+    fn group_order() {
+        let group_a = question(
+            1,
+            BonusKind::GroupOrder {
+                group: GroupId::try_from('A').unwrap(),
+                points: 1.0,
+            },
+            vec![BonusAnswer::TeamOrder(vec![
+                TeamId(1),
+                TeamId(2),
+                TeamId(3),
+                TeamId(4),
+            ])],
+        );
+        let answer = BonusAnswer::TeamOrder(vec![TeamId(1), TeamId(3), TeamId(2), TeamId(4)]);
+        assert_approx_eq!(points(&group_a, answer), 2.0);
+    }
+
+    #[test]
+    fn validation() {
+        let mut champion = BonusQuestion::new(
+            BonusQuestionId::from(1),
+            "Who wins the tournament?",
+            BonusKind::TeamPick { points: 10.0 },
+            Date::mock(),
+        );
+        assert_approx_eq!(points(&champion, BonusAnswer::Team(TeamId(3))), 0.0);
+        assert_eq!(
+            champion.validate_answer(&BonusAnswer::Number(3)),
+            Err(BonusError::WrongAnswerType(BonusQuestionId::from(1)))
+        );
+        assert_eq!(
+            champion.validate_submission(&BonusAnswer::Team(TeamId(3)), Date::mock()),
+            Err(BonusError::DeadlinePassed(BonusQuestionId::from(1)))
+        );
+        champion.kind = BonusKind::GroupOrder {
+            group: GroupId::try_from('A').unwrap(),
+            points: 1.0,
+        };
+        assert_eq!(
+            champion.validate_answer(&BonusAnswer::TeamOrder(vec![TeamId(1), TeamId(1)])),
+            Err(BonusError::InvalidTeamOrder)
+        );
+    }
+
+    #[test]
+    fn total_score() {
+        let questions = vec![
+            question(
+                1,
+                BonusKind::TeamPick { points: 10.0 },
+                vec![BonusAnswer::Team(TeamId(3))],
+            ),
+            question(
+                2,
+                BonusKind::PlayerPick { points: 5.0 },
+                vec![BonusAnswer::Player("Kane".into())],
+            ),
+        ];
+        let preds = vec![
+            BonusPrediction(BonusQuestionId::from(1), BonusAnswer::Team(TeamId(3))),
+            BonusPrediction(
+                BonusQuestionId::from(2),
+                BonusAnswer::Player("Messi".into()),
+            ),
+            // Unknown question
+            BonusPrediction(BonusQuestionId::from(3), BonusAnswer::Number(3)),
+        ];
+        assert_approx_eq!(f32::from(bonus_score(&questions, &preds)), 10.0);
+    }
+}
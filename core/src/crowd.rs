@@ -0,0 +1,180 @@
+//! Crowd consensus
+//!
+//! "The Crowd" is a virtual competitor whose prediction for a game is aggregated from all
+//! predictions of that game, see [`CrowdMethod`] for the available aggregations. It serves as a
+//! baseline on the leaderboard, see
+//! [`LeaderboardRules::with_crowd`](crate::leaderboard::LeaderboardRules::with_crowd).
+//!
+//! The crowd only predicts group games, and a game is only predicted if at least one competitor
+//! has predicted it.
+use crate::game::{GameId, GoalCount};
+use crate::group::game::{GroupGameOutcome, GroupGameScore};
+use crate::player::Prediction;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Display name of the crowd
+pub const CROWD_NAME: &str = "The Crowd";
+
+/// How the predictions of a game are aggregated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrowdMethod {
+    /// The most common scoreline
+    #[default]
+    ModalScore,
+    /// The median home goals and the median away goals, each taken separately
+    MedianGoals,
+    /// The most common outcome, with its most common scoreline
+    OutcomeMajority,
+}
+
+impl CrowdMethod {
+    /// Aggregate the predicted scores of a single game, `None` if there are none
+    pub fn aggregate(&self, scores: &[GroupGameScore]) -> Option<GroupGameScore> {
+        match self {
+            CrowdMethod::ModalScore => modal_score(scores.iter()),
+            CrowdMethod::MedianGoals => {
+                let home = median(scores.iter().map(|score| score.home))?;
+                let away = median(scores.iter().map(|score| score.away))?;
+                Some(GroupGameScore::new(home, away))
+            }
+            CrowdMethod::OutcomeMajority => {
+                let count = |outcome| {
+                    scores
+                        .iter()
+                        .filter(|score| score.home_outcome() == outcome)
+                        .count()
+                };
+                // Ties between outcomes go to the draw, then to the home win
+                let outcome = [
+                    GroupGameOutcome::Draw,
+                    GroupGameOutcome::Win,
+                    GroupGameOutcome::Lose,
+                ]
+                .into_iter()
+                .rev()
+                .max_by_key(|outcome| count(*outcome))?;
+                modal_score(
+                    scores
+                        .iter()
+                        .filter(|score| score.home_outcome() == outcome),
+                )
+            }
+        }
+    }
+}
+
+/// Most common score, ties go to the lower scoreline (fewer goals, then fewer home goals)
+fn modal_score<'a>(scores: impl Iterator<Item = &'a GroupGameScore>) -> Option<GroupGameScore> {
+    let mut counts: BTreeMap<(GoalCount, GoalCount, GoalCount), usize> = BTreeMap::new();
+    for score in scores {
+        *counts
+            .entry((score.home + score.away, score.home, score.away))
+            .or_default() += 1;
+    }
+    // `max_by_key` returns the last maximum, iterate from the highest scoreline
+    counts
+        .into_iter()
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map(|((_, home, away), _)| GroupGameScore::new(home, away))
+}
+
+/// Lower median
+fn median(goals: impl Iterator<Item = GoalCount>) -> Option<GoalCount> {
+    let mut goals: Vec<GoalCount> = goals.collect();
+    goals.sort();
+    let mid = goals.len().checked_sub(1)? / 2;
+    goals.get(mid).copied()
+}
+
+/// The crowd's predictions, aggregated per game from all given predictions, ordered by game id
+pub fn crowd_predictions<'a>(
+    preds: impl IntoIterator<Item = &'a Prediction>,
+    method: CrowdMethod,
+) -> Vec<Prediction> {
+    let mut per_game: BTreeMap<GameId, Vec<GroupGameScore>> = BTreeMap::new();
+    for Prediction(game_id, score) in preds {
+        per_game.entry(*game_id).or_default().push(*score);
+    }
+    per_game
+        .into_iter()
+        .filter_map(|(game_id, scores)| {
+            method
+                .aggregate(&scores)
+                .map(|score| Prediction(game_id, score))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(home: u32, away: u32) -> GroupGameScore {
+        GroupGameScore::new(
+            GoalCount::try_from(home).unwrap(),
+            GoalCount::try_from(away).unwrap(),
+        )
+    }
+
+    fn scores(scores: &[(u32, u32)]) -> Vec<GroupGameScore> {
+        scores
+            .iter()
+            .map(|(home, away)| score(*home, *away))
+            .collect()
+    }
+
+    #[test]
+    fn aggregations() {
+        let preds = scores(&[(2, 1), (2, 1), (0, 0), (0, 1), (1, 2), (3, 2)]);
+        assert_eq!(CrowdMethod::ModalScore.aggregate(&preds), Some(score(2, 1)));
+        // Home goals 0 0 1 2 2 3, away goals 0 1 1 1 2 2
+        assert_eq!(
+            CrowdMethod::MedianGoals.aggregate(&preds),
+            Some(score(1, 1))
+        );
+        assert_eq!(
+            CrowdMethod::OutcomeMajority.aggregate(&preds),
+            Some(score(2, 1))
+        );
+
+        // Two away wins with different scores beat a single home win
+        let preds = scores(&[(0, 1), (1, 3), (2, 0)]);
+        assert_eq!(CrowdMethod::ModalScore.aggregate(&preds), Some(score(0, 1)));
+        assert_eq!(
+            CrowdMethod::OutcomeMajority.aggregate(&preds),
+            Some(score(0, 1))
+        );
+        assert_eq!(CrowdMethod::ModalScore.aggregate(&[]), None);
+    }
+
+    #[test]
+    fn ties() {
+        // Modal tie goes to the lower scoreline
+        let preds = scores(&[(3, 1), (1, 0), (3, 1), (1, 0)]);
+        assert_eq!(CrowdMethod::ModalScore.aggregate(&preds), Some(score(1, 0)));
+        // Outcome tie goes to the draw
+        let preds = scores(&[(2, 0), (1, 1), (0, 2)]);
+        assert_eq!(
+            CrowdMethod::OutcomeMajority.aggregate(&preds),
+            Some(score(1, 1))
+        );
+    }
+
+    #[test]
+    fn per_game() {
+        let preds = [
+            Prediction(GameId::from(2), score(1, 0)),
+            Prediction(GameId::from(1), score(0, 0)),
+            Prediction(GameId::from(2), score(1, 0)),
+            Prediction(GameId::from(2), score(2, 2)),
+        ];
+        let crowd = crowd_predictions(&preds, CrowdMethod::ModalScore);
+        assert_eq!(crowd.len(), 2);
+        assert_eq!(crowd[0].0, GameId::from(1));
+        assert_eq!(crowd[1].0, GameId::from(2));
+        assert_eq!(crowd[1].1, score(1, 0));
+    }
+}
@@ -1,6 +1,10 @@
 //! Top-level error type for `wwc_core`
+use crate::bonus::BonusError;
 use crate::game::GoalCountError;
 use crate::group::GroupError;
+use crate::playoff::{BracketError, PlayoffError};
+use crate::pred_score::joker::JokerError;
+use crate::pred_score::proba::ProbaError;
 use crate::team::TeamError;
 use thiserror::Error;
 
@@ -13,4 +17,14 @@ pub enum WwcError {
     Team(#[from] TeamError),
     #[error("Goal count error: {0}")]
     GoalCount(#[from] GoalCountError),
+    #[error("Playoff error: {0}")]
+    Playoff(#[from] PlayoffError),
+    #[error("Bracket error: {0}")]
+    Bracket(#[from] BracketError),
+    #[error("Probability error: {0}")]
+    Proba(#[from] ProbaError),
+    #[error("Bonus error: {0}")]
+    Bonus(#[from] BonusError),
+    #[error("Joker error: {0}")]
+    Joker(#[from] JokerError),
 }
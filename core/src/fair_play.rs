@@ -9,7 +9,7 @@ use std::ops::Mul;
 /// Fair play data
 ///
 /// Represent the actual card count and is (barring huge changes) invariant under Fifa/Uefa/other rules changes.
-#[derive(Default, Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub struct FairPlay {
     yellow: CardCount,
     indirect_red: CardCount,
@@ -31,10 +31,28 @@ impl FairPlay {
             yellow_and_direct: yellow_and_direct.into(),
         }
     }
+
+    pub fn yellow(&self) -> CardCount {
+        self.yellow
+    }
+
+    /// Red cards from a second yellow card
+    pub fn indirect_red(&self) -> CardCount {
+        self.indirect_red
+    }
+
+    pub fn direct_red(&self) -> CardCount {
+        self.direct_red
+    }
+
+    /// Direct red cards to players who already had a yellow card
+    pub fn yellow_and_direct(&self) -> CardCount {
+        self.yellow_and_direct
+    }
 }
 
 /// Fair play outcome of a game.
-#[derive(Copy, Clone, Deserialize, Serialize, Debug, Default)]
+#[derive(Copy, Clone, Deserialize, Serialize, Debug, Default, PartialEq, Eq)]
 pub struct FairPlayScore {
     pub home: FairPlay,
     pub away: FairPlay,
@@ -163,6 +181,12 @@ impl num::Zero for UefaFairPlayValue {
 )]
 pub struct CardCount(u32);
 
+impl From<CardCount> for u32 {
+    fn from(count: CardCount) -> Self {
+        count.0
+    }
+}
+
 impl Mul<i32> for CardCount {
     type Output = i32;
     fn mul(self, rhs: i32) -> Self::Output {
@@ -52,6 +52,11 @@ impl UnplayedGroupGame {
         }
     }
 
+    /// Kickoff
+    pub fn date(&self) -> Date {
+        self.date
+    }
+
     /// Transform unplayed game to played.
     ///
     /// Only (public) way of constructing a [`PlayedGroupGame`].
@@ -127,6 +132,16 @@ impl PlayedGroupGame {
         }
     }
 
+    /// Kickoff
+    pub fn date(&self) -> Date {
+        self.date
+    }
+
+    /// Cards of the home and away team
+    pub fn fair_play(&self) -> FairPlayScore {
+        self.fair_play
+    }
+
     /// Transform played game to unplayed.
     pub fn unplay(self) -> UnplayedGroupGame {
         UnplayedGroupGame {
@@ -213,6 +228,7 @@ pub enum GroupGameOutcome {
 #[cfg(test)]
 mod group_game {
     use super::*;
+    use crate::fair_play::FairPlay;
     #[test]
     fn home_win() {
         let game = PlayedGroupGame::try_new(
@@ -269,4 +285,21 @@ mod group_game {
         assert_eq!(home, GroupPoint(1));
         assert_eq!(away, GroupPoint(1));
     }
+
+    #[test]
+    fn play_keeps_fair_play() {
+        let fair_play = FairPlayScore::new(FairPlay::new(2, 0, 1, 0), FairPlay::new(0, 1, 0, 0));
+        let game = UnplayedGroupGame::try_new(0, 0, 1, Date::mock())
+            .unwrap()
+            .play(
+                GroupGameScore::new(
+                    GoalCount::try_from(1).unwrap(),
+                    GoalCount::try_from(1).unwrap(),
+                ),
+                fair_play,
+            );
+        assert_eq!(game.fair_play(), fair_play);
+        assert_eq!(u32::from(game.fair_play().home.yellow()), 2);
+        assert_eq!(u32::from(game.fair_play().away.indirect_red()), 1);
+    }
 }
@@ -90,7 +90,8 @@ impl Group {
 
     pub fn play_game(&mut self, game_id: GameId, score: GroupGameScore) {
         let idx = self
-            .unplayed_games()
+            .unplayed_games
+            .iter()
             .position(|game| game.id == game_id)
             .unwrap_or_else(|| panic!("No game with id: {:?}", game_id));
         let game = self
@@ -396,6 +397,24 @@ mod tests {
     use crate::group::game::{GroupGameScore, UnplayedGroupGame};
     use crate::team::{TeamId, TeamName};
     use std::collections::HashSet;
+    #[test]
+    fn replay_game() {
+        let (mut groups, _) = mock_data::groups_and_teams();
+        let group = groups.get_mut(&GroupId('A')).unwrap();
+        let score = group.played_games().next().unwrap().score;
+        // Unplayed games are no longer sorted by id after this
+        group.unplay_game(GameId::from(1));
+        group.play_game(GameId::from(1), score);
+        assert_eq!(
+            group.played_games().map(|game| game.id).collect::<Vec<_>>(),
+            vec![GameId::from(1)]
+        );
+        assert_eq!(
+            group.unplayed_games().map(|game| game.id).collect::<Vec<_>>(),
+            vec![GameId::from(2)]
+        );
+    }
+
     #[test]
     fn mock_data_access() {
         let (_, mock_teams) = mock_data::groups_and_teams();
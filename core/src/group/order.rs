@@ -79,6 +79,19 @@ pub struct Rules<T: Tiebreaker> {
     tiebreaker: T,
 }
 
+impl<T: Tiebreaker> Rules<T> {
+    /// Same sub-orderings with another tiebreaker
+    ///
+    /// E.g. to rank predicted groups reproducibly, where the drawing of lots of the actual
+    /// tournament can't be known.
+    pub fn with_tiebreaker<U: Tiebreaker>(self, tiebreaker: U) -> Rules<U> {
+        Rules {
+            non_strict: self.non_strict,
+            tiebreaker,
+        }
+    }
+}
+
 /// Order group based on rules
 ///
 /// First orders by a list of non-strict sub-orders.
@@ -98,61 +111,60 @@ pub fn order_group<T: Tiebreaker>(group: &Group, rules: &Rules<T>) -> TeamOrder
     }
 }
 
-// /// Order general set of teams based on rules
-// ///
-// /// First orders by a list of non-strict sub-orders.
-// /// If the sub-order is not strict, the rules' tiebreaker is used.
-// pub(crate) fn order_teams<T: Tiebreaker>(
-//     teams: &HashMap<TeamId, &Group>,
-//     rules: &Rules<T>,
-// ) -> TeamOrder {
-//     let possibly_non_strict = non_strict_teams_ordering(
-//         teams,
-//         &rules.non_strict,
-//         // NonStrictOrder::init_from_teams(teams.keys().map(|id| *id)),
-//         NonStrictOrder::init_from_teams(teams.keys().cloned()),
-//     );
-//     // Does not panic since the unwrapping match arm is checked to be strict.
-//     #[allow(clippy::unwrap_used)]
-//     if possibly_non_strict.is_strict() {
-//         possibly_non_strict.try_into().unwrap()
-//     } else {
-//         rules.tiebreaker.order_teams(possibly_non_strict)
-//     }
-// }
-//
-// /// Try ordering teams across groups
-// ///
-// /// Returns the input group order if it is strict or if there are no more rules left to apply.
-// /// Otherwise recursively calls itself with the next rule.
-// fn non_strict_teams_ordering(
-//     teams: &HashMap<TeamId, &Group>,
-//     rules: &[Box<dyn SubOrdering>],
-//     sub_order: NonStrictOrder,
-// ) -> NonStrictOrder {
-//     if sub_order.is_strict() || rules.is_empty() {
-//         sub_order
-//     } else {
-//         let (current_rule, remaining_rules) = rules.split_at(1);
-//         // current_rule is always a vec with a single element,
-//         let current_rule = &current_rule[0];
-//         let sub_order = sub_order
-//             .into_iter()
-//             .fold(NonStrictOrder::empty(), |acc, x| {
-//                 // Don't apply rule if the sub-order is already strict,
-//                 // i.e. if x consists of a single TeamId
-//                 // TODO: benchmark, possible that the allocation in the else branch is more costly.
-//                 let new_order = if x.len() > 1 {
-//                     current_rule.order_teams(teams, x)
-//                 } else {
-//                     NonStrictOrder::single(x)
-//                 };
-//
-//                 acc.extend(new_order)
-//             });
-//         non_strict_teams_ordering(teams, remaining_rules, sub_order)
-//     }
-// }
+/// Order general set of teams based on rules
+///
+/// Orders teams from different groups, e.g. the third-placed teams, where each team is mapped to
+/// its group. The rules must only use stats over all group games, see [`SubOrdering::order_teams`].
+///
+/// First orders by a list of non-strict sub-orders.
+/// If the sub-order is not strict, the rules' tiebreaker is used.
+pub fn order_teams<T: Tiebreaker>(teams: &HashMap<TeamId, &Group>, rules: &Rules<T>) -> TeamOrder {
+    let possibly_non_strict = non_strict_teams_ordering(
+        teams,
+        &rules.non_strict,
+        NonStrictOrder::init_from_teams(teams.keys().cloned()),
+    );
+    // Does not panic since the unwrapping match arm is checked to be strict.
+    #[allow(clippy::unwrap_used)]
+    if possibly_non_strict.is_strict() {
+        possibly_non_strict.try_into().unwrap()
+    } else {
+        rules.tiebreaker.order_teams(possibly_non_strict)
+    }
+}
+
+/// Try ordering teams across groups
+///
+/// Returns the input group order if it is strict or if there are no more rules left to apply.
+/// Otherwise recursively calls itself with the next rule.
+fn non_strict_teams_ordering(
+    teams: &HashMap<TeamId, &Group>,
+    rules: &[Box<dyn SubOrdering>],
+    sub_order: NonStrictOrder,
+) -> NonStrictOrder {
+    if sub_order.is_strict() || rules.is_empty() {
+        sub_order
+    } else {
+        let (current_rule, remaining_rules) = rules.split_at(1);
+        // current_rule is always a vec with a single element,
+        let current_rule = &current_rule[0];
+        let sub_order = sub_order
+            .into_iter()
+            .fold(NonStrictOrder::empty(), |acc, x| {
+                // Don't apply rule if the sub-order is already strict,
+                // i.e. if x consists of a single TeamId
+                // TODO: benchmark, possible that the allocation in the else branch is more costly.
+                let new_order = if x.len() > 1 {
+                    current_rule.order_teams(teams, x)
+                } else {
+                    NonStrictOrder::single(x)
+                };
+
+                acc.extend(new_order)
+            });
+        non_strict_teams_ordering(teams, remaining_rules, sub_order)
+    }
+}
 
 /// Try ordering a group
 ///
@@ -272,9 +284,9 @@ impl NonStrictOrder {
         NonStrictOrder(vec![group.team_ids().collect()])
     }
 
-    // fn init_from_teams(teams: impl Iterator<Item = TeamId>) -> Self {
-    //     NonStrictOrder(vec![teams.collect()])
-    // }
+    fn init_from_teams(teams: impl Iterator<Item = TeamId>) -> Self {
+        NonStrictOrder(vec![teams.collect()])
+    }
 
     /// Strict ordering check
     ///
@@ -660,6 +672,37 @@ pub fn euro_2020_third_place_rules(ranking: UefaRanking) -> Rules<UefaRanking> {
     }
 }
 
+/// Group rules of a tournament format
+///
+/// Predicted groups can't know the drawing of lots or other tiebreakers decided outside the
+/// games, so the ranked rules break remaining ties by team rank. Both are deterministic.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RuleSet {
+    #[default]
+    Fifa2018,
+    Euro2020,
+}
+
+impl RuleSet {
+    /// Group rules, ties are broken by team rank
+    pub fn ranked_rules(self, ranking: UefaRanking) -> Rules<UefaRanking> {
+        match self {
+            RuleSet::Fifa2018 => fifa_2018_rules().with_tiebreaker(ranking),
+            RuleSet::Euro2020 => euro_2020_rules(ranking),
+        }
+    }
+
+    /// Rules to rank the third-placed teams of all groups, ties are broken by team rank
+    ///
+    /// Third-placed teams do not advance in the Fifa World Cup 2018, they are ranked like in the
+    /// Euro 2020 nonetheless.
+    pub fn ranked_third_place_rules(self, ranking: UefaRanking) -> Rules<UefaRanking> {
+        match self {
+            RuleSet::Fifa2018 | RuleSet::Euro2020 => euro_2020_third_place_rules(ranking),
+        }
+    }
+}
+
 #[cfg(test)]
 mod fifa_2018_ordering_tests {
     use super::*;
@@ -917,4 +960,45 @@ mod tiebreaker_test {
         let ranking = UefaRanking(ranking);
         assert_eq!(ranking.cmp(TeamId(0), TeamId(1)), Ordering::Greater);
     }
+
+    /// All games drawn 0-0, only the tiebreaker orders the teams
+    #[test]
+    fn ranked_rule_sets_are_deterministic() {
+        use crate::Date;
+        use crate::fair_play::FairPlayScore;
+        use crate::group::game::{GroupGameScore, PlayedGroupGame};
+        use crate::group::{Group, GroupId};
+
+        let pairs = [(0, 1), (2, 3), (0, 2), (1, 3), (0, 3), (1, 2)];
+        let games = (0_u32..)
+            .zip(pairs)
+            .map(|(id, (home, away))| {
+                let score = GroupGameScore::new(
+                    GoalCount::try_from(0).unwrap(),
+                    GoalCount::try_from(0).unwrap(),
+                );
+                PlayedGroupGame::try_new(
+                    id,
+                    home,
+                    away,
+                    score,
+                    FairPlayScore::default(),
+                    Date::mock(),
+                )
+                .unwrap()
+            })
+            .collect();
+        let group = Group::try_new(Vec::new(), games).unwrap();
+        let groups: Groups = [(GroupId::try_from('A').unwrap(), group.clone())].into();
+        // Team 3 has the best rank
+        let ranking: HashMap<TeamId, TeamRank> =
+            (0..4).map(|id| (TeamId(id), TeamRank(4 - id))).collect();
+        let ranking = UefaRanking::try_new(&groups, ranking).unwrap();
+
+        for rule_set in [RuleSet::Fifa2018, RuleSet::Euro2020] {
+            let rules = rule_set.ranked_rules(ranking.clone());
+            let order: Vec<TeamId> = order_group(&group, &rules).into_iter().collect();
+            assert_eq!(order, [3, 2, 1, 0].map(TeamId));
+        }
+    }
 }
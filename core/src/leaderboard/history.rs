@@ -0,0 +1,344 @@
+//! Leaderboard history
+//!
+//! Standings after each played game, in kickoff order, for rank-over-time charts and
+//! "biggest mover" stats. Every step is computed by replaying the played games one at a time.
+//!
+//! Group games are ordered by date, then game id. Playoff games have no date in the bracket state
+//! and follow after the group stage, ordered by their stored kickoff (see [`Truth::with_kickoffs`]),
+//! then game id.
+use crate::game::GameId;
+use crate::group::GroupId;
+use crate::group::game::GroupGameScore;
+use crate::leaderboard::{Competitor, CompetitorPredictions, LeaderboardRules, Truth};
+use crate::playoff::BracketState;
+use crate::pred_score::playoff::PlayoffPredScoreFn;
+use crate::pred_score::{PredScore, PredScoreFn};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A competitor's standing after a game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Standing {
+    pub competitor: Competitor,
+    pub rank: usize,
+    pub total: PredScore,
+}
+
+/// Standings after a single game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryStep {
+    /// The game played in this step
+    pub game_id: GameId,
+    /// Best first
+    pub standings: Vec<Standing>,
+}
+
+/// Rank change of a competitor in a single step
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Move {
+    pub competitor: Competitor,
+    pub game_id: GameId,
+    pub from_rank: usize,
+    pub to_rank: usize,
+}
+
+impl Move {
+    /// Number of places climbed (negative if dropped)
+    pub fn rank_change(&self) -> i64 {
+        i64::try_from(self.from_rank).unwrap_or(i64::MAX)
+            - i64::try_from(self.to_rank).unwrap_or(i64::MAX)
+    }
+}
+
+/// Standings after each played game, in kickoff order
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LeaderboardHistory {
+    steps: Vec<HistoryStep>,
+}
+
+impl LeaderboardHistory {
+    pub fn steps(&self) -> impl Iterator<Item = &HistoryStep> {
+        self.steps.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// A competitor's standing after each game
+    pub fn series<'a>(
+        &'a self,
+        competitor: &'a Competitor,
+    ) -> impl Iterator<Item = (GameId, &'a Standing)> + 'a {
+        self.steps.iter().filter_map(move |step| {
+            step.standings
+                .iter()
+                .find(|standing| &standing.competitor == competitor)
+                .map(|standing| (step.game_id, standing))
+        })
+    }
+
+    /// All rank changes between consecutive steps
+    pub fn moves(&self) -> impl Iterator<Item = Move> + '_ {
+        self.steps
+            .iter()
+            .tuple_windows()
+            .flat_map(|(previous, current)| {
+                current.standings.iter().filter_map(|standing| {
+                    let previous = previous
+                        .standings
+                        .iter()
+                        .find(|prev| prev.competitor == standing.competitor)?;
+                    (previous.rank != standing.rank).then(|| Move {
+                        competitor: standing.competitor.clone(),
+                        game_id: current.game_id,
+                        from_rank: previous.rank,
+                        to_rank: standing.rank,
+                    })
+                })
+            })
+    }
+
+    /// Largest climb in a single game, the earliest one if several are equal
+    pub fn biggest_climb(&self) -> Option<Move> {
+        self.moves()
+            .filter(|mv| mv.rank_change() > 0)
+            .min_by_key(|mv| -mv.rank_change())
+    }
+
+    /// Largest drop in a single game, the earliest one if several are equal
+    pub fn biggest_drop(&self) -> Option<Move> {
+        self.moves()
+            .filter(|mv| mv.rank_change() < 0)
+            .min_by_key(Move::rank_change)
+    }
+}
+
+/// Played games in kickoff order
+pub fn kickoff_order(truth: &Truth) -> Vec<GameId> {
+    let group_games = truth
+        .groups
+        .values()
+        .flat_map(|group| group.played_games())
+        .sorted_by_key(|game| (game.date, game.id))
+        .map(|game| game.id);
+    // Games without a stored kickoff follow the others
+    let kickoff = |game_id: &GameId| truth.kickoffs.and_then(|kickoffs| kickoffs.get(game_id));
+    let playoff_games = truth
+        .playoff
+        .iter()
+        .flat_map(|(_, state)| state.played_games().map(|(game_id, _)| *game_id))
+        .sorted_by_key(|game_id| {
+            (
+                kickoff(game_id).is_none(),
+                kickoff(game_id).copied(),
+                *game_id,
+            )
+        });
+    group_games.chain(playoff_games).collect()
+}
+
+impl<G: PredScoreFn, P: PlayoffPredScoreFn> LeaderboardRules<G, P> {
+    /// Compute the standings after each played game
+    pub fn history(&self, preds: &[CompetitorPredictions], truth: &Truth) -> LeaderboardHistory {
+        let group_scores: HashMap<GameId, (GroupId, GroupGameScore)> = truth
+            .groups
+            .iter()
+            .flat_map(|(group_id, group)| {
+                group
+                    .played_games()
+                    .map(move |game| (game.id, (*group_id, game.score)))
+            })
+            .collect();
+
+        // Replay from a state where no game is played
+        let mut groups = truth.groups.clone();
+        for (game_id, (group_id, _)) in &group_scores {
+            if let Some(group) = groups.get_mut(group_id) {
+                group.unplay_game(*game_id);
+            }
+        }
+        let mut playoff = BracketState::new();
+
+        let steps = kickoff_order(truth)
+            .into_iter()
+            .map(|game_id| {
+                if let Some((group_id, score)) = group_scores.get(&game_id) {
+                    if let Some(group) = groups.get_mut(group_id) {
+                        group.play_game(game_id, *score);
+                    }
+                } else if let Some(result) =
+                    truth.playoff.and_then(|(_, state)| state.result(game_id))
+                {
+                    playoff = playoff.play_game(game_id, result.home, result.away, result.score);
+                }
+                let step_truth = Truth {
+                    groups: &groups,
+                    playoff: truth.playoff.map(|(bracket, _)| (bracket, &playoff)),
+                    // Resolved bonus questions count from the start
+                    bonus: truth.bonus,
+                    // Standings count once a group is complete in the replayed groups
+                    standings: truth.standings,
+                    kickoffs: truth.kickoffs,
+                };
+                let standings = self
+                    .leaderboard(preds, &step_truth)
+                    .rows()
+                    .map(|row| Standing {
+                        competitor: row.competitor.clone(),
+                        rank: row.rank,
+                        total: row.total,
+                    })
+                    .collect();
+                HistoryStep { game_id, standings }
+            })
+            .collect();
+        LeaderboardHistory { steps }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Date;
+    use crate::game::GoalCount;
+    use crate::group::mock_data::groups_and_teams;
+    use crate::group::{GroupOutcome, Groups};
+    use crate::player::{PlayerId, Prediction};
+    use crate::playoff::{BracketStructure, PlayoffScore, TeamSource};
+    use crate::pred_score::SimplePredScoreFn;
+    use crate::pred_score::playoff::SimplePlayoffPredScoreFn;
+    use crate::team::TeamId;
+    use assert_approx_eq::assert_approx_eq;
+
+    fn score(home: u32, away: u32) -> GroupGameScore {
+        GroupGameScore::new(
+            GoalCount::try_from(home).unwrap(),
+            GoalCount::try_from(away).unwrap(),
+        )
+    }
+
+    /// Mock groups with games 1 (2-1), 3 (0-0) and 4 (1-3) played
+    fn groups() -> Groups {
+        let (mut groups, _) = groups_and_teams();
+        let group_b = groups.get_mut(&GroupId::try_from('B').unwrap()).unwrap();
+        group_b.play_game(GameId::from(3), score(0, 0));
+        group_b.play_game(GameId::from(4), score(1, 3));
+        groups
+    }
+
+    fn player(id: i32) -> Competitor {
+        Competitor::player(PlayerId::from(id))
+    }
+
+    fn preds(id: i32, scores: &[(u32, (u32, u32))]) -> CompetitorPredictions {
+        let group = scores
+            .iter()
+            .map(|(game_id, (home, away))| Prediction(GameId::from(*game_id), score(*home, *away)))
+            .collect();
+        CompetitorPredictions::new(player(id), group, BracketState::new())
+    }
+
+    fn history() -> LeaderboardHistory {
+        let groups = groups();
+        let all_preds = vec![
+            preds(1, &[(1, (2, 1)), (3, (1, 0)), (4, (1, 0))]),
+            preds(2, &[(1, (0, 1)), (3, (0, 0)), (4, (1, 3))]),
+        ];
+        LeaderboardRules::new(
+            SimplePredScoreFn::new(1.0, 1.0),
+            SimplePlayoffPredScoreFn::default(),
+        )
+        .history(&all_preds, &Truth::groups(&groups))
+    }
+
+    #[test]
+    fn standings_after_each_game() {
+        let history = history();
+        let game_ids: Vec<_> = history.steps().map(|step| step.game_id).collect();
+        assert_eq!(
+            game_ids,
+            vec![GameId::from(1), GameId::from(3), GameId::from(4)]
+        );
+
+        let series: Vec<_> = history
+            .series(&player(2))
+            .map(|(_, standing)| (standing.rank, f32::from(standing.total)))
+            .collect();
+        assert_eq!(
+            series.iter().map(|(rank, _)| *rank).collect::<Vec<_>>(),
+            vec![2, 1, 1]
+        );
+        assert_approx_eq!(series[2].1, 4.0);
+    }
+
+    #[test]
+    fn biggest_movers() {
+        let history = history();
+        let climb = history.biggest_climb().unwrap();
+        assert_eq!(climb.competitor, player(2));
+        assert_eq!(climb.game_id, GameId::from(3));
+        assert_eq!(climb.rank_change(), 1);
+
+        let drop = history.biggest_drop().unwrap();
+        assert_eq!(drop.competitor, player(1));
+        assert_eq!((drop.from_rank, drop.to_rank), (1, 2));
+        assert_eq!(history.moves().count(), 2);
+    }
+
+    #[test]
+    fn playoff_games_in_kickoff_order() {
+        let groups = groups();
+        let group = |id| GroupId::try_from(id).unwrap();
+        let sources = vec![
+            (
+                GameId::from(5),
+                (
+                    TeamSource::GroupOutcome(GroupOutcome::Winner(group('A'))),
+                    TeamSource::GroupOutcome(GroupOutcome::RunnerUp(group('B'))),
+                ),
+            ),
+            (
+                GameId::from(6),
+                (
+                    TeamSource::GroupOutcome(GroupOutcome::Winner(group('B'))),
+                    TeamSource::GroupOutcome(GroupOutcome::RunnerUp(group('A'))),
+                ),
+            ),
+            (
+                GameId::from(7),
+                (
+                    TeamSource::WinnerOf(GameId::from(5)),
+                    TeamSource::WinnerOf(GameId::from(6)),
+                ),
+            ),
+        ];
+        let bracket = BracketStructure::from_team_sources(&sources).unwrap();
+        let win = PlayoffScore::regular_time(
+            GoalCount::try_from(1).unwrap(),
+            GoalCount::try_from(0).unwrap(),
+        )
+        .unwrap();
+        let state = BracketState::new()
+            .play_game(GameId::from(5), TeamId(1), TeamId(6), win)
+            .play_game(GameId::from(6), TeamId(5), TeamId(2), win);
+        let truth = Truth::groups(&groups).with_playoff(&bracket, &state);
+        let order = |truth: &Truth| kickoff_order(truth).split_off(3);
+
+        // Without kickoffs, by game id
+        assert_eq!(order(&truth), [5_u32, 6].map(GameId::from));
+
+        let date = |rfc3339| Date::parse_rfc3339(rfc3339).unwrap();
+        let kickoffs = HashMap::from([
+            (GameId::from(5), date("2026-07-01T18:00:00+00:00")),
+            (GameId::from(6), date("2026-07-01T15:00:00+00:00")),
+        ]);
+        let truth = truth.with_kickoffs(&kickoffs);
+        assert_eq!(order(&truth), [6_u32, 5].map(GameId::from));
+    }
+}
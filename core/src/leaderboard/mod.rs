@@ -0,0 +1,922 @@
+//! Leaderboard
+//!
+//! Standings of all competitors (players and their bots), derived from their predictions and the
+//! actual results. Like the rest of the library the leaderboard is never stored, only computed,
+//! so the server, the CLI and the UI get identical standings from the same data.
+//!
+//! Every row contains the total, the points per stage (group stage, each playoff round and the
+//! [bonus questions](crate::bonus)) and a per-game breakdown. Competitors with equal points are separated by the configured
+//! [`Tiebreak`]s (by default exact hits, then outcome hits) and share a rank if still equal.
+//!
+//! Games picked as jokers have their points multiplied, see [`crate::pred_score::joker`].
+//! Optionally [the crowd](crate::crowd) is ranked as a baseline, and predicted group standings
+//! get bonus points, see [`crate::pred_score::standings`].
+//!
+//! The standings after each played game are available through [`history`].
+pub mod history;
+
+use crate::Date;
+use crate::bonus::{BonusPrediction, BonusQuestion, bonus_score};
+use crate::crowd::{CROWD_NAME, CrowdMethod, crowd_predictions};
+use crate::game::GameId;
+use crate::group::Groups;
+use crate::group::game::{GroupGameOutcome, GroupGameScore};
+use crate::player::{OutcomePrediction, PlayerId, PlayerPredictions, Prediction};
+use crate::playoff::{BracketState, BracketStructure};
+use crate::pred_score::joker::JokerRules;
+use crate::pred_score::playoff::{PlayoffPredScoreFn, SimplePlayoffPredScoreFn};
+use crate::pred_score::scheme::{ScoringConfig, Stage, StageMultipliers};
+use crate::pred_score::standings::{GroupStandings, StandingsRules};
+use crate::pred_score::{PredScore, PredScoreFn};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// A player, or one of the player's bots
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Competitor {
+    pub player_id: PlayerId,
+    pub bot_name: Option<String>,
+}
+
+impl Competitor {
+    pub fn player(player_id: PlayerId) -> Self {
+        Competitor {
+            player_id,
+            bot_name: None,
+        }
+    }
+
+    pub fn bot(player_id: PlayerId, bot_name: impl Into<String>) -> Self {
+        Competitor {
+            player_id,
+            bot_name: Some(bot_name.into()),
+        }
+    }
+
+    /// The virtual crowd competitor, a reserved bot of the non-existent player 0
+    pub fn crowd() -> Self {
+        Competitor::bot(PlayerId::from(0), CROWD_NAME)
+    }
+
+    pub fn is_bot(&self) -> bool {
+        self.bot_name.is_some()
+    }
+
+    pub fn is_crowd(&self) -> bool {
+        *self == Competitor::crowd()
+    }
+}
+
+/// Which competitors to rank
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompetitorFilter {
+    /// Humans and bots together
+    #[default]
+    All,
+    Humans,
+    Bots,
+}
+
+impl CompetitorFilter {
+    pub fn includes(&self, competitor: &Competitor) -> bool {
+        match self {
+            CompetitorFilter::All => true,
+            CompetitorFilter::Humans => !competitor.is_bot(),
+            CompetitorFilter::Bots => competitor.is_bot(),
+        }
+    }
+}
+
+/// All predictions of a single competitor
+#[derive(Debug, Clone)]
+pub struct CompetitorPredictions {
+    pub competitor: Competitor,
+    pub group: Vec<Prediction>,
+    /// Outcome-only group predictions, games with a score prediction ignore these
+    pub outcomes: Vec<OutcomePrediction>,
+    pub playoff: BracketState,
+    pub bonus: Vec<BonusPrediction>,
+    /// Games picked as jokers
+    pub jokers: Vec<GameId>,
+    /// Group standings which follow from `group`, see [`StandingsRules::predicted_standings`]
+    pub standings: Option<GroupStandings>,
+}
+
+impl CompetitorPredictions {
+    pub fn new(competitor: Competitor, group: Vec<Prediction>, playoff: BracketState) -> Self {
+        CompetitorPredictions {
+            competitor,
+            group,
+            outcomes: Vec::new(),
+            playoff,
+            bonus: Vec::new(),
+            jokers: Vec::new(),
+            standings: None,
+        }
+    }
+
+    pub fn with_outcomes(self, outcomes: Vec<OutcomePrediction>) -> Self {
+        CompetitorPredictions { outcomes, ..self }
+    }
+
+    pub fn with_bonus(self, bonus: Vec<BonusPrediction>) -> Self {
+        CompetitorPredictions { bonus, ..self }
+    }
+
+    pub fn with_jokers(self, jokers: Vec<GameId>) -> Self {
+        CompetitorPredictions { jokers, ..self }
+    }
+
+    pub fn with_standings(self, standings: GroupStandings) -> Self {
+        CompetitorPredictions {
+            standings: Some(standings),
+            ..self
+        }
+    }
+
+    /// Group predictions only, with an empty playoff bracket
+    pub fn from_player_preds(preds: &PlayerPredictions, bot_name: Option<String>) -> Self {
+        CompetitorPredictions {
+            competitor: Competitor {
+                player_id: preds.id,
+                bot_name,
+            },
+            group: preds.preds().copied().collect(),
+            outcomes: Vec::new(),
+            playoff: BracketState::new(),
+            bonus: Vec::new(),
+            jokers: Vec::new(),
+            standings: None,
+        }
+    }
+}
+
+/// The actual results to score predictions against
+#[derive(Debug, Clone, Copy)]
+pub struct Truth<'a> {
+    pub groups: &'a Groups,
+    pub playoff: Option<(&'a BracketStructure, &'a BracketState)>,
+    pub bonus: Option<&'a [BonusQuestion]>,
+    /// Final standings of the complete groups, scored once a group is complete in `groups`
+    pub standings: Option<&'a GroupStandings>,
+    /// Stored kickoff times, which order the played playoff games in the history
+    pub kickoffs: Option<&'a HashMap<GameId, Date>>,
+}
+
+impl<'a> Truth<'a> {
+    /// Group stage only
+    pub fn groups(groups: &'a Groups) -> Self {
+        Truth {
+            groups,
+            playoff: None,
+            bonus: None,
+            standings: None,
+            kickoffs: None,
+        }
+    }
+
+    /// Group standings, only competitors with predicted standings get standings points
+    pub fn with_standings(self, standings: &'a GroupStandings) -> Self {
+        Truth {
+            standings: Some(standings),
+            ..self
+        }
+    }
+
+    /// Bonus questions, only resolved questions give points
+    pub fn with_bonus(self, questions: &'a [BonusQuestion]) -> Self {
+        Truth {
+            bonus: Some(questions),
+            ..self
+        }
+    }
+
+    /// Kickoff times of the games, not needed for scoring
+    pub fn with_kickoffs(self, kickoffs: &'a HashMap<GameId, Date>) -> Self {
+        Truth {
+            kickoffs: Some(kickoffs),
+            ..self
+        }
+    }
+
+    pub fn with_playoff(self, bracket: &'a BracketStructure, state: &'a BracketState) -> Self {
+        Truth {
+            playoff: Some((bracket, state)),
+            ..self
+        }
+    }
+
+    /// Played group games and their scores
+    pub fn group_scores(&self) -> impl Iterator<Item = (GameId, GroupGameScore)> + '_ {
+        self.groups
+            .values()
+            .flat_map(|group| group.played_games())
+            .map(|game| (game.id, game.score))
+    }
+}
+
+/// Tie-breaking criteria for competitors with equal points, higher is better
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Tiebreak {
+    /// Number of exactly predicted results
+    ExactHits,
+    /// Number of correctly predicted outcomes (winners in the playoff)
+    OutcomeHits,
+}
+
+/// Score of a single predicted game
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GameScore {
+    pub game_id: GameId,
+    pub stage: Stage,
+    pub points: PredScore,
+    /// Exact result, for playoff games with the right teams and regular time score
+    pub exact: bool,
+    /// Right outcome, for playoff games the right winner
+    pub outcome: bool,
+    /// Picked as joker, `points` include the joker multiplier
+    pub joker: bool,
+}
+
+/// A competitor's standing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeaderboardRow {
+    /// 1-based, competitors which are equal after all tie-breaks share rank
+    pub rank: usize,
+    /// Rank in the leaderboard passed to [`Leaderboard::with_previous`]
+    pub previous_rank: Option<usize>,
+    pub competitor: Competitor,
+    pub total: PredScore,
+    /// Points per stage, including playoff progression points
+    pub stages: Vec<(Stage, PredScore)>,
+    pub exact_hits: usize,
+    pub outcome_hits: usize,
+    /// Per-game breakdown, progression points are only part of `stages`
+    pub games: Vec<GameScore>,
+}
+
+impl LeaderboardRow {
+    /// Number of places climbed since the previous leaderboard (negative if dropped)
+    pub fn rank_change(&self) -> Option<i64> {
+        let previous = i64::try_from(self.previous_rank?).ok()?;
+        let current = i64::try_from(self.rank).ok()?;
+        Some(previous - current)
+    }
+
+    pub fn stage_points(&self, stage: Stage) -> PredScore {
+        self.stages
+            .iter()
+            .find(|(s, _)| *s == stage)
+            .map(|(_, points)| *points)
+            .unwrap_or_default()
+    }
+
+    fn tiebreak_value(&self, tiebreak: Tiebreak) -> usize {
+        match tiebreak {
+            Tiebreak::ExactHits => self.exact_hits,
+            Tiebreak::OutcomeHits => self.outcome_hits,
+        }
+    }
+}
+
+/// Ranked standings, best first
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Leaderboard {
+    rows: Vec<LeaderboardRow>,
+}
+
+impl Leaderboard {
+    pub fn rows(&self) -> impl Iterator<Item = &LeaderboardRow> {
+        self.rows.iter()
+    }
+
+    pub fn row(&self, competitor: &Competitor) -> Option<&LeaderboardRow> {
+        self.rows.iter().find(|row| &row.competitor == competitor)
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Record the ranks of an earlier leaderboard, to get rank changes
+    pub fn with_previous(self, previous: &Leaderboard) -> Self {
+        let previous_ranks: HashMap<&Competitor, usize> = previous
+            .rows()
+            .map(|row| (&row.competitor, row.rank))
+            .collect();
+        let rows = self
+            .rows
+            .into_iter()
+            .map(|row| LeaderboardRow {
+                previous_rank: previous_ranks.get(&row.competitor).copied(),
+                ..row
+            })
+            .collect();
+        Leaderboard { rows }
+    }
+}
+
+/// How a leaderboard is computed
+#[derive(Debug, Clone)]
+pub struct LeaderboardRules<G, P> {
+    pub group_score: G,
+    pub playoff_score: P,
+    pub tiebreaks: Vec<Tiebreak>,
+    pub filter: CompetitorFilter,
+    pub jokers: JokerRules,
+    /// Rank the crowd as well, aggregated from the competitors passing the filter
+    pub crowd: Option<CrowdMethod>,
+    pub standings: StandingsRules,
+    /// Multipliers of the playoff games' points, by round. The group stage multiplier is part of
+    /// the group score fn, see [`ScoringConfig`].
+    pub multipliers: StageMultipliers,
+}
+
+impl LeaderboardRules<ScoringConfig, SimplePlayoffPredScoreFn> {
+    /// Rules of a tournament's stored scoring config
+    pub fn from_config(config: &ScoringConfig) -> Self {
+        LeaderboardRules::new(config.clone(), config.playoff.clone())
+            .with_jokers(config.jokers.clone())
+            .with_standings(config.standings)
+            .with_multipliers(config.multipliers.clone())
+    }
+}
+
+impl<G: PredScoreFn, P: PlayoffPredScoreFn> LeaderboardRules<G, P> {
+    /// Rank all competitors, tie-breaking on exact hits, then outcome hits
+    pub fn new(group_score: G, playoff_score: P) -> Self {
+        LeaderboardRules {
+            group_score,
+            playoff_score,
+            tiebreaks: vec![Tiebreak::ExactHits, Tiebreak::OutcomeHits],
+            filter: CompetitorFilter::All,
+            jokers: JokerRules::default(),
+            crowd: None,
+            standings: StandingsRules::default(),
+            multipliers: StageMultipliers::default(),
+        }
+    }
+
+    /// Multiply the points of the competitors' joker games
+    ///
+    /// The joker selections are assumed to be validated with [`JokerRules::validate`].
+    pub fn with_jokers(self, jokers: JokerRules) -> Self {
+        LeaderboardRules { jokers, ..self }
+    }
+
+    /// Score the competitors' predicted group standings
+    pub fn with_standings(self, standings: StandingsRules) -> Self {
+        LeaderboardRules { standings, ..self }
+    }
+
+    /// Multiply the points of the playoff games by their round's multiplier
+    pub fn with_multipliers(self, multipliers: StageMultipliers) -> Self {
+        LeaderboardRules {
+            multipliers,
+            ..self
+        }
+    }
+
+    pub fn with_tiebreaks(self, tiebreaks: Vec<Tiebreak>) -> Self {
+        LeaderboardRules { tiebreaks, ..self }
+    }
+
+    pub fn with_filter(self, filter: CompetitorFilter) -> Self {
+        LeaderboardRules { filter, ..self }
+    }
+
+    /// Add [`Competitor::crowd`] as a baseline, its group predictions aggregated with `method`
+    pub fn with_crowd(self, method: CrowdMethod) -> Self {
+        LeaderboardRules {
+            crowd: Some(method),
+            ..self
+        }
+    }
+
+    /// Compute the ranked leaderboard
+    pub fn leaderboard(&self, preds: &[CompetitorPredictions], truth: &Truth) -> Leaderboard {
+        let included: Vec<&CompetitorPredictions> = preds
+            .iter()
+            .filter(|preds| self.filter.includes(&preds.competitor))
+            .collect();
+        let crowd = self.crowd.map(|method| {
+            CompetitorPredictions::new(
+                Competitor::crowd(),
+                crowd_predictions(included.iter().flat_map(|preds| &preds.group), method),
+                BracketState::new(),
+            )
+        });
+        let mut rows: Vec<LeaderboardRow> = included
+            .into_iter()
+            .chain(crowd.as_ref())
+            .map(|preds| self.score(preds, truth))
+            .collect();
+        rows.sort_by(|a, b| {
+            self.compare(a, b)
+                .then_with(|| a.competitor.cmp(&b.competitor))
+        });
+
+        for idx in 0..rows.len() {
+            rows[idx].rank = match idx.checked_sub(1).map(|prev| &rows[prev]) {
+                Some(prev) if self.compare(prev, &rows[idx]) == Ordering::Equal => prev.rank,
+                _ => idx + 1,
+            };
+        }
+        Leaderboard { rows }
+    }
+
+    /// Score a single competitor, the row is unranked
+    pub fn score(&self, preds: &CompetitorPredictions, truth: &Truth) -> LeaderboardRow {
+        let group_preds: HashMap<GameId, GroupGameScore> = preds
+            .group
+            .iter()
+            .map(|Prediction(game_id, score)| (*game_id, *score))
+            .collect();
+
+        let mut games: Vec<GameScore> = truth
+            .group_scores()
+            .filter_map(|(game_id, truth)| {
+                group_preds.get(&game_id).map(|pred| (game_id, pred, truth))
+            })
+            .map(|(game_id, pred, truth)| GameScore {
+                game_id,
+                stage: Stage::Group,
+                points: self.group_score.pred_score(pred, &truth),
+                exact: *pred == truth,
+                outcome: pred.home_outcome() == truth.home_outcome(),
+                joker: false,
+            })
+            .collect();
+        let outcome_preds: HashMap<GameId, GroupGameOutcome> = preds
+            .outcomes
+            .iter()
+            .filter(|OutcomePrediction(game_id, _)| !group_preds.contains_key(game_id))
+            .map(|OutcomePrediction(game_id, outcome)| (*game_id, *outcome))
+            .collect();
+        games.extend(
+            truth
+                .group_scores()
+                .filter_map(|(game_id, truth)| {
+                    outcome_preds
+                        .get(&game_id)
+                        .map(|pred| (game_id, pred, truth))
+                })
+                .map(|(game_id, pred, truth)| GameScore {
+                    game_id,
+                    stage: Stage::Group,
+                    points: self.group_score.outcome_score(*pred, &truth),
+                    exact: false,
+                    outcome: *pred == truth.home_outcome(),
+                    joker: false,
+                }),
+        );
+
+        let mut stages: BTreeMap<Stage, PredScore> = BTreeMap::new();
+        if let Some((bracket, state)) = truth.playoff {
+            let depths = bracket.game_depths();
+            games.extend(state.played_games().filter_map(|(game_id, truth)| {
+                let pred = preds.playoff.result(*game_id)?;
+                // Games off the path to the final (third-place playoff) belong to the final round
+                let stage = Stage::Playoff {
+                    depth: depths.get(game_id).copied().unwrap_or_default(),
+                };
+                let points = f32::from(self.playoff_score.game_score(pred, truth))
+                    * self.multipliers.multiplier(stage);
+                Some(GameScore {
+                    game_id: *game_id,
+                    stage,
+                    points: PredScore::from(points),
+                    exact: pred.home == truth.home
+                        && pred.away == truth.away
+                        && pred.score.regular_time_score() == truth.score.regular_time_score(),
+                    outcome: pred.winner() == truth.winner(),
+                    joker: false,
+                })
+            }));
+            self.playoff_score
+                .round_scores(bracket, &preds.playoff, state)
+                .into_iter()
+                .for_each(|(depth, points)| {
+                    *stages.entry(Stage::Playoff { depth }).or_default() += points;
+                });
+        }
+        let jokers: HashSet<&GameId> = preds.jokers.iter().collect();
+        for game in games
+            .iter_mut()
+            .filter(|game| jokers.contains(&game.game_id))
+        {
+            game.joker = true;
+            game.points = PredScore::from(f32::from(game.points) * self.jokers.multiplier);
+        }
+        if let (Some(pred), Some(truth_standings)) = (&preds.standings, truth.standings) {
+            stages.insert(
+                Stage::Standings,
+                self.standings.score(pred, truth_standings, truth.groups),
+            );
+        }
+        if let Some(questions) = truth.bonus {
+            stages.insert(Stage::Bonus, bonus_score(questions, &preds.bonus));
+        }
+        games.sort_by_key(|game| (game.stage, game.game_id));
+        games
+            .iter()
+            .for_each(|game| *stages.entry(game.stage).or_default() += game.points);
+
+        LeaderboardRow {
+            rank: 0,
+            previous_rank: None,
+            competitor: preds.competitor.clone(),
+            total: stages.values().copied().sum(),
+            stages: stages.into_iter().collect(),
+            exact_hits: games.iter().filter(|game| game.exact).count(),
+            outcome_hits: games.iter().filter(|game| game.outcome).count(),
+            games,
+        }
+    }
+
+    /// Better rows first, `Equal` if the rows are tied after all tie-breaks
+    fn compare(&self, a: &LeaderboardRow, b: &LeaderboardRow) -> Ordering {
+        self.tiebreaks.iter().fold(
+            b.total.partial_cmp(&a.total).unwrap_or(Ordering::Equal),
+            |ord, tiebreak| {
+                ord.then_with(|| {
+                    b.tiebreak_value(*tiebreak)
+                        .cmp(&a.tiebreak_value(*tiebreak))
+                })
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bonus::{BonusAnswer, BonusKind, BonusQuestionId};
+    use crate::game::GoalCount;
+    use crate::group::GroupId;
+    use crate::group::mock_data::groups_and_teams;
+    use crate::group::order::{UefaRanking, fifa_2018_rules, noop_fifa_2018_third_place_rules};
+    use crate::playoff::{PlayoffScore, TeamSource};
+    use crate::pred_score::SimplePredScoreFn;
+    use crate::pred_score::playoff::SimplePlayoffPredScoreFn;
+    use crate::pred_score::scheme::ScoringScheme;
+    use crate::team::TeamId;
+    use assert_approx_eq::assert_approx_eq;
+
+    fn score(home: u32, away: u32) -> GroupGameScore {
+        GroupGameScore::new(
+            GoalCount::try_from(home).unwrap(),
+            GoalCount::try_from(away).unwrap(),
+        )
+    }
+
+    /// Mock groups with games 1 (2-1), 3 (0-0) and 4 (1-3) played
+    fn groups() -> Groups {
+        let (mut groups, _) = groups_and_teams();
+        let group_b = groups.get_mut(&GroupId::try_from('B').unwrap()).unwrap();
+        group_b.play_game(GameId::from(3), score(0, 0));
+        group_b.play_game(GameId::from(4), score(1, 3));
+        groups
+    }
+
+    fn preds(competitor: Competitor, scores: &[(u32, (u32, u32))]) -> CompetitorPredictions {
+        let group = scores
+            .iter()
+            .map(|(id, (home, away))| Prediction(GameId::from(*id), score(*home, *away)))
+            .collect();
+        CompetitorPredictions::new(competitor, group, BracketState::new())
+    }
+
+    fn no_playoff_score() -> SimplePlayoffPredScoreFn {
+        SimplePlayoffPredScoreFn {
+            round_weights: vec![],
+            winner_weight: 0.0,
+            result_weight: 0.0,
+            shootout_weight: 0.0,
+        }
+    }
+
+    fn player(id: i32) -> Competitor {
+        Competitor::player(PlayerId::from(id))
+    }
+
+    #[test]
+    fn ranks_with_tiebreaks() {
+        let groups = groups();
+        let truth = Truth::groups(&groups);
+        let all_preds = vec![
+            // Outcome only: 3 points, 1 exact
+            preds(player(1), &[(1, (2, 1)), (3, (1, 1)), (4, (0, 2))]),
+            // Outcome only: 3 points, 2 exact
+            preds(player(2), &[(1, (2, 1)), (3, (0, 0)), (4, (0, 2))]),
+            // 1 point, 0 exact
+            preds(player(3), &[(1, (3, 0)), (3, (1, 0)), (4, (2, 0))]),
+        ];
+        let outcome_only = ScoringScheme::Additive {
+            outcome: 1.0,
+            goal_difference: 0.0,
+            team_goals: 0.0,
+            result: 0.0,
+        };
+
+        let rules = LeaderboardRules::new(outcome_only, no_playoff_score());
+        let leaderboard = rules.leaderboard(&all_preds, &truth);
+        let ranking: Vec<_> = leaderboard
+            .rows()
+            .map(|row| (row.competitor.player_id, row.rank))
+            .collect();
+        assert_eq!(
+            ranking,
+            vec![
+                (PlayerId::from(2), 1),
+                (PlayerId::from(1), 2),
+                (PlayerId::from(3), 3)
+            ]
+        );
+        let row = leaderboard.row(&player(1)).unwrap();
+        assert_approx_eq!(f32::from(row.total), 3.0);
+        assert_eq!((row.exact_hits, row.outcome_hits), (1, 3));
+        assert_eq!(row.games.len(), 3);
+
+        // Without tie-breaks players 1 and 2 share the first place
+        let leaderboard = rules.with_tiebreaks(vec![]).leaderboard(&all_preds, &truth);
+        let ranks: Vec<_> = leaderboard.rows().map(|row| row.rank).collect();
+        assert_eq!(ranks, vec![1, 1, 3]);
+    }
+
+    #[test]
+    fn humans_and_bots() {
+        let groups = groups();
+        let truth = Truth::groups(&groups);
+        let all_preds = vec![
+            preds(player(1), &[(1, (0, 1))]),
+            preds(Competitor::bot(PlayerId::from(1), "bot"), &[(1, (2, 1))]),
+        ];
+        let rules = LeaderboardRules::new(SimplePredScoreFn::new(1.0, 1.0), no_playoff_score());
+
+        let together = rules.leaderboard(&all_preds, &truth);
+        assert_eq!(together.len(), 2);
+        assert!(together.rows().next().unwrap().competitor.is_bot());
+
+        let humans = rules
+            .clone()
+            .with_filter(CompetitorFilter::Humans)
+            .leaderboard(&all_preds, &truth);
+        assert_eq!(humans.len(), 1);
+        assert_eq!(humans.rows().next().unwrap().rank, 1);
+
+        let bots = rules
+            .with_filter(CompetitorFilter::Bots)
+            .leaderboard(&all_preds, &truth);
+        assert_eq!(bots.len(), 1);
+        assert!(bots.row(&player(1)).is_none());
+    }
+
+    /// A final between the winners of groups A and B, won 1-0 by team 1
+    fn played_final() -> (BracketStructure, BracketState) {
+        let sources = vec![(
+            GameId::from(5),
+            (
+                TeamSource::GroupOutcome(crate::group::GroupOutcome::Winner(
+                    GroupId::try_from('A').unwrap(),
+                )),
+                TeamSource::GroupOutcome(crate::group::GroupOutcome::Winner(
+                    GroupId::try_from('B').unwrap(),
+                )),
+            ),
+        )];
+        let bracket = BracketStructure::from_team_sources(&sources).unwrap();
+        let final_score = PlayoffScore::regular_time(
+            GoalCount::try_from(1).unwrap(),
+            GoalCount::try_from(0).unwrap(),
+        )
+        .unwrap();
+        let state =
+            BracketState::new().play_game(GameId::from(5), TeamId(1), TeamId(8), final_score);
+        (bracket, state)
+    }
+
+    #[test]
+    fn playoff_stages() {
+        let groups = groups();
+        let (bracket, state) = played_final();
+        let truth = Truth::groups(&groups).with_playoff(&bracket, &state);
+
+        let mut competitor_preds = preds(player(1), &[(1, (2, 1))]);
+        competitor_preds.playoff = state.clone();
+
+        let playoff_score = SimplePlayoffPredScoreFn {
+            round_weights: vec![2.0],
+            winner_weight: 3.0,
+            result_weight: 1.0,
+            shootout_weight: 0.0,
+        };
+        let rules = LeaderboardRules::new(SimplePredScoreFn::new(1.0, 1.0), playoff_score);
+        let row = rules.score(&competitor_preds, &truth);
+
+        assert_approx_eq!(f32::from(row.stage_points(Stage::Group)), 2.0);
+        // Winner and result, plus both finalists
+        assert_approx_eq!(
+            f32::from(row.stage_points(Stage::Playoff { depth: 0 })),
+            3.0 + 1.0 + 2.0 * 2.0
+        );
+        assert_approx_eq!(f32::from(row.total), 10.0);
+        assert_eq!(
+            row.games.iter().map(|game| game.stage).collect::<Vec<_>>(),
+            vec![Stage::Group, Stage::Playoff { depth: 0 }]
+        );
+        assert_eq!((row.exact_hits, row.outcome_hits), (2, 2));
+    }
+
+    #[test]
+    fn playoff_round_multipliers() {
+        let groups = groups();
+        let (bracket, state) = played_final();
+        let truth = Truth::groups(&groups).with_playoff(&bracket, &state);
+
+        let mut finalist = preds(player(1), &[]);
+        finalist.playoff = state.clone();
+        let all_preds = vec![finalist, preds(player(2), &[(1, (2, 1)), (3, (0, 0))])];
+        let playoff_score = SimplePlayoffPredScoreFn {
+            round_weights: vec![],
+            winner_weight: 1.0,
+            result_weight: 0.0,
+            shootout_weight: 0.0,
+        };
+        let rules = LeaderboardRules::new(SimplePredScoreFn::new(1.0, 1.0), playoff_score);
+        assert_eq!(
+            rules.leaderboard(&all_preds, &truth).rows[0].competitor,
+            player(2)
+        );
+
+        // The final counts five times, more than both group games
+        let rules = rules.with_multipliers(StageMultipliers {
+            group: 1.0,
+            playoff_rounds: vec![5.0],
+        });
+        let leaderboard = rules.leaderboard(&all_preds, &truth);
+        assert_eq!(leaderboard.rows[0].competitor, player(1));
+        let row = leaderboard.row(&player(1)).unwrap();
+        assert_approx_eq!(
+            f32::from(row.stage_points(Stage::Playoff { depth: 0 })),
+            5.0
+        );
+        assert_approx_eq!(f32::from(row.total), 5.0);
+    }
+
+    #[test]
+    fn bonus_points_in_total() {
+        let groups = groups();
+        let mut champion = BonusQuestion::new(
+            BonusQuestionId::from(1),
+            "Champion",
+            BonusKind::TeamPick { points: 5.0 },
+            Date::mock(),
+        );
+        champion.solution = Some(vec![BonusAnswer::Team(TeamId(3))]);
+        let questions = vec![champion];
+        let all_preds = vec![
+            preds(player(1), &[(1, (2, 1))]).with_bonus(vec![BonusPrediction(
+                BonusQuestionId::from(1),
+                BonusAnswer::Team(TeamId(3)),
+            )]),
+            preds(player(2), &[(1, (2, 1)), (3, (0, 0))]),
+        ];
+        let rules = LeaderboardRules::new(SimplePredScoreFn::new(1.0, 1.0), no_playoff_score());
+        let leaderboard =
+            rules.leaderboard(&all_preds, &Truth::groups(&groups).with_bonus(&questions));
+
+        let row = leaderboard.row(&player(1)).unwrap();
+        assert_eq!(row.rank, 1);
+        assert_approx_eq!(f32::from(row.stage_points(Stage::Bonus)), 5.0);
+        assert_approx_eq!(f32::from(row.total), 7.0);
+        assert_eq!(row.stages.last().unwrap().0, Stage::Bonus);
+    }
+
+    #[test]
+    fn joker_points() {
+        let groups = groups();
+        let competitor_preds =
+            preds(player(1), &[(1, (2, 1)), (3, (1, 0))]).with_jokers(vec![GameId::from(1)]);
+        let rules = LeaderboardRules::new(SimplePredScoreFn::new(1.0, 1.0), no_playoff_score())
+            .with_jokers(JokerRules {
+                multiplier: 3.0,
+                group: 1,
+                playoff_rounds: vec![],
+            });
+        let row = rules.score(&competitor_preds, &Truth::groups(&groups));
+        assert_approx_eq!(f32::from(row.total), 6.0);
+        let joker_game = row.games.iter().find(|game| game.joker).unwrap();
+        assert_eq!(joker_game.game_id, GameId::from(1));
+        assert_approx_eq!(f32::from(joker_game.points), 6.0);
+    }
+
+    #[test]
+    fn outcome_predictions() {
+        let groups = groups();
+        let competitor_preds = preds(player(1), &[(1, (2, 1))]).with_outcomes(vec![
+            // Ignored, game 1 has a score prediction
+            OutcomePrediction(GameId::from(1), GroupGameOutcome::Lose),
+            OutcomePrediction(GameId::from(3), GroupGameOutcome::Draw),
+            OutcomePrediction(GameId::from(4), GroupGameOutcome::Win),
+        ]);
+        let rules = LeaderboardRules::new(SimplePredScoreFn::new(1.0, 2.0), no_playoff_score());
+        let row = rules.score(&competitor_preds, &Truth::groups(&groups));
+        // 3 points for the exact 2-1, 1 for the correct draw
+        assert_approx_eq!(f32::from(row.total), 4.0);
+        assert_eq!(row.games.len(), 3);
+        assert_eq!(row.exact_hits, 1);
+        assert_eq!(row.outcome_hits, 2);
+    }
+
+    #[test]
+    fn standings_points() {
+        let (_, teams) = groups_and_teams();
+        let groups = groups();
+        let ranking = UefaRanking::try_new(
+            &groups,
+            teams.iter().map(|(id, team)| (*id, team.rank)).collect(),
+        )
+        .unwrap();
+        let order_rules = fifa_2018_rules().with_tiebreaker(ranking);
+        let third_rules = noop_fifa_2018_third_place_rules();
+        let standings_rules = StandingsRules {
+            position: 1.0,
+            ..StandingsRules::default()
+        };
+        let truth_standings = standings_rules.standings(&groups, &order_rules, &third_rules);
+        let truth = Truth::groups(&groups).with_standings(&truth_standings);
+
+        // Other scores, same order of group B
+        let competitor_preds = preds(player(1), &[(3, (1, 0)), (4, (0, 2))]);
+        let predicted = standings_rules.predicted_standings(
+            &groups,
+            &competitor_preds.group,
+            &order_rules,
+            &third_rules,
+        );
+        let rules = LeaderboardRules::new(SimplePredScoreFn::new(0.0, 0.0), no_playoff_score())
+            .with_standings(standings_rules);
+        let row = rules.score(&competitor_preds.clone().with_standings(predicted), &truth);
+        assert_approx_eq!(f32::from(row.stage_points(Stage::Standings)), 4.0);
+        assert_approx_eq!(f32::from(row.total), 4.0);
+
+        // Without predicted standings there are no standings points
+        let row = rules.score(&competitor_preds, &truth);
+        assert_eq!(row.stages.len(), 1);
+    }
+
+    #[test]
+    fn crowd_baseline() {
+        let groups = groups();
+        let all_preds = vec![
+            preds(player(1), &[(1, (2, 1)), (3, (0, 0))]),
+            preds(player(2), &[(1, (2, 1)), (3, (1, 0))]),
+            preds(player(3), &[(1, (0, 3)), (3, (1, 0))]),
+        ];
+        let rules = LeaderboardRules::new(SimplePredScoreFn::new(1.0, 1.0), no_playoff_score())
+            .with_crowd(CrowdMethod::ModalScore);
+        let leaderboard = rules.leaderboard(&all_preds, &Truth::groups(&groups));
+        assert_eq!(leaderboard.len(), 4);
+        // Crowd predicts 2-1 and 1-0, the same as player 2
+        let crowd = leaderboard.row(&Competitor::crowd()).unwrap();
+        let player_2 = leaderboard.row(&player(2)).unwrap();
+        assert_eq!(crowd.exact_hits, 1);
+        assert_eq!(crowd.rank, 2);
+        assert_eq!(crowd.rank, player_2.rank);
+        assert_approx_eq!(f32::from(crowd.total), f32::from(player_2.total));
+        assert!(crowd.competitor.is_crowd());
+        assert!(!player(2).is_crowd());
+    }
+
+    #[test]
+    fn rank_changes() {
+        let groups = groups();
+        let all_preds = vec![
+            preds(player(1), &[(1, (2, 1)), (3, (1, 0)), (4, (1, 0))]),
+            preds(player(2), &[(1, (0, 1)), (3, (0, 0)), (4, (1, 3))]),
+        ];
+        let rules = LeaderboardRules::new(SimplePredScoreFn::new(1.0, 1.0), no_playoff_score());
+
+        // Before group B was played
+        let mut before = groups.clone();
+        let group_b = before.get_mut(&GroupId::try_from('B').unwrap()).unwrap();
+        group_b.unplay_game(GameId::from(3));
+        group_b.unplay_game(GameId::from(4));
+        let previous = rules.leaderboard(&all_preds, &Truth::groups(&before));
+        assert_eq!(previous.row(&player(1)).unwrap().rank_change(), None);
+
+        let current = rules
+            .leaderboard(&all_preds, &Truth::groups(&groups))
+            .with_previous(&previous);
+        assert_eq!(current.row(&player(1)).unwrap().rank_change(), Some(-1));
+        assert_eq!(current.row(&player(2)).unwrap().rank_change(), Some(1));
+        assert_eq!(current.rows().next().unwrap().competitor, player(2));
+    }
+}
@@ -39,15 +39,20 @@
 #![cfg_attr(not(test), warn(clippy::unwrap_used))]
 #![warn(clippy::unseparated_literal_suffix)]
 
+pub mod bonus;
+pub mod crowd;
 pub mod error;
 pub mod fair_play;
 pub mod game;
 pub mod group;
+pub mod leaderboard;
+pub mod live;
 pub mod player;
 pub mod playoff;
 pub mod pred_score;
 pub mod team;
 pub mod utils;
+pub mod validation;
 // Exports
 pub use team::Team;
 pub use utils::date::Date;
@@ -0,0 +1,43 @@
+//! Live updates pushed to clients
+//!
+//! The server sends an event whenever the data of a tournament changes, clients reload or update
+//! the affected data instead of polling. Events only name what changed, the data itself is read
+//! through the regular endpoints.
+use crate::game::GameId;
+use serde::{Deserialize, Serialize};
+
+/// A change of a tournament's data
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LiveEvent {
+    /// The result of a group game was recorded, corrected or cleared
+    GroupResult { game_id: GameId },
+    /// The result of a playoff game was recorded, corrected or cleared
+    PlayoffResult { game_id: GameId },
+    /// The scores of the leaderboard changed
+    Leaderboard,
+    /// Predictions of these games can no longer be changed, since they have kicked off or have a
+    /// result
+    PredsLocked { game_ids: Vec<GameId> },
+    /// Earlier events were missed, all data should be reloaded
+    Reset,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tagged_json() {
+        let event = LiveEvent::PredsLocked {
+            game_ids: vec![GameId::from(1), GameId::from(3)],
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(json, r#"{"kind":"preds_locked","game_ids":[1,3]}"#);
+        assert_eq!(serde_json::from_str::<LiveEvent>(&json).unwrap(), event);
+        assert_eq!(
+            serde_json::to_string(&LiveEvent::Leaderboard).unwrap(),
+            r#"{"kind":"leaderboard"}"#
+        );
+    }
+}
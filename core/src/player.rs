@@ -1,6 +1,7 @@
 //! Player/User/Better/Predictor
+use crate::Date;
 use crate::game::GameId;
-use crate::group::game::{GroupGameScore, PlayedGroupGame};
+use crate::group::game::{GroupGameOutcome, GroupGameScore, PlayedGroupGame};
 use derive_more::{Display, From, Into};
 use serde::{Deserialize, Serialize};
 
@@ -24,7 +25,21 @@ impl Player {
 }
 
 /// Numeric player id for db.
-#[derive(Display, Debug, Copy, Clone, From, Into, Serialize, Deserialize)]
+#[derive(
+    Display,
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    From,
+    Into,
+    Serialize,
+    Deserialize,
+)]
 pub struct PlayerId(i32);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +66,13 @@ impl PlayerPredictions {
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Prediction(pub GameId, pub GroupGameScore);
 
+/// A player's stored group (score or outcome) predictions right after a submission
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PredSubmission<P = Prediction> {
+    pub submitted_at: Date,
+    pub preds: Vec<P>,
+}
+
 impl std::fmt::Display for Prediction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "Game {}: {}", self.0, self.1)
@@ -62,3 +84,29 @@ impl From<PlayedGroupGame> for Prediction {
         Prediction(game.id, game.score)
     }
 }
+
+/// What a group game prediction consists of, chosen per tournament
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PredictionMode {
+    /// Exact scores, see [`Prediction`]
+    #[default]
+    Score,
+    /// Home win, draw or away win only (1X2), see [`OutcomePrediction`]
+    Outcome,
+}
+
+/// Outcome-only prediction of a group game, from the home team's perspective
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct OutcomePrediction(pub GameId, pub GroupGameOutcome);
+
+impl std::fmt::Display for OutcomePrediction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let outcome = match self.1 {
+            GroupGameOutcome::Win => "1",
+            GroupGameOutcome::Draw => "X",
+            GroupGameOutcome::Lose => "2",
+        };
+        write!(f, "Game {}: {}", self.0, outcome)
+    }
+}
@@ -358,6 +358,20 @@ impl BracketStructure {
         state.result(final_game_id).map(|r| r.loser())
     }
 
+    /// Get the depth of every game on the path to the final
+    ///
+    /// Depth 0 = final, depth 1 = semifinals, etc.
+    /// Games which do not lead to the final (e.g. a third-place playoff) have no depth.
+    pub fn game_depths(&self) -> HashMap<GameId, usize> {
+        use petgraph::algo::dijkstra;
+        use petgraph::visit::Reversed;
+
+        dijkstra(Reversed(&self.graph), self.final_node, None, |_| 1)
+            .into_iter()
+            .map(|(idx, depth)| (self.graph[idx], depth))
+            .collect()
+    }
+
     /// Get the maximum depth (number of rounds) in the bracket
     /// Depth 0 = final, depth 1 = semifinals, etc.
     pub fn max_depth(&self) -> usize {
@@ -385,6 +399,38 @@ impl BracketStructure {
             Vec::new()
         }
     }
+
+    /// Check that a (predicted) bracket state fits the structure
+    ///
+    /// Every played game must be part of the bracket and have two different teams. A team from
+    /// the winner or loser of an earlier game must be that game's winner or loser in the state.
+    /// Teams from group outcomes depend on the group results and are not checked.
+    pub fn validate_state(&self, state: &BracketState) -> Result<(), BracketError> {
+        for (game_id, result) in state.played_games() {
+            let node_idx = self
+                .game_to_node
+                .get(game_id)
+                .ok_or(BracketError::MissingGameNode(*game_id))?;
+            if result.home == result.away {
+                return Err(BracketError::SameTeams(*game_id));
+            }
+            let (home_source, away_source) = &self.sources[node_idx];
+            for (source, team) in [(home_source, result.home), (away_source, result.away)] {
+                let expected = match source {
+                    TeamSource::GroupOutcome(_) => continue,
+                    TeamSource::WinnerOf(src_id) => state.result(*src_id).map(|r| r.winner()),
+                    TeamSource::LoserOf(src_id) => state.result(*src_id).map(|r| r.loser()),
+                };
+                if expected != Some(team) {
+                    return Err(BracketError::UnexpectedTeam {
+                        game_id: *game_id,
+                        team,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 /// The computed state of a playoff game
@@ -460,11 +506,16 @@ pub enum BracketError {
     CyclicBracket,
     #[error("{0:?} not found in games_to_node map.")]
     MissingGameNode(GameId),
+    #[error("{0:?} is played by the same team twice")]
+    SameTeams(GameId),
+    #[error("Team {team} does not advance to {game_id:?}")]
+    UnexpectedTeam { game_id: GameId, team: TeamId },
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::game::GoalCount;
     use crate::group::GroupId;
     use crate::group::mock_data::groups_and_teams;
     use crate::group::order::fifa_2018_rules;
@@ -512,4 +563,77 @@ mod tests {
         // Both games should be ready (teams from group stage)
         assert!(games.iter().all(|g| g.is_ready()));
     }
+
+    #[test]
+    fn validate_state() {
+        let team_sources = vec![
+            (
+                GameId::from(1),
+                (
+                    TeamSource::GroupOutcome(GroupOutcome::Winner(GroupId::try_from('A').unwrap())),
+                    TeamSource::GroupOutcome(GroupOutcome::RunnerUp(
+                        GroupId::try_from('B').unwrap(),
+                    )),
+                ),
+            ),
+            (
+                GameId::from(2),
+                (
+                    TeamSource::GroupOutcome(GroupOutcome::Winner(GroupId::try_from('B').unwrap())),
+                    TeamSource::GroupOutcome(GroupOutcome::RunnerUp(
+                        GroupId::try_from('A').unwrap(),
+                    )),
+                ),
+            ),
+            (
+                GameId::from(3),
+                (
+                    TeamSource::WinnerOf(GameId::from(1)),
+                    TeamSource::WinnerOf(GameId::from(2)),
+                ),
+            ),
+        ];
+        let bracket = BracketStructure::from_team_sources(&team_sources).unwrap();
+        let score = PlayoffScore::regular_time(
+            GoalCount::try_from(2).unwrap(),
+            GoalCount::try_from(1).unwrap(),
+        )
+        .unwrap();
+        let semis = BracketState::new()
+            .play_game(GameId::from(1), TeamId(1), TeamId(2), score)
+            .play_game(GameId::from(2), TeamId(3), TeamId(4), score);
+        assert!(bracket.validate_state(&semis).is_ok());
+
+        let valid = semis.play_game(GameId::from(3), TeamId(1), TeamId(3), score);
+        assert!(bracket.validate_state(&valid).is_ok());
+
+        let loser = semis.play_game(GameId::from(3), TeamId(2), TeamId(3), score);
+        assert!(matches!(
+            bracket.validate_state(&loser),
+            Err(BracketError::UnexpectedTeam {
+                team: TeamId(2),
+                ..
+            })
+        ));
+
+        let unknown_source =
+            BracketState::new().play_game(GameId::from(3), TeamId(1), TeamId(3), score);
+        assert!(matches!(
+            bracket.validate_state(&unknown_source),
+            Err(BracketError::UnexpectedTeam { .. })
+        ));
+
+        let same_teams =
+            BracketState::new().play_game(GameId::from(1), TeamId(1), TeamId(1), score);
+        assert!(matches!(
+            bracket.validate_state(&same_teams),
+            Err(BracketError::SameTeams(_))
+        ));
+
+        let unknown_game = semis.play_game(GameId::from(9), TeamId(1), TeamId(3), score);
+        assert!(matches!(
+            bracket.validate_state(&unknown_game),
+            Err(BracketError::MissingGameNode(_))
+        ));
+    }
 }
@@ -22,6 +22,10 @@
 //!   - Shows current status of each game (Pending, Ready, Played, etc.)
 //!   - Never stored, always computed on demand
 //!
+//! - **Validation** ([`validation`]): Structural checks of a bracket template
+//!   - Reports every problem (extra finals, unreachable games, reused sources, ...) at once
+//!   - Run when importing tournament data
+//!
 //! # Graph Model
 //!
 //! The bracket is a directed acyclic graph (DAG) where:
@@ -64,6 +68,7 @@ pub mod bracket;
 pub mod game;
 pub mod template;
 pub mod transition;
+pub mod validation;
 
 // Re-exports for convenience
 pub use bracket::{
@@ -71,6 +76,7 @@ pub use bracket::{
 };
 pub use game::{PlayoffError, PlayoffScore};
 use template::BracketTemplate;
+pub use validation::{BracketIssue, InvalidBracket, validate_team_sources};
 
 use crate::game::GameId;
 use crate::group::Groups;
@@ -0,0 +1,460 @@
+//! # Structural bracket validation
+//!
+//! [`BracketStructure::from_template`](super::BracketStructure::from_template) only rejects
+//! templates which cannot be turned into a graph at all: a missing final, a reference to an unknown
+//! game or a cycle.
+//! A template can still describe a graph which is not a sensible bracket,
+//! e.g. two games that both act as a final or a game whose winner is sent to two different games.
+//!
+//! The validator in this module inspects the full template and reports *every* problem it finds,
+//! rather than stopping at the first one.
+//! This makes it useful as a diagnostic when importing new tournament data.
+//!
+//! The rules are:
+//!
+//! - Every game id is unique and the final is one of the games.
+//! - Every `WinnerOf`/`LoserOf` source refers to an existing game, other than the game itself.
+//! - The bracket is acyclic.
+//! - The winner (and loser) of a game is used as a source at most once.
+//! - The final is not fed by the loser of any game.
+//! - The final is the only game whose winner is not used further.
+//!   The exception is a consolation game (e.g. a third-place playoff), which is fed only by losers.
+//! - Every other game leads to the final.
+use crate::game::GameId;
+use crate::playoff::bracket::TeamSource;
+use crate::playoff::template::BracketTemplate;
+use itertools::Itertools;
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::{Bfs, Reversed};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use thiserror::Error;
+
+/// Single structural problem in a bracket template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BracketIssue {
+    /// The template does not contain any games
+    NoGames,
+    /// The same game id occurs more than once in the template
+    DuplicateGame(GameId),
+    /// The declared final is not one of the games in the template
+    FinalGameNotFound(GameId),
+    /// A game refers to a source game which is not in the template
+    MissingSourceGame { game: GameId, source: GameId },
+    /// A game refers to itself as a source
+    SelfReference(GameId),
+    /// The games form a cycle
+    Cycle(Vec<GameId>),
+    /// The winner of `source` is used by more than one game
+    WinnerUsedTwice { source: GameId, games: Vec<GameId> },
+    /// The loser of `source` is used by more than one game
+    LoserUsedTwice { source: GameId, games: Vec<GameId> },
+    /// The final is fed by the loser of `source`
+    LoserToFinal { source: GameId },
+    /// A game other than the final whose winner is not used by any other game.
+    ///
+    /// Consolation games, which are fed by losers only, are not reported.
+    ExtraFinal(GameId),
+    /// A game that does not lead to the final
+    Unreachable(GameId),
+}
+
+impl fmt::Display for BracketIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BracketIssue::NoGames => write!(f, "Bracket has no games"),
+            BracketIssue::DuplicateGame(game) => {
+                write!(f, "Game {} is defined more than once", game)
+            }
+            BracketIssue::FinalGameNotFound(game) => {
+                write!(f, "Final game {} is not in the bracket", game)
+            }
+            BracketIssue::MissingSourceGame { game, source } => {
+                write!(f, "Game {} depends on unknown game {}", game, source)
+            }
+            BracketIssue::SelfReference(game) => write!(f, "Game {} depends on itself", game),
+            BracketIssue::Cycle(games) => write!(f, "Games {} form a cycle", join_ids(games)),
+            BracketIssue::WinnerUsedTwice { source, games } => write!(
+                f,
+                "Winner of {} is used by several games: {}",
+                source,
+                join_ids(games)
+            ),
+            BracketIssue::LoserUsedTwice { source, games } => write!(
+                f,
+                "Loser of {} is used by several games: {}",
+                source,
+                join_ids(games)
+            ),
+            BracketIssue::LoserToFinal { source } => {
+                write!(f, "Final is fed by the loser of {}", source)
+            }
+            BracketIssue::ExtraFinal(game) => {
+                write!(
+                    f,
+                    "Game {} is a second final: its winner goes nowhere",
+                    game
+                )
+            }
+            BracketIssue::Unreachable(game) => {
+                write!(f, "Game {} does not lead to the final", game)
+            }
+        }
+    }
+}
+
+fn join_ids(games: &[GameId]) -> String {
+    games.iter().map(|id| id.to_string()).join(", ")
+}
+
+/// Collection of all issues found in a bracket template
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub struct InvalidBracket(pub Vec<BracketIssue>);
+
+impl InvalidBracket {
+    pub fn issues(&self) -> impl Iterator<Item = &BracketIssue> {
+        self.0.iter()
+    }
+}
+
+impl fmt::Display for InvalidBracket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.0.iter().map(|issue| issue.to_string()).join("; ")
+        )
+    }
+}
+
+impl BracketTemplate {
+    /// Validate the structure of the template
+    ///
+    /// # Errors
+    ///
+    /// Returns every [`BracketIssue`] found, see the module docs for the rules.
+    pub fn validate(&self) -> Result<(), InvalidBracket> {
+        let issues = find_issues(&self.games, self.final_game_id);
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(InvalidBracket(issues))
+        }
+    }
+}
+
+/// Validate team sources
+///
+/// Uses the same convention as
+/// [`BracketStructure::from_team_sources`](super::BracketStructure::from_team_sources),
+/// i.e. the last game is the final.
+pub fn validate_team_sources(
+    team_sources: &[(GameId, (TeamSource, TeamSource))],
+) -> Result<(), InvalidBracket> {
+    match team_sources.last() {
+        Some((final_game_id, _)) => BracketTemplate {
+            games: team_sources.to_vec(),
+            final_game_id: *final_game_id,
+        }
+        .validate(),
+        None => Err(InvalidBracket(vec![BracketIssue::NoGames])),
+    }
+}
+
+fn find_issues(
+    games: &[(GameId, (TeamSource, TeamSource))],
+    final_game_id: GameId,
+) -> Vec<BracketIssue> {
+    if games.is_empty() {
+        return vec![BracketIssue::NoGames];
+    }
+    let mut issues = Vec::new();
+
+    let mut graph: DiGraph<GameId, ()> = DiGraph::new();
+    let mut game_to_node: HashMap<GameId, NodeIndex> = HashMap::new();
+    for (game_id, _) in games {
+        if game_to_node.contains_key(game_id) {
+            issues.push(BracketIssue::DuplicateGame(*game_id));
+        } else {
+            game_to_node.insert(*game_id, graph.add_node(*game_id));
+        }
+    }
+    // With duplicates, only the first definition of a game is used for the remaining checks.
+    let games = games.iter().unique_by(|(game_id, _)| *game_id);
+
+    // Usage of winners and losers: source game -> games using it.
+    let mut winner_usage: BTreeMap<GameId, Vec<GameId>> = BTreeMap::new();
+    let mut loser_usage: BTreeMap<GameId, Vec<GameId>> = BTreeMap::new();
+    // Games fed only by losers, candidates for consolation games.
+    let mut only_losers: HashSet<GameId> = HashSet::new();
+
+    for (game_id, (home, away)) in games {
+        for source in [home, away] {
+            let (source_id, usage) = match source {
+                TeamSource::GroupOutcome(_) => continue,
+                TeamSource::WinnerOf(id) => (*id, &mut winner_usage),
+                TeamSource::LoserOf(id) => (*id, &mut loser_usage),
+            };
+            if source_id == *game_id {
+                issues.push(BracketIssue::SelfReference(*game_id));
+                continue;
+            }
+            match game_to_node.get(&source_id) {
+                Some(source_node) => {
+                    graph.add_edge(*source_node, game_to_node[game_id], ());
+                    usage.entry(source_id).or_default().push(*game_id);
+                }
+                None => issues.push(BracketIssue::MissingSourceGame {
+                    game: *game_id,
+                    source: source_id,
+                }),
+            }
+        }
+        if matches!(
+            (home, away),
+            (TeamSource::LoserOf(_), TeamSource::LoserOf(_))
+        ) {
+            only_losers.insert(*game_id);
+        }
+    }
+
+    issues.extend(
+        petgraph::algo::tarjan_scc(&graph)
+            .into_iter()
+            .filter(|component| component.len() > 1)
+            .map(|component| {
+                BracketIssue::Cycle(
+                    component
+                        .into_iter()
+                        .map(|idx| graph[idx])
+                        .sorted()
+                        .collect(),
+                )
+            }),
+    );
+    issues.extend(
+        winner_usage
+            .iter()
+            .filter(|(_, users)| users.len() > 1)
+            .map(|(source, users)| BracketIssue::WinnerUsedTwice {
+                source: *source,
+                games: users.clone(),
+            }),
+    );
+    issues.extend(loser_usage.iter().filter(|(_, users)| users.len() > 1).map(
+        |(source, users)| BracketIssue::LoserUsedTwice {
+            source: *source,
+            games: users.clone(),
+        },
+    ));
+
+    let Some(final_node) = game_to_node.get(&final_game_id).copied() else {
+        issues.push(BracketIssue::FinalGameNotFound(final_game_id));
+        return issues;
+    };
+    issues.extend(
+        loser_usage
+            .iter()
+            .filter(|(_, users)| users.contains(&final_game_id))
+            .map(|(source, _)| BracketIssue::LoserToFinal { source: *source }),
+    );
+
+    // All games with a path to the final.
+    let mut leads_to_final = HashSet::new();
+    let mut bfs = Bfs::new(Reversed(&graph), final_node);
+    while let Some(node) = bfs.next(Reversed(&graph)) {
+        leads_to_final.insert(node);
+    }
+
+    for node in graph.node_indices().filter(|node| *node != final_node) {
+        let game_id = graph[node];
+        if !winner_usage.contains_key(&game_id) {
+            if !only_losers.contains(&game_id) {
+                issues.push(BracketIssue::ExtraFinal(game_id));
+            }
+        } else if !leads_to_final.contains(&node) {
+            issues.push(BracketIssue::Unreachable(game_id));
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::group::{GroupId, GroupOutcome};
+
+    fn winner(group: char) -> TeamSource {
+        TeamSource::GroupOutcome(GroupOutcome::Winner(GroupId::try_from(group).unwrap()))
+    }
+
+    fn runner_up(group: char) -> TeamSource {
+        TeamSource::GroupOutcome(GroupOutcome::RunnerUp(GroupId::try_from(group).unwrap()))
+    }
+
+    fn w(id: u32) -> TeamSource {
+        TeamSource::WinnerOf(GameId::from(id))
+    }
+
+    fn l(id: u32) -> TeamSource {
+        TeamSource::LoserOf(GameId::from(id))
+    }
+
+    fn template(games: Vec<(u32, (TeamSource, TeamSource))>, final_id: u32) -> BracketTemplate {
+        BracketTemplate {
+            games: games
+                .into_iter()
+                .map(|(id, sources)| (GameId::from(id), sources))
+                .collect(),
+            final_game_id: GameId::from(final_id),
+        }
+    }
+
+    /// Semi-finals, third-place playoff and final
+    fn four_team_bracket() -> Vec<(u32, (TeamSource, TeamSource))> {
+        vec![
+            (1, (winner('A'), runner_up('B'))),
+            (2, (winner('B'), runner_up('A'))),
+            (3, (l(1), l(2))),
+            (4, (w(1), w(2))),
+        ]
+    }
+
+    #[test]
+    fn valid_bracket_with_third_place_game() {
+        assert_eq!(template(four_team_bracket(), 4).validate(), Ok(()));
+    }
+
+    #[test]
+    fn team_sources_use_last_game_as_final() {
+        let sources = template(four_team_bracket(), 4).games;
+        assert_eq!(validate_team_sources(&sources), Ok(()));
+        assert_eq!(
+            validate_team_sources(&[]),
+            Err(InvalidBracket(vec![BracketIssue::NoGames]))
+        );
+    }
+
+    #[test]
+    fn multiple_finals() {
+        let mut games = four_team_bracket();
+        games.push((5, (winner('C'), runner_up('D'))));
+        let issues = template(games, 4).validate().unwrap_err();
+        assert_eq!(issues.0, vec![BracketIssue::ExtraFinal(GameId::from(5))]);
+    }
+
+    #[test]
+    fn winner_used_twice_and_unreachable() {
+        let games = vec![
+            (1, (winner('A'), runner_up('B'))),
+            (2, (winner('B'), runner_up('A'))),
+            (3, (w(1), winner('C'))),
+            (4, (w(1), w(2))),
+        ];
+        let issues = template(games, 4).validate().unwrap_err();
+        assert!(issues.issues().any(|issue| *issue
+            == BracketIssue::WinnerUsedTwice {
+                source: GameId::from(1),
+                games: vec![GameId::from(3), GameId::from(4)]
+            }));
+        assert!(
+            issues
+                .issues()
+                .any(|issue| *issue == BracketIssue::ExtraFinal(GameId::from(3)))
+        );
+    }
+
+    #[test]
+    fn unreachable_game() {
+        let games = vec![
+            (1, (winner('A'), runner_up('B'))),
+            (2, (winner('B'), runner_up('A'))),
+            (3, (winner('C'), runner_up('D'))),
+            (4, (w(3), winner('D'))),
+            (5, (w(1), w(2))),
+            (6, (w(4), l(5))),
+        ];
+        let issues = template(games, 5).validate().unwrap_err();
+        assert!(
+            issues
+                .issues()
+                .any(|issue| *issue == BracketIssue::Unreachable(GameId::from(3)))
+        );
+        assert!(
+            issues
+                .issues()
+                .any(|issue| *issue == BracketIssue::Unreachable(GameId::from(4)))
+        );
+        assert!(
+            issues
+                .issues()
+                .any(|issue| *issue == BracketIssue::ExtraFinal(GameId::from(6)))
+        );
+    }
+
+    #[test]
+    fn loser_to_final() {
+        let games = vec![
+            (1, (winner('A'), runner_up('B'))),
+            (2, (winner('B'), runner_up('A'))),
+            (3, (w(1), l(2))),
+        ];
+        let issues = template(games, 3).validate().unwrap_err();
+        assert!(issues.issues().any(|issue| *issue
+            == BracketIssue::LoserToFinal {
+                source: GameId::from(2)
+            }));
+        // Game 2's winner goes nowhere.
+        assert!(
+            issues
+                .issues()
+                .any(|issue| *issue == BracketIssue::ExtraFinal(GameId::from(2)))
+        );
+    }
+
+    #[test]
+    fn reports_all_reference_problems() {
+        let games = vec![
+            (1, (winner('A'), w(1))),
+            (2, (w(9), runner_up('A'))),
+            (2, (winner('B'), runner_up('B'))),
+            (3, (w(1), w(2))),
+        ];
+        let issues = template(games, 7).validate().unwrap_err();
+        assert!(
+            issues
+                .issues()
+                .any(|issue| *issue == BracketIssue::DuplicateGame(GameId::from(2)))
+        );
+        assert!(
+            issues
+                .issues()
+                .any(|issue| *issue == BracketIssue::SelfReference(GameId::from(1)))
+        );
+        assert!(issues.issues().any(|issue| *issue
+            == BracketIssue::MissingSourceGame {
+                game: GameId::from(2),
+                source: GameId::from(9)
+            }));
+        assert!(
+            issues
+                .issues()
+                .any(|issue| *issue == BracketIssue::FinalGameNotFound(GameId::from(7)))
+        );
+    }
+
+    #[test]
+    fn cycle() {
+        let games = vec![
+            (1, (winner('A'), w(2))),
+            (2, (winner('B'), w(1))),
+            (3, (l(1), l(2))),
+        ];
+        let issues = template(games, 3).validate().unwrap_err();
+        assert!(
+            issues
+                .issues()
+                .any(|issue| *issue == BracketIssue::Cycle(vec![GameId::from(1), GameId::from(2)]))
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! Jokers
+//!
+//! A competitor can pick a limited number of games per stage as jokers, which multiplies the
+//! points of those games (double points by default). Jokers are locked at kickoff: a joker can
+//! not be added to, or removed from, a game which has started.
+//!
+//! The scoring itself is part of the leaderboard, see
+//! [`LeaderboardRules::with_jokers`](crate::leaderboard::LeaderboardRules::with_jokers).
+use crate::game::GameId;
+use crate::group::Groups;
+use crate::playoff::BracketStructure;
+use crate::pred_score::scheme::Stage;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use thiserror::Error;
+
+/// Number of jokers per stage and their effect
+///
+/// The default allows no jokers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JokerRules {
+    /// Points multiplier of a joker game
+    pub multiplier: f32,
+    /// Jokers in the group stage
+    pub group: usize,
+    /// Jokers per playoff round, indexed by depth, 0 = final
+    #[serde(default)]
+    pub playoff_rounds: Vec<usize>,
+}
+
+impl Default for JokerRules {
+    fn default() -> Self {
+        JokerRules {
+            multiplier: 2.0,
+            group: 0,
+            playoff_rounds: Vec::new(),
+        }
+    }
+}
+
+impl JokerRules {
+    /// Number of jokers allowed in a stage
+    pub fn allowed(&self, stage: Stage) -> usize {
+        match stage {
+            Stage::Group => self.group,
+            Stage::Playoff { depth } => self.playoff_rounds.get(depth).copied().unwrap_or(0),
+            Stage::Standings | Stage::Bonus => 0,
+        }
+    }
+
+    /// Validate a new joker selection against the previous one
+    ///
+    /// `stages` must contain every game of the tournament and `locked` the games which have
+    /// kicked off.
+    pub fn validate(
+        &self,
+        jokers: &[GameId],
+        previous: &[GameId],
+        stages: &HashMap<GameId, Stage>,
+        locked: &HashSet<GameId>,
+    ) -> Result<(), JokerError> {
+        let mut seen = HashSet::new();
+        let mut per_stage: HashMap<Stage, usize> = HashMap::new();
+        for game_id in jokers {
+            if !seen.insert(*game_id) {
+                return Err(JokerError::Duplicate(*game_id));
+            }
+            let stage = stages
+                .get(game_id)
+                .ok_or(JokerError::UnknownGame(*game_id))?;
+            let count = per_stage.entry(*stage).or_default();
+            *count += 1;
+            if *count > self.allowed(*stage) {
+                return Err(JokerError::TooMany {
+                    stage: *stage,
+                    allowed: self.allowed(*stage),
+                });
+            }
+        }
+
+        let previous: HashSet<GameId> = previous.iter().copied().collect();
+        match seen
+            .symmetric_difference(&previous)
+            .find(|game_id| locked.contains(game_id))
+        {
+            Some(game_id) => Err(JokerError::Locked(*game_id)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Stage of every game in the tournament
+///
+/// Playoff games off the path to the final (third-place playoff) belong to the final round.
+pub fn game_stages(groups: &Groups, bracket: Option<&BracketStructure>) -> HashMap<GameId, Stage> {
+    let group_games = groups.values().flat_map(|group| {
+        group
+            .played_games()
+            .map(|game| game.id)
+            .chain(group.unplayed_games().map(|game| game.id))
+    });
+    let mut stages: HashMap<GameId, Stage> =
+        group_games.map(|game_id| (game_id, Stage::Group)).collect();
+    if let Some(bracket) = bracket {
+        let depths = bracket.game_depths();
+        stages.extend(bracket.all_game_ids().map(|game_id| {
+            let depth = depths.get(&game_id).copied().unwrap_or_default();
+            (game_id, Stage::Playoff { depth })
+        }));
+    }
+    stages
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JokerError {
+    #[error("Game {0} picked as joker more than once")]
+    Duplicate(GameId),
+    #[error("No game with id {0}")]
+    UnknownGame(GameId),
+    #[error("Only {allowed} jokers allowed in stage {stage:?}")]
+    TooMany { stage: Stage, allowed: usize },
+    #[error("Game {0} has kicked off, its joker is locked")]
+    Locked(GameId),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::group::mock_data::groups_and_teams;
+
+    fn ids(ids: &[u32]) -> Vec<GameId> {
+        ids.iter().copied().map(GameId::from).collect()
+    }
+
+    fn rules() -> JokerRules {
+        JokerRules {
+            group: 2,
+            ..JokerRules::default()
+        }
+    }
+
+    #[test]
+    fn limits_per_stage() {
+        let (groups, _) = groups_and_teams();
+        let stages = game_stages(&groups, None);
+        assert_eq!(stages.len(), 4);
+        let locked = HashSet::new();
+
+        assert_eq!(
+            rules().validate(&ids(&[2, 3]), &[], &stages, &locked),
+            Ok(())
+        );
+        assert_eq!(
+            rules().validate(&ids(&[2, 3, 4]), &[], &stages, &locked),
+            Err(JokerError::TooMany {
+                stage: Stage::Group,
+                allowed: 2
+            })
+        );
+        assert_eq!(
+            rules().validate(&ids(&[2, 2]), &[], &stages, &locked),
+            Err(JokerError::Duplicate(GameId::from(2)))
+        );
+        assert_eq!(
+            rules().validate(&ids(&[9]), &[], &stages, &locked),
+            Err(JokerError::UnknownGame(GameId::from(9)))
+        );
+        assert_eq!(
+            JokerRules::default().validate(&ids(&[2]), &[], &stages, &locked),
+            Err(JokerError::TooMany {
+                stage: Stage::Group,
+                allowed: 0
+            })
+        );
+    }
+
+    #[test]
+    fn locked_at_kickoff() {
+        let (groups, _) = groups_and_teams();
+        let stages = game_stages(&groups, None);
+        // Game 1 is played
+        let locked: HashSet<GameId> = ids(&[1]).into_iter().collect();
+
+        // Keeping a locked joker and moving an open one is fine
+        assert_eq!(
+            rules().validate(&ids(&[1, 3]), &ids(&[1, 2]), &stages, &locked),
+            Ok(())
+        );
+        assert_eq!(
+            rules().validate(&ids(&[2]), &ids(&[1]), &stages, &locked),
+            Err(JokerError::Locked(GameId::from(1)))
+        );
+        assert_eq!(
+            rules().validate(&ids(&[1]), &[], &stages, &locked),
+            Err(JokerError::Locked(GameId::from(1)))
+        );
+    }
+}
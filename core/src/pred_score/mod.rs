@@ -4,11 +4,23 @@
 //! This module defines various measurements of the quality of a prediction
 //!
 //! NB: This module is intended as a hands-on introduction to Rust and the codebase.
+//!
+//! Scoring of playoff predictions lives in the [`playoff`] submodule, serializable,
+//! configurable schemes (e.g. Kicktipp 4/3/2) in the [`scheme`] submodule and proper scoring
+//! rules for probabilistic predictions in the [`proba`] submodule.
+//! Double-points picks are defined in the [`joker`] submodule and bonus points for predicted
+//! group standings in the [`standings`] submodule.
+
+pub mod joker;
+pub mod playoff;
+pub mod proba;
+pub mod scheme;
+pub mod standings;
 
 // 'use' statements import code from other modules
 // Imports starting with 'crate' are internal to this crate (`core`)
 // External crates are listed in core/Cargo.toml
-use crate::group::game::GroupGameScore;
+use crate::group::game::{GroupGameOutcome, GroupGameScore};
 use derive_more::{Add, AddAssign, Display, From, Into, Neg, Sub, Sum};
 use serde::{Deserialize, Serialize};
 
@@ -22,6 +34,9 @@ pub trait PredScoreFn {
     // By only providing a method definition, we require the implementor to implement it.
     fn pred_score(&self, pred: &GroupGameScore, truth: &GroupGameScore) -> PredScore;
 
+    // Score of an outcome-only (1X2) prediction, see `crate::player::PredictionMode`.
+    fn outcome_score(&self, pred: GroupGameOutcome, truth: &GroupGameScore) -> PredScore;
+
     // We can also give a default impl. The implementor can override it, but doesn't have to.
     // This is useful to reduce code duplication.
     //
@@ -40,12 +55,22 @@ pub trait PredScoreFn {
 // Here is an example of a concrete type that implements the `PredScoreFn` trait.
 // We are free to give it any parameters (fields) we want, here the weights for the two terms in the score
 // fn.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct SimplePredScoreFn {
     outcome_weight: f32,
     result_weight: f32,
 }
 
+// Since the fields are private, code outside this module needs a constructor.
+impl SimplePredScoreFn {
+    pub fn new(outcome_weight: f32, result_weight: f32) -> Self {
+        SimplePredScoreFn {
+            outcome_weight,
+            result_weight,
+        }
+    }
+}
+
 // A trait is implemented by providing this type of `impl TraitX for ConcreteTypeY` block
 // if this block does not implement all the methods specified (and without defaults) in the above `PredScoreFn` trait,
 // the compiler will give an error.
@@ -67,6 +92,11 @@ impl PredScoreFn for SimplePredScoreFn {
         // return PredScore(score);
         // but it is considered un-idiomatic.
     }
+
+    // Only the outcome part of the score applies
+    fn outcome_score(&self, pred: GroupGameOutcome, truth: &GroupGameScore) -> PredScore {
+        PredScore((pred == truth.home_outcome()) as u8 as f32 * self.outcome_weight)
+    }
 }
 
 // Perhaps you noticed that the `PredScoreFn.pred_score` function in the trait above had `PredScore` as the return type,
@@ -0,0 +1,288 @@
+//! Playoff prediction scores
+//!
+//! A playoff prediction is a complete predicted [`BracketState`], where the teams in later games
+//! follow from the player's own earlier predictions. It is scored by comparing it to the real
+//! state through the shared [`BracketStructure`]:
+//!
+//! - **Progression**: points for every team correctly predicted to reach a round,
+//!   regardless of which game (path) the team was predicted to get there through.
+//! - **Winner**: points for every game where the predicted winner is the real winner.
+//! - **Result**: points for the exact score after regular (and extra) time.
+//! - **Shoot-out**: bonus for correctly predicting that a game is decided by penalties.
+//!
+//! A penalty shoot-out only decides the winner, the shoot-out score itself is never compared.
+//! E.g. a predicted 1-1 (4-3 pen.) against a real 1-1 (2-4 pen.) gets the result and shoot-out
+//! points, but not the winner points.
+use crate::playoff::bracket::EdgeType;
+use crate::playoff::{BracketState, BracketStructure, PlayoffResult};
+use crate::pred_score::PredScore;
+use crate::team::TeamId;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+/// Scoring of a predicted playoff bracket
+pub trait PlayoffPredScoreFn {
+    /// Score for a single game, `pred` and `truth` are results of the same game
+    fn game_score(&self, pred: &PlayoffResult, truth: &PlayoffResult) -> PredScore;
+
+    /// Score for a single team correctly predicted to reach the round at `depth`
+    ///
+    /// Depth 0 = final, depth 1 = semifinals, etc.
+    fn round_score(&self, depth: usize) -> PredScore;
+
+    /// Total score of a predicted bracket
+    ///
+    /// Only games played in `truth` contribute, so this can be computed during the tournament.
+    fn bracket_score(
+        &self,
+        bracket: &BracketStructure,
+        pred: &BracketState,
+        truth: &BracketState,
+    ) -> PredScore {
+        let game_scores: PredScore = truth
+            .played_games()
+            .filter_map(|(game_id, truth)| pred.result(*game_id).map(|pred| (pred, truth)))
+            .map(|(pred, truth)| self.game_score(pred, truth))
+            .sum();
+        let round_scores: PredScore = self.round_scores(bracket, pred, truth).into_values().sum();
+        game_scores + round_scores
+    }
+
+    /// Progression score per round (depth), see [`teams_by_round`]
+    fn round_scores(
+        &self,
+        bracket: &BracketStructure,
+        pred: &BracketState,
+        truth: &BracketState,
+    ) -> BTreeMap<usize, PredScore> {
+        let pred_rounds = teams_by_round(bracket, pred);
+        teams_by_round(bracket, truth)
+            .into_iter()
+            .filter_map(|(depth, teams)| pred_rounds.get(&depth).map(|pred| (depth, pred, teams)))
+            .map(|(depth, pred, teams)| {
+                let score = pred
+                    .intersection(&teams)
+                    .map(|_| self.round_score(depth))
+                    .sum();
+                (depth, score)
+            })
+            .collect()
+    }
+}
+
+/// Teams known to have reached each round of the bracket
+///
+/// A team has reached a round if it played a game at that depth, or if it won (lost) a game
+/// which feeds into a game at that depth. Games off the path to the final, e.g. a third-place
+/// playoff, do not belong to any round.
+pub fn teams_by_round(
+    bracket: &BracketStructure,
+    state: &BracketState,
+) -> BTreeMap<usize, HashSet<TeamId>> {
+    let depths = bracket.game_depths();
+    let mut rounds: BTreeMap<usize, HashSet<TeamId>> = BTreeMap::new();
+    for (game_id, result) in state.played_games() {
+        if let Some(depth) = depths.get(game_id) {
+            rounds
+                .entry(*depth)
+                .or_default()
+                .extend([result.home, result.away]);
+        }
+        for (edge_type, team) in [
+            (EdgeType::Winner, result.winner()),
+            (EdgeType::Loser, result.loser()),
+        ] {
+            bracket
+                .dependents(*game_id, edge_type)
+                .iter()
+                .filter_map(|next_game| depths.get(next_game))
+                .for_each(|depth| {
+                    rounds.entry(*depth).or_default().insert(team);
+                });
+        }
+    }
+    rounds
+}
+
+/// Playoff score with fixed weights
+///
+/// `round_weights[depth]` is awarded per team correctly predicted to reach that round,
+/// rounds without a weight give no points.
+/// The result and shoot-out points require the predicted game to have the real teams.
+/// The default has no weights, i.e. the playoff is not scored.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SimplePlayoffPredScoreFn {
+    pub round_weights: Vec<f32>,
+    pub winner_weight: f32,
+    pub result_weight: f32,
+    pub shootout_weight: f32,
+}
+
+impl PlayoffPredScoreFn for SimplePlayoffPredScoreFn {
+    fn game_score(&self, pred: &PlayoffResult, truth: &PlayoffResult) -> PredScore {
+        let correct_winner = pred.winner() == truth.winner();
+        let correct_teams = pred.home == truth.home && pred.away == truth.away;
+        let correct_result =
+            correct_teams && pred.score.regular_time_score() == truth.score.regular_time_score();
+        let correct_shootout =
+            correct_teams && pred.score.went_to_penalties() && truth.score.went_to_penalties();
+        PredScore(
+            correct_winner as u8 as f32 * self.winner_weight
+                + correct_result as u8 as f32 * self.result_weight
+                + correct_shootout as u8 as f32 * self.shootout_weight,
+        )
+    }
+
+    fn round_score(&self, depth: usize) -> PredScore {
+        PredScore(self.round_weights.get(depth).copied().unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameId, GoalCount};
+    use crate::group::{GroupId, GroupOutcome};
+    use crate::playoff::{PlayoffScore, TeamSource};
+    use assert_approx_eq::assert_approx_eq;
+
+    fn group_winner(group: char) -> TeamSource {
+        TeamSource::GroupOutcome(GroupOutcome::Winner(GroupId::try_from(group).unwrap()))
+    }
+
+    fn group_runner_up(group: char) -> TeamSource {
+        TeamSource::GroupOutcome(GroupOutcome::RunnerUp(GroupId::try_from(group).unwrap()))
+    }
+
+    /// Semi-finals (1, 2), third-place playoff (3) and final (4)
+    fn four_team_bracket() -> BracketStructure {
+        let sources = vec![
+            (GameId::from(1), (group_winner('A'), group_runner_up('B'))),
+            (GameId::from(2), (group_winner('B'), group_runner_up('A'))),
+            (
+                GameId::from(3),
+                (
+                    TeamSource::LoserOf(GameId::from(1)),
+                    TeamSource::LoserOf(GameId::from(2)),
+                ),
+            ),
+            (
+                GameId::from(4),
+                (
+                    TeamSource::WinnerOf(GameId::from(1)),
+                    TeamSource::WinnerOf(GameId::from(2)),
+                ),
+            ),
+        ];
+        BracketStructure::from_team_sources(&sources).unwrap()
+    }
+
+    fn score(home: u32, away: u32) -> PlayoffScore {
+        PlayoffScore::regular_time(
+            GoalCount::try_from(home).unwrap(),
+            GoalCount::try_from(away).unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn penalties(goals: u32, home_pen: u32, away_pen: u32) -> PlayoffScore {
+        PlayoffScore::with_penalties(
+            GoalCount::try_from(goals).unwrap(),
+            GoalCount::try_from(goals).unwrap(),
+            GoalCount::try_from(home_pen).unwrap(),
+            GoalCount::try_from(away_pen).unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn play(games: &[(u32, u32, u32, PlayoffScore)]) -> BracketState {
+        games.iter().fold(
+            BracketState::new(),
+            |state, (game_id, home, away, score)| {
+                state.play_game(GameId::from(*game_id), TeamId(*home), TeamId(*away), *score)
+            },
+        )
+    }
+
+    fn score_fn() -> SimplePlayoffPredScoreFn {
+        SimplePlayoffPredScoreFn {
+            round_weights: vec![4.0, 2.0],
+            winner_weight: 3.0,
+            result_weight: 1.0,
+            shootout_weight: 0.5,
+        }
+    }
+
+    #[test]
+    fn rounds_follow_the_bracket() {
+        let bracket = four_team_bracket();
+        // Only the semi-finals played: finalists are known, third-place game has no round
+        let state = play(&[(1, 1, 2, score(1, 0)), (2, 3, 4, score(0, 2))]);
+        let rounds = teams_by_round(&bracket, &state);
+        assert_eq!(rounds.len(), 2);
+        assert_eq!(
+            rounds[&1],
+            HashSet::from([TeamId(1), TeamId(2), TeamId(3), TeamId(4)])
+        );
+        assert_eq!(rounds[&0], HashSet::from([TeamId(1), TeamId(4)]));
+    }
+
+    #[test]
+    fn perfect_prediction() {
+        let bracket = four_team_bracket();
+        let truth = play(&[
+            (1, 1, 2, score(1, 0)),
+            (2, 3, 4, score(0, 2)),
+            (3, 2, 3, score(2, 1)),
+            (4, 1, 4, score(3, 1)),
+        ]);
+        // 4 games * (3 + 1) + 4 semi-finalists * 2 + 2 finalists * 4
+        assert_approx_eq!(
+            f32::from(score_fn().bracket_score(&bracket, &truth, &truth)),
+            32.0
+        );
+    }
+
+    #[test]
+    fn team_reaching_round_through_different_path() {
+        let bracket = four_team_bracket();
+        let pred = play(&[
+            (1, 1, 2, score(1, 0)),
+            (2, 3, 4, score(2, 0)),
+            (4, 1, 3, score(1, 0)),
+        ]);
+        let truth = play(&[
+            (1, 3, 2, score(0, 1)),
+            (2, 1, 4, score(2, 0)),
+            (4, 2, 1, score(0, 1)),
+        ]);
+        // Winners: only the final (team 1)
+        // Rounds: all 4 semi-finalists, team 1 in the final (via game 2 instead of game 1)
+        assert_approx_eq!(
+            f32::from(score_fn().bracket_score(&bracket, &pred, &truth)),
+            3.0 + 4.0 * 2.0 + 4.0
+        );
+    }
+
+    #[test]
+    fn penalty_shootouts() {
+        let score_fn = score_fn();
+        let truth = PlayoffResult::new(TeamId(1), TeamId(2), penalties(1, 2, 4));
+
+        // Right draw, wrong shoot-out winner
+        let pred = PlayoffResult::new(TeamId(1), TeamId(2), penalties(1, 4, 3));
+        assert_approx_eq!(f32::from(score_fn.game_score(&pred, &truth)), 1.5);
+
+        // Right draw and shoot-out winner, shoot-out score is not compared
+        let pred = PlayoffResult::new(TeamId(1), TeamId(2), penalties(1, 3, 5));
+        assert_approx_eq!(f32::from(score_fn.game_score(&pred, &truth)), 4.5);
+
+        // Right winner in regular time
+        let pred = PlayoffResult::new(TeamId(1), TeamId(2), score(0, 1));
+        assert_approx_eq!(f32::from(score_fn.game_score(&pred, &truth)), 3.0);
+
+        // Right score, but for another pairing
+        let pred = PlayoffResult::new(TeamId(3), TeamId(2), penalties(1, 2, 4));
+        assert_approx_eq!(f32::from(score_fn.game_score(&pred, &truth)), 3.0);
+    }
+}
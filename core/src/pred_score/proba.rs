@@ -0,0 +1,343 @@
+//! Probabilistic predictions
+//!
+//! Bots often predict probabilities rather than a single scoreline. A [`ProbForecast`] is either
+//! a win/draw/loss (1X2) distribution or a distribution over scorelines, which is reduced to its
+//! 1X2 marginal when scored.
+//!
+//! Forecasts are scored with proper scoring rules, lower is better for all of them:
+//!
+//! - **Brier score**: sum of squared errors over the three outcomes, in \[0, 2\].
+//! - **Log loss**: negative log probability of the real outcome.
+//! - **Ranked probability score (RPS)**: like Brier, but on the cumulative distribution, so that
+//!   a predicted home win is closer to a draw than to an away win. In \[0, 1\].
+//!
+//! See [`proba_table`] for the scores of all competitors, next to the points based
+//! [`Leaderboard`](crate::leaderboard::Leaderboard).
+use crate::game::GameId;
+use crate::group::Groups;
+use crate::group::game::{GroupGameOutcome, GroupGameScore};
+use crate::leaderboard::Competitor;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Allowed deviation from a total probability of 1
+const SUM_TOLERANCE: f32 = 1e-3;
+/// Probabilities are clamped to this before taking the log, so a confident miss costs a lot,
+/// but not infinitely much
+const MIN_LOG_PROB: f32 = 1e-15;
+
+/// Probabilities of home win, draw and away win
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OutcomeProbs {
+    pub home: f32,
+    pub draw: f32,
+    pub away: f32,
+}
+
+impl OutcomeProbs {
+    pub fn try_new(home: f32, draw: f32, away: f32) -> Result<Self, ProbaError> {
+        let probs = OutcomeProbs { home, draw, away };
+        probs.validate()?;
+        Ok(probs)
+    }
+
+    pub fn validate(&self) -> Result<(), ProbaError> {
+        validate_probs([self.home, self.draw, self.away])
+    }
+
+    /// Probability of an outcome, from the home team's perspective
+    pub fn prob(&self, outcome: GroupGameOutcome) -> f32 {
+        match outcome {
+            GroupGameOutcome::Win => self.home,
+            GroupGameOutcome::Draw => self.draw,
+            GroupGameOutcome::Lose => self.away,
+        }
+    }
+
+    /// Brier score against the real outcome
+    pub fn brier(&self, outcome: GroupGameOutcome) -> f32 {
+        self.as_array()
+            .iter()
+            .zip(one_hot(outcome))
+            .map(|(prob, truth)| (prob - truth).powi(2))
+            .sum()
+    }
+
+    /// Log loss (natural log) against the real outcome
+    pub fn log_loss(&self, outcome: GroupGameOutcome) -> f32 {
+        -self.prob(outcome).max(MIN_LOG_PROB).ln()
+    }
+
+    /// Ranked probability score against the real outcome
+    pub fn rps(&self, outcome: GroupGameOutcome) -> f32 {
+        let (pred, truth) = (self.as_array(), one_hot(outcome));
+        let (mut pred_cum, mut truth_cum, mut sum) = (0.0, 0.0, 0.0);
+        // The last cumulative probability is always 1 for both
+        for (pred, truth) in pred.iter().zip(truth).take(2) {
+            pred_cum += pred;
+            truth_cum += truth;
+            sum += (pred_cum - truth_cum).powi(2);
+        }
+        sum / 2.0
+    }
+
+    /// Ordered home win, draw, away win
+    fn as_array(&self) -> [f32; 3] {
+        [self.home, self.draw, self.away]
+    }
+}
+
+fn one_hot(outcome: GroupGameOutcome) -> [f32; 3] {
+    match outcome {
+        GroupGameOutcome::Win => [1.0, 0.0, 0.0],
+        GroupGameOutcome::Draw => [0.0, 1.0, 0.0],
+        GroupGameOutcome::Lose => [0.0, 0.0, 1.0],
+    }
+}
+
+fn validate_probs(probs: impl IntoIterator<Item = f32>) -> Result<(), ProbaError> {
+    let mut sum = 0.0;
+    for prob in probs {
+        if !(0.0..=1.0).contains(&prob) {
+            return Err(ProbaError::InvalidProb(prob));
+        }
+        sum += prob;
+    }
+    if (sum - 1.0).abs() > SUM_TOLERANCE {
+        return Err(ProbaError::NotNormalised(sum));
+    }
+    Ok(())
+}
+
+/// Probabilistic prediction of a single game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProbForecast {
+    /// Win/draw/loss (1X2) probabilities
+    Outcome(OutcomeProbs),
+    /// Probabilities of exact scorelines, must cover (practically) all probability mass
+    Scores { scores: Vec<(GroupGameScore, f32)> },
+}
+
+impl ProbForecast {
+    pub fn validate(&self) -> Result<(), ProbaError> {
+        match self {
+            ProbForecast::Outcome(probs) => probs.validate(),
+            ProbForecast::Scores { scores } => validate_probs(scores.iter().map(|(_, prob)| *prob)),
+        }
+    }
+
+    /// 1X2 probabilities, marginalised over scorelines if needed
+    pub fn outcome_probs(&self) -> OutcomeProbs {
+        match self {
+            ProbForecast::Outcome(probs) => *probs,
+            ProbForecast::Scores { scores } => scores.iter().fold(
+                OutcomeProbs {
+                    home: 0.0,
+                    draw: 0.0,
+                    away: 0.0,
+                },
+                |mut probs, (score, prob)| {
+                    match score.home_outcome() {
+                        GroupGameOutcome::Win => probs.home += prob,
+                        GroupGameOutcome::Draw => probs.draw += prob,
+                        GroupGameOutcome::Lose => probs.away += prob,
+                    }
+                    probs
+                },
+            ),
+        }
+    }
+}
+
+/// Probabilistic prediction of a game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProbPrediction(pub GameId, pub ProbForecast);
+
+/// All probabilistic predictions of a single competitor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompetitorProbPredictions {
+    pub competitor: Competitor,
+    pub preds: Vec<ProbPrediction>,
+}
+
+/// Mean proper scores of a competitor over the played games it has forecasts for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProbScoreRow {
+    pub competitor: Competitor,
+    /// Number of scored games
+    pub games: usize,
+    pub brier: f32,
+    pub log_loss: f32,
+    pub rps: f32,
+}
+
+/// Proper scores of all competitors, best (lowest) mean RPS first
+///
+/// Competitors without any forecast for a played game are left out, since their means are
+/// undefined.
+pub fn proba_table(preds: &[CompetitorProbPredictions], groups: &Groups) -> Vec<ProbScoreRow> {
+    let outcomes: HashMap<GameId, GroupGameOutcome> = groups
+        .values()
+        .flat_map(|group| group.played_games())
+        .map(|game| (game.id, game.score.home_outcome()))
+        .collect();
+
+    let mut rows: Vec<ProbScoreRow> = preds
+        .iter()
+        .filter_map(|competitor_preds| {
+            let scores: Vec<(f32, f32, f32)> = competitor_preds
+                .preds
+                .iter()
+                .filter_map(|ProbPrediction(game_id, forecast)| {
+                    let outcome = *outcomes.get(game_id)?;
+                    let probs = forecast.outcome_probs();
+                    Some((
+                        probs.brier(outcome),
+                        probs.log_loss(outcome),
+                        probs.rps(outcome),
+                    ))
+                })
+                .collect();
+            if scores.is_empty() {
+                return None;
+            }
+            // There are far fewer games than u16::MAX in a tournament
+            let num_games = f32::from(u16::try_from(scores.len()).unwrap_or(u16::MAX));
+            let mean =
+                |f: fn(&(f32, f32, f32)) -> f32| scores.iter().map(f).sum::<f32>() / num_games;
+            Some(ProbScoreRow {
+                competitor: competitor_preds.competitor.clone(),
+                games: scores.len(),
+                brier: mean(|s| s.0),
+                log_loss: mean(|s| s.1),
+                rps: mean(|s| s.2),
+            })
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        a.rps
+            .total_cmp(&b.rps)
+            .then_with(|| a.competitor.cmp(&b.competitor))
+    });
+    rows
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq)]
+pub enum ProbaError {
+    #[error("Probability {0} is not in [0, 1]")]
+    InvalidProb(f32),
+    #[error("Probabilities sum to {0}, not 1")]
+    NotNormalised(f32),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GoalCount;
+    use crate::group::mock_data::groups_and_teams;
+    use crate::player::PlayerId;
+    use assert_approx_eq::assert_approx_eq;
+
+    fn score(home: u32, away: u32) -> GroupGameScore {
+        GroupGameScore::new(
+            GoalCount::try_from(home).unwrap(),
+            GoalCount::try_from(away).unwrap(),
+        )
+    }
+
+    #[test]
+    fn proper_scores() {
+        let probs = OutcomeProbs::try_new(0.5, 0.3, 0.2).unwrap();
+        assert_approx_eq!(probs.brier(GroupGameOutcome::Win), 0.25 + 0.09 + 0.04);
+        assert_approx_eq!(probs.log_loss(GroupGameOutcome::Draw), -(0.3_f32.ln()));
+        // Cumulative: (0.5, 0.8) vs. (1, 1)
+        assert_approx_eq!(probs.rps(GroupGameOutcome::Win), (0.25 + 0.04) / 2.0);
+        // Cumulative: (0.5, 0.8) vs. (0, 0)
+        assert_approx_eq!(probs.rps(GroupGameOutcome::Lose), (0.25 + 0.64) / 2.0);
+
+        let certain = OutcomeProbs::try_new(1.0, 0.0, 0.0).unwrap();
+        assert_approx_eq!(certain.brier(GroupGameOutcome::Win), 0.0);
+        assert_approx_eq!(certain.rps(GroupGameOutcome::Win), 0.0);
+        assert!(certain.log_loss(GroupGameOutcome::Lose).is_finite());
+    }
+
+    #[test]
+    fn rps_rewards_near_misses() {
+        let near = OutcomeProbs::try_new(0.0, 1.0, 0.0).unwrap();
+        let far = OutcomeProbs::try_new(0.0, 0.0, 1.0).unwrap();
+        assert_approx_eq!(
+            near.brier(GroupGameOutcome::Win),
+            far.brier(GroupGameOutcome::Win)
+        );
+        assert!(near.rps(GroupGameOutcome::Win) < far.rps(GroupGameOutcome::Win));
+    }
+
+    #[test]
+    fn validation() {
+        assert!(matches!(
+            OutcomeProbs::try_new(0.5, 0.3, 0.3),
+            Err(ProbaError::NotNormalised(_))
+        ));
+        assert_eq!(
+            OutcomeProbs::try_new(1.5, -0.3, -0.2),
+            Err(ProbaError::InvalidProb(1.5))
+        );
+        let scores = ProbForecast::Scores {
+            scores: vec![(score(1, 0), 0.6), (score(0, 0), 0.2)],
+        };
+        assert!(scores.validate().is_err());
+    }
+
+    #[test]
+    fn scoreline_marginals() {
+        let forecast = ProbForecast::Scores {
+            scores: vec![
+                (score(1, 0), 0.3),
+                (score(2, 1), 0.2),
+                (score(1, 1), 0.25),
+                (score(0, 1), 0.25),
+            ],
+        };
+        assert!(forecast.validate().is_ok());
+        let probs = forecast.outcome_probs();
+        assert_approx_eq!(probs.home, 0.5);
+        assert_approx_eq!(probs.draw, 0.25);
+        assert_approx_eq!(probs.away, 0.25);
+    }
+
+    #[test]
+    fn table_ranks_by_rps() {
+        // Only game 1 is played in the mock data: 2-1
+        let (groups, _) = groups_and_teams();
+        let forecast = |home, draw, away| {
+            ProbForecast::Outcome(OutcomeProbs::try_new(home, draw, away).unwrap())
+        };
+        let preds = vec![
+            CompetitorProbPredictions {
+                competitor: Competitor::bot(PlayerId::from(1), "uniform"),
+                preds: vec![
+                    ProbPrediction(GameId::from(1), forecast(0.34, 0.33, 0.33)),
+                    // Unplayed game, not scored
+                    ProbPrediction(GameId::from(2), forecast(1.0, 0.0, 0.0)),
+                ],
+            },
+            CompetitorProbPredictions {
+                competitor: Competitor::bot(PlayerId::from(2), "sharp"),
+                preds: vec![ProbPrediction(GameId::from(1), forecast(0.7, 0.2, 0.1))],
+            },
+            CompetitorProbPredictions {
+                competitor: Competitor::bot(PlayerId::from(3), "silent"),
+                preds: Vec::new(),
+            },
+        ];
+        let table = proba_table(&preds, &groups);
+        assert_eq!(table.len(), 2);
+        assert_eq!(
+            table[0].competitor,
+            Competitor::bot(PlayerId::from(2), "sharp")
+        );
+        assert_eq!(table[1].games, 1);
+    }
+}
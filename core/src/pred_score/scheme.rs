@@ -0,0 +1,395 @@
+//! Configurable scoring schemes
+//!
+//! A [`ScoringConfig`] is plain data: it is (de)serialized and stored once per tournament so that
+//! every client computes identical scores.
+//!
+//! Two kinds of schemes are supported:
+//!
+//! - [`ScoringScheme::Additive`]: independent terms which are summed, e.g. points for the
+//!   outcome *and* for each team's number of goals.
+//! - [`ScoringScheme::Tiered`]: only the best matching tier counts, e.g. the Kicktipp 4/3/2
+//!   scheme: exact result 4, correct goal difference 3, correct outcome 2.
+//!
+//! The scheme score is multiplied by a per-stage multiplier, see [`StageMultipliers`].
+//! Predicted playoff brackets are scored separately, see [`SimplePlayoffPredScoreFn`], and the
+//! number of jokers per stage is part of the config as well, see [`JokerRules`], as are the bonus
+//! points for predicted group standings, see [`StandingsRules`].
+//!
+//! In [`PredictionMode::Outcome`] group games are predicted as 1X2 only and a correct prediction
+//! gets the scheme's outcome points.
+use crate::group::game::{GroupGameOutcome, GroupGameScore};
+use crate::player::PredictionMode;
+use crate::pred_score::joker::JokerRules;
+use crate::pred_score::playoff::SimplePlayoffPredScoreFn;
+use crate::pred_score::standings::StandingsRules;
+use crate::pred_score::{PredScore, PredScoreFn, SimplePredScoreFn};
+use serde::{Deserialize, Serialize};
+
+/// Scoring of a single game prediction
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScoringScheme {
+    /// Sum of the weights of every correct part of the prediction
+    Additive {
+        /// Correct win/draw/loss
+        outcome: f32,
+        /// Correct goal difference (including draws)
+        goal_difference: f32,
+        /// Awarded once per team with the correct number of goals
+        team_goals: f32,
+        /// Exact result
+        result: f32,
+    },
+    /// Weight of the best matching tier only
+    ///
+    /// As in Kicktipp, the goal difference tier does not apply to draws:
+    /// a draw with the wrong score only counts as a correct outcome.
+    Tiered {
+        result: f32,
+        goal_difference: f32,
+        outcome: f32,
+    },
+}
+
+impl ScoringScheme {
+    /// Kicktipp style 4/3/2 scheme
+    pub fn kicktipp() -> Self {
+        ScoringScheme::Tiered {
+            result: 4.0,
+            goal_difference: 3.0,
+            outcome: 2.0,
+        }
+    }
+}
+
+impl From<SimplePredScoreFn> for ScoringScheme {
+    fn from(score_fn: SimplePredScoreFn) -> Self {
+        ScoringScheme::Additive {
+            outcome: score_fn.outcome_weight,
+            goal_difference: 0.0,
+            team_goals: 0.0,
+            result: score_fn.result_weight,
+        }
+    }
+}
+
+impl PredScoreFn for ScoringScheme {
+    fn pred_score(&self, pred: &GroupGameScore, truth: &GroupGameScore) -> PredScore {
+        let correct_outcome = pred.home_outcome() == truth.home_outcome();
+        let correct_goal_difference = pred.home - pred.away == truth.home - truth.away;
+        let correct_result = pred == truth;
+        let score = match *self {
+            ScoringScheme::Additive {
+                outcome,
+                goal_difference,
+                team_goals,
+                result,
+            } => {
+                let correct_team_goals =
+                    (pred.home == truth.home) as u8 + (pred.away == truth.away) as u8;
+                correct_outcome as u8 as f32 * outcome
+                    + correct_goal_difference as u8 as f32 * goal_difference
+                    + f32::from(correct_team_goals) * team_goals
+                    + correct_result as u8 as f32 * result
+            }
+            ScoringScheme::Tiered {
+                result,
+                goal_difference,
+                outcome,
+            } => {
+                let is_draw = truth.home == truth.away;
+                if correct_result {
+                    result
+                } else if correct_goal_difference && !is_draw {
+                    goal_difference
+                } else if correct_outcome {
+                    outcome
+                } else {
+                    0.0
+                }
+            }
+        };
+        PredScore(score)
+    }
+
+    fn outcome_score(&self, pred: GroupGameOutcome, truth: &GroupGameScore) -> PredScore {
+        let outcome = match *self {
+            ScoringScheme::Additive { outcome, .. } | ScoringScheme::Tiered { outcome, .. } => {
+                outcome
+            }
+        };
+        PredScore((pred == truth.home_outcome()) as u8 as f32 * outcome)
+    }
+}
+
+/// Stage of the tournament a game belongs to
+///
+/// Ordered as played: group stage first, then the final group standings, then playoff rounds from
+/// the first round to the final. Bonus questions are resolved last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Stage {
+    Group,
+    /// Predicted final group standings, see [`crate::pred_score::standings`]
+    Standings,
+    /// Playoff round, depth 0 = final, depth 1 = semifinals, etc.
+    Playoff {
+        depth: usize,
+    },
+    /// Bonus questions, see [`crate::bonus`]
+    Bonus,
+}
+
+/// Multipliers applied to the scheme score of each stage
+///
+/// Playoff rounds without a multiplier (including all rounds if `playoff_rounds` is empty)
+/// fall back to 1.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StageMultipliers {
+    pub group: f32,
+    /// Indexed by depth, 0 = final
+    #[serde(default)]
+    pub playoff_rounds: Vec<f32>,
+}
+
+impl Ord for Stage {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        match (self, other) {
+            (Stage::Playoff { depth }, Stage::Playoff { depth: other }) => other.cmp(depth),
+            _ => self.order().cmp(&other.order()),
+        }
+    }
+}
+
+impl Stage {
+    /// Order of the kinds of stages
+    fn order(&self) -> u8 {
+        match self {
+            Stage::Group => 0,
+            Stage::Standings => 1,
+            Stage::Playoff { .. } => 2,
+            Stage::Bonus => 3,
+        }
+    }
+}
+
+impl PartialOrd for Stage {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl StageMultipliers {
+    pub fn multiplier(&self, stage: Stage) -> f32 {
+        match stage {
+            Stage::Group => self.group,
+            Stage::Playoff { depth } => self.playoff_rounds.get(depth).copied().unwrap_or(1.0),
+            // Standings and bonus questions have their own points
+            Stage::Standings | Stage::Bonus => 1.0,
+        }
+    }
+}
+
+impl Default for StageMultipliers {
+    fn default() -> Self {
+        StageMultipliers {
+            group: 1.0,
+            playoff_rounds: Vec::new(),
+        }
+    }
+}
+
+/// Complete scoring configuration of a tournament
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoringConfig {
+    pub scheme: ScoringScheme,
+    #[serde(default)]
+    pub multipliers: StageMultipliers,
+    /// Scoring of predicted playoff brackets, not scored by default
+    #[serde(default)]
+    pub playoff: SimplePlayoffPredScoreFn,
+    /// No jokers by default
+    #[serde(default)]
+    pub jokers: JokerRules,
+    /// Exact scores by default
+    #[serde(default)]
+    pub mode: PredictionMode,
+    /// Predicted group standings are not scored by default
+    #[serde(default)]
+    pub standings: StandingsRules,
+}
+
+impl ScoringConfig {
+    pub fn new(scheme: ScoringScheme) -> Self {
+        ScoringConfig {
+            scheme,
+            multipliers: StageMultipliers::default(),
+            playoff: SimplePlayoffPredScoreFn::default(),
+            jokers: JokerRules::default(),
+            mode: PredictionMode::default(),
+            standings: StandingsRules::default(),
+        }
+    }
+
+    pub fn with_standings(self, standings: StandingsRules) -> Self {
+        ScoringConfig { standings, ..self }
+    }
+
+    pub fn with_mode(self, mode: PredictionMode) -> Self {
+        ScoringConfig { mode, ..self }
+    }
+
+    pub fn with_jokers(self, jokers: JokerRules) -> Self {
+        ScoringConfig { jokers, ..self }
+    }
+
+    pub fn with_playoff(self, playoff: SimplePlayoffPredScoreFn) -> Self {
+        ScoringConfig { playoff, ..self }
+    }
+
+    pub fn with_multipliers(self, multipliers: StageMultipliers) -> Self {
+        ScoringConfig {
+            multipliers,
+            ..self
+        }
+    }
+
+    /// Score of a game prediction in a specific stage
+    pub fn stage_score(
+        &self,
+        stage: Stage,
+        pred: &GroupGameScore,
+        truth: &GroupGameScore,
+    ) -> PredScore {
+        PredScore(self.scheme.pred_score(pred, truth).0 * self.multipliers.multiplier(stage))
+    }
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        ScoringConfig::new(ScoringScheme::kicktipp())
+    }
+}
+
+/// Scores group games, i.e. with the group stage multiplier
+impl PredScoreFn for ScoringConfig {
+    fn pred_score(&self, pred: &GroupGameScore, truth: &GroupGameScore) -> PredScore {
+        self.stage_score(Stage::Group, pred, truth)
+    }
+
+    fn outcome_score(&self, pred: GroupGameOutcome, truth: &GroupGameScore) -> PredScore {
+        PredScore(
+            self.scheme.outcome_score(pred, truth).0 * self.multipliers.multiplier(Stage::Group),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GoalCount;
+    use assert_approx_eq::assert_approx_eq;
+
+    fn score(home: u32, away: u32) -> GroupGameScore {
+        GroupGameScore::new(
+            GoalCount::try_from(home).unwrap(),
+            GoalCount::try_from(away).unwrap(),
+        )
+    }
+
+    fn points<T: PredScoreFn>(score_fn: &T, pred: (u32, u32), truth: (u32, u32)) -> f32 {
+        score_fn
+            .pred_score(&score(pred.0, pred.1), &score(truth.0, truth.1))
+            .into()
+    }
+
+    #[test]
+    fn outcome_mode() {
+        let config = ScoringConfig::default()
+            .with_mode(PredictionMode::Outcome)
+            .with_multipliers(StageMultipliers {
+                group: 1.5,
+                playoff_rounds: vec![],
+            });
+        let truth = score(2, 1);
+        assert_approx_eq!(
+            f32::from(config.outcome_score(GroupGameOutcome::Win, &truth)),
+            3.0
+        );
+        assert_approx_eq!(
+            f32::from(config.outcome_score(GroupGameOutcome::Draw, &truth)),
+            0.0
+        );
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(json.contains(r#""mode":"outcome""#));
+    }
+
+    #[test]
+    fn kicktipp() {
+        let scheme = ScoringScheme::kicktipp();
+        assert_approx_eq!(points(&scheme, (2, 1), (2, 1)), 4.0);
+        assert_approx_eq!(points(&scheme, (3, 2), (2, 1)), 3.0);
+        assert_approx_eq!(points(&scheme, (3, 0), (2, 1)), 2.0);
+        assert_approx_eq!(points(&scheme, (0, 1), (2, 1)), 0.0);
+        // No goal difference tier for draws
+        assert_approx_eq!(points(&scheme, (1, 1), (1, 1)), 4.0);
+        assert_approx_eq!(points(&scheme, (0, 0), (1, 1)), 2.0);
+    }
+
+    #[test]
+    fn additive() {
+        let scheme = ScoringScheme::Additive {
+            outcome: 2.0,
+            goal_difference: 1.0,
+            team_goals: 0.5,
+            result: 3.0,
+        };
+        assert_approx_eq!(points(&scheme, (2, 1), (2, 1)), 2.0 + 1.0 + 2.0 * 0.5 + 3.0);
+        assert_approx_eq!(points(&scheme, (3, 2), (2, 1)), 2.0 + 1.0);
+        assert_approx_eq!(points(&scheme, (2, 0), (2, 1)), 2.0 + 0.5);
+        assert_approx_eq!(points(&scheme, (0, 0), (1, 1)), 2.0 + 1.0);
+        assert_approx_eq!(points(&scheme, (1, 2), (2, 1)), 0.0);
+    }
+
+    #[test]
+    fn simple_score_fn_as_scheme() {
+        let simple = SimplePredScoreFn::new(3.0, 2.0);
+        let scheme = ScoringScheme::from(simple);
+        for (pred, truth) in [((2, 2), (2, 2)), ((3, 1), (2, 1)), ((0, 1), (2, 1))] {
+            assert_approx_eq!(points(&scheme, pred, truth), points(&simple, pred, truth));
+        }
+    }
+
+    #[test]
+    fn stage_multipliers() {
+        let config = ScoringConfig::default().with_multipliers(StageMultipliers {
+            group: 1.0,
+            playoff_rounds: vec![3.0, 2.0],
+        });
+        let (pred, truth) = (score(2, 1), score(2, 1));
+        let stage_points = |stage| f32::from(config.stage_score(stage, &pred, &truth));
+        assert_approx_eq!(stage_points(Stage::Group), 4.0);
+        assert_approx_eq!(stage_points(Stage::Playoff { depth: 0 }), 12.0);
+        assert_approx_eq!(stage_points(Stage::Playoff { depth: 1 }), 8.0);
+        assert_approx_eq!(stage_points(Stage::Playoff { depth: 2 }), 4.0);
+        assert_approx_eq!(points(&config, (2, 1), (2, 1)), 4.0);
+    }
+
+    #[test]
+    fn serde_roundtrip() {
+        let config = ScoringConfig::default();
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ScoringConfig>(&json).unwrap(),
+            config
+        );
+
+        let config: ScoringConfig = serde_json::from_str(
+            r#"{"scheme": {"type": "additive", "outcome": 1, "goal_difference": 0, "team_goals": 0, "result": 2}}"#,
+        )
+        .unwrap();
+        assert_eq!(config.multipliers, StageMultipliers::default());
+        assert_eq!(config.playoff, SimplePlayoffPredScoreFn::default());
+        assert_eq!(config.jokers, JokerRules::default());
+        assert_eq!(config.mode, PredictionMode::Score);
+    }
+}
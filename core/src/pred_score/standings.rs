@@ -0,0 +1,296 @@
+//! Group standings
+//!
+//! Bonus points for the final group standings which follow from a competitor's game predictions:
+//! every team predicted in its exact final position, every predicted qualifier which qualified
+//! and every predicted best third-placed team which qualified as one.
+//!
+//! Standings are ordered with the tournament's [`Rules`]. Ties which the rules leave to the
+//! drawing of lots can't be predicted, so use a deterministic tiebreaker (see
+//! [`Rules::with_tiebreaker`]) to get reproducible scores.
+//!
+//! Only complete groups are scored, both predicted and actual. Best third-placed teams are only
+//! scored once every group is complete.
+use crate::group::order::{Rules, Tiebreaker, order_teams};
+use crate::group::{Group, GroupId, Groups};
+use crate::player::Prediction;
+use crate::pred_score::PredScore;
+use crate::team::TeamId;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Points for predicted group standings, nothing is scored by default
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StandingsRules {
+    /// Per team in its exact final position
+    pub position: f32,
+    /// Per predicted qualifier which qualified from the group
+    pub qualifier: f32,
+    /// Per predicted best third-placed team which qualified as one
+    pub best_third: f32,
+    /// Number of teams qualifying directly from each group
+    #[serde(default = "default_qualifiers")]
+    pub qualifiers: usize,
+    /// Number of third-placed teams qualifying across all groups
+    #[serde(default)]
+    pub best_thirds: usize,
+}
+
+fn default_qualifiers() -> usize {
+    2
+}
+
+impl Default for StandingsRules {
+    fn default() -> Self {
+        StandingsRules {
+            position: 0.0,
+            qualifier: 0.0,
+            best_third: 0.0,
+            qualifiers: default_qualifiers(),
+            best_thirds: 0,
+        }
+    }
+}
+
+/// Final order of the complete groups
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GroupStandings {
+    /// Best first
+    groups: BTreeMap<GroupId, Vec<TeamId>>,
+    /// Empty unless every group is complete
+    best_thirds: HashSet<TeamId>,
+}
+
+impl GroupStandings {
+    pub fn group(&self, group_id: GroupId) -> Option<&[TeamId]> {
+        self.groups.get(&group_id).map(Vec::as_slice)
+    }
+
+    pub fn best_thirds(&self) -> &HashSet<TeamId> {
+        &self.best_thirds
+    }
+}
+
+fn is_complete(group: &Group) -> bool {
+    group.unplayed_games().next().is_none()
+}
+
+impl StandingsRules {
+    /// Some points are awarded
+    pub fn is_scored(&self) -> bool {
+        self.position != 0.0 || self.qualifier != 0.0 || self.best_third != 0.0
+    }
+
+    /// Standings of the complete groups
+    ///
+    /// `third_place_rules` rank the third-placed teams across groups, they are only used if any
+    /// third-placed team qualifies.
+    pub fn standings<T: Tiebreaker, U: Tiebreaker>(
+        &self,
+        groups: &Groups,
+        rules: &Rules<T>,
+        third_place_rules: &Rules<U>,
+    ) -> GroupStandings {
+        let complete: BTreeMap<GroupId, &Group> = groups
+            .iter()
+            .filter(|(_, group)| is_complete(group))
+            .map(|(group_id, group)| (*group_id, group))
+            .collect();
+        let orders: BTreeMap<GroupId, Vec<TeamId>> = complete
+            .iter()
+            .map(|(group_id, group)| (*group_id, group.rank_teams(rules).into_iter().collect()))
+            .collect();
+
+        let best_thirds = if self.best_thirds > 0 && complete.len() == groups.len() {
+            let thirds: HashMap<TeamId, &Group> = orders
+                .iter()
+                .filter_map(|(group_id, order)| Some((*order.get(2)?, *complete.get(group_id)?)))
+                .collect();
+            order_teams(&thirds, third_place_rules)
+                .into_iter()
+                .take(self.best_thirds)
+                .collect()
+        } else {
+            HashSet::new()
+        };
+        GroupStandings {
+            groups: orders,
+            best_thirds,
+        }
+    }
+
+    /// Standings which follow from a competitor's game predictions
+    ///
+    /// Results of played games are replaced by the predictions, groups with a game without a
+    /// prediction are left out.
+    pub fn predicted_standings<T: Tiebreaker, U: Tiebreaker>(
+        &self,
+        groups: &Groups,
+        preds: &[Prediction],
+        rules: &Rules<T>,
+        third_place_rules: &Rules<U>,
+    ) -> GroupStandings {
+        let preds: HashMap<_, _> = preds
+            .iter()
+            .map(|Prediction(game_id, score)| (*game_id, *score))
+            .collect();
+        let mut groups = groups.clone();
+        for group in groups.values_mut() {
+            let played: Vec<_> = group.played_games().map(|game| game.id).collect();
+            played
+                .into_iter()
+                .for_each(|game_id| group.unplay_game(game_id));
+            let unplayed: Vec<_> = group.unplayed_games().map(|game| game.id).collect();
+            for game_id in unplayed {
+                if let Some(score) = preds.get(&game_id) {
+                    group.play_game(game_id, *score);
+                }
+            }
+        }
+        self.standings(&groups, rules, third_place_rules)
+    }
+
+    /// Score predicted standings
+    ///
+    /// Only groups which are complete in `groups` are scored, which allows scoring against the
+    /// final standings while replaying the tournament, e.g. for the leaderboard history.
+    pub fn score(
+        &self,
+        pred: &GroupStandings,
+        truth: &GroupStandings,
+        groups: &Groups,
+    ) -> PredScore {
+        let complete: Vec<GroupId> = groups
+            .iter()
+            .filter(|(_, group)| is_complete(group))
+            .map(|(group_id, _)| *group_id)
+            .collect();
+        let groups_score: f32 = complete
+            .iter()
+            .filter_map(|group_id| Some((pred.group(*group_id)?, truth.group(*group_id)?)))
+            .map(|(pred, truth)| {
+                let positions: f32 = pred
+                    .iter()
+                    .zip(truth)
+                    .filter(|(pred, truth)| pred == truth)
+                    .map(|_| self.position)
+                    .sum();
+                let qualified = &truth[..self.qualifiers.min(truth.len())];
+                let qualifiers: f32 = pred
+                    .iter()
+                    .take(self.qualifiers)
+                    .filter(|team| qualified.contains(team))
+                    .map(|_| self.qualifier)
+                    .sum();
+                positions + qualifiers
+            })
+            .sum();
+        let best_thirds: f32 = if complete.len() == groups.len() {
+            pred.best_thirds
+                .intersection(&truth.best_thirds)
+                .map(|_| self.best_third)
+                .sum()
+        } else {
+            0.0
+        };
+        PredScore::from(groups_score + best_thirds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameId, GoalCount};
+    use crate::group::game::GroupGameScore;
+    use crate::group::mock_data::groups_and_teams;
+    use crate::group::order::{UefaRanking, euro_2020_third_place_rules, fifa_2018_rules};
+    use assert_approx_eq::assert_approx_eq;
+
+    fn score(home: u32, away: u32) -> GroupGameScore {
+        GroupGameScore::new(
+            GoalCount::try_from(home).unwrap(),
+            GoalCount::try_from(away).unwrap(),
+        )
+    }
+
+    fn preds(scores: &[(u32, (u32, u32))]) -> Vec<Prediction> {
+        scores
+            .iter()
+            .map(|(id, (home, away))| Prediction(GameId::from(*id), score(*home, *away)))
+            .collect()
+    }
+
+    fn team_ids(ids: &[u32]) -> Vec<TeamId> {
+        ids.iter().map(|id| TeamId(*id)).collect()
+    }
+
+    fn rules() -> StandingsRules {
+        StandingsRules {
+            position: 1.0,
+            qualifier: 2.0,
+            best_third: 3.0,
+            qualifiers: 2,
+            best_thirds: 1,
+        }
+    }
+
+    #[test]
+    fn predicted_standings() {
+        let (mut groups, teams) = groups_and_teams();
+        let ranking = UefaRanking::try_new(
+            &groups,
+            teams.iter().map(|(id, team)| (*id, team.rank)).collect(),
+        )
+        .unwrap();
+        let order_rules = fifa_2018_rules().with_tiebreaker(ranking.clone());
+        let third_rules = euro_2020_third_place_rules(ranking);
+        let id_a = GroupId::try_from('A').unwrap();
+        let id_b = GroupId::try_from('B').unwrap();
+
+        // Nothing is scored before the groups are complete
+        let pred = rules().predicted_standings(
+            &groups,
+            &preds(&[(1, (2, 1)), (2, (0, 1)), (3, (0, 0)), (4, (1, 3))]),
+            &order_rules,
+            &third_rules,
+        );
+        let truth = rules().standings(&groups, &order_rules, &third_rules);
+        assert_eq!(truth, GroupStandings::default());
+        assert_approx_eq!(f32::from(rules().score(&pred, &truth, &groups)), 0.0);
+
+        groups
+            .get_mut(&id_a)
+            .unwrap()
+            .play_game(GameId::from(2), score(1, 0));
+        groups
+            .get_mut(&id_b)
+            .unwrap()
+            .play_game(GameId::from(3), score(0, 0));
+        groups
+            .get_mut(&id_b)
+            .unwrap()
+            .play_game(GameId::from(4), score(1, 3));
+        let truth = rules().standings(&groups, &order_rules, &third_rules);
+        assert_eq!(truth.group(id_a), Some(&team_ids(&[1, 3, 2, 4])[..]));
+        // 5 and 6 are tied, the ranking decides
+        assert_eq!(truth.group(id_b), Some(&team_ids(&[8, 5, 6, 7])[..]));
+        assert_eq!(truth.best_thirds(), &HashSet::from([TeamId(6)]));
+
+        // Group A: positions 1 and 3, qualifier 1. Group B: all right. Best third right.
+        assert_eq!(pred.group(id_a), Some(&team_ids(&[1, 4, 2, 3])[..]));
+        assert_approx_eq!(
+            f32::from(rules().score(&pred, &truth, &groups)),
+            2.0 + 2.0 + 4.0 + 4.0 + 3.0
+        );
+
+        // Group B is incomplete without a prediction for game 4, and so are the best thirds
+        let pred = rules().predicted_standings(
+            &groups,
+            &preds(&[(1, (2, 1)), (2, (0, 1)), (3, (0, 0))]),
+            &order_rules,
+            &third_rules,
+        );
+        assert_eq!(pred.group(id_b), None);
+        assert!(pred.best_thirds().is_empty());
+        assert_approx_eq!(f32::from(rules().score(&pred, &truth, &groups)), 4.0);
+    }
+}
@@ -2,7 +2,7 @@
 use chrono::{DateTime, FixedOffset, TimeZone};
 use serde::{self, Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Clone, Copy, Debug)]
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Date(#[serde(with = "serde_date")] DateTime<FixedOffset>);
 
 impl Date {
@@ -16,6 +16,22 @@ impl Date {
     }
 }
 
+impl Date {
+    pub fn parse_rfc3339(date: &str) -> Result<Self, chrono::ParseError> {
+        DateTime::parse_from_rfc3339(date).map(Date)
+    }
+
+    pub fn to_rfc3339(&self) -> String {
+        self.0.to_rfc3339()
+    }
+}
+
+impl From<DateTime<FixedOffset>> for Date {
+    fn from(date: DateTime<FixedOffset>) -> Self {
+        Date(date)
+    }
+}
+
 mod serde_date {
     use chrono::{DateTime, FixedOffset};
     use serde::{self, Deserialize, Deserializer, Serializer};
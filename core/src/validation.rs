@@ -0,0 +1,167 @@
+//! Validation of submitted predictions
+//!
+//! Predictions are checked against the current tournament before they are stored: the game must
+//! exist, must not have kicked off and may only be predicted once per submission. Instead of
+//! failing on the first problem, every prediction gets a [`PredStatus`] so that clients can show
+//! exactly which predictions were dropped.
+//!
+//! Score, outcome-only and probabilistic predictions are validated alike, see [`GamePrediction`].
+use crate::game::GameId;
+use crate::player::{OutcomePrediction, Prediction};
+use crate::pred_score::proba::ProbPrediction;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// A prediction of a single game
+pub trait GamePrediction: Clone {
+    fn game_id(&self) -> GameId;
+}
+
+impl GamePrediction for Prediction {
+    fn game_id(&self) -> GameId {
+        self.0
+    }
+}
+
+impl GamePrediction for OutcomePrediction {
+    fn game_id(&self) -> GameId {
+        self.0
+    }
+}
+
+impl GamePrediction for ProbPrediction {
+    fn game_id(&self) -> GameId {
+        self.0
+    }
+}
+
+/// Outcome of validating a single prediction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PredStatus {
+    Accepted,
+    /// No group game with this id
+    RejectedUnknown,
+    /// The game has kicked off
+    RejectedLocked,
+    /// An earlier prediction in the same submission has the same game id
+    Duplicate,
+}
+
+/// A prediction and its validation outcome
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PredCheck<P = Prediction> {
+    pub pred: P,
+    pub status: PredStatus,
+}
+
+/// Per-prediction validation report, in submission order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PredReport<P = Prediction> {
+    pub checks: Vec<PredCheck<P>>,
+}
+
+impl<P: GamePrediction> PredReport<P> {
+    /// Check predictions against the tournament's group games and the games which have kicked off
+    ///
+    /// For duplicates, the first prediction of a game is checked as usual and the rest are
+    /// marked [`PredStatus::Duplicate`].
+    pub fn new<'a>(
+        preds: impl IntoIterator<Item = &'a P>,
+        games: &HashSet<GameId>,
+        locked: &HashSet<GameId>,
+    ) -> Self
+    where
+        P: 'a,
+    {
+        let mut seen = HashSet::new();
+        let checks = preds
+            .into_iter()
+            .map(|pred| {
+                let game_id = pred.game_id();
+                let status = if !seen.insert(game_id) {
+                    PredStatus::Duplicate
+                } else if !games.contains(&game_id) {
+                    PredStatus::RejectedUnknown
+                } else if locked.contains(&game_id) {
+                    PredStatus::RejectedLocked
+                } else {
+                    PredStatus::Accepted
+                };
+                PredCheck {
+                    pred: pred.clone(),
+                    status,
+                }
+            })
+            .collect();
+        PredReport { checks }
+    }
+
+    pub fn accepted(&self) -> impl Iterator<Item = &P> {
+        self.with_status(PredStatus::Accepted)
+    }
+
+    pub fn with_status(&self, status: PredStatus) -> impl Iterator<Item = &P> {
+        self.checks
+            .iter()
+            .filter(move |check| check.status == status)
+            .map(|check| &check.pred)
+    }
+
+    /// Every prediction was accepted
+    pub fn is_clean(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|check| check.status == PredStatus::Accepted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GoalCount;
+    use crate::group::game::GroupGameScore;
+
+    fn pred(game_id: u32, home: u32, away: u32) -> Prediction {
+        Prediction(
+            GameId::from(game_id),
+            GroupGameScore::new(
+                GoalCount::try_from(home).unwrap(),
+                GoalCount::try_from(away).unwrap(),
+            ),
+        )
+    }
+
+    fn ids(ids: &[u32]) -> HashSet<GameId> {
+        ids.iter().copied().map(GameId::from).collect()
+    }
+
+    #[test]
+    fn statuses() {
+        let preds = [
+            pred(2, 1, 0),
+            pred(9, 1, 1),
+            pred(1, 2, 1),
+            pred(2, 0, 0),
+            pred(3, 0, 2),
+        ];
+        let report = PredReport::new(&preds, &ids(&[1, 2, 3, 4]), &ids(&[1]));
+        let statuses: Vec<PredStatus> = report.checks.iter().map(|check| check.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                PredStatus::Accepted,
+                PredStatus::RejectedUnknown,
+                PredStatus::RejectedLocked,
+                PredStatus::Duplicate,
+                PredStatus::Accepted,
+            ]
+        );
+        let accepted: Vec<GameId> = report.accepted().map(|pred| pred.0).collect();
+        assert_eq!(accepted, vec![GameId::from(2), GameId::from(3)]);
+        // The first prediction of a game is the one kept
+        assert_eq!(report.accepted().next().unwrap().1, pred(2, 1, 0).1);
+        assert!(!report.is_clean());
+        assert!(PredReport::new(&preds[..1], &ids(&[2]), &ids(&[])).is_clean());
+    }
+}
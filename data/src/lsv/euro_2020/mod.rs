@@ -7,13 +7,13 @@ pub mod playoff;
 use crate::file_io::read_json_file_to_str;
 use crate::lsv::euro_2020::group::ParseGroup;
 use crate::lsv::euro_2020::playoff::ParsePlayoff;
-use crate::lsv::{LsvData, LsvParseError};
+use crate::lsv::{LsvData, LsvParseError, TeamSources};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use wwc_core::game::GameId;
 use wwc_core::group::{GroupError, GroupId, GroupOutcome, Groups};
-use wwc_core::playoff::TeamSource;
 use wwc_core::playoff::transition::{PlayoffTransition, PlayoffTransitions};
+use wwc_core::playoff::{TeamSource, validate_team_sources};
 use wwc_core::team::{FifaCode, Team, TeamId, TeamRank, Teams};
 
 #[derive(Debug, Clone)]
@@ -81,6 +81,11 @@ impl LsvData for Euro2020Data {
     fn try_playoff_transitions(&self) -> Result<PlayoffTransitions, LsvParseError> {
         Ok(self.playoff_trans.clone())
     }
+
+    fn try_team_sources(&self) -> Result<TeamSources, LsvParseError> {
+        validate_team_sources(&self.team_sources)?;
+        Ok(self.team_sources.clone())
+    }
 }
 
 impl Euro2020Data {
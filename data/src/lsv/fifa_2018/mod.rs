@@ -6,14 +6,14 @@ pub mod playoff;
 use crate::file_io::read_json_file_to_str;
 use crate::lsv::fifa_2018::group::ParseGroup;
 use crate::lsv::fifa_2018::playoff::ParsePlayoff;
-use crate::lsv::{LsvData, LsvParseError};
+use crate::lsv::{LsvData, LsvParseError, TeamSources};
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap};
 use std::convert::{TryFrom, TryInto};
 use wwc_core::game::GameId;
 use wwc_core::group::{GroupError, GroupId, GroupOutcome, Groups};
-use wwc_core::playoff::TeamSource;
 use wwc_core::playoff::transition::{PlayoffTransition, PlayoffTransitions};
+use wwc_core::playoff::{TeamSource, validate_team_sources};
 use wwc_core::team::{Team, TeamId, TeamRank, Teams};
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -86,6 +86,11 @@ impl LsvData for Fifa2018Data {
             .collect::<Result<Vec<Team>, LsvParseError>>()?;
         Ok(tmp.into_iter().map(|t| (t.id, t)).collect())
     }
+
+    fn try_team_sources(&self) -> Result<TeamSources, LsvParseError> {
+        validate_team_sources(&self.team_sources)?;
+        Ok(self.team_sources.clone())
+    }
 }
 
 impl Fifa2018Data {
@@ -1,10 +1,10 @@
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use wwc_core::game::GoalCountError;
+use wwc_core::game::{GameId, GoalCountError};
 use wwc_core::group::GroupError;
 use wwc_core::group::Groups;
-use wwc_core::playoff::PlayoffError;
 use wwc_core::playoff::transition::PlayoffTransitions;
+use wwc_core::playoff::{InvalidBracket, PlayoffError, TeamSource};
 use wwc_core::team::FifaCode;
 use wwc_core::team::{TeamId, Teams};
 
@@ -19,11 +19,16 @@ pub fn get_data<T: LsvData>(data_path: &str) -> Result<T, LsvParseError> {
     Ok(data)
 }
 
+/// Playoff team sources, the last game being the final
+pub type TeamSources = Vec<(GameId, (TeamSource, TeamSource))>;
+
 pub trait LsvData: Sized {
     fn try_data_from_file(filename: &str) -> Result<Self, LsvParseError>;
     fn try_groups(&self) -> Result<Groups, LsvParseError>;
     fn try_teams(&self) -> Result<Teams, LsvParseError>;
     fn try_playoff_transitions(&self) -> Result<PlayoffTransitions, LsvParseError>;
+    /// Playoff team sources, validated with [`wwc_core::playoff::validate_team_sources`]
+    fn try_team_sources(&self) -> Result<TeamSources, LsvParseError>;
 }
 
 #[derive(Debug, Clone, Copy, Deserialize, Serialize)]
@@ -55,6 +60,8 @@ pub enum LsvParseError {
     TransitionIncomplete(String),
     #[error("Error parsing playoff: {0}")]
     Playoff(#[from] PlayoffError),
+    #[error("Invalid playoff bracket: {0}")]
+    Bracket(#[from] InvalidBracket),
     #[error("Both 'group' and 'match' or none of the set")]
     InvalidQualification,
     #[error("Got 'runnerup' or 'thirdplace' in playoff qualification")]
@@ -11,3 +11,15 @@ fn euro_2020_teams_from_full_data() {
     let data: Euro2020Data = get_data("lsv_data/blank-euro-2020.json").unwrap();
     assert_eq!(data.try_teams().unwrap().len(), 24);
 }
+
+#[test]
+fn fifa_2018_bracket_is_valid() {
+    let data: Fifa2018Data = get_data("lsv_data/blank-fifa-2018.json").unwrap();
+    assert_eq!(data.try_team_sources().unwrap().len(), 16);
+}
+
+#[test]
+fn euro_2020_bracket_is_valid() {
+    let data: Euro2020Data = get_data("lsv_data/blank-euro-2020.json").unwrap();
+    assert_eq!(data.try_team_sources().unwrap().len(), 15);
+}
@@ -1,5 +1,5 @@
--- Note: bot_name column for preds table should be added manually if upgrading existing DB
--- For fresh databases, add it to 001_create_tables.sql instead
+-- The bot_name column of the preds table is added to older databases by this migration's
+-- AddColumn step, see src/migrations.rs
 
 -- Users table (human users with username/password)
 -- user.id IS the player_id (they are the same)
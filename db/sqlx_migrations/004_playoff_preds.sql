@@ -0,0 +1,19 @@
+-- Playoff predictions (a player's predicted BracketState)
+-- Unlike group preds, the teams are part of the prediction since they depend
+-- on the player's own group and earlier playoff predictions.
+CREATE TABLE IF NOT EXISTS playoff_preds (
+  id INTEGER PRIMARY KEY NOT NULL,
+  player_id INTEGER NOT NULL,
+  game_id INTEGER NOT NULL,
+  home_team INTEGER NOT NULL,
+  away_team INTEGER NOT NULL,
+  home_result INTEGER NOT NULL,
+  away_result INTEGER NOT NULL,
+  home_penalty INTEGER,
+  away_penalty INTEGER,
+  bot_name VARCHAR,  -- NULL for human predictions, bot identifier for bot predictions
+  FOREIGN KEY(game_id) REFERENCES playoff_games(id),
+  FOREIGN KEY(player_id) REFERENCES players(id),
+  FOREIGN KEY(home_team) REFERENCES teams(id),
+  FOREIGN KEY(away_team) REFERENCES teams(id)
+);
@@ -0,0 +1,6 @@
+-- Scoring configuration per tournament
+-- Stored in the db so that every client computes identical scores
+CREATE TABLE IF NOT EXISTS scoring_configs (
+  tournament VARCHAR PRIMARY KEY NOT NULL,
+  config VARCHAR NOT NULL  -- JSON serialized wwc_core::pred_score::scheme::ScoringConfig
+);
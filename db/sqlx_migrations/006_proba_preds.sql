@@ -0,0 +1,11 @@
+-- Probabilistic predictions, submitted by bots
+-- Scored with proper scoring rules (Brier, log loss, RPS) alongside the points table.
+CREATE TABLE IF NOT EXISTS proba_preds (
+  id INTEGER PRIMARY KEY NOT NULL,
+  player_id INTEGER NOT NULL,
+  bot_name VARCHAR NOT NULL,
+  game_id INTEGER NOT NULL,
+  forecast VARCHAR NOT NULL,  -- JSON serialized wwc_core::pred_score::proba::ProbForecast
+  FOREIGN KEY(game_id) REFERENCES games(id),
+  FOREIGN KEY(player_id) REFERENCES players(id)
+);
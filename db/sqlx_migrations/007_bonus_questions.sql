@@ -0,0 +1,19 @@
+-- Bonus questions (champion, top scorer, total goals, group orders, ...)
+CREATE TABLE IF NOT EXISTS bonus_questions (
+  id INTEGER PRIMARY KEY NOT NULL,
+  text VARCHAR NOT NULL,
+  kind VARCHAR NOT NULL,  -- JSON serialized wwc_core::bonus::BonusKind
+  deadline VARCHAR NOT NULL,  -- RFC 3339
+  solution VARCHAR  -- JSON serialized accepted answers, NULL until resolved
+);
+
+-- Answers to bonus questions
+CREATE TABLE IF NOT EXISTS bonus_preds (
+  id INTEGER PRIMARY KEY NOT NULL,
+  player_id INTEGER NOT NULL,
+  question_id INTEGER NOT NULL,
+  answer VARCHAR NOT NULL,  -- JSON serialized wwc_core::bonus::BonusAnswer
+  bot_name VARCHAR,  -- NULL for human predictions, bot identifier for bot predictions
+  FOREIGN KEY(question_id) REFERENCES bonus_questions(id),
+  FOREIGN KEY(player_id) REFERENCES players(id)
+);
@@ -0,0 +1,16 @@
+-- Games picked as jokers (multiplied points)
+CREATE TABLE IF NOT EXISTS jokers (
+  id INTEGER PRIMARY KEY NOT NULL,
+  player_id INTEGER NOT NULL,
+  game_id INTEGER NOT NULL,
+  bot_name VARCHAR,  -- NULL for human predictions, bot identifier for bot predictions
+  FOREIGN KEY(player_id) REFERENCES players(id),
+  FOREIGN KEY(game_id) REFERENCES games(id)
+);
+
+-- Kickoff times, jokers are locked from kickoff
+CREATE TABLE IF NOT EXISTS game_kickoffs (
+  game_id INTEGER PRIMARY KEY NOT NULL,
+  kickoff VARCHAR NOT NULL,  -- RFC 3339
+  FOREIGN KEY(game_id) REFERENCES games(id)
+);
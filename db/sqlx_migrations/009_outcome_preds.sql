@@ -0,0 +1,10 @@
+-- Outcome-only (1X2) group predictions, for tournaments in outcome prediction mode
+CREATE TABLE IF NOT EXISTS outcome_preds (
+  id INTEGER PRIMARY KEY NOT NULL,
+  player_id INTEGER NOT NULL,
+  game_id INTEGER NOT NULL,
+  outcome VARCHAR NOT NULL,  -- 'win', 'draw' or 'lose', from the home team's perspective
+  bot_name VARCHAR,  -- NULL for human predictions, bot identifier for bot predictions
+  FOREIGN KEY(player_id) REFERENCES players(id),
+  FOREIGN KEY(game_id) REFERENCES games(id)
+);
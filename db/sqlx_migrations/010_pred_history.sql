@@ -0,0 +1,19 @@
+-- Append-only history of group prediction submissions, rows are never updated or deleted
+CREATE TABLE IF NOT EXISTS pred_submissions (
+  id INTEGER PRIMARY KEY NOT NULL,
+  player_id INTEGER NOT NULL,
+  bot_name VARCHAR,  -- NULL for human predictions, bot identifier for bot predictions
+  submitted_at VARCHAR NOT NULL,  -- RFC 3339
+  FOREIGN KEY(player_id) REFERENCES players(id)
+);
+
+-- The stored predictions after each submission, none if the predictions were cleared
+CREATE TABLE IF NOT EXISTS pred_history (
+  id INTEGER PRIMARY KEY NOT NULL,
+  submission_id INTEGER NOT NULL,
+  game_id INTEGER NOT NULL,
+  home_result INTEGER NOT NULL,
+  away_result INTEGER NOT NULL,
+  FOREIGN KEY(submission_id) REFERENCES pred_submissions(id),
+  FOREIGN KEY(game_id) REFERENCES games(id)
+);
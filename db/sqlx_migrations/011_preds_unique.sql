@@ -0,0 +1,11 @@
+-- At most one prediction per player, bot and game
+--
+-- Existing duplicates are removed first, the latest (highest id) prediction is kept.
+-- NULL bot names (human predictions) are distinct in a plain UNIQUE constraint, hence the IFNULL.
+DELETE FROM preds
+WHERE id NOT IN (
+  SELECT MAX(id) FROM preds GROUP BY player_id, game_id, IFNULL(bot_name, '')
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS preds_player_game_bot
+  ON preds (player_id, game_id, IFNULL(bot_name, ''));
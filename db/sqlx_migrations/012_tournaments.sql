@@ -0,0 +1,311 @@
+-- Several tournaments in one database
+--
+-- Teams, games, groups, playoff sources, predictions and scoring configs are scoped by
+-- tournament. Game, team and bonus question ids are only unique within a tournament, so the
+-- tables are rebuilt with the tournament in their keys: the old tables are renamed, which also
+-- renames the references to them, the new tables are created and filled, and the old ones dropped.
+--
+-- Existing data is assigned to the 'fifa-2018' tournament, the only one before tournaments
+-- were added. Scoring configs were already stored per tournament name.
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TABLE tournaments (
+  id INTEGER PRIMARY KEY NOT NULL,
+  name VARCHAR UNIQUE NOT NULL
+);
+
+INSERT INTO tournaments (name)
+SELECT 'fifa-2018' WHERE EXISTS (SELECT 1 FROM teams) OR EXISTS (SELECT 1 FROM games);
+INSERT OR IGNORE INTO tournaments (name) SELECT tournament FROM scoring_configs;
+
+ALTER TABLE teams RENAME TO teams_old;
+ALTER TABLE games RENAME TO games_old;
+ALTER TABLE group_game_map RENAME TO group_game_map_old;
+ALTER TABLE playoff_games RENAME TO playoff_games_old;
+ALTER TABLE playoff_team_sources RENAME TO playoff_team_sources_old;
+ALTER TABLE playoff_results RENAME TO playoff_results_old;
+ALTER TABLE preds RENAME TO preds_old;
+ALTER TABLE playoff_preds RENAME TO playoff_preds_old;
+ALTER TABLE scoring_configs RENAME TO scoring_configs_old;
+ALTER TABLE proba_preds RENAME TO proba_preds_old;
+ALTER TABLE bonus_questions RENAME TO bonus_questions_old;
+ALTER TABLE bonus_preds RENAME TO bonus_preds_old;
+ALTER TABLE jokers RENAME TO jokers_old;
+ALTER TABLE game_kickoffs RENAME TO game_kickoffs_old;
+ALTER TABLE outcome_preds RENAME TO outcome_preds_old;
+ALTER TABLE pred_submissions RENAME TO pred_submissions_old;
+ALTER TABLE pred_history RENAME TO pred_history_old;
+
+CREATE TABLE teams (
+  tournament_id INTEGER NOT NULL,
+  id INTEGER NOT NULL,
+  name VARCHAR NOT NULL,
+  fifa_code VARCHAR NOT NULL,
+  rank_ INTEGER NOT NULL,
+  PRIMARY KEY(tournament_id, id),
+  FOREIGN KEY(tournament_id) REFERENCES tournaments(id)
+);
+
+CREATE TABLE games (
+  tournament_id INTEGER NOT NULL,
+  id INTEGER NOT NULL,
+  type_ VARCHAR NOT NULL,
+  home_team INTEGER NOT NULL,
+  away_team INTEGER NOT NULL,
+  home_result INTEGER,
+  away_result INTEGER,
+  home_penalty INTEGER,
+  away_penalty INTEGER,
+  home_fair_play INTEGER,
+  away_fair_play INTEGER,
+  played BOOLEAN NOT NULL DEFAULT 0,
+  PRIMARY KEY(tournament_id, id),
+  FOREIGN KEY(tournament_id) REFERENCES tournaments(id)
+);
+
+CREATE TABLE group_game_map (
+  tournament_id INTEGER NOT NULL,
+  id INTEGER NOT NULL,
+  group_id_ CHAR NOT NULL,
+  PRIMARY KEY(tournament_id, id),
+  FOREIGN KEY(tournament_id, id) REFERENCES games(tournament_id, id)
+);
+
+CREATE TABLE playoff_games (
+  tournament_id INTEGER NOT NULL,
+  id INTEGER NOT NULL,
+  PRIMARY KEY(tournament_id, id),
+  FOREIGN KEY(tournament_id) REFERENCES tournaments(id)
+);
+
+CREATE TABLE playoff_team_sources (
+  tournament_id INTEGER NOT NULL,
+  game_id INTEGER NOT NULL,
+  home_source_type VARCHAR NOT NULL,
+  home_group_id CHAR,
+  home_outcome VARCHAR,
+  home_third_place_groups VARCHAR,
+  home_source_game_id INTEGER,
+  away_source_type VARCHAR NOT NULL,
+  away_group_id CHAR,
+  away_outcome VARCHAR,
+  away_third_place_groups VARCHAR,
+  away_source_game_id INTEGER,
+  PRIMARY KEY(tournament_id, game_id),
+  FOREIGN KEY(tournament_id, game_id) REFERENCES playoff_games(tournament_id, id)
+);
+
+CREATE TABLE playoff_results (
+  tournament_id INTEGER NOT NULL,
+  game_id INTEGER NOT NULL,
+  home_team INTEGER NOT NULL,
+  away_team INTEGER NOT NULL,
+  home_result INTEGER NOT NULL,
+  away_result INTEGER NOT NULL,
+  home_penalty INTEGER,
+  away_penalty INTEGER,
+  PRIMARY KEY(tournament_id, game_id),
+  FOREIGN KEY(tournament_id, game_id) REFERENCES playoff_games(tournament_id, id),
+  FOREIGN KEY(tournament_id, home_team) REFERENCES teams(tournament_id, id),
+  FOREIGN KEY(tournament_id, away_team) REFERENCES teams(tournament_id, id)
+);
+
+CREATE TABLE preds (
+  id INTEGER PRIMARY KEY NOT NULL,
+  tournament_id INTEGER NOT NULL,
+  player_id INTEGER NOT NULL,
+  game_id INTEGER NOT NULL,
+  home_result INTEGER NOT NULL,
+  away_result INTEGER NOT NULL,
+  bot_name VARCHAR,  -- NULL for human predictions, bot identifier for bot predictions
+  FOREIGN KEY(tournament_id, game_id) REFERENCES games(tournament_id, id),
+  FOREIGN KEY(player_id) REFERENCES players(id)
+);
+
+CREATE TABLE playoff_preds (
+  id INTEGER PRIMARY KEY NOT NULL,
+  tournament_id INTEGER NOT NULL,
+  player_id INTEGER NOT NULL,
+  game_id INTEGER NOT NULL,
+  home_team INTEGER NOT NULL,
+  away_team INTEGER NOT NULL,
+  home_result INTEGER NOT NULL,
+  away_result INTEGER NOT NULL,
+  home_penalty INTEGER,
+  away_penalty INTEGER,
+  bot_name VARCHAR,  -- NULL for human predictions, bot identifier for bot predictions
+  FOREIGN KEY(tournament_id, game_id) REFERENCES playoff_games(tournament_id, id),
+  FOREIGN KEY(player_id) REFERENCES players(id),
+  FOREIGN KEY(tournament_id, home_team) REFERENCES teams(tournament_id, id),
+  FOREIGN KEY(tournament_id, away_team) REFERENCES teams(tournament_id, id)
+);
+
+CREATE TABLE scoring_configs (
+  tournament_id INTEGER PRIMARY KEY NOT NULL,
+  config VARCHAR NOT NULL,  -- JSON serialized wwc_core::pred_score::scheme::ScoringConfig
+  FOREIGN KEY(tournament_id) REFERENCES tournaments(id)
+);
+
+CREATE TABLE proba_preds (
+  id INTEGER PRIMARY KEY NOT NULL,
+  tournament_id INTEGER NOT NULL,
+  player_id INTEGER NOT NULL,
+  bot_name VARCHAR NOT NULL,
+  game_id INTEGER NOT NULL,
+  forecast VARCHAR NOT NULL,  -- JSON serialized wwc_core::pred_score::proba::ProbForecast
+  FOREIGN KEY(tournament_id, game_id) REFERENCES games(tournament_id, id),
+  FOREIGN KEY(player_id) REFERENCES players(id)
+);
+
+CREATE TABLE bonus_questions (
+  tournament_id INTEGER NOT NULL,
+  id INTEGER NOT NULL,
+  text VARCHAR NOT NULL,
+  kind VARCHAR NOT NULL,  -- JSON serialized wwc_core::bonus::BonusKind
+  deadline VARCHAR NOT NULL,  -- RFC 3339
+  solution VARCHAR,  -- JSON serialized accepted answers, NULL until resolved
+  PRIMARY KEY(tournament_id, id),
+  FOREIGN KEY(tournament_id) REFERENCES tournaments(id)
+);
+
+CREATE TABLE bonus_preds (
+  id INTEGER PRIMARY KEY NOT NULL,
+  tournament_id INTEGER NOT NULL,
+  player_id INTEGER NOT NULL,
+  question_id INTEGER NOT NULL,
+  answer VARCHAR NOT NULL,  -- JSON serialized wwc_core::bonus::BonusAnswer
+  bot_name VARCHAR,  -- NULL for human predictions, bot identifier for bot predictions
+  FOREIGN KEY(tournament_id, question_id) REFERENCES bonus_questions(tournament_id, id),
+  FOREIGN KEY(player_id) REFERENCES players(id)
+);
+
+CREATE TABLE jokers (
+  id INTEGER PRIMARY KEY NOT NULL,
+  tournament_id INTEGER NOT NULL,
+  player_id INTEGER NOT NULL,
+  game_id INTEGER NOT NULL,
+  bot_name VARCHAR,  -- NULL for human predictions, bot identifier for bot predictions
+  FOREIGN KEY(player_id) REFERENCES players(id),
+  FOREIGN KEY(tournament_id, game_id) REFERENCES games(tournament_id, id)
+);
+
+CREATE TABLE game_kickoffs (
+  tournament_id INTEGER NOT NULL,
+  game_id INTEGER NOT NULL,
+  kickoff VARCHAR NOT NULL,  -- RFC 3339
+  PRIMARY KEY(tournament_id, game_id),
+  FOREIGN KEY(tournament_id, game_id) REFERENCES games(tournament_id, id)
+);
+
+CREATE TABLE outcome_preds (
+  id INTEGER PRIMARY KEY NOT NULL,
+  tournament_id INTEGER NOT NULL,
+  player_id INTEGER NOT NULL,
+  game_id INTEGER NOT NULL,
+  outcome VARCHAR NOT NULL,  -- 'win', 'draw' or 'lose', from the home team's perspective
+  bot_name VARCHAR,  -- NULL for human predictions, bot identifier for bot predictions
+  FOREIGN KEY(player_id) REFERENCES players(id),
+  FOREIGN KEY(tournament_id, game_id) REFERENCES games(tournament_id, id)
+);
+
+CREATE TABLE pred_submissions (
+  id INTEGER PRIMARY KEY NOT NULL,
+  tournament_id INTEGER NOT NULL,
+  player_id INTEGER NOT NULL,
+  bot_name VARCHAR,  -- NULL for human predictions, bot identifier for bot predictions
+  submitted_at VARCHAR NOT NULL,  -- RFC 3339
+  FOREIGN KEY(tournament_id) REFERENCES tournaments(id),
+  FOREIGN KEY(player_id) REFERENCES players(id)
+);
+
+-- Game ids are those of the submission's tournament
+CREATE TABLE pred_history (
+  id INTEGER PRIMARY KEY NOT NULL,
+  submission_id INTEGER NOT NULL,
+  game_id INTEGER NOT NULL,
+  home_result INTEGER NOT NULL,
+  away_result INTEGER NOT NULL,
+  FOREIGN KEY(submission_id) REFERENCES pred_submissions(id)
+);
+
+INSERT INTO teams
+SELECT t.id, o.id, o.name, o.fifa_code, o.rank_
+FROM teams_old o, tournaments t WHERE t.name = 'fifa-2018';
+INSERT INTO games
+SELECT t.id, o.id, o.type_, o.home_team, o.away_team, o.home_result, o.away_result,
+  o.home_penalty, o.away_penalty, o.home_fair_play, o.away_fair_play, o.played
+FROM games_old o, tournaments t WHERE t.name = 'fifa-2018';
+INSERT INTO group_game_map
+SELECT t.id, o.id, o.group_id_
+FROM group_game_map_old o, tournaments t WHERE t.name = 'fifa-2018';
+INSERT INTO playoff_games
+SELECT t.id, o.id
+FROM playoff_games_old o, tournaments t WHERE t.name = 'fifa-2018';
+INSERT INTO playoff_team_sources
+SELECT t.id, o.game_id,
+  o.home_source_type, o.home_group_id, o.home_outcome, o.home_third_place_groups,
+  o.home_source_game_id,
+  o.away_source_type, o.away_group_id, o.away_outcome, o.away_third_place_groups,
+  o.away_source_game_id
+FROM playoff_team_sources_old o, tournaments t WHERE t.name = 'fifa-2018';
+INSERT INTO playoff_results
+SELECT t.id, o.game_id, o.home_team, o.away_team, o.home_result, o.away_result,
+  o.home_penalty, o.away_penalty
+FROM playoff_results_old o, tournaments t WHERE t.name = 'fifa-2018';
+INSERT INTO preds
+SELECT o.id, t.id, o.player_id, o.game_id, o.home_result, o.away_result, o.bot_name
+FROM preds_old o, tournaments t WHERE t.name = 'fifa-2018';
+INSERT INTO playoff_preds
+SELECT o.id, t.id, o.player_id, o.game_id, o.home_team, o.away_team, o.home_result,
+  o.away_result, o.home_penalty, o.away_penalty, o.bot_name
+FROM playoff_preds_old o, tournaments t WHERE t.name = 'fifa-2018';
+INSERT INTO scoring_configs
+SELECT t.id, o.config
+FROM scoring_configs_old o JOIN tournaments t ON t.name = o.tournament;
+INSERT INTO proba_preds
+SELECT o.id, t.id, o.player_id, o.bot_name, o.game_id, o.forecast
+FROM proba_preds_old o, tournaments t WHERE t.name = 'fifa-2018';
+INSERT INTO bonus_questions
+SELECT t.id, o.id, o.text, o.kind, o.deadline, o.solution
+FROM bonus_questions_old o, tournaments t WHERE t.name = 'fifa-2018';
+INSERT INTO bonus_preds
+SELECT o.id, t.id, o.player_id, o.question_id, o.answer, o.bot_name
+FROM bonus_preds_old o, tournaments t WHERE t.name = 'fifa-2018';
+INSERT INTO jokers
+SELECT o.id, t.id, o.player_id, o.game_id, o.bot_name
+FROM jokers_old o, tournaments t WHERE t.name = 'fifa-2018';
+INSERT INTO game_kickoffs
+SELECT t.id, o.game_id, o.kickoff
+FROM game_kickoffs_old o, tournaments t WHERE t.name = 'fifa-2018';
+INSERT INTO outcome_preds
+SELECT o.id, t.id, o.player_id, o.game_id, o.outcome, o.bot_name
+FROM outcome_preds_old o, tournaments t WHERE t.name = 'fifa-2018';
+INSERT INTO pred_submissions
+SELECT o.id, t.id, o.player_id, o.bot_name, o.submitted_at
+FROM pred_submissions_old o, tournaments t WHERE t.name = 'fifa-2018';
+INSERT INTO pred_history
+SELECT o.id, o.submission_id, o.game_id, o.home_result, o.away_result
+FROM pred_history_old o;
+
+-- Children first, the old tables only reference each other
+DROP TABLE pred_history_old;
+DROP TABLE pred_submissions_old;
+DROP TABLE outcome_preds_old;
+DROP TABLE game_kickoffs_old;
+DROP TABLE jokers_old;
+DROP TABLE bonus_preds_old;
+DROP TABLE bonus_questions_old;
+DROP TABLE proba_preds_old;
+DROP TABLE scoring_configs_old;
+DROP TABLE playoff_preds_old;
+DROP TABLE preds_old;
+DROP TABLE playoff_results_old;
+DROP TABLE playoff_team_sources_old;
+DROP TABLE playoff_games_old;
+DROP TABLE group_game_map_old;
+DROP TABLE games_old;
+DROP TABLE teams_old;
+
+-- At most one prediction per tournament, player, bot and game, see 011_preds_unique.sql
+CREATE UNIQUE INDEX preds_player_game_bot
+  ON preds (tournament_id, player_id, game_id, IFNULL(bot_name, ''));
@@ -0,0 +1,21 @@
+-- Private leagues within a tournament, with their own members, invite code and scoring config
+--
+-- Predictions belong to the tournament, so a player's predictions count in all of their leagues.
+CREATE TABLE IF NOT EXISTS leagues (
+  id INTEGER PRIMARY KEY NOT NULL,
+  tournament_id INTEGER NOT NULL,
+  name VARCHAR NOT NULL,
+  invite_code VARCHAR NOT NULL UNIQUE,
+  config TEXT,  -- JSON ScoringConfig, NULL to use the tournament's config
+  FOREIGN KEY(tournament_id) REFERENCES tournaments(id)
+);
+
+CREATE TABLE IF NOT EXISTS league_members (
+  league_id INTEGER NOT NULL,
+  player_id INTEGER NOT NULL,
+  role VARCHAR NOT NULL DEFAULT 'member',  -- 'owner' or 'member'
+  joined_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY(league_id, player_id),
+  FOREIGN KEY(league_id) REFERENCES leagues(id) ON DELETE CASCADE,
+  FOREIGN KEY(player_id) REFERENCES players(id)
+);
@@ -0,0 +1,19 @@
+-- Card counts of each team in a played group game, used by the fair play tiebreaker
+--
+-- Replaces the unused home_fair_play/away_fair_play columns of games, a single integer can't
+-- hold the four card categories. A game without rows here has no cards.
+CREATE TABLE IF NOT EXISTS fair_play_cards (
+  tournament_id INTEGER NOT NULL,
+  game_id INTEGER NOT NULL,
+  team_id INTEGER NOT NULL,
+  yellow INTEGER NOT NULL DEFAULT 0,
+  indirect_red INTEGER NOT NULL DEFAULT 0,  -- second yellow card
+  direct_red INTEGER NOT NULL DEFAULT 0,
+  yellow_and_direct INTEGER NOT NULL DEFAULT 0,
+  PRIMARY KEY(tournament_id, game_id, team_id),
+  FOREIGN KEY(tournament_id, game_id) REFERENCES games(tournament_id, id),
+  FOREIGN KEY(tournament_id, team_id) REFERENCES teams(tournament_id, id)
+);
+
+ALTER TABLE games DROP COLUMN home_fair_play;
+ALTER TABLE games DROP COLUMN away_fair_play;
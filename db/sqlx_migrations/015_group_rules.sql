@@ -0,0 +1,5 @@
+-- Group rules of every tournament, see wwc_core::group::order::RuleSet
+--
+-- Tournaments added before the rules were stored follow the data they were added from, which
+-- the CLI names after the tournament.
+UPDATE tournaments SET group_rules = 'euro-2020' WHERE name = 'euro-2020';
@@ -0,0 +1,19 @@
+-- Append-only history of outcome prediction submissions, like pred_submissions and pred_history
+CREATE TABLE IF NOT EXISTS outcome_pred_submissions (
+  id INTEGER PRIMARY KEY NOT NULL,
+  tournament_id INTEGER NOT NULL,
+  player_id INTEGER NOT NULL,
+  bot_name VARCHAR,  -- NULL for human predictions, bot identifier for bot predictions
+  submitted_at VARCHAR NOT NULL,  -- RFC 3339
+  FOREIGN KEY(tournament_id) REFERENCES tournaments(id),
+  FOREIGN KEY(player_id) REFERENCES players(id)
+);
+
+-- The stored outcome predictions after each submission, none if the predictions were cleared
+CREATE TABLE IF NOT EXISTS outcome_pred_history (
+  id INTEGER PRIMARY KEY NOT NULL,
+  submission_id INTEGER NOT NULL,
+  game_id INTEGER NOT NULL,
+  outcome VARCHAR NOT NULL,  -- 'win', 'draw' or 'lose', from the home team's perspective
+  FOREIGN KEY(submission_id) REFERENCES outcome_pred_submissions(id)
+);
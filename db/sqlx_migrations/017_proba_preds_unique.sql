@@ -0,0 +1,12 @@
+-- At most one probabilistic prediction per tournament, player, bot and game, like the preds in
+-- 011_preds_unique.sql
+--
+-- Existing duplicates are removed first, the latest (highest id) prediction is kept.
+DELETE FROM proba_preds
+WHERE id NOT IN (
+  SELECT MAX(id) FROM proba_preds
+  GROUP BY tournament_id, player_id, game_id, IFNULL(bot_name, '')
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS proba_preds_player_game_bot
+  ON proba_preds (tournament_id, player_id, game_id, IFNULL(bot_name, ''));
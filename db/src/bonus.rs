@@ -0,0 +1,185 @@
+use crate::DbError;
+use crate::decode::to_column;
+use crate::models::{BonusPred, BonusQuestionRow, TournamentId};
+use sqlx::SqlitePool;
+use wwc_core::bonus::{BonusAnswer, BonusPrediction, BonusQuestion, BonusQuestionId};
+use wwc_core::player::PlayerId;
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String, DbError> {
+    serde_json::to_string(value)
+        .map_err(|e| DbError::Generic(format!("Could not serialize bonus data: {}", e)))
+}
+
+fn question_id_to_i32(id: BonusQuestionId) -> Result<i32, DbError> {
+    to_column("question_id", u32::from(id))
+}
+
+/// Get all bonus questions of a tournament, ordered by id
+pub async fn get_bonus_questions(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+) -> Result<Vec<BonusQuestion>, DbError> {
+    let rows = sqlx::query_as::<_, BonusQuestionRow>(
+        "SELECT * FROM bonus_questions WHERE tournament_id = ? ORDER BY id",
+    )
+    .bind(tournament)
+    .fetch_all(pool)
+    .await
+    .map_err(DbError::Sqlx)?;
+
+    rows.into_iter().map(BonusQuestion::try_from).collect()
+}
+
+/// Insert a bonus question into a tournament (replaces any existing question with the same id)
+pub async fn insert_bonus_question(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    question: &BonusQuestion,
+) -> Result<(), DbError> {
+    let solution = question.solution.as_ref().map(to_json).transpose()?;
+    sqlx::query(
+        "INSERT OR REPLACE INTO bonus_questions (tournament_id, id, text, kind, deadline, solution)
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(tournament)
+    .bind(question_id_to_i32(question.id)?)
+    .bind(&question.text)
+    .bind(to_json(&question.kind)?)
+    .bind(question.deadline.to_rfc3339())
+    .bind(solution)
+    .execute(pool)
+    .await
+    .map_err(DbError::Sqlx)?;
+    Ok(())
+}
+
+/// Set the accepted answers of a bonus question
+pub async fn resolve_bonus_question(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    id: BonusQuestionId,
+    solution: &[BonusAnswer],
+) -> Result<(), DbError> {
+    let result =
+        sqlx::query("UPDATE bonus_questions SET solution = ? WHERE tournament_id = ? AND id = ?")
+            .bind(to_json(&solution)?)
+            .bind(tournament)
+            .bind(question_id_to_i32(id)?)
+            .execute(pool)
+            .await
+            .map_err(DbError::Sqlx)?;
+    if result.rows_affected() == 0 {
+        return Err(DbError::Generic(format!(
+            "No bonus question with id {}",
+            id
+        )));
+    }
+    Ok(())
+}
+
+/// Get the bonus answers of a specific player and optional bot
+///
+/// With `None`, only the player's own (non-bot) answers are returned.
+pub async fn get_bonus_preds(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    player_id: PlayerId,
+    bot_name: Option<&str>,
+) -> Result<Vec<BonusPrediction>, DbError> {
+    let preds = sqlx::query_as::<_, BonusPred>(
+        "SELECT * FROM bonus_preds WHERE tournament_id = ? AND player_id = ? AND bot_name IS ?
+         ORDER BY question_id",
+    )
+    .bind(tournament)
+    .bind(i32::from(player_id))
+    .bind(bot_name)
+    .fetch_all(pool)
+    .await
+    .map_err(DbError::Sqlx)?;
+
+    preds.into_iter().map(BonusPrediction::try_from).collect()
+}
+
+/// Insert the bonus answers of a player and optional bot
+///
+/// Replaces the previous answers to the same questions, answers to other questions are kept.
+pub async fn insert_bonus_preds(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    player_id: PlayerId,
+    preds: &[BonusPrediction],
+    bot_name: Option<&str>,
+) -> Result<(), DbError> {
+    for BonusPrediction(question_id, answer) in preds {
+        sqlx::query(
+            "DELETE FROM bonus_preds
+             WHERE tournament_id = ? AND player_id = ? AND question_id = ? AND bot_name IS ?",
+        )
+        .bind(tournament)
+        .bind(i32::from(player_id))
+        .bind(question_id_to_i32(*question_id)?)
+        .bind(bot_name)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+
+        sqlx::query(
+            "INSERT INTO bonus_preds (tournament_id, player_id, question_id, answer, bot_name)
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(tournament)
+        .bind(i32::from(player_id))
+        .bind(question_id_to_i32(*question_id)?)
+        .bind(to_json(answer)?)
+        .bind(bot_name)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    }
+    Ok(())
+}
+
+/// Clear the bonus answers of a specific player and optional bot
+///
+/// With `None`, only the player's own (non-bot) answers are cleared.
+pub async fn clear_player_bonus_preds(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    player_id: PlayerId,
+    bot_name: Option<&str>,
+) -> Result<(), DbError> {
+    sqlx::query(
+        "DELETE FROM bonus_preds WHERE tournament_id = ? AND player_id = ? AND bot_name IS ?",
+    )
+    .bind(tournament)
+    .bind(i32::from(player_id))
+    .bind(bot_name)
+    .execute(pool)
+    .await
+    .map_err(DbError::Sqlx)?;
+    Ok(())
+}
+
+/// Clear all bonus answers of a tournament
+pub async fn clear_bonus_preds(pool: &SqlitePool, tournament: TournamentId) -> Result<(), DbError> {
+    sqlx::query("DELETE FROM bonus_preds WHERE tournament_id = ?")
+        .bind(tournament)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    Ok(())
+}
+
+/// Clear all bonus questions (and answers) of a tournament
+pub async fn clear_bonus_questions(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+) -> Result<(), DbError> {
+    clear_bonus_preds(pool, tournament).await?;
+    sqlx::query("DELETE FROM bonus_questions WHERE tournament_id = ?")
+        .bind(tournament)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    Ok(())
+}
@@ -0,0 +1,344 @@
+//! Consistency check of a whole database
+//!
+//! The regular reads stop at the first row which does not decode into its core type. The check
+//! decodes every row on its own instead, to report all corrupt rows at once.
+
+use crate::DbError;
+use crate::decode::RowRef;
+use crate::joker::decode_kickoff;
+use crate::models::{
+    BonusPred, BonusQuestionRow, FairPlayCards, Game, GroupGameMap, OutcomePred, PlayoffPred,
+    PlayoffResultRow, PlayoffTeamSourceRow, Pred, PredHistoryRow, Team, Tournament, TournamentId,
+};
+use crate::playoff::decode_team_sources;
+use crate::proba::decode_pred;
+use crate::tournament::get_tournaments;
+use sqlx::sqlite::SqliteRow;
+use sqlx::{FromRow, SqlitePool};
+use wwc_core::bonus::{BonusPrediction, BonusQuestion};
+use wwc_core::fair_play::{FairPlay, FairPlayScore};
+use wwc_core::game::GameId;
+use wwc_core::group::GroupId;
+use wwc_core::group::game::UnplayedGroupGame;
+use wwc_core::player::{OutcomePrediction, Prediction};
+use wwc_core::playoff::PlayoffResult;
+use wwc_core::pred_score::scheme::ScoringConfig;
+use wwc_core::team::TeamId;
+
+/// A row which does not decode into its core type
+#[derive(Debug)]
+pub struct CorruptRow {
+    pub tournament: Tournament,
+    /// Names the table, row and column of the corrupt value
+    pub error: DbError,
+}
+
+/// Decode every row of every tournament, returns the rows which do not decode
+///
+/// Errors only if the database cannot be read.
+pub async fn check_consistency(pool: &SqlitePool) -> Result<Vec<CorruptRow>, DbError> {
+    let mut corrupt = Vec::new();
+    for tournament in get_tournaments(pool).await? {
+        let errors = check_tournament(pool, tournament.id).await?;
+        corrupt.extend(errors.into_iter().map(|error| CorruptRow {
+            tournament: tournament.clone(),
+            error,
+        }));
+    }
+    Ok(corrupt)
+}
+
+/// All rows of a query with the tournament as its only parameter
+async fn rows<T>(pool: &SqlitePool, sql: &str, tournament: TournamentId) -> Result<Vec<T>, DbError>
+where
+    T: for<'r> FromRow<'r, SqliteRow> + Send + Unpin,
+{
+    sqlx::query_as::<_, T>(sql)
+        .bind(tournament)
+        .fetch_all(pool)
+        .await
+        .map_err(DbError::Sqlx)
+}
+
+async fn check_tournament(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+) -> Result<Vec<DbError>, DbError> {
+    let mut errors = Vec::new();
+    let mut check = |results: Vec<Result<(), DbError>>| {
+        errors.extend(results.into_iter().filter_map(Result::err));
+    };
+
+    let teams: Vec<Team> = rows(
+        pool,
+        "SELECT * FROM teams WHERE tournament_id = ?",
+        tournament,
+    )
+    .await?;
+    check(
+        teams
+            .into_iter()
+            .map(|team| wwc_core::Team::try_from(team).map(drop))
+            .collect(),
+    );
+
+    let games: Vec<Game> = rows(
+        pool,
+        "SELECT * FROM games WHERE tournament_id = ?",
+        tournament,
+    )
+    .await?;
+    check(
+        games
+            .into_iter()
+            .map(|game| {
+                if game.played {
+                    game.try_into_played(FairPlayScore::default()).map(drop)
+                } else {
+                    UnplayedGroupGame::try_from(game).map(drop)
+                }
+            })
+            .collect(),
+    );
+
+    let cards: Vec<FairPlayCards> = rows(
+        pool,
+        "SELECT * FROM fair_play_cards WHERE tournament_id = ?",
+        tournament,
+    )
+    .await?;
+    check(
+        cards
+            .iter()
+            .map(|row| <((GameId, TeamId), FairPlay)>::try_from(row).map(drop))
+            .collect(),
+    );
+
+    let maps: Vec<GroupGameMap> = rows(
+        pool,
+        "SELECT * FROM group_game_map WHERE tournament_id = ?",
+        tournament,
+    )
+    .await?;
+    check(
+        maps.into_iter()
+            .map(|map_| <(GameId, GroupId)>::try_from(map_).map(drop))
+            .collect(),
+    );
+
+    let playoff_games: Vec<(i32,)> = rows(
+        pool,
+        "SELECT id FROM playoff_games WHERE tournament_id = ?",
+        tournament,
+    )
+    .await?;
+    check(
+        playoff_games
+            .into_iter()
+            .map(|(id,)| RowRef::new("playoff_games", id).game_id("id", id).map(drop))
+            .collect(),
+    );
+
+    let sources: Vec<PlayoffTeamSourceRow> = rows(
+        pool,
+        "SELECT * FROM playoff_team_sources WHERE tournament_id = ?",
+        tournament,
+    )
+    .await?;
+    check(
+        sources
+            .into_iter()
+            .map(|row| decode_team_sources(row).map(drop))
+            .collect(),
+    );
+
+    let results: Vec<PlayoffResultRow> = rows(
+        pool,
+        "SELECT * FROM playoff_results WHERE tournament_id = ?",
+        tournament,
+    )
+    .await?;
+    check(
+        results
+            .into_iter()
+            .map(|row| <(GameId, PlayoffResult)>::try_from(row).map(drop))
+            .collect(),
+    );
+
+    let preds: Vec<Pred> = rows(
+        pool,
+        "SELECT * FROM preds WHERE tournament_id = ?",
+        tournament,
+    )
+    .await?;
+    check(
+        preds
+            .into_iter()
+            .map(|pred| Prediction::try_from(pred).map(drop))
+            .collect(),
+    );
+
+    let playoff_preds: Vec<PlayoffPred> = rows(
+        pool,
+        "SELECT * FROM playoff_preds WHERE tournament_id = ?",
+        tournament,
+    )
+    .await?;
+    check(
+        playoff_preds
+            .into_iter()
+            .map(|pred| <(GameId, PlayoffResult)>::try_from(pred).map(drop))
+            .collect(),
+    );
+
+    let outcome_preds: Vec<OutcomePred> = rows(
+        pool,
+        "SELECT * FROM outcome_preds WHERE tournament_id = ?",
+        tournament,
+    )
+    .await?;
+    check(
+        outcome_preds
+            .into_iter()
+            .map(|pred| OutcomePrediction::try_from(pred).map(drop))
+            .collect(),
+    );
+
+    let proba_preds: Vec<(i32, i32, String)> = rows(
+        pool,
+        "SELECT id, game_id, forecast FROM proba_preds WHERE tournament_id = ?",
+        tournament,
+    )
+    .await?;
+    check(
+        proba_preds
+            .iter()
+            .map(|(id, game_id, forecast)| decode_pred(*id, *game_id, forecast).map(drop))
+            .collect(),
+    );
+
+    let questions: Vec<BonusQuestionRow> = rows(
+        pool,
+        "SELECT * FROM bonus_questions WHERE tournament_id = ?",
+        tournament,
+    )
+    .await?;
+    check(
+        questions
+            .into_iter()
+            .map(|row| BonusQuestion::try_from(row).map(drop))
+            .collect(),
+    );
+
+    let bonus_preds: Vec<BonusPred> = rows(
+        pool,
+        "SELECT * FROM bonus_preds WHERE tournament_id = ?",
+        tournament,
+    )
+    .await?;
+    check(
+        bonus_preds
+            .into_iter()
+            .map(|pred| BonusPrediction::try_from(pred).map(drop))
+            .collect(),
+    );
+
+    let jokers: Vec<(i32, i32)> = rows(
+        pool,
+        "SELECT id, game_id FROM jokers WHERE tournament_id = ?",
+        tournament,
+    )
+    .await?;
+    check(
+        jokers
+            .into_iter()
+            .map(|(id, game_id)| {
+                RowRef::new("jokers", id)
+                    .game_id("game_id", game_id)
+                    .map(drop)
+            })
+            .collect(),
+    );
+
+    let kickoffs: Vec<(i32, String)> = rows(
+        pool,
+        "SELECT game_id, kickoff FROM game_kickoffs WHERE tournament_id = ?",
+        tournament,
+    )
+    .await?;
+    check(
+        kickoffs
+            .iter()
+            .map(|(game_id, kickoff)| decode_kickoff(*game_id, kickoff).map(drop))
+            .collect(),
+    );
+
+    let submissions: Vec<(i32, String)> = rows(
+        pool,
+        "SELECT id, submitted_at FROM pred_submissions WHERE tournament_id = ?",
+        tournament,
+    )
+    .await?;
+    check(
+        submissions
+            .iter()
+            .map(|(id, submitted_at)| {
+                RowRef::new("pred_submissions", id)
+                    .date("submitted_at", submitted_at)
+                    .map(drop)
+            })
+            .collect(),
+    );
+
+    let history: Vec<PredHistoryRow> = rows(
+        pool,
+        "SELECT s.id AS submission_id, s.submitted_at, h.game_id, h.home_result, h.away_result
+         FROM pred_history h
+         JOIN pred_submissions s ON h.submission_id = s.id
+         WHERE s.tournament_id = ?",
+        tournament,
+    )
+    .await?;
+    check(
+        history
+            .iter()
+            .map(|row| row.prediction().map(drop))
+            .collect(),
+    );
+
+    let configs: Vec<(String,)> = rows(
+        pool,
+        "SELECT config FROM scoring_configs WHERE tournament_id = ?",
+        tournament,
+    )
+    .await?;
+    check(
+        configs
+            .iter()
+            .map(|(config,)| {
+                RowRef::new("scoring_configs", tournament)
+                    .json::<ScoringConfig>("config", config)
+                    .map(drop)
+            })
+            .collect(),
+    );
+
+    let league_configs: Vec<(i32, String)> = rows(
+        pool,
+        "SELECT id, config FROM leagues WHERE tournament_id = ? AND config IS NOT NULL",
+        tournament,
+    )
+    .await?;
+    check(
+        league_configs
+            .iter()
+            .map(|(id, config)| {
+                RowRef::new("leagues", id)
+                    .json::<ScoringConfig>("config", config)
+                    .map(drop)
+            })
+            .collect(),
+    );
+
+    Ok(errors)
+}
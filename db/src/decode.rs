@@ -0,0 +1,112 @@
+//! Checked decoding of column values into core types
+//!
+//! Every conversion names the table, row and column of the value, so that a corrupt row is
+//! reported instead of crashing the caller.
+
+use crate::DbError;
+use serde::de::DeserializeOwned;
+use std::fmt::Display;
+use wwc_core::Date;
+use wwc_core::game::{GameId, GoalCount};
+use wwc_core::group::GroupId;
+use wwc_core::group::game::GroupGameOutcome;
+use wwc_core::team::TeamId;
+
+/// A row of a table, the location of the values decoded from it
+#[derive(Debug, Clone)]
+pub(crate) struct RowRef {
+    table: &'static str,
+    row: String,
+}
+
+impl RowRef {
+    /// Row of `table`, identified by `row` (its id, or its key columns)
+    pub(crate) fn new(table: &'static str, row: impl Display) -> Self {
+        RowRef {
+            table,
+            row: row.to_string(),
+        }
+    }
+
+    /// Error for an invalid value in the column `field`
+    pub(crate) fn invalid(&self, field: &'static str, reason: impl Display) -> DbError {
+        DbError::InvalidValue {
+            table: self.table,
+            row: self.row.clone(),
+            field,
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Value of a nullable column which must be set
+    pub(crate) fn required<T>(&self, field: &'static str, value: Option<T>) -> Result<T, DbError> {
+        value.ok_or_else(|| DbError::MissingValue {
+            table: self.table,
+            row: self.row.clone(),
+            field,
+        })
+    }
+
+    pub(crate) fn unsigned(&self, field: &'static str, value: i32) -> Result<u32, DbError> {
+        u32::try_from(value).map_err(|_| DbError::NegativeValue {
+            table: self.table,
+            row: self.row.clone(),
+            field,
+            value,
+        })
+    }
+
+    pub(crate) fn game_id(&self, field: &'static str, value: i32) -> Result<GameId, DbError> {
+        self.unsigned(field, value).map(GameId::from)
+    }
+
+    pub(crate) fn team_id(&self, field: &'static str, value: i32) -> Result<TeamId, DbError> {
+        self.unsigned(field, value).map(TeamId)
+    }
+
+    pub(crate) fn goals(&self, field: &'static str, value: i32) -> Result<GoalCount, DbError> {
+        GoalCount::try_from(self.unsigned(field, value)?).map_err(|e| self.invalid(field, e))
+    }
+
+    /// Group id stored as a single letter
+    pub(crate) fn group_id(&self, field: &'static str, value: &str) -> Result<GroupId, DbError> {
+        let mut chars = value.chars();
+        match (chars.next(), chars.next()) {
+            (Some(id), None) => GroupId::try_from(id).map_err(|e| self.invalid(field, e)),
+            _ => Err(self.invalid(field, format!("'{}' is not a group letter", value))),
+        }
+    }
+
+    /// Outcome stored as 'win', 'draw' or 'lose', from the home team's perspective
+    pub(crate) fn outcome(
+        &self,
+        field: &'static str,
+        value: &str,
+    ) -> Result<GroupGameOutcome, DbError> {
+        match value {
+            "win" => Ok(GroupGameOutcome::Win),
+            "draw" => Ok(GroupGameOutcome::Draw),
+            "lose" => Ok(GroupGameOutcome::Lose),
+            other => Err(self.invalid(field, format!("'{}' is not an outcome", other))),
+        }
+    }
+
+    pub(crate) fn date(&self, field: &'static str, value: &str) -> Result<Date, DbError> {
+        Date::parse_rfc3339(value).map_err(|e| self.invalid(field, e))
+    }
+
+    pub(crate) fn json<T: DeserializeOwned>(
+        &self,
+        field: &'static str,
+        value: &str,
+    ) -> Result<T, DbError> {
+        serde_json::from_str(value).map_err(|e| self.invalid(field, e))
+    }
+}
+
+/// Column value of an unsigned id or count, the checked inverse of [`RowRef::unsigned`]
+///
+/// Values are stored as `INTEGER` and bound as `i32`. Ids from clients may exceed that range.
+pub(crate) fn to_column(field: &'static str, value: u32) -> Result<i32, DbError> {
+    i32::try_from(value).map_err(|_| DbError::OutOfRange { field, value })
+}
@@ -0,0 +1,87 @@
+use crate::DbError;
+use crate::decode::to_column;
+use crate::models::{FairPlayCards, TournamentId};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use wwc_core::fair_play::{FairPlay, FairPlayScore};
+use wwc_core::game::GameId;
+use wwc_core::team::TeamId;
+
+/// Get the card counts of all teams in the games of a tournament, by game and team
+///
+/// Teams without cards in a game are left out.
+pub async fn get_fair_play_cards(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+) -> Result<HashMap<(GameId, TeamId), FairPlay>, DbError> {
+    let cards = sqlx::query_as::<_, FairPlayCards>(
+        "SELECT game_id, team_id, yellow, indirect_red, direct_red, yellow_and_direct
+         FROM fair_play_cards WHERE tournament_id = ?",
+    )
+    .bind(tournament)
+    .fetch_all(pool)
+    .await
+    .map_err(DbError::Sqlx)?;
+
+    cards.iter().map(TryFrom::try_from).collect()
+}
+
+/// Insert the cards of both teams in a game (replaces any existing cards of the game)
+pub async fn insert_fair_play_cards(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    game_id: GameId,
+    (home, away): (TeamId, TeamId),
+    fair_play: &FairPlayScore,
+) -> Result<(), DbError> {
+    clear_game_fair_play_cards(pool, tournament, game_id).await?;
+    for (team_id, cards) in [(home, fair_play.home), (away, fair_play.away)] {
+        if cards == FairPlay::default() {
+            continue;
+        }
+        let count = |field, x| to_column(field, u32::from(x));
+        sqlx::query(
+            "INSERT INTO fair_play_cards (tournament_id, game_id, team_id, yellow, indirect_red, \
+             direct_red, yellow_and_direct) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(tournament)
+        .bind(to_column("game_id", u32::from(game_id))?)
+        .bind(to_column("team_id", u32::from(team_id))?)
+        .bind(count("yellow", cards.yellow())?)
+        .bind(count("indirect_red", cards.indirect_red())?)
+        .bind(count("direct_red", cards.direct_red())?)
+        .bind(count("yellow_and_direct", cards.yellow_and_direct())?)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    }
+    Ok(())
+}
+
+/// Clear the cards of both teams in a game
+pub async fn clear_game_fair_play_cards(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    game_id: GameId,
+) -> Result<(), DbError> {
+    sqlx::query("DELETE FROM fair_play_cards WHERE tournament_id = ? AND game_id = ?")
+        .bind(tournament)
+        .bind(to_column("game_id", u32::from(game_id))?)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    Ok(())
+}
+
+/// Clear the cards of all games of a tournament
+pub async fn clear_fair_play_cards(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+) -> Result<(), DbError> {
+    sqlx::query("DELETE FROM fair_play_cards WHERE tournament_id = ?")
+        .bind(tournament)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    Ok(())
+}
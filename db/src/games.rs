@@ -1,12 +1,22 @@
 //! Shared game operations (used by both group and playoff)
 
-use crate::{DbError, models::Game};
-use sqlx::SqlitePool;
+use crate::DbError;
+use crate::decode::{RowRef, to_column};
+use crate::fair_play::{clear_fair_play_cards, clear_game_fair_play_cards, insert_fair_play_cards};
+use crate::joker::get_game_kickoffs;
+use crate::models::{Game, TournamentId};
+use crate::playoff::get_playoff_results;
+use sqlx::{SqliteConnection, SqlitePool};
+use std::collections::HashSet;
+use wwc_core::Date;
+use wwc_core::game::GameId;
 use wwc_core::group::game::{PlayedGroupGame, UnplayedGroupGame};
+use wwc_core::player::PlayerId;
 
-/// Get all games from the database
-pub async fn get_games(pool: &SqlitePool) -> Result<Vec<Game>, DbError> {
-    let games = sqlx::query_as::<_, Game>("SELECT * FROM games")
+/// Get all games of a tournament
+pub async fn get_games(pool: &SqlitePool, tournament: TournamentId) -> Result<Vec<Game>, DbError> {
+    let games = sqlx::query_as::<_, Game>("SELECT * FROM games WHERE tournament_id = ?")
+        .bind(tournament)
         .fetch_all(pool)
         .await
         .map_err(DbError::Sqlx)?;
@@ -14,23 +24,112 @@ pub async fn get_games(pool: &SqlitePool) -> Result<Vec<Game>, DbError> {
     Ok(games)
 }
 
-/// Insert unplayed games into the database
+/// Get the ids of all group games of a tournament
+pub async fn get_group_game_ids(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+) -> Result<HashSet<GameId>, DbError> {
+    let ids =
+        sqlx::query_scalar::<_, i32>("SELECT id FROM games WHERE tournament_id = ? AND type_ = ?")
+            .bind(tournament)
+            .bind("group")
+            .fetch_all(pool)
+            .await
+            .map_err(DbError::Sqlx)?;
+
+    ids.into_iter()
+        .map(|id| RowRef::new("games", id).game_id("id", id))
+        .collect()
+}
+
+/// Get the ids of all games of a tournament which have kicked off at `now`
+///
+/// A game has kicked off once it is played or its kickoff time has passed.
+pub async fn get_locked_games(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    now: Date,
+) -> Result<HashSet<GameId>, DbError> {
+    let played =
+        sqlx::query_scalar::<_, i32>("SELECT id FROM games WHERE tournament_id = ? AND played = 1")
+            .bind(tournament)
+            .fetch_all(pool)
+            .await
+            .map_err(DbError::Sqlx)?;
+    let playoff_results = get_playoff_results(pool, tournament).await?;
+    let kickoffs = get_game_kickoffs(pool, tournament).await?;
+
+    let played = played
+        .into_iter()
+        .map(|id| RowRef::new("games", id).game_id("id", id))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(played
+        .into_iter()
+        .chain(playoff_results.played_games().map(|(game_id, _)| *game_id))
+        .chain(
+            kickoffs
+                .into_iter()
+                .filter(|(_, kickoff)| *kickoff <= now)
+                .map(|(game_id, _)| game_id),
+        )
+        .collect())
+}
+
+/// Delete the predictions in `table` of a player and optional bot, except those of `locked` games
+///
+/// With `all_bots`, the predictions of the player's bots are deleted as well, whatever `bot_name`.
+pub(crate) async fn delete_unlocked_preds(
+    conn: &mut SqliteConnection,
+    table: &'static str,
+    tournament: TournamentId,
+    player_id: PlayerId,
+    bot_name: Option<&str>,
+    all_bots: bool,
+    locked: &HashSet<GameId>,
+) -> Result<(), DbError> {
+    let stored = sqlx::query_as::<_, (i32, i32)>(&format!(
+        "SELECT id, game_id FROM {} WHERE tournament_id = ? AND player_id = ? \
+         AND (bot_name IS ? OR ?)",
+        table
+    ))
+    .bind(tournament)
+    .bind(i32::from(player_id))
+    .bind(bot_name)
+    .bind(all_bots)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(DbError::Sqlx)?;
+    for (id, game_id) in stored {
+        if locked.contains(&RowRef::new(table, id).game_id("game_id", game_id)?) {
+            continue;
+        }
+        sqlx::query(&format!("DELETE FROM {} WHERE id = ?", table))
+            .bind(id)
+            .execute(&mut *conn)
+            .await
+            .map_err(DbError::Sqlx)?;
+    }
+    Ok(())
+}
+
+/// Insert unplayed games into a tournament
 pub async fn insert_unplayed_games(
     pool: &SqlitePool,
+    tournament: TournamentId,
     games: &[UnplayedGroupGame],
 ) -> Result<(), DbError> {
     for game in games {
         sqlx::query(
-            "INSERT INTO games (id, type_, home_team, away_team, home_result, away_result, \
-             home_penalty, away_penalty, home_fair_play, away_fair_play, played) \
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            "INSERT INTO games (tournament_id, id, type_, home_team, away_team, home_result, \
+             away_result, home_penalty, away_penalty, played) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
-        .bind(i32::try_from(u32::from(game.id)).unwrap())
+        .bind(tournament)
+        .bind(to_column("id", u32::from(game.id))?)
         .bind("group")
-        .bind(i32::try_from(u32::from(game.home)).unwrap())
-        .bind(i32::try_from(u32::from(game.away)).unwrap())
-        .bind(None::<i32>)
-        .bind(None::<i32>)
+        .bind(to_column("home_team", u32::from(game.home))?)
+        .bind(to_column("away_team", u32::from(game.away))?)
         .bind(None::<i32>)
         .bind(None::<i32>)
         .bind(None::<i32>)
@@ -44,39 +143,108 @@ pub async fn insert_unplayed_games(
     Ok(())
 }
 
-/// Insert played games into the database
+/// Insert played games into a tournament
 pub async fn insert_played_games(
     pool: &SqlitePool,
+    tournament: TournamentId,
     games: &[PlayedGroupGame],
 ) -> Result<(), DbError> {
     for game in games {
         sqlx::query(
-            "INSERT INTO games (id, type_, home_team, away_team, home_result, away_result, \
-             home_penalty, away_penalty, home_fair_play, away_fair_play, played) \
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            "INSERT INTO games (tournament_id, id, type_, home_team, away_team, home_result, \
+             away_result, home_penalty, away_penalty, played) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
-        .bind(i32::try_from(u32::from(game.id)).unwrap())
+        .bind(tournament)
+        .bind(to_column("id", u32::from(game.id))?)
         .bind("group")
-        .bind(i32::try_from(u32::from(game.home)).unwrap())
-        .bind(i32::try_from(u32::from(game.away)).unwrap())
-        .bind(Some(i32::try_from(u32::from(game.score.home)).unwrap()))
-        .bind(Some(i32::try_from(u32::from(game.score.away)).unwrap()))
-        .bind(None::<i32>)
-        .bind(None::<i32>)
+        .bind(to_column("home_team", u32::from(game.home))?)
+        .bind(to_column("away_team", u32::from(game.away))?)
+        .bind(Some(to_column("home_result", u32::from(game.score.home))?))
+        .bind(Some(to_column("away_result", u32::from(game.score.away))?))
         .bind(None::<i32>)
         .bind(None::<i32>)
         .bind(true)
         .execute(pool)
         .await
         .map_err(DbError::Sqlx)?;
+        insert_fair_play_cards(
+            pool,
+            tournament,
+            game.id,
+            (game.home, game.away),
+            &game.fair_play(),
+        )
+        .await?;
     }
 
     Ok(())
 }
 
-/// Clear all games from the database
-pub async fn clear_games(pool: &SqlitePool) -> Result<(), DbError> {
-    sqlx::query("DELETE FROM games")
+/// Record the result of a group game, with the cards of both teams
+///
+/// Replaces any previous result of the game. Errors if the tournament has no such group game.
+pub async fn insert_group_game_result(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    game: &PlayedGroupGame,
+) -> Result<(), DbError> {
+    let updated = sqlx::query(
+        "UPDATE games SET home_result = ?, away_result = ?, played = 1 \
+         WHERE tournament_id = ? AND id = ? AND type_ = ?",
+    )
+    .bind(to_column("home_result", u32::from(game.score.home))?)
+    .bind(to_column("away_result", u32::from(game.score.away))?)
+    .bind(tournament)
+    .bind(to_column("id", u32::from(game.id))?)
+    .bind("group")
+    .execute(pool)
+    .await
+    .map_err(DbError::Sqlx)?
+    .rows_affected();
+    if updated == 0 {
+        return Err(DbError::Generic(format!("No group game {}", game.id)));
+    }
+    insert_fair_play_cards(
+        pool,
+        tournament,
+        game.id,
+        (game.home, game.away),
+        &game.fair_play(),
+    )
+    .await
+}
+
+/// Clear the result of a group game, with its cards
+///
+/// Errors if the tournament has no such group game.
+pub async fn clear_group_game_result(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    game_id: GameId,
+) -> Result<(), DbError> {
+    let updated = sqlx::query(
+        "UPDATE games SET home_result = NULL, away_result = NULL, played = 0 \
+         WHERE tournament_id = ? AND id = ? AND type_ = ?",
+    )
+    .bind(tournament)
+    .bind(to_column("id", u32::from(game_id))?)
+    .bind("group")
+    .execute(pool)
+    .await
+    .map_err(DbError::Sqlx)?
+    .rows_affected();
+    if updated == 0 {
+        return Err(DbError::Generic(format!("No group game {}", game_id)));
+    }
+    clear_game_fair_play_cards(pool, tournament, game_id).await
+}
+
+/// Clear all games of a tournament, with their cards
+pub async fn clear_games(pool: &SqlitePool, tournament: TournamentId) -> Result<(), DbError> {
+    clear_fair_play_cards(pool, tournament).await?;
+    sqlx::query("DELETE FROM games WHERE tournament_id = ?")
+        .bind(tournament)
         .execute(pool)
         .await
         .map_err(DbError::Sqlx)?;
@@ -1,22 +1,31 @@
 use crate::DbError;
-use crate::models::{Game, GroupGameMap};
+use crate::decode::{RowRef, to_column};
+use crate::fair_play::get_fair_play_cards;
+use crate::models::{Game, GroupGameMap, TournamentId};
 use itertools::{Either, Itertools};
 use sqlx::SqlitePool;
+use std::collections::HashMap;
+use wwc_core::fair_play::{FairPlay, FairPlayScore};
 use wwc_core::game::GameId;
 use wwc_core::group::{
     GroupId,
     game::{PlayedGroupGame, UnplayedGroupGame},
 };
+use wwc_core::team::TeamId;
 
-/// Get all group games from the database
+/// Get all group games of a tournament, played games with the cards of both teams
 pub async fn get_group_games(
     pool: &SqlitePool,
+    tournament: TournamentId,
 ) -> Result<(Vec<PlayedGroupGame>, Vec<UnplayedGroupGame>), DbError> {
-    let group_games = sqlx::query_as::<_, Game>("SELECT * FROM games WHERE type_ = ?")
-        .bind("group")
-        .fetch_all(pool)
-        .await
-        .map_err(DbError::Sqlx)?;
+    let group_games =
+        sqlx::query_as::<_, Game>("SELECT * FROM games WHERE tournament_id = ? AND type_ = ?")
+            .bind(tournament)
+            .bind("group")
+            .fetch_all(pool)
+            .await
+            .map_err(DbError::Sqlx)?;
+    let cards = get_fair_play_cards(pool, tournament).await?;
 
     type FetchedPlayedGroupGame = Vec<Result<PlayedGroupGame, DbError>>;
     type FetchedUnplayedGroupGame = Vec<Result<UnplayedGroupGame, DbError>>;
@@ -24,7 +33,10 @@ pub async fn get_group_games(
     let (played_games, unplayed_games): (FetchedPlayedGroupGame, FetchedUnplayedGroupGame) =
         group_games.into_iter().partition_map(|game| {
             if game.played {
-                Either::Left(PlayedGroupGame::try_from(game))
+                Either::Left(
+                    game_fair_play(&game, &cards)
+                        .and_then(|fair_play| game.try_into_played(fair_play)),
+                )
             } else {
                 Either::Right(UnplayedGroupGame::try_from(game))
             }
@@ -40,33 +52,49 @@ pub async fn get_group_games(
     Ok((played_games, unplayed_games))
 }
 
-/// Get group-to-game mappings from the database
-pub async fn get_group_game_maps(pool: &SqlitePool) -> Result<Vec<(GameId, GroupId)>, DbError> {
-    let db_maps = sqlx::query_as::<_, GroupGameMap>("SELECT * FROM group_game_map")
-        .fetch_all(pool)
-        .await
-        .map_err(DbError::Sqlx)?;
+/// Cards of both teams in a game, teams without cards have none
+fn game_fair_play(
+    game: &Game,
+    cards: &HashMap<(GameId, TeamId), FairPlay>,
+) -> Result<FairPlayScore, DbError> {
+    let row = RowRef::new("games", game.id);
+    let game_id = row.game_id("id", game.id)?;
+    let team_cards = |field, team_id| {
+        let key = (game_id, row.team_id(field, team_id)?);
+        Ok::<_, DbError>(cards.get(&key).copied().unwrap_or_default())
+    };
+    Ok(FairPlayScore::new(
+        team_cards("home_team", game.home_team)?,
+        team_cards("away_team", game.away_team)?,
+    ))
+}
 
-    Ok(db_maps
-        .into_iter()
-        .map(|map_| {
-            (
-                GameId::from(u32::try_from(map_.id).unwrap()),
-                GroupId::try_from(map_.group_id_.chars().next().unwrap()).unwrap(),
-            )
-        })
-        .collect())
+/// Get the group-to-game mappings of a tournament
+pub async fn get_group_game_maps(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+) -> Result<Vec<(GameId, GroupId)>, DbError> {
+    let db_maps =
+        sqlx::query_as::<_, GroupGameMap>("SELECT * FROM group_game_map WHERE tournament_id = ?")
+            .bind(tournament)
+            .fetch_all(pool)
+            .await
+            .map_err(DbError::Sqlx)?;
+
+    db_maps.into_iter().map(TryFrom::try_from).collect()
 }
 
-/// Insert group game mappings into the database
+/// Insert group game mappings into a tournament
 pub async fn insert_group_game_mappings(
     pool: &SqlitePool,
+    tournament: TournamentId,
     group_mappings: &[(GroupId, GameId)],
 ) -> Result<(), DbError> {
     for (group_id, game_id) in group_mappings {
         let group_str = String::from(char::from(*group_id));
-        sqlx::query("INSERT INTO group_game_map (id, group_id_) VALUES (?, ?)")
-            .bind(i32::try_from(u32::from(*game_id)).unwrap())
+        sqlx::query("INSERT INTO group_game_map (tournament_id, id, group_id_) VALUES (?, ?, ?)")
+            .bind(tournament)
+            .bind(to_column("id", u32::from(*game_id))?)
             .bind(group_str)
             .execute(pool)
             .await
@@ -76,9 +104,13 @@ pub async fn insert_group_game_mappings(
     Ok(())
 }
 
-/// Clear all group game mappings from the database
-pub async fn clear_group_game_maps(pool: &SqlitePool) -> Result<(), DbError> {
-    sqlx::query("DELETE FROM group_game_map")
+/// Clear all group game mappings of a tournament
+pub async fn clear_group_game_maps(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+) -> Result<(), DbError> {
+    sqlx::query("DELETE FROM group_game_map WHERE tournament_id = ?")
+        .bind(tournament)
         .execute(pool)
         .await
         .map_err(DbError::Sqlx)?;
@@ -0,0 +1,243 @@
+use crate::DbError;
+use crate::decode::{RowRef, to_column};
+use crate::models::{OutcomePredHistoryRow, PredHistoryRow, TournamentId};
+use crate::outcome::outcome_to_str;
+use itertools::Itertools;
+use sqlx::{SqliteConnection, SqlitePool};
+use wwc_core::Date;
+use wwc_core::player::{OutcomePrediction, PlayerId, PredSubmission, Prediction};
+
+/// Append a submission and the predictions stored by it to the prediction history
+///
+/// Takes a connection, so that the history is written in the transaction storing the predictions.
+pub async fn record_pred_submission(
+    conn: &mut SqliteConnection,
+    tournament: TournamentId,
+    player_id: PlayerId,
+    bot_name: Option<&str>,
+    preds: &[Prediction],
+    submitted_at: Date,
+) -> Result<(), DbError> {
+    let rows = preds
+        .iter()
+        .map(|Prediction(game_id, score)| {
+            Ok((
+                to_column("game_id", u32::from(*game_id))?,
+                to_column("home_result", u32::from(score.home))?,
+                to_column("away_result", u32::from(score.away))?,
+            ))
+        })
+        .collect::<Result<Vec<_>, DbError>>()?;
+
+    let submission_id = sqlx::query(
+        "INSERT INTO pred_submissions (tournament_id, player_id, bot_name, submitted_at)
+         VALUES (?, ?, ?, ?)",
+    )
+    .bind(tournament)
+    .bind(i32::from(player_id))
+    .bind(bot_name)
+    .bind(submitted_at.to_rfc3339())
+    .execute(&mut *conn)
+    .await
+    .map_err(DbError::Sqlx)?
+    .last_insert_rowid();
+
+    for (game_id, home_result, away_result) in rows {
+        sqlx::query(
+            "INSERT INTO pred_history (submission_id, game_id, home_result, away_result) VALUES (?, ?, ?, ?)",
+        )
+        .bind(submission_id)
+        .bind(game_id)
+        .bind(home_result)
+        .bind(away_result)
+        .execute(&mut *conn)
+        .await
+        .map_err(DbError::Sqlx)?;
+    }
+    Ok(())
+}
+
+/// Append a submission and the outcome predictions stored by it to the outcome history
+///
+/// Takes a connection, like [`record_pred_submission`].
+pub async fn record_outcome_pred_submission(
+    conn: &mut SqliteConnection,
+    tournament: TournamentId,
+    player_id: PlayerId,
+    bot_name: Option<&str>,
+    preds: &[OutcomePrediction],
+    submitted_at: Date,
+) -> Result<(), DbError> {
+    let rows = preds
+        .iter()
+        .map(|OutcomePrediction(game_id, outcome)| {
+            Ok((
+                to_column("game_id", u32::from(*game_id))?,
+                outcome_to_str(*outcome),
+            ))
+        })
+        .collect::<Result<Vec<_>, DbError>>()?;
+
+    let submission_id = sqlx::query(
+        "INSERT INTO outcome_pred_submissions (tournament_id, player_id, bot_name, submitted_at)
+         VALUES (?, ?, ?, ?)",
+    )
+    .bind(tournament)
+    .bind(i32::from(player_id))
+    .bind(bot_name)
+    .bind(submitted_at.to_rfc3339())
+    .execute(&mut *conn)
+    .await
+    .map_err(DbError::Sqlx)?
+    .last_insert_rowid();
+
+    for (game_id, outcome) in rows {
+        sqlx::query(
+            "INSERT INTO outcome_pred_history (submission_id, game_id, outcome) VALUES (?, ?, ?)",
+        )
+        .bind(submission_id)
+        .bind(game_id)
+        .bind(outcome)
+        .execute(&mut *conn)
+        .await
+        .map_err(DbError::Sqlx)?;
+    }
+    Ok(())
+}
+
+/// Get every submission of a specific player and optional bot, oldest first
+///
+/// With `None`, only the player's own (non-bot) submissions are returned.
+pub async fn get_pred_history(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    player_id: PlayerId,
+    bot_name: Option<&str>,
+) -> Result<Vec<PredSubmission>, DbError> {
+    let rows = sqlx::query_as::<_, PredHistoryRow>(
+        "SELECT s.id AS submission_id, s.submitted_at, h.game_id, h.home_result, h.away_result
+         FROM pred_submissions s
+         LEFT JOIN pred_history h ON h.submission_id = s.id
+         WHERE s.tournament_id = ? AND s.player_id = ? AND s.bot_name IS ?
+         ORDER BY s.id, h.game_id",
+    )
+    .bind(tournament)
+    .bind(i32::from(player_id))
+    .bind(bot_name)
+    .fetch_all(pool)
+    .await
+    .map_err(DbError::Sqlx)?;
+
+    rows.into_iter()
+        .chunk_by(|row| (row.submission_id, row.submitted_at.clone()))
+        .into_iter()
+        .map(|((submission_id, submitted_at), rows)| {
+            let submitted_at = RowRef::new("pred_submissions", submission_id)
+                .date("submitted_at", &submitted_at)?;
+            Ok(PredSubmission {
+                submitted_at,
+                preds: rows
+                    .map(|row| row.prediction())
+                    .filter_map(Result::transpose)
+                    .collect::<Result<_, _>>()?,
+            })
+        })
+        .collect()
+}
+
+/// Reconstruct the predictions of a specific player and optional bot as of an instant
+///
+/// These are the predictions stored by the latest submission at or before `at`, none if there
+/// was no submission yet.
+pub async fn get_preds_as_of(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    player_id: PlayerId,
+    bot_name: Option<&str>,
+    at: Date,
+) -> Result<Vec<Prediction>, DbError> {
+    // Submissions are appended in order, the last one wins if several have the same time
+    Ok(get_pred_history(pool, tournament, player_id, bot_name)
+        .await?
+        .into_iter()
+        .rfind(|submission| submission.submitted_at <= at)
+        .map(|submission| submission.preds)
+        .unwrap_or_default())
+}
+
+/// Get every outcome submission of a specific player and optional bot, oldest first
+///
+/// With `None`, only the player's own (non-bot) submissions are returned.
+pub async fn get_outcome_pred_history(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    player_id: PlayerId,
+    bot_name: Option<&str>,
+) -> Result<Vec<PredSubmission<OutcomePrediction>>, DbError> {
+    let rows = sqlx::query_as::<_, OutcomePredHistoryRow>(
+        "SELECT s.id AS submission_id, s.submitted_at, h.game_id, h.outcome
+         FROM outcome_pred_submissions s
+         LEFT JOIN outcome_pred_history h ON h.submission_id = s.id
+         WHERE s.tournament_id = ? AND s.player_id = ? AND s.bot_name IS ?
+         ORDER BY s.id, h.game_id",
+    )
+    .bind(tournament)
+    .bind(i32::from(player_id))
+    .bind(bot_name)
+    .fetch_all(pool)
+    .await
+    .map_err(DbError::Sqlx)?;
+
+    rows.into_iter()
+        .chunk_by(|row| (row.submission_id, row.submitted_at.clone()))
+        .into_iter()
+        .map(|((submission_id, submitted_at), rows)| {
+            let submitted_at = RowRef::new("outcome_pred_submissions", submission_id)
+                .date("submitted_at", &submitted_at)?;
+            Ok(PredSubmission {
+                submitted_at,
+                preds: rows
+                    .map(|row| row.prediction())
+                    .filter_map(Result::transpose)
+                    .collect::<Result<_, _>>()?,
+            })
+        })
+        .collect()
+}
+
+/// Clear the whole (score and outcome) prediction history of a tournament
+///
+/// Only to remove all data of a tournament, see [`crate::clear_tournament_data`]. Clearing
+/// predictions is recorded in the history instead.
+pub(crate) async fn clear_pred_history(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+) -> Result<(), DbError> {
+    sqlx::query(
+        "DELETE FROM pred_history WHERE submission_id IN
+         (SELECT id FROM pred_submissions WHERE tournament_id = ?)",
+    )
+    .bind(tournament)
+    .execute(pool)
+    .await
+    .map_err(DbError::Sqlx)?;
+    sqlx::query("DELETE FROM pred_submissions WHERE tournament_id = ?")
+        .bind(tournament)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    sqlx::query(
+        "DELETE FROM outcome_pred_history WHERE submission_id IN
+         (SELECT id FROM outcome_pred_submissions WHERE tournament_id = ?)",
+    )
+    .bind(tournament)
+    .execute(pool)
+    .await
+    .map_err(DbError::Sqlx)?;
+    sqlx::query("DELETE FROM outcome_pred_submissions WHERE tournament_id = ?")
+        .bind(tournament)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    Ok(())
+}
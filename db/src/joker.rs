@@ -0,0 +1,149 @@
+use crate::DbError;
+use crate::decode::{RowRef, to_column};
+use crate::models::TournamentId;
+use sqlx::SqlitePool;
+use wwc_core::Date;
+use wwc_core::game::GameId;
+use wwc_core::player::PlayerId;
+
+fn game_id_to_i32(id: GameId) -> Result<i32, DbError> {
+    to_column("game_id", u32::from(id))
+}
+
+/// Decode a kickoff row
+pub(crate) fn decode_kickoff(game_id: i32, kickoff: &str) -> Result<(GameId, Date), DbError> {
+    let row = RowRef::new("game_kickoffs", game_id);
+    Ok((
+        row.game_id("game_id", game_id)?,
+        row.date("kickoff", kickoff)?,
+    ))
+}
+
+/// Get the jokers of a specific player and optional bot
+///
+/// With `None`, only the player's own (non-bot) jokers are returned.
+pub async fn get_jokers(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    player_id: PlayerId,
+    bot_name: Option<&str>,
+) -> Result<Vec<GameId>, DbError> {
+    let rows = sqlx::query_as::<_, (i32, i32)>(
+        "SELECT id, game_id FROM jokers WHERE tournament_id = ? AND player_id = ? AND bot_name IS ?
+         ORDER BY game_id",
+    )
+    .bind(tournament)
+    .bind(i32::from(player_id))
+    .bind(bot_name)
+    .fetch_all(pool)
+    .await
+    .map_err(DbError::Sqlx)?;
+
+    rows.into_iter()
+        .map(|(id, game_id)| RowRef::new("jokers", id).game_id("game_id", game_id))
+        .collect()
+}
+
+/// Insert the jokers of a player and optional bot (replaces the previous selection)
+pub async fn insert_jokers(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    player_id: PlayerId,
+    jokers: &[GameId],
+    bot_name: Option<&str>,
+) -> Result<(), DbError> {
+    clear_player_jokers(pool, tournament, player_id, bot_name).await?;
+    for game_id in jokers {
+        sqlx::query(
+            "INSERT INTO jokers (tournament_id, player_id, game_id, bot_name) VALUES (?, ?, ?, ?)",
+        )
+        .bind(tournament)
+        .bind(i32::from(player_id))
+        .bind(game_id_to_i32(*game_id)?)
+        .bind(bot_name)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    }
+    Ok(())
+}
+
+/// Clear the jokers of a specific player and optional bot
+///
+/// With `None`, only the player's own (non-bot) jokers are cleared.
+pub async fn clear_player_jokers(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    player_id: PlayerId,
+    bot_name: Option<&str>,
+) -> Result<(), DbError> {
+    sqlx::query("DELETE FROM jokers WHERE tournament_id = ? AND player_id = ? AND bot_name IS ?")
+        .bind(tournament)
+        .bind(i32::from(player_id))
+        .bind(bot_name)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    Ok(())
+}
+
+/// Clear all jokers of a tournament
+pub async fn clear_jokers(pool: &SqlitePool, tournament: TournamentId) -> Result<(), DbError> {
+    sqlx::query("DELETE FROM jokers WHERE tournament_id = ?")
+        .bind(tournament)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    Ok(())
+}
+
+/// Get the kickoff time of every game of a tournament with a known kickoff
+pub async fn get_game_kickoffs(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+) -> Result<Vec<(GameId, Date)>, DbError> {
+    let rows = sqlx::query_as::<_, (i32, String)>(
+        "SELECT game_id, kickoff FROM game_kickoffs WHERE tournament_id = ? ORDER BY game_id",
+    )
+    .bind(tournament)
+    .fetch_all(pool)
+    .await
+    .map_err(DbError::Sqlx)?;
+
+    rows.iter()
+        .map(|(game_id, kickoff)| decode_kickoff(*game_id, kickoff))
+        .collect()
+}
+
+/// Insert game kickoff times (replaces existing kickoffs of the same games)
+pub async fn insert_game_kickoffs(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    kickoffs: &[(GameId, Date)],
+) -> Result<(), DbError> {
+    for (game_id, kickoff) in kickoffs {
+        sqlx::query(
+            "INSERT OR REPLACE INTO game_kickoffs (tournament_id, game_id, kickoff) VALUES (?, ?, ?)",
+        )
+        .bind(tournament)
+            .bind(game_id_to_i32(*game_id)?)
+            .bind(kickoff.to_rfc3339())
+            .execute(pool)
+            .await
+            .map_err(DbError::Sqlx)?;
+    }
+    Ok(())
+}
+
+/// Clear all game kickoff times of a tournament
+pub async fn clear_game_kickoffs(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+) -> Result<(), DbError> {
+    sqlx::query("DELETE FROM game_kickoffs WHERE tournament_id = ?")
+        .bind(tournament)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    Ok(())
+}
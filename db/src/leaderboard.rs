@@ -0,0 +1,192 @@
+//! Loading the results of a tournament, to score its leaderboard
+//!
+//! Shared by the server and the CLI, so that both rank the competitors the same.
+use crate::DbError;
+use crate::models::TournamentId;
+use crate::store::Store;
+use itertools::Itertools;
+use std::collections::{BTreeMap, HashMap};
+use wwc_core::Date;
+use wwc_core::bonus::BonusQuestion;
+use wwc_core::error::WwcError;
+use wwc_core::game::GameId;
+use wwc_core::group::order::{Rules, UefaRanking};
+use wwc_core::group::{Group, GroupId, Groups, game::PlayedGroupGame, game::UnplayedGroupGame};
+use wwc_core::leaderboard::{CompetitorPredictions, Truth};
+use wwc_core::playoff::{BracketState, BracketStructure};
+use wwc_core::pred_score::scheme::ScoringConfig;
+use wwc_core::pred_score::standings::GroupStandings;
+
+/// Groups of a tournament, with their played and unplayed games
+///
+/// Played games carry the cards of both teams, which the fair play tiebreaker uses.
+pub async fn load_groups<S: Store>(store: &S, tournament: TournamentId) -> Result<Groups, DbError> {
+    let (played_games, unplayed_games) = store.get_group_games(tournament).await?;
+
+    let game_group_map = store
+        .get_group_game_maps(tournament)
+        .await?
+        .into_iter()
+        .collect::<HashMap<GameId, GroupId>>();
+
+    let empty_groups = game_group_map.values().unique();
+
+    let groups_played =
+        played_games.into_iter().fold(
+            empty_groups
+                .clone()
+                .map(|group_id| (*group_id, Vec::new()))
+                .collect::<BTreeMap<GroupId, Vec<PlayedGroupGame>>>(),
+            |mut acc, game| {
+                let entry = acc
+                    .entry(*game_group_map.get(&game.id).unwrap_or_else(|| {
+                        panic!("game group map discrepancy: no id: {:?}", game.id)
+                    }))
+                    .or_insert_with(Vec::new);
+                entry.push(game);
+                acc
+            },
+        );
+
+    let groups_unplayed =
+        unplayed_games.into_iter().fold(
+            empty_groups
+                .clone()
+                .map(|group_id| (*group_id, Vec::new()))
+                .collect::<BTreeMap<GroupId, Vec<UnplayedGroupGame>>>(),
+            |mut acc, game| {
+                let entry = acc
+                    .entry(*game_group_map.get(&game.id).unwrap_or_else(|| {
+                        panic!("game group map discrepancy: no id: {:?}", game.id)
+                    }))
+                    .or_insert_with(Vec::new);
+                entry.push(game);
+                acc
+            },
+        );
+
+    let groups: Result<Groups, WwcError> = groups_played
+        .into_iter()
+        .zip(groups_unplayed)
+        .map(
+            |((group_id_played, played), (group_id_unplayed, unplayed))| {
+                assert!(group_id_played == group_id_unplayed);
+                Group::try_new(unplayed, played)
+                    .map(|group| (group_id_played, group))
+                    .map_err(WwcError::from)
+            },
+        )
+        .collect();
+
+    Ok(groups?)
+}
+
+/// Group rules and third-place rules of a tournament, ties are broken by team rank
+///
+/// The drawing of lots can't be predicted, so predicted and actual groups are ranked the same.
+pub async fn ranked_group_rules<S: Store>(
+    store: &S,
+    tournament: TournamentId,
+    groups: &Groups,
+) -> Result<(Rules<UefaRanking>, Rules<UefaRanking>), DbError> {
+    let rule_set = store.get_group_rules(tournament).await?;
+    let teams = store.get_teams(tournament).await?;
+    let ranking = UefaRanking::try_new(
+        groups,
+        teams.iter().map(|team| (team.id, team.rank)).collect(),
+    )
+    .map_err(WwcError::from)?;
+    Ok((
+        rule_set.ranked_rules(ranking.clone()),
+        rule_set.ranked_third_place_rules(ranking),
+    ))
+}
+
+/// The results of a tournament and the competitors' predictions, to score them with a config
+pub struct TournamentScoring {
+    groups: Groups,
+    /// None if there are no playoff games in the db, only the group stage is scored then
+    bracket: Option<BracketStructure>,
+    playoff_results: BracketState,
+    bonus_questions: Vec<BonusQuestion>,
+    /// Final group standings, only computed if the config scores predicted standings
+    standings: Option<GroupStandings>,
+    /// Stored kickoff times, to order the played games in the history
+    kickoffs: HashMap<GameId, Date>,
+    preds: Vec<CompetitorPredictions>,
+}
+
+impl TournamentScoring {
+    /// Load the results of a tournament and add predicted standings to the predictions
+    pub async fn load<S: Store>(
+        store: &S,
+        id: TournamentId,
+        config: &ScoringConfig,
+        preds: Vec<CompetitorPredictions>,
+    ) -> Result<Self, DbError> {
+        let groups = load_groups(store, id).await?;
+        let team_sources = store.get_playoff_team_sources(id).await?;
+        let bracket = if team_sources.is_empty() {
+            None
+        } else {
+            Some(BracketStructure::from_team_sources(&team_sources).map_err(WwcError::from)?)
+        };
+        let playoff_results = store.get_playoff_results(id).await?;
+        let bonus_questions = store.get_bonus_questions(id).await?;
+        let kickoffs = store.get_game_kickoffs(id).await?.into_iter().collect();
+
+        // Predicted group standings, only computed if the config scores them
+        let (preds, standings) = if config.standings.is_scored() {
+            let (rules, third_place_rules) = ranked_group_rules(store, id, &groups).await?;
+            let preds = preds
+                .into_iter()
+                .map(|competitor_preds| {
+                    let predicted = config.standings.predicted_standings(
+                        &groups,
+                        &competitor_preds.group,
+                        &rules,
+                        &third_place_rules,
+                    );
+                    competitor_preds.with_standings(predicted)
+                })
+                .collect();
+            let standings = config
+                .standings
+                .standings(&groups, &rules, &third_place_rules);
+            (preds, Some(standings))
+        } else {
+            (preds, None)
+        };
+
+        Ok(TournamentScoring {
+            groups,
+            bracket,
+            playoff_results,
+            bonus_questions,
+            standings,
+            kickoffs,
+            preds,
+        })
+    }
+
+    /// The competitors' predictions, with their predicted group standings if the config scores them
+    pub fn preds(&self) -> &[CompetitorPredictions] {
+        &self.preds
+    }
+
+    /// The results to score the predictions against
+    pub fn truth(&self) -> Truth<'_> {
+        let truth = match &self.bracket {
+            Some(bracket) => {
+                Truth::groups(&self.groups).with_playoff(bracket, &self.playoff_results)
+            }
+            None => Truth::groups(&self.groups),
+        }
+        .with_bonus(&self.bonus_questions)
+        .with_kickoffs(&self.kickoffs);
+        match &self.standings {
+            Some(standings) => truth.with_standings(standings),
+            None => truth,
+        }
+    }
+}
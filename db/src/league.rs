@@ -0,0 +1,227 @@
+//! Private league operations
+//!
+//! A league groups some of a tournament's players under an owner, who hands out the league's
+//! invite code and may set a scoring config for the league. Predictions are not stored per
+//! league, a player's tournament predictions count in every league they are a member of.
+
+use crate::DbError;
+use crate::decode::RowRef;
+use crate::models::{League, LeagueId, LeagueMember, LeagueRole, TournamentId};
+use sqlx::SqlitePool;
+use wwc_core::player::PlayerId;
+use wwc_core::pred_score::scheme::ScoringConfig;
+
+/// Fresh random invite code, generated by SQLite
+const NEW_INVITE_CODE: &str = "lower(hex(randomblob(6)))";
+
+/// Create a league with the player as its owner, returns the new league
+pub async fn insert_league(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    name: &str,
+    owner: PlayerId,
+) -> Result<League, DbError> {
+    let mut tx = pool.begin().await.map_err(DbError::Sqlx)?;
+    let league = sqlx::query_as::<_, League>(&format!(
+        "INSERT INTO leagues (tournament_id, name, invite_code) VALUES (?, ?, {})
+         RETURNING id, tournament_id, name, invite_code",
+        NEW_INVITE_CODE
+    ))
+    .bind(tournament)
+    .bind(name)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(DbError::Sqlx)?;
+
+    sqlx::query("INSERT INTO league_members (league_id, player_id, role) VALUES (?, ?, ?)")
+        .bind(league.id)
+        .bind(i32::from(owner))
+        .bind(LeagueRole::Owner)
+        .execute(&mut *tx)
+        .await
+        .map_err(DbError::Sqlx)?;
+
+    tx.commit().await.map_err(DbError::Sqlx)?;
+    Ok(league)
+}
+
+/// Get a league by id, `None` if there is no such league
+pub async fn get_league(pool: &SqlitePool, league: LeagueId) -> Result<Option<League>, DbError> {
+    sqlx::query_as::<_, League>(
+        "SELECT id, tournament_id, name, invite_code FROM leagues WHERE id = ?",
+    )
+    .bind(league)
+    .fetch_optional(pool)
+    .await
+    .map_err(DbError::Sqlx)
+}
+
+/// Get the league with an invite code, `None` if no league has the code
+pub async fn get_league_by_invite_code(
+    pool: &SqlitePool,
+    invite_code: &str,
+) -> Result<Option<League>, DbError> {
+    sqlx::query_as::<_, League>(
+        "SELECT id, tournament_id, name, invite_code FROM leagues WHERE invite_code = ?",
+    )
+    .bind(invite_code)
+    .fetch_optional(pool)
+    .await
+    .map_err(DbError::Sqlx)
+}
+
+/// Get the leagues of a tournament which the player is a member of, ordered by id
+pub async fn get_player_leagues(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    player_id: PlayerId,
+) -> Result<Vec<League>, DbError> {
+    sqlx::query_as::<_, League>(
+        "SELECT l.id, l.tournament_id, l.name, l.invite_code
+         FROM leagues l
+         JOIN league_members m ON m.league_id = l.id
+         WHERE l.tournament_id = ? AND m.player_id = ?
+         ORDER BY l.id",
+    )
+    .bind(tournament)
+    .bind(i32::from(player_id))
+    .fetch_all(pool)
+    .await
+    .map_err(DbError::Sqlx)
+}
+
+/// Replace the invite code of a league, the old code stops working
+pub async fn reset_league_invite_code(
+    pool: &SqlitePool,
+    league: LeagueId,
+) -> Result<String, DbError> {
+    sqlx::query_scalar::<_, String>(&format!(
+        "UPDATE leagues SET invite_code = {} WHERE id = ? RETURNING invite_code",
+        NEW_INVITE_CODE
+    ))
+    .bind(league)
+    .fetch_one(pool)
+    .await
+    .map_err(DbError::Sqlx)
+}
+
+/// Delete a league and its memberships
+pub async fn delete_league(pool: &SqlitePool, league: LeagueId) -> Result<(), DbError> {
+    sqlx::query("DELETE FROM leagues WHERE id = ?")
+        .bind(league)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    Ok(())
+}
+
+/// Clear all leagues of a tournament
+pub async fn clear_leagues(pool: &SqlitePool, tournament: TournamentId) -> Result<(), DbError> {
+    sqlx::query("DELETE FROM leagues WHERE tournament_id = ?")
+        .bind(tournament)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    Ok(())
+}
+
+/// Get the members of a league, owner first and then in order of joining
+pub async fn get_league_members(
+    pool: &SqlitePool,
+    league: LeagueId,
+) -> Result<Vec<LeagueMember>, DbError> {
+    sqlx::query_as::<_, LeagueMember>(
+        "SELECT player_id, role, joined_at FROM league_members WHERE league_id = ?
+         ORDER BY role = 'owner' DESC, joined_at, player_id",
+    )
+    .bind(league)
+    .fetch_all(pool)
+    .await
+    .map_err(DbError::Sqlx)
+}
+
+/// Get the role of a player in a league, `None` if the player is not a member
+pub async fn get_league_role(
+    pool: &SqlitePool,
+    league: LeagueId,
+    player_id: PlayerId,
+) -> Result<Option<LeagueRole>, DbError> {
+    sqlx::query_scalar::<_, LeagueRole>(
+        "SELECT role FROM league_members WHERE league_id = ? AND player_id = ?",
+    )
+    .bind(league)
+    .bind(i32::from(player_id))
+    .fetch_optional(pool)
+    .await
+    .map_err(DbError::Sqlx)
+}
+
+/// Add a player to a league as a member, a no-op if the player already is one
+pub async fn insert_league_member(
+    pool: &SqlitePool,
+    league: LeagueId,
+    player_id: PlayerId,
+) -> Result<(), DbError> {
+    sqlx::query(
+        "INSERT OR IGNORE INTO league_members (league_id, player_id, role) VALUES (?, ?, ?)",
+    )
+    .bind(league)
+    .bind(i32::from(player_id))
+    .bind(LeagueRole::Member)
+    .execute(pool)
+    .await
+    .map_err(DbError::Sqlx)?;
+    Ok(())
+}
+
+/// Remove a player from a league
+pub async fn delete_league_member(
+    pool: &SqlitePool,
+    league: LeagueId,
+    player_id: PlayerId,
+) -> Result<(), DbError> {
+    sqlx::query("DELETE FROM league_members WHERE league_id = ? AND player_id = ?")
+        .bind(league)
+        .bind(i32::from(player_id))
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    Ok(())
+}
+
+/// Get the scoring config of a league, `None` if the league uses the tournament's config
+pub async fn get_league_scoring_config(
+    pool: &SqlitePool,
+    league: LeagueId,
+) -> Result<Option<ScoringConfig>, DbError> {
+    let config = sqlx::query_scalar::<_, Option<String>>("SELECT config FROM leagues WHERE id = ?")
+        .bind(league)
+        .fetch_optional(pool)
+        .await
+        .map_err(DbError::Sqlx)?
+        .flatten();
+
+    config
+        .map(|config| RowRef::new("leagues", league).json("config", &config))
+        .transpose()
+}
+
+/// Set the scoring config of a league, `None` to use the tournament's config again
+pub async fn set_league_scoring_config(
+    pool: &SqlitePool,
+    league: LeagueId,
+    config: Option<&ScoringConfig>,
+) -> Result<(), DbError> {
+    let config = config
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| DbError::Generic(format!("Could not serialize scoring config: {}", e)))?;
+
+    sqlx::query("UPDATE leagues SET config = ? WHERE id = ?")
+        .bind(config)
+        .bind(league)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    Ok(())
+}
@@ -1,4 +1,6 @@
 // Core modules
+mod decode;
+mod migrations;
 mod models;
 mod pool;
 
@@ -8,9 +10,24 @@ mod team;
 
 // Domain-specific operations
 mod auth;
+mod bonus;
+mod consistency;
+mod fair_play;
 mod group;
+mod history;
+mod joker;
+mod leaderboard;
+mod league;
+mod outcome;
 mod player;
 mod playoff;
+mod proba;
+mod scoring;
+mod snapshot;
+mod tournament;
+
+// Storage backends
+mod store;
 
 use thiserror::Error;
 use wwc_core::error::WwcError;
@@ -20,23 +37,70 @@ pub use auth::{
     create_bot, create_user, delete_bot, delete_user, get_all_display_names, get_bot,
     get_user_by_id, get_user_by_username, list_bots_for_user, list_users,
 };
-pub use games::{clear_games, get_games, insert_played_games, insert_unplayed_games};
+pub use bonus::{
+    clear_bonus_preds, clear_bonus_questions, clear_player_bonus_preds, get_bonus_preds,
+    get_bonus_questions, insert_bonus_preds, insert_bonus_question, resolve_bonus_question,
+};
+pub use consistency::{CorruptRow, check_consistency};
+pub use fair_play::{
+    clear_fair_play_cards, clear_game_fair_play_cards, get_fair_play_cards, insert_fair_play_cards,
+};
+pub use games::{
+    clear_games, clear_group_game_result, get_games, get_group_game_ids, get_locked_games,
+    insert_group_game_result, insert_played_games, insert_unplayed_games,
+};
 pub use group::{
     clear_group_game_maps, get_group_game_maps, get_group_games, insert_group_game_mappings,
 };
+pub use history::{
+    get_outcome_pred_history, get_pred_history, get_preds_as_of, record_outcome_pred_submission,
+    record_pred_submission,
+};
+pub use joker::{
+    clear_game_kickoffs, clear_jokers, clear_player_jokers, get_game_kickoffs, get_jokers,
+    insert_game_kickoffs, insert_jokers,
+};
+pub use leaderboard::{TournamentScoring, load_groups, ranked_group_rules};
+pub use league::{
+    clear_leagues, delete_league, delete_league_member, get_league, get_league_by_invite_code,
+    get_league_members, get_league_role, get_league_scoring_config, get_player_leagues,
+    insert_league, insert_league_member, reset_league_invite_code, set_league_scoring_config,
+};
+pub use migrations::{
+    MIGRATIONS, Migration, MigrationState, MigrationStatus, Step, migration_status, run_migrations,
+};
+pub use outcome::{
+    clear_outcome_preds, clear_player_outcome_preds, get_outcome_preds,
+    insert_validated_outcome_preds,
+};
 pub use player::{
-    clear_player_preds, clear_players, clear_preds, get_players, get_preds, insert_preds,
-    register_player,
+    clear_player_preds, clear_players, clear_preds, ensure_player, get_all_competitor_preds,
+    get_players, get_preds, insert_preds, insert_validated_preds, register_player,
 };
 pub use playoff::{
-    clear_playoff_games, clear_playoff_team_sources, get_playoff_team_sources,
-    insert_playoff_games, insert_playoff_team_sources,
+    clear_player_playoff_preds, clear_playoff_games, clear_playoff_preds, clear_playoff_result,
+    clear_playoff_results, clear_playoff_team_sources, get_playoff_preds, get_playoff_results,
+    get_playoff_team_sources, insert_playoff_games, insert_playoff_preds, insert_playoff_result,
+    insert_playoff_team_sources,
 };
-pub use pool::create_pool;
+pub use pool::{connect_pool, create_pool};
+pub use proba::{
+    clear_bot_proba_preds, clear_proba_preds, get_all_proba_preds, get_proba_preds,
+    insert_proba_preds, insert_validated_proba_preds,
+};
+pub use scoring::{clear_scoring_config, get_scoring_config, insert_scoring_config};
+pub use snapshot::{SNAPSHOT_FORMAT, Snapshot, SnapshotRow, export_snapshot, import_snapshot};
+pub use store::{MemoryStore, SqliteStore, Store};
 pub use team::{clear_teams, get_teams, insert_teams};
+pub use tournament::{
+    clear_tournament_data, get_group_rules, get_tournament_id, get_tournaments, insert_tournament,
+    set_group_rules,
+};
 
 // Re-export models that are used in public APIs
-pub use models::{Bot, Game, Player, User};
+pub use models::{
+    Bot, Game, League, LeagueId, LeagueMember, LeagueRole, Player, Tournament, TournamentId, User,
+};
 
 #[derive(Error, Debug)]
 pub enum DbError {
@@ -46,6 +110,32 @@ pub enum DbError {
     Sqlx(#[from] sqlx::Error),
     #[error("Core error: {0}")]
     Core(#[from] WwcError),
+    #[error("Migration error: {0}")]
+    Migration(String),
+    #[error("Snapshot error: {0}")]
+    Snapshot(String),
+    #[error("Negative {field} in table '{table}', row {row}: {value}")]
+    NegativeValue {
+        table: &'static str,
+        row: String,
+        field: &'static str,
+        value: i32,
+    },
+    #[error("Missing {field} in table '{table}', row {row}")]
+    MissingValue {
+        table: &'static str,
+        row: String,
+        field: &'static str,
+    },
+    #[error("Invalid {field} in table '{table}', row {row}: {reason}")]
+    InvalidValue {
+        table: &'static str,
+        row: String,
+        field: &'static str,
+        reason: String,
+    },
+    #[error("{field} out of range: {value}")]
+    OutOfRange { field: &'static str, value: u32 },
     #[error("Could you be more specific: {0}")]
     Generic(String),
 }
@@ -0,0 +1,354 @@
+//! Versioned schema migrations
+//!
+//! Migrations are applied once, in version order, and recorded in the `schema_version` table
+//! together with a checksum of their steps. A recorded migration whose steps have changed since
+//! it was applied is an error: add a new migration instead of editing an old one.
+//!
+//! Databases created before migrations were versioned are upgraded like fresh ones, the scripts
+//! only create what is missing. Columns added to an existing table use [`Step::AddColumn`],
+//! which is skipped if the table already has the column.
+
+use crate::DbError;
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+
+/// Single step of a migration
+#[derive(Debug, Clone, Copy)]
+pub enum Step {
+    /// SQL script, may contain several statements
+    Sql(&'static str),
+    /// `ALTER TABLE .. ADD COLUMN`, skipped if the column exists
+    AddColumn {
+        table: &'static str,
+        column: &'static str,
+        definition: &'static str,
+    },
+}
+
+impl Step {
+    fn sql(&self) -> String {
+        match self {
+            Step::Sql(sql) => sql.to_string(),
+            Step::AddColumn {
+                table,
+                column,
+                definition,
+            } => format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub steps: &'static [Step],
+}
+
+impl Migration {
+    /// Hex encoded SHA-256 of the migration's SQL
+    pub fn checksum(&self) -> String {
+        let mut hasher = Sha256::new();
+        for step in self.steps {
+            hasher.update(step.sql().as_bytes());
+        }
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+/// All migrations, ordered by version
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "create_tables",
+        steps: &[Step::Sql(include_str!(
+            "../sqlx_migrations/001_create_tables.sql"
+        ))],
+    },
+    Migration {
+        version: 2,
+        name: "playoff_tables",
+        steps: &[Step::Sql(include_str!(
+            "../sqlx_migrations/002_playoff_tables.sql"
+        ))],
+    },
+    Migration {
+        version: 3,
+        name: "auth_tables",
+        steps: &[
+            // Databases from before bots were added lack the column
+            Step::AddColumn {
+                table: "preds",
+                column: "bot_name",
+                definition: "VARCHAR",
+            },
+            Step::Sql(include_str!("../sqlx_migrations/003_auth_tables.sql")),
+        ],
+    },
+    Migration {
+        version: 4,
+        name: "playoff_preds",
+        steps: &[Step::Sql(include_str!(
+            "../sqlx_migrations/004_playoff_preds.sql"
+        ))],
+    },
+    Migration {
+        version: 5,
+        name: "scoring_configs",
+        steps: &[Step::Sql(include_str!(
+            "../sqlx_migrations/005_scoring_configs.sql"
+        ))],
+    },
+    Migration {
+        version: 6,
+        name: "proba_preds",
+        steps: &[Step::Sql(include_str!(
+            "../sqlx_migrations/006_proba_preds.sql"
+        ))],
+    },
+    Migration {
+        version: 7,
+        name: "bonus_questions",
+        steps: &[Step::Sql(include_str!(
+            "../sqlx_migrations/007_bonus_questions.sql"
+        ))],
+    },
+    Migration {
+        version: 8,
+        name: "jokers",
+        steps: &[Step::Sql(include_str!("../sqlx_migrations/008_jokers.sql"))],
+    },
+    Migration {
+        version: 9,
+        name: "outcome_preds",
+        steps: &[Step::Sql(include_str!(
+            "../sqlx_migrations/009_outcome_preds.sql"
+        ))],
+    },
+    Migration {
+        version: 10,
+        name: "pred_history",
+        steps: &[Step::Sql(include_str!(
+            "../sqlx_migrations/010_pred_history.sql"
+        ))],
+    },
+    Migration {
+        version: 11,
+        name: "preds_unique",
+        steps: &[Step::Sql(include_str!(
+            "../sqlx_migrations/011_preds_unique.sql"
+        ))],
+    },
+    Migration {
+        version: 12,
+        name: "tournaments",
+        steps: &[Step::Sql(include_str!(
+            "../sqlx_migrations/012_tournaments.sql"
+        ))],
+    },
+    Migration {
+        version: 13,
+        name: "leagues",
+        steps: &[Step::Sql(include_str!(
+            "../sqlx_migrations/013_leagues.sql"
+        ))],
+    },
+    Migration {
+        version: 14,
+        name: "fair_play_cards",
+        steps: &[Step::Sql(include_str!(
+            "../sqlx_migrations/014_fair_play_cards.sql"
+        ))],
+    },
+    Migration {
+        version: 15,
+        name: "group_rules",
+        steps: &[
+            Step::AddColumn {
+                table: "tournaments",
+                column: "group_rules",
+                definition: "VARCHAR NOT NULL DEFAULT 'fifa-2018'",
+            },
+            Step::Sql(include_str!("../sqlx_migrations/015_group_rules.sql")),
+        ],
+    },
+    Migration {
+        version: 16,
+        name: "outcome_pred_history",
+        steps: &[Step::Sql(include_str!(
+            "../sqlx_migrations/016_outcome_pred_history.sql"
+        ))],
+    },
+    Migration {
+        version: 17,
+        name: "proba_preds_unique",
+        steps: &[Step::Sql(include_str!(
+            "../sqlx_migrations/017_proba_preds_unique.sql"
+        ))],
+    },
+];
+
+const SCHEMA_VERSION_TABLE: &str = "CREATE TABLE IF NOT EXISTS schema_version (
+  version INTEGER PRIMARY KEY NOT NULL,
+  name VARCHAR NOT NULL,
+  checksum VARCHAR NOT NULL,
+  applied_at DATETIME DEFAULT CURRENT_TIMESTAMP
+)";
+
+/// State of a migration in a database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationState {
+    Applied {
+        applied_at: String,
+    },
+    Pending,
+    /// Applied, but the migration has changed since
+    ChecksumMismatch {
+        applied_at: String,
+    },
+    /// Applied by a newer version of this crate
+    Unknown {
+        applied_at: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: String,
+    pub state: MigrationState,
+}
+
+/// Recorded migrations, by version: (name, checksum, applied_at)
+///
+/// None without a `schema_version` table, which is only created when migrations are run.
+async fn applied_migrations(
+    pool: &SqlitePool,
+) -> Result<HashMap<i64, (String, String, String)>, DbError> {
+    let recorded: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'schema_version'",
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(DbError::Sqlx)?;
+    if recorded == 0 {
+        return Ok(HashMap::new());
+    }
+    let rows = sqlx::query_as::<_, (i64, String, String, String)>(
+        "SELECT version, name, checksum, applied_at FROM schema_version",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(DbError::Sqlx)?;
+    Ok(rows
+        .into_iter()
+        .map(|(version, name, checksum, applied_at)| (version, (name, checksum, applied_at)))
+        .collect())
+}
+
+/// Status of every known and every recorded migration, ordered by version
+///
+/// Read only: in a database without a `schema_version` table, every migration is pending.
+pub async fn migration_status(pool: &SqlitePool) -> Result<Vec<MigrationStatus>, DbError> {
+    let mut applied = applied_migrations(pool).await?;
+    let mut status: Vec<MigrationStatus> = MIGRATIONS
+        .iter()
+        .map(|migration| {
+            let state = match applied.remove(&migration.version) {
+                Some((_, checksum, applied_at)) if checksum == migration.checksum() => {
+                    MigrationState::Applied { applied_at }
+                }
+                Some((_, _, applied_at)) => MigrationState::ChecksumMismatch { applied_at },
+                None => MigrationState::Pending,
+            };
+            MigrationStatus {
+                version: migration.version,
+                name: migration.name.to_string(),
+                state,
+            }
+        })
+        .collect();
+    status.extend(
+        applied
+            .into_iter()
+            .map(|(version, (name, _, applied_at))| MigrationStatus {
+                version,
+                name,
+                state: MigrationState::Unknown { applied_at },
+            }),
+    );
+    status.sort_by_key(|status| status.version);
+    Ok(status)
+}
+
+/// Apply all pending migrations, each in its own transaction
+///
+/// Fails without applying anything if an applied migration has changed or the database has
+/// migrations unknown to this crate.
+pub async fn run_migrations(pool: &SqlitePool) -> Result<(), DbError> {
+    sqlx::query(SCHEMA_VERSION_TABLE)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    let status = migration_status(pool).await?;
+    if let Some(invalid) = status.iter().find(|status| {
+        !matches!(
+            status.state,
+            MigrationState::Applied { .. } | MigrationState::Pending
+        )
+    }) {
+        return Err(DbError::Migration(format!(
+            "migration {} ({}) is {:?}",
+            invalid.version, invalid.name, invalid.state
+        )));
+    }
+
+    for migration in MIGRATIONS.iter().filter(|migration| {
+        status.iter().any(|status| {
+            status.version == migration.version && status.state == MigrationState::Pending
+        })
+    }) {
+        let mut tx = pool.begin().await.map_err(DbError::Sqlx)?;
+        for step in migration.steps {
+            if let Step::AddColumn { table, column, .. } = step {
+                let exists: i64 =
+                    sqlx::query_scalar("SELECT COUNT(*) FROM pragma_table_info(?) WHERE name = ?")
+                        .bind(table)
+                        .bind(column)
+                        .fetch_one(&mut *tx)
+                        .await
+                        .map_err(DbError::Sqlx)?;
+                // Also skipped for tables which don't exist yet, their script creates the column
+                let table_exists: i64 = sqlx::query_scalar(
+                    "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?",
+                )
+                .bind(table)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(DbError::Sqlx)?;
+                if exists > 0 || table_exists == 0 {
+                    continue;
+                }
+            }
+            sqlx::raw_sql(&step.sql())
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| {
+                    DbError::Migration(format!(
+                        "migration {} ({}) failed: {}",
+                        migration.version, migration.name, e
+                    ))
+                })?;
+        }
+        sqlx::query("INSERT INTO schema_version (version, name, checksum) VALUES (?, ?, ?)")
+            .bind(migration.version)
+            .bind(migration.name)
+            .bind(migration.checksum())
+            .execute(&mut *tx)
+            .await
+            .map_err(DbError::Sqlx)?;
+        tx.commit().await.map_err(DbError::Sqlx)?;
+    }
+    Ok(())
+}
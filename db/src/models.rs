@@ -1,14 +1,69 @@
 //! Database models (structs that map to database rows)
 
 use crate::DbError;
+use crate::decode::RowRef;
 use sqlx::FromRow;
-use wwc_core::error::WwcError;
-use wwc_core::fair_play::FairPlayScore;
-use wwc_core::game::{GameId, GoalCount};
+use wwc_core::bonus::{BonusPrediction, BonusQuestion, BonusQuestionId};
+use wwc_core::fair_play::{FairPlay, FairPlayScore};
+use wwc_core::game::GameId;
+use wwc_core::group::GroupId;
 use wwc_core::group::game::{GroupGameScore, PlayedGroupGame, UnplayedGroupGame};
-use wwc_core::player::Prediction;
+use wwc_core::player::{OutcomePrediction, Prediction};
+use wwc_core::playoff::{PlayoffResult, PlayoffScore};
 use wwc_core::team::{FifaCode, TeamId, TeamName, TeamRank};
 
+/// Id of a tournament, the scope of most other tables
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, sqlx::Type)]
+#[sqlx(transparent)]
+pub struct TournamentId(pub i32);
+
+impl std::fmt::Display for TournamentId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct Tournament {
+    pub id: TournamentId,
+    pub name: String,
+}
+
+/// Id of a private league within a tournament
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, sqlx::Type)]
+#[sqlx(transparent)]
+pub struct LeagueId(pub i32);
+
+impl std::fmt::Display for LeagueId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct League {
+    pub id: LeagueId,
+    pub tournament_id: TournamentId,
+    pub name: String,
+    pub invite_code: String,
+}
+
+/// Role of a player in a league, the owner manages the league
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, serde::Serialize)]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum LeagueRole {
+    Owner,
+    Member,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct LeagueMember {
+    pub player_id: i32,
+    pub role: LeagueRole,
+    pub joined_at: String,
+}
+
 #[derive(Debug, FromRow)]
 pub struct Team {
     pub id: i32,
@@ -17,18 +72,21 @@ pub struct Team {
     pub rank_: i32,
 }
 
-impl From<Team> for wwc_core::Team {
-    fn from(db_team: Team) -> wwc_core::Team {
-        let id = TeamId(u32::try_from(db_team.id).unwrap());
+impl TryFrom<Team> for wwc_core::Team {
+    type Error = DbError;
+    fn try_from(db_team: Team) -> Result<Self, Self::Error> {
+        let row = RowRef::new("teams", db_team.id);
+        let id = row.team_id("id", db_team.id)?;
         let name = TeamName::from(db_team.name);
-        let fifa_code = FifaCode::try_from(db_team.fifa_code).unwrap();
-        let rank = TeamRank(u32::try_from(db_team.rank_).unwrap());
-        wwc_core::Team {
+        let fifa_code =
+            FifaCode::try_from(db_team.fifa_code).map_err(|e| row.invalid("fifa_code", e))?;
+        let rank = TeamRank(row.unsigned("rank_", db_team.rank_)?);
+        Ok(wwc_core::Team {
             id,
             name,
             fifa_code,
             rank,
-        }
+        })
     }
 }
 
@@ -42,43 +100,73 @@ pub struct Game {
     pub away_result: Option<i32>,
     pub home_penalty: Option<i32>,
     pub away_penalty: Option<i32>,
-    pub home_fair_play: Option<i32>,
-    pub away_fair_play: Option<i32>,
     pub played: bool,
 }
 
-impl TryFrom<Game> for PlayedGroupGame {
-    type Error = DbError;
-    fn try_from(game: Game) -> Result<Self, Self::Error> {
-        Ok(UnplayedGroupGame::try_new(
-            u32::try_from(game.id).unwrap(),
-            u32::try_from(game.home_team).unwrap(),
-            u32::try_from(game.away_team).unwrap(),
+impl Game {
+    /// Played group game with the cards of both teams
+    pub fn try_into_played(self, fair_play: FairPlayScore) -> Result<PlayedGroupGame, DbError> {
+        let row = RowRef::new("games", self.id);
+        let score = GroupGameScore::new(
+            row.goals(
+                "home_result",
+                row.required("home_result", self.home_result)?,
+            )?,
+            row.goals(
+                "away_result",
+                row.required("away_result", self.away_result)?,
+            )?,
+        );
+        Ok(self.try_into_unplayed(&row)?.play(score, fair_play))
+    }
+
+    fn try_into_unplayed(&self, row: &RowRef) -> Result<UnplayedGroupGame, DbError> {
+        UnplayedGroupGame::try_new(
+            row.game_id("id", self.id)?,
+            row.team_id("home_team", self.home_team)?,
+            row.team_id("away_team", self.away_team)?,
             wwc_core::Date::mock(),
         )
-        .map_err(WwcError::from)
-        .map_err(DbError::from)?
-        .play(
-            GroupGameScore::new(
-                GoalCount::try_from(u32::try_from(game.home_result.unwrap()).unwrap()).unwrap(),
-                GoalCount::try_from(u32::try_from(game.away_result.unwrap()).unwrap()).unwrap(),
-            ),
-            FairPlayScore::default(),
-        ))
+        .map_err(|e| row.invalid("home_team, away_team", e))
     }
 }
 
 impl TryFrom<Game> for UnplayedGroupGame {
     type Error = DbError;
     fn try_from(game: Game) -> Result<Self, Self::Error> {
-        UnplayedGroupGame::try_new(
-            u32::try_from(game.id).unwrap(),
-            u32::try_from(game.home_team).unwrap(),
-            u32::try_from(game.away_team).unwrap(),
-            wwc_core::Date::mock(),
-        )
-        .map_err(WwcError::from)
-        .map_err(DbError::from)
+        game.try_into_unplayed(&RowRef::new("games", game.id))
+    }
+}
+
+/// Card counts of one team in a game
+#[derive(Debug, FromRow)]
+pub struct FairPlayCards {
+    pub game_id: i32,
+    pub team_id: i32,
+    pub yellow: i32,
+    pub indirect_red: i32,
+    pub direct_red: i32,
+    pub yellow_and_direct: i32,
+}
+
+impl TryFrom<&FairPlayCards> for ((GameId, TeamId), FairPlay) {
+    type Error = DbError;
+    fn try_from(cards: &FairPlayCards) -> Result<Self, Self::Error> {
+        let row = RowRef::new(
+            "fair_play_cards",
+            format!("game {}, team {}", cards.game_id, cards.team_id),
+        );
+        let key = (
+            row.game_id("game_id", cards.game_id)?,
+            row.team_id("team_id", cards.team_id)?,
+        );
+        let fair_play = FairPlay::new(
+            row.unsigned("yellow", cards.yellow)?,
+            row.unsigned("indirect_red", cards.indirect_red)?,
+            row.unsigned("direct_red", cards.direct_red)?,
+            row.unsigned("yellow_and_direct", cards.yellow_and_direct)?,
+        );
+        Ok((key, fair_play))
     }
 }
 
@@ -88,6 +176,17 @@ pub struct GroupGameMap {
     pub group_id_: String,
 }
 
+impl TryFrom<GroupGameMap> for (GameId, GroupId) {
+    type Error = DbError;
+    fn try_from(map_: GroupGameMap) -> Result<Self, Self::Error> {
+        let row = RowRef::new("group_game_map", map_.id);
+        Ok((
+            row.game_id("id", map_.id)?,
+            row.group_id("group_id_", &map_.group_id_)?,
+        ))
+    }
+}
+
 #[derive(Debug, FromRow)]
 pub struct PlayoffTeamSourceRow {
     pub game_id: i32,
@@ -113,13 +212,211 @@ pub struct Pred {
     pub bot_name: Option<String>,
 }
 
-impl From<Pred> for Prediction {
-    fn from(pred: Pred) -> Prediction {
+impl TryFrom<Pred> for Prediction {
+    type Error = DbError;
+    fn try_from(pred: Pred) -> Result<Self, Self::Error> {
+        let row = RowRef::new("preds", pred.id);
+        let score = GroupGameScore::new(
+            row.goals("home_result", pred.home_result)?,
+            row.goals("away_result", pred.away_result)?,
+        );
+        Ok(Prediction(row.game_id("game_id", pred.game_id)?, score))
+    }
+}
+
+/// A submission joined with one of its predictions, without any if the predictions were cleared
+#[derive(Debug, FromRow)]
+pub struct PredHistoryRow {
+    pub submission_id: i32,
+    pub submitted_at: String,
+    pub game_id: Option<i32>,
+    pub home_result: Option<i32>,
+    pub away_result: Option<i32>,
+}
+
+impl PredHistoryRow {
+    /// The prediction of the row, `None` if the submission has none
+    pub fn prediction(&self) -> Result<Option<Prediction>, DbError> {
+        let Some(game_id) = self.game_id else {
+            return Ok(None);
+        };
+        let row = RowRef::new(
+            "pred_history",
+            format!("submission {}, game {}", self.submission_id, game_id),
+        );
         let score = GroupGameScore::new(
-            GoalCount::try_from(u32::try_from(pred.home_result).unwrap()).unwrap(),
-            GoalCount::try_from(u32::try_from(pred.away_result).unwrap()).unwrap(),
+            row.goals(
+                "home_result",
+                row.required("home_result", self.home_result)?,
+            )?,
+            row.goals(
+                "away_result",
+                row.required("away_result", self.away_result)?,
+            )?,
         );
-        Prediction(GameId::from(u32::try_from(pred.game_id).unwrap()), score)
+        Ok(Some(Prediction(row.game_id("game_id", game_id)?, score)))
+    }
+}
+
+#[derive(Debug, FromRow)]
+pub struct PlayoffPred {
+    pub id: i32,
+    pub player_id: i32,
+    pub game_id: i32,
+    pub home_team: i32,
+    pub away_team: i32,
+    pub home_result: i32,
+    pub away_result: i32,
+    pub home_penalty: Option<i32>,
+    pub away_penalty: Option<i32>,
+    pub bot_name: Option<String>,
+}
+
+impl TryFrom<PlayoffPred> for (GameId, PlayoffResult) {
+    type Error = DbError;
+    fn try_from(pred: PlayoffPred) -> Result<Self, Self::Error> {
+        PlayoffResultRow {
+            game_id: pred.game_id,
+            home_team: pred.home_team,
+            away_team: pred.away_team,
+            home_result: pred.home_result,
+            away_result: pred.away_result,
+            home_penalty: pred.home_penalty,
+            away_penalty: pred.away_penalty,
+        }
+        .decode(&RowRef::new("playoff_preds", pred.id))
+    }
+}
+
+#[derive(Debug, FromRow)]
+pub struct PlayoffResultRow {
+    pub game_id: i32,
+    pub home_team: i32,
+    pub away_team: i32,
+    pub home_result: i32,
+    pub away_result: i32,
+    pub home_penalty: Option<i32>,
+    pub away_penalty: Option<i32>,
+}
+
+impl PlayoffResultRow {
+    /// Decode the result, stored in `row`
+    fn decode(self, row: &RowRef) -> Result<(GameId, PlayoffResult), DbError> {
+        let penalty = |field, goals: Option<i32>| goals.map(|x| row.goals(field, x)).transpose();
+        let score = PlayoffScore::try_new(
+            row.goals("home_result", self.home_result)?,
+            row.goals("away_result", self.away_result)?,
+            penalty("home_penalty", self.home_penalty)?,
+            penalty("away_penalty", self.away_penalty)?,
+        )
+        .map_err(|e| row.invalid("home_penalty, away_penalty", e))?;
+        let result = PlayoffResult::new(
+            row.team_id("home_team", self.home_team)?,
+            row.team_id("away_team", self.away_team)?,
+            score,
+        );
+        Ok((row.game_id("game_id", self.game_id)?, result))
+    }
+}
+
+impl TryFrom<PlayoffResultRow> for (GameId, PlayoffResult) {
+    type Error = DbError;
+    fn try_from(result: PlayoffResultRow) -> Result<Self, Self::Error> {
+        let row = RowRef::new("playoff_results", result.game_id);
+        result.decode(&row)
+    }
+}
+
+#[derive(Debug, FromRow)]
+pub struct BonusQuestionRow {
+    pub id: i32,
+    pub text: String,
+    pub kind: String,
+    pub deadline: String,
+    pub solution: Option<String>,
+}
+
+impl TryFrom<BonusQuestionRow> for BonusQuestion {
+    type Error = DbError;
+    fn try_from(row: BonusQuestionRow) -> Result<Self, Self::Error> {
+        let at = RowRef::new("bonus_questions", row.id);
+        Ok(BonusQuestion {
+            id: BonusQuestionId::from(at.unsigned("id", row.id)?),
+            text: row.text,
+            kind: at.json("kind", &row.kind)?,
+            deadline: at.date("deadline", &row.deadline)?,
+            solution: row
+                .solution
+                .map(|solution| at.json("solution", &solution))
+                .transpose()?,
+        })
+    }
+}
+
+#[derive(Debug, FromRow)]
+pub struct BonusPred {
+    pub id: i32,
+    pub player_id: i32,
+    pub question_id: i32,
+    pub answer: String,
+    pub bot_name: Option<String>,
+}
+
+impl TryFrom<BonusPred> for BonusPrediction {
+    type Error = DbError;
+    fn try_from(pred: BonusPred) -> Result<Self, Self::Error> {
+        let row = RowRef::new("bonus_preds", pred.id);
+        Ok(BonusPrediction(
+            BonusQuestionId::from(row.unsigned("question_id", pred.question_id)?),
+            row.json("answer", &pred.answer)?,
+        ))
+    }
+}
+
+#[derive(Debug, FromRow)]
+pub struct OutcomePred {
+    pub id: i32,
+    pub player_id: i32,
+    pub game_id: i32,
+    pub outcome: String,
+    pub bot_name: Option<String>,
+}
+
+impl TryFrom<OutcomePred> for OutcomePrediction {
+    type Error = DbError;
+    fn try_from(pred: OutcomePred) -> Result<Self, Self::Error> {
+        let row = RowRef::new("outcome_preds", pred.id);
+        Ok(OutcomePrediction(
+            row.game_id("game_id", pred.game_id)?,
+            row.outcome("outcome", &pred.outcome)?,
+        ))
+    }
+}
+
+/// An outcome submission joined with one of its predictions, without any if they were cleared
+#[derive(Debug, FromRow)]
+pub struct OutcomePredHistoryRow {
+    pub submission_id: i32,
+    pub submitted_at: String,
+    pub game_id: Option<i32>,
+    pub outcome: Option<String>,
+}
+
+impl OutcomePredHistoryRow {
+    /// The outcome prediction of the row, `None` if the submission has none
+    pub fn prediction(&self) -> Result<Option<OutcomePrediction>, DbError> {
+        let Some(game_id) = self.game_id else {
+            return Ok(None);
+        };
+        let row = RowRef::new(
+            "outcome_pred_history",
+            format!("submission {}, game {}", self.submission_id, game_id),
+        );
+        let outcome = row.required("outcome", self.outcome.as_deref())?;
+        Ok(Some(OutcomePrediction(
+            row.game_id("game_id", game_id)?,
+            row.outcome("outcome", outcome)?,
+        )))
     }
 }
 
@@ -129,7 +426,7 @@ pub struct Player {
     pub name: String,
 }
 
-#[derive(Debug, FromRow)]
+#[derive(Debug, Clone, FromRow)]
 pub struct User {
     pub id: i32, // This is also the player_id
     pub username: String,
@@ -138,7 +435,7 @@ pub struct User {
     pub created_at: String,
 }
 
-#[derive(Debug, FromRow)]
+#[derive(Debug, Clone, FromRow)]
 pub struct Bot {
     pub id: i32,
     pub user_id: i32, // This is also the player_id
@@ -0,0 +1,198 @@
+use crate::DbError;
+use crate::decode::to_column;
+use crate::games::{delete_unlocked_preds, get_group_game_ids, get_locked_games};
+use crate::history::record_outcome_pred_submission;
+use crate::models::{OutcomePred, TournamentId};
+use itertools::Itertools;
+use sqlx::SqlitePool;
+use wwc_core::Date;
+use wwc_core::group::game::GroupGameOutcome;
+use wwc_core::player::{OutcomePrediction, PlayerId};
+use wwc_core::validation::PredReport;
+
+pub(crate) fn outcome_to_str(outcome: GroupGameOutcome) -> &'static str {
+    match outcome {
+        GroupGameOutcome::Win => "win",
+        GroupGameOutcome::Draw => "draw",
+        GroupGameOutcome::Lose => "lose",
+    }
+}
+
+/// Get the outcome predictions of a specific player and optional bot
+///
+/// With `None`, only the player's own (non-bot) predictions are returned.
+pub async fn get_outcome_preds(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    player_id: PlayerId,
+    bot_name: Option<&str>,
+) -> Result<Vec<OutcomePrediction>, DbError> {
+    let preds = sqlx::query_as::<_, OutcomePred>(
+        "SELECT * FROM outcome_preds WHERE tournament_id = ? AND player_id = ? AND bot_name IS ?
+         ORDER BY game_id",
+    )
+    .bind(tournament)
+    .bind(i32::from(player_id))
+    .bind(bot_name)
+    .fetch_all(pool)
+    .await
+    .map_err(DbError::Sqlx)?;
+
+    preds.into_iter().map(OutcomePrediction::try_from).collect()
+}
+
+/// Validate outcome predictions against a tournament and store the accepted ones
+///
+/// Replaces the stored outcome predictions of the player+bot combination, except for games which
+/// have kicked off at `now`: their stored predictions are kept as they are. The stored
+/// predictions are appended to the outcome prediction history, submitted at `now`, in the same
+/// transaction.
+pub async fn insert_validated_outcome_preds(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    player_id: PlayerId,
+    preds: &[OutcomePrediction],
+    bot_name: Option<&str>,
+    now: Date,
+) -> Result<PredReport<OutcomePrediction>, DbError> {
+    let games = get_group_game_ids(pool, tournament).await?;
+    let locked = get_locked_games(pool, tournament, now).await?;
+    let report = PredReport::new(preds, &games, &locked);
+
+    // The kept predictions are read, and the predictions and their history entry written, in
+    // one transaction
+    let mut tx = pool.begin().await.map_err(DbError::Sqlx)?;
+    let kept: Vec<OutcomePrediction> = sqlx::query_as::<_, OutcomePred>(
+        "SELECT * FROM outcome_preds WHERE tournament_id = ? AND player_id = ? AND bot_name IS ?",
+    )
+    .bind(tournament)
+    .bind(i32::from(player_id))
+    .bind(bot_name)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(DbError::Sqlx)?
+    .into_iter()
+    .map(OutcomePrediction::try_from)
+    .filter_ok(|OutcomePrediction(game_id, _)| locked.contains(game_id))
+    .collect::<Result<_, _>>()?;
+
+    let stored: Vec<OutcomePrediction> = report.accepted().copied().chain(kept).collect();
+    let rows = stored
+        .iter()
+        .map(|OutcomePrediction(game_id, outcome)| {
+            Ok((
+                to_column("game_id", u32::from(*game_id))?,
+                outcome_to_str(*outcome),
+            ))
+        })
+        .collect::<Result<Vec<_>, DbError>>()?;
+    sqlx::query(
+        "DELETE FROM outcome_preds WHERE tournament_id = ? AND player_id = ? AND bot_name IS ?",
+    )
+    .bind(tournament)
+    .bind(i32::from(player_id))
+    .bind(bot_name)
+    .execute(&mut *tx)
+    .await
+    .map_err(DbError::Sqlx)?;
+    for (game_id, outcome) in rows {
+        sqlx::query(
+            "INSERT INTO outcome_preds (tournament_id, player_id, game_id, outcome, bot_name)
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(tournament)
+        .bind(i32::from(player_id))
+        .bind(game_id)
+        .bind(outcome)
+        .bind(bot_name)
+        .execute(&mut *tx)
+        .await
+        .map_err(DbError::Sqlx)?;
+    }
+    record_outcome_pred_submission(&mut tx, tournament, player_id, bot_name, &stored, now).await?;
+    tx.commit().await.map_err(DbError::Sqlx)?;
+
+    Ok(report)
+}
+
+/// Clear the outcome predictions of a specific player and optional bot
+///
+/// With `None`, only the player's own (non-bot) predictions are cleared. Predictions of games which
+/// have kicked off at `now` are kept, and appended to the outcome prediction history, submitted at
+/// `now`, in the same transaction.
+pub async fn clear_player_outcome_preds(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    player_id: PlayerId,
+    bot_name: Option<&str>,
+    now: Date,
+) -> Result<(), DbError> {
+    let locked = get_locked_games(pool, tournament, now).await?;
+    let mut tx = pool.begin().await.map_err(DbError::Sqlx)?;
+    delete_unlocked_preds(
+        &mut tx,
+        "outcome_preds",
+        tournament,
+        player_id,
+        bot_name,
+        false,
+        &locked,
+    )
+    .await?;
+    let kept: Vec<OutcomePrediction> = sqlx::query_as::<_, OutcomePred>(
+        "SELECT * FROM outcome_preds WHERE tournament_id = ? AND player_id = ? AND bot_name IS ?
+         ORDER BY game_id",
+    )
+    .bind(tournament)
+    .bind(i32::from(player_id))
+    .bind(bot_name)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(DbError::Sqlx)?
+    .into_iter()
+    .map(OutcomePrediction::try_from)
+    .collect::<Result<_, _>>()?;
+    record_outcome_pred_submission(&mut tx, tournament, player_id, bot_name, &kept, now).await?;
+    tx.commit().await.map_err(DbError::Sqlx)?;
+    Ok(())
+}
+
+/// Clear all outcome predictions of a tournament
+///
+/// The clearing is recorded at `now` in the history of every player+bot combination with
+/// predictions or submissions, in the same transaction.
+pub async fn clear_outcome_preds(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    now: Date,
+) -> Result<(), DbError> {
+    let mut tx = pool.begin().await.map_err(DbError::Sqlx)?;
+    let competitors = sqlx::query_as::<_, (i32, Option<String>)>(
+        "SELECT player_id, bot_name FROM outcome_preds WHERE tournament_id = ?
+         UNION
+         SELECT player_id, bot_name FROM outcome_pred_submissions WHERE tournament_id = ?",
+    )
+    .bind(tournament)
+    .bind(tournament)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(DbError::Sqlx)?;
+    sqlx::query("DELETE FROM outcome_preds WHERE tournament_id = ?")
+        .bind(tournament)
+        .execute(&mut *tx)
+        .await
+        .map_err(DbError::Sqlx)?;
+    for (player_id, bot_name) in competitors {
+        record_outcome_pred_submission(
+            &mut tx,
+            tournament,
+            PlayerId::from(player_id),
+            bot_name.as_deref(),
+            &[],
+            now,
+        )
+        .await?;
+    }
+    tx.commit().await.map_err(DbError::Sqlx)?;
+    Ok(())
+}
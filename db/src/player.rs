@@ -1,7 +1,19 @@
 use crate::DbError;
-use crate::models::{Player, Pred};
-use sqlx::SqlitePool;
+use crate::bonus::get_bonus_preds;
+use crate::decode::to_column;
+use crate::games::{delete_unlocked_preds, get_group_game_ids, get_locked_games};
+use crate::history::record_pred_submission;
+use crate::joker::get_jokers;
+use crate::models::{Player, Pred, TournamentId};
+use crate::outcome::get_outcome_preds;
+use crate::playoff::get_playoff_preds;
+use itertools::Itertools;
+use sqlx::{SqliteConnection, SqlitePool};
+use std::collections::HashSet;
+use wwc_core::Date;
+use wwc_core::leaderboard::{Competitor, CompetitorPredictions};
 use wwc_core::player::{PlayerId, PlayerPredictions, Prediction};
+use wwc_core::validation::PredReport;
 
 /// Register a new player in the database
 pub async fn register_player(pool: &SqlitePool, name: &str) -> Result<(), DbError> {
@@ -27,9 +39,23 @@ pub async fn register_player(pool: &SqlitePool, name: &str) -> Result<(), DbErro
     Ok(())
 }
 
+/// Make sure a player exists, creating a default player with the given id if it doesn't
+///
+/// Returns whether the player was created.
+pub async fn ensure_player(pool: &SqlitePool, player_id: PlayerId) -> Result<bool, DbError> {
+    let result = sqlx::query("INSERT OR IGNORE INTO players (id, name) VALUES (?, ?)")
+        .bind(i32::from(player_id))
+        .bind(format!("Player {}", i32::from(player_id)))
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    Ok(result.rows_affected() > 0)
+}
+
 /// Get all predictions for a specific player and optional bot
 pub async fn get_preds(
     pool: &SqlitePool,
+    tournament: TournamentId,
     player_id: PlayerId,
     bot_name: Option<&str>,
 ) -> Result<Vec<Prediction>, DbError> {
@@ -37,22 +63,87 @@ pub async fn get_preds(
 
     let db_preds = if let Some(bot) = bot_name {
         // If bot_name is specified, filter by it
-        sqlx::query_as::<_, Pred>("SELECT * FROM preds WHERE player_id = ? AND bot_name = ?")
-            .bind(player_id)
-            .bind(bot)
-            .fetch_all(pool)
-            .await
-            .map_err(DbError::Sqlx)?
+        sqlx::query_as::<_, Pred>(
+            "SELECT * FROM preds WHERE tournament_id = ? AND player_id = ? AND bot_name = ?",
+        )
+        .bind(tournament)
+        .bind(player_id)
+        .bind(bot)
+        .fetch_all(pool)
+        .await
+        .map_err(DbError::Sqlx)?
     } else {
         // If no bot_name specified, get all predictions for this player (backwards compatible)
-        sqlx::query_as::<_, Pred>("SELECT * FROM preds WHERE player_id = ?")
+        sqlx::query_as::<_, Pred>("SELECT * FROM preds WHERE tournament_id = ? AND player_id = ?")
+            .bind(tournament)
             .bind(player_id)
             .fetch_all(pool)
             .await
             .map_err(DbError::Sqlx)?
     };
 
-    Ok(db_preds.into_iter().map(Prediction::from).collect())
+    db_preds.into_iter().map(Prediction::try_from).collect()
+}
+
+/// Get all predictions of every competitor (players and their bots) in a tournament
+///
+/// Group (score and outcome), playoff and bonus predictions, and jokers.
+pub async fn get_all_competitor_preds(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+) -> Result<Vec<CompetitorPredictions>, DbError> {
+    let competitors = sqlx::query_as::<_, (i32, Option<String>)>(
+        "SELECT player_id, bot_name FROM preds WHERE tournament_id = ?1
+         UNION
+         SELECT player_id, bot_name FROM playoff_preds WHERE tournament_id = ?1
+         UNION
+         SELECT player_id, bot_name FROM bonus_preds WHERE tournament_id = ?1
+         UNION
+         SELECT player_id, bot_name FROM jokers WHERE tournament_id = ?1
+         UNION
+         SELECT player_id, bot_name FROM outcome_preds WHERE tournament_id = ?1
+         ORDER BY player_id, bot_name",
+    )
+    .bind(tournament)
+    .fetch_all(pool)
+    .await
+    .map_err(DbError::Sqlx)?;
+
+    let mut all_preds = Vec::with_capacity(competitors.len());
+    for (player_id, bot_name) in competitors {
+        // 'IS' also matches NULL, i.e. the human predictions
+        let group = sqlx::query_as::<_, Pred>(
+            "SELECT * FROM preds WHERE tournament_id = ? AND player_id = ? AND bot_name IS ?",
+        )
+        .bind(tournament)
+        .bind(player_id)
+        .bind(bot_name.as_deref())
+        .fetch_all(pool)
+        .await
+        .map_err(DbError::Sqlx)?
+        .into_iter()
+        .map(Prediction::try_from)
+        .collect::<Result<_, _>>()?;
+        let player_id = PlayerId::from(player_id);
+        let playoff = get_playoff_preds(pool, tournament, player_id, bot_name.as_deref()).await?;
+        let bonus = get_bonus_preds(pool, tournament, player_id, bot_name.as_deref()).await?;
+        let jokers = get_jokers(pool, tournament, player_id, bot_name.as_deref()).await?;
+        let outcomes = get_outcome_preds(pool, tournament, player_id, bot_name.as_deref()).await?;
+        all_preds.push(
+            CompetitorPredictions::new(
+                Competitor {
+                    player_id,
+                    bot_name,
+                },
+                group,
+                playoff,
+            )
+            .with_outcomes(outcomes)
+            .with_bonus(bonus)
+            .with_jokers(jokers),
+        );
+    }
+    Ok(all_preds)
 }
 
 /// Get all players from the database
@@ -66,79 +157,243 @@ pub async fn get_players(pool: &SqlitePool) -> Result<Vec<Player>, DbError> {
 }
 
 /// Insert predictions for a player (replaces existing predictions for that player+bot combination)
+///
+/// Runs in a single transaction: predictions of games missing from `preds` are deleted and the
+/// rest are upserted, so repeating the same save leaves the table unchanged. Only predictions of
+/// the given tournament are affected.
 pub async fn insert_preds(
     pool: &SqlitePool,
+    tournament: TournamentId,
+    preds: &PlayerPredictions,
+    bot_name: Option<&str>,
+) -> Result<(), DbError> {
+    let mut tx = pool.begin().await.map_err(DbError::Sqlx)?;
+    write_preds(&mut tx, tournament, preds, bot_name).await?;
+    tx.commit().await.map_err(DbError::Sqlx)?;
+    Ok(())
+}
+
+/// Replace the predictions of a player+bot combination on a connection, see [`insert_preds`]
+async fn write_preds(
+    conn: &mut SqliteConnection,
+    tournament: TournamentId,
     preds: &PlayerPredictions,
     bot_name: Option<&str>,
 ) -> Result<(), DbError> {
     let player_id = i32::from(preds.id);
+    let rows = preds
+        .preds()
+        .map(|Prediction(game_id, score)| {
+            Ok((
+                to_column("game_id", u32::from(*game_id))?,
+                to_column("home_result", u32::from(score.home))?,
+                to_column("away_result", u32::from(score.away))?,
+            ))
+        })
+        .collect::<Result<Vec<_>, DbError>>()?;
+    let game_ids: HashSet<i32> = rows.iter().map(|(game_id, _, _)| *game_id).collect();
 
-    // Delete existing predictions for this player+bot combination
-    if let Some(bot) = bot_name {
-        sqlx::query("DELETE FROM preds WHERE player_id = ? AND bot_name = ?")
-            .bind(player_id)
-            .bind(bot)
-            .execute(pool)
-            .await
-            .map_err(DbError::Sqlx)?;
-    } else {
-        sqlx::query("DELETE FROM preds WHERE player_id = ? AND bot_name IS NULL")
-            .bind(player_id)
-            .execute(pool)
-            .await
-            .map_err(DbError::Sqlx)?;
+    // Delete predictions of games which are no longer predicted
+    let stored: Vec<i32> = sqlx::query_scalar(
+        "SELECT game_id FROM preds WHERE tournament_id = ? AND player_id = ? AND bot_name IS ?",
+    )
+    .bind(tournament)
+    .bind(player_id)
+    .bind(bot_name)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(DbError::Sqlx)?;
+    for game_id in stored.into_iter().filter(|id| !game_ids.contains(id)) {
+        sqlx::query(
+            "DELETE FROM preds
+             WHERE tournament_id = ? AND player_id = ? AND bot_name IS ? AND game_id = ?",
+        )
+        .bind(tournament)
+        .bind(player_id)
+        .bind(bot_name)
+        .bind(game_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(DbError::Sqlx)?;
     }
 
-    // Insert new predictions
-    for pred in preds.preds() {
+    // Upsert, the conflict target is the unique index of migration 012
+    for (game_id, home_result, away_result) in rows {
         sqlx::query(
-            "INSERT INTO preds (player_id, game_id, home_result, away_result, bot_name) VALUES (?, ?, ?, ?, ?)",
+            "INSERT INTO preds (tournament_id, player_id, game_id, home_result, away_result, bot_name)
+             VALUES (?, ?, ?, ?, ?, ?)
+             ON CONFLICT (tournament_id, player_id, game_id, IFNULL(bot_name, ''))
+             DO UPDATE SET home_result = excluded.home_result, away_result = excluded.away_result",
         )
+        .bind(tournament)
         .bind(player_id)
-        .bind(i32::try_from(u32::from(pred.0)).unwrap())
-        .bind(i32::try_from(u32::from(pred.1.home)).unwrap())
-        .bind(i32::try_from(u32::from(pred.1.away)).unwrap())
+        .bind(game_id)
+        .bind(home_result)
+        .bind(away_result)
         .bind(bot_name)
-        .execute(pool)
+        .execute(&mut *conn)
         .await
         .map_err(DbError::Sqlx)?;
     }
-
     Ok(())
 }
 
-/// Clear all predictions from the database (admin only)
-pub async fn clear_preds(pool: &SqlitePool) -> Result<(), DbError> {
-    sqlx::query("DELETE FROM preds")
-        .execute(pool)
+/// Validate predictions against a tournament and store the accepted ones
+///
+/// Replaces the stored predictions of the player+bot combination, except for games which have
+/// kicked off at `now`: their stored predictions are kept as they are. The stored predictions are
+/// appended to the prediction history, submitted at `now`, in the same transaction.
+pub async fn insert_validated_preds(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    preds: &PlayerPredictions,
+    bot_name: Option<&str>,
+    now: Date,
+) -> Result<PredReport, DbError> {
+    let games = get_group_game_ids(pool, tournament).await?;
+    let locked = get_locked_games(pool, tournament, now).await?;
+    let report = PredReport::new(preds.preds(), &games, &locked);
+
+    // The kept predictions are read, and the predictions and their history entry written, in
+    // one transaction
+    let mut tx = pool.begin().await.map_err(DbError::Sqlx)?;
+    let kept: Vec<Prediction> = sqlx::query_as::<_, Pred>(
+        "SELECT * FROM preds WHERE tournament_id = ? AND player_id = ? AND bot_name IS ?",
+    )
+    .bind(tournament)
+    .bind(i32::from(preds.id))
+    .bind(bot_name)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(DbError::Sqlx)?
+    .into_iter()
+    .map(Prediction::try_from)
+    .filter_ok(|Prediction(game_id, _)| locked.contains(game_id))
+    .collect::<Result<_, _>>()?;
+    let stored: Vec<Prediction> = report.accepted().copied().chain(kept).collect();
+    write_preds(
+        &mut tx,
+        tournament,
+        &PlayerPredictions::new(preds.id, stored.clone()),
+        bot_name,
+    )
+    .await?;
+    record_pred_submission(&mut tx, tournament, preds.id, bot_name, &stored, now).await?;
+    tx.commit().await.map_err(DbError::Sqlx)?;
+
+    Ok(report)
+}
+
+/// Clear all predictions of a tournament (admin only)
+///
+/// The clearing is recorded at `now` in the history of every player+bot combination with
+/// predictions or submissions, in the same transaction.
+pub async fn clear_preds(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    now: Date,
+) -> Result<(), DbError> {
+    let mut tx = pool.begin().await.map_err(DbError::Sqlx)?;
+    let competitors = sqlx::query_as::<_, (i32, Option<String>)>(
+        "SELECT player_id, bot_name FROM preds WHERE tournament_id = ?
+         UNION
+         SELECT player_id, bot_name FROM pred_submissions WHERE tournament_id = ?",
+    )
+    .bind(tournament)
+    .bind(tournament)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(DbError::Sqlx)?;
+    sqlx::query("DELETE FROM preds WHERE tournament_id = ?")
+        .bind(tournament)
+        .execute(&mut *tx)
         .await
         .map_err(DbError::Sqlx)?;
+    for (player_id, bot_name) in competitors {
+        record_pred_submission(
+            &mut tx,
+            tournament,
+            PlayerId::from(player_id),
+            bot_name.as_deref(),
+            &[],
+            now,
+        )
+        .await?;
+    }
+    tx.commit().await.map_err(DbError::Sqlx)?;
     Ok(())
 }
 
 /// Clear predictions for a specific player and optional bot
+///
+/// With `None`, the predictions of all the player's bots are cleared as well. Predictions of games
+/// which have kicked off at `now` are kept, like in [`insert_validated_preds`]. The kept
+/// predictions of each cleared player+bot combination are appended to the prediction history,
+/// submitted at `now`, in the same transaction.
 pub async fn clear_player_preds(
     pool: &SqlitePool,
+    tournament: TournamentId,
     player_id: PlayerId,
     bot_name: Option<&str>,
+    now: Date,
 ) -> Result<(), DbError> {
-    let player_id = i32::from(player_id);
-
-    if let Some(bot) = bot_name {
-        sqlx::query("DELETE FROM preds WHERE player_id = ? AND bot_name = ?")
-            .bind(player_id)
-            .bind(bot)
-            .execute(pool)
-            .await
-            .map_err(DbError::Sqlx)?;
-    } else {
-        // Clear all predictions for this player (all bots + human)
-        sqlx::query("DELETE FROM preds WHERE player_id = ?")
-            .bind(player_id)
-            .execute(pool)
-            .await
-            .map_err(DbError::Sqlx)?;
+    let locked = get_locked_games(pool, tournament, now).await?;
+    let mut tx = pool.begin().await.map_err(DbError::Sqlx)?;
+    let bot_names: Vec<Option<String>> = match bot_name {
+        Some(bot) => vec![Some(bot.to_string())],
+        None => sqlx::query_scalar::<_, Option<String>>(
+            "SELECT bot_name FROM preds
+             WHERE tournament_id = ? AND player_id = ? AND bot_name IS NOT NULL
+             UNION
+             SELECT bot_name FROM pred_submissions
+             WHERE tournament_id = ? AND player_id = ? AND bot_name IS NOT NULL",
+        )
+        .bind(tournament)
+        .bind(i32::from(player_id))
+        .bind(tournament)
+        .bind(i32::from(player_id))
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(DbError::Sqlx)?
+        .into_iter()
+        .chain([None])
+        .collect(),
+    };
+    delete_unlocked_preds(
+        &mut tx,
+        "preds",
+        tournament,
+        player_id,
+        bot_name,
+        bot_name.is_none(),
+        &locked,
+    )
+    .await?;
+    for bot_name in bot_names {
+        let kept: Vec<Prediction> = sqlx::query_as::<_, Pred>(
+            "SELECT * FROM preds WHERE tournament_id = ? AND player_id = ? AND bot_name IS ?
+             ORDER BY game_id",
+        )
+        .bind(tournament)
+        .bind(i32::from(player_id))
+        .bind(bot_name.as_deref())
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(DbError::Sqlx)?
+        .into_iter()
+        .map(Prediction::try_from)
+        .collect::<Result<_, _>>()?;
+        record_pred_submission(
+            &mut tx,
+            tournament,
+            player_id,
+            bot_name.as_deref(),
+            &kept,
+            now,
+        )
+        .await?;
     }
+    tx.commit().await.map_err(DbError::Sqlx)?;
     Ok(())
 }
 
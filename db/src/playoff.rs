@@ -1,14 +1,44 @@
 use crate::DbError;
-use crate::models::PlayoffTeamSourceRow;
+use crate::decode::{RowRef, to_column};
+use crate::games::{delete_unlocked_preds, get_locked_games};
+use crate::models::{PlayoffPred, PlayoffResultRow, PlayoffTeamSourceRow, TournamentId};
 use sqlx::SqlitePool;
 use std::collections::HashSet;
-use wwc_core::error::WwcError;
+use wwc_core::Date;
 use wwc_core::game::GameId;
 use wwc_core::group::{GroupId, GroupOutcome};
-use wwc_core::playoff::TeamSource;
+use wwc_core::player::PlayerId;
+use wwc_core::playoff::{BracketState, PlayoffResult, TeamSource};
+
+/// Columns of the team source of one side of a playoff game
+struct SourceColumns {
+    source_type: &'static str,
+    group_id: &'static str,
+    outcome: &'static str,
+    third_place_groups: &'static str,
+    source_game_id: &'static str,
+}
+
+const HOME_SOURCE: SourceColumns = SourceColumns {
+    source_type: "home_source_type",
+    group_id: "home_group_id",
+    outcome: "home_outcome",
+    third_place_groups: "home_third_place_groups",
+    source_game_id: "home_source_game_id",
+};
+
+const AWAY_SOURCE: SourceColumns = SourceColumns {
+    source_type: "away_source_type",
+    group_id: "away_group_id",
+    outcome: "away_outcome",
+    third_place_groups: "away_third_place_groups",
+    source_game_id: "away_source_game_id",
+};
 
 /// Parse a TeamSource from database row fields
 fn parse_team_source(
+    row: &RowRef,
+    columns: &SourceColumns,
     source_type: &str,
     group_id: Option<&str>,
     outcome: Option<&str>,
@@ -17,160 +47,168 @@ fn parse_team_source(
 ) -> Result<TeamSource, DbError> {
     match source_type {
         "group_outcome" => {
-            let group_id = group_id
-                .ok_or_else(|| DbError::Generic("Missing group_id for group_outcome".into()))?;
-            let group_id = GroupId::try_from(group_id.chars().next().unwrap())
-                .map_err(|e| DbError::Core(WwcError::from(e)))?;
+            let group_id = row.required(columns.group_id, group_id)?;
+            let group_id = row.group_id(columns.group_id, group_id)?;
 
-            let outcome = outcome
-                .ok_or_else(|| DbError::Generic("Missing outcome for group_outcome".into()))?;
+            let outcome = row.required(columns.outcome, outcome)?;
 
             let group_outcome = match outcome {
                 "winner" => GroupOutcome::Winner(group_id),
                 "runner_up" => GroupOutcome::RunnerUp(group_id),
                 "third_place" => {
-                    let groups_str = third_place_groups
-                        .ok_or_else(|| DbError::Generic("Missing third_place_groups".into()))?;
+                    let groups_str =
+                        row.required(columns.third_place_groups, third_place_groups)?;
                     // Parse JSON array like '["A","B","C"]'
-                    let groups: Vec<String> = serde_json::from_str(groups_str).map_err(|e| {
-                        DbError::Generic(format!("Invalid third_place_groups JSON: {}", e))
-                    })?;
+                    let groups: Vec<String> = row.json(columns.third_place_groups, groups_str)?;
                     let group_set: HashSet<GroupId> = groups
-                        .into_iter()
-                        .map(|s| GroupId::try_from(s.chars().next().unwrap()))
-                        .collect::<Result<_, _>>()
-                        .map_err(|e| DbError::Core(WwcError::from(e)))?;
+                        .iter()
+                        .map(|s| row.group_id(columns.third_place_groups, s))
+                        .collect::<Result<_, _>>()?;
                     GroupOutcome::ThirdPlace(group_set)
                 }
-                _ => return Err(DbError::Generic(format!("Unknown outcome: {}", outcome))),
+                _ => {
+                    return Err(
+                        row.invalid(columns.outcome, format!("Unknown outcome: {}", outcome))
+                    );
+                }
             };
 
             Ok(TeamSource::GroupOutcome(group_outcome))
         }
         "winner_of" => {
-            let game_id = source_game_id
-                .ok_or_else(|| DbError::Generic("Missing source_game_id for winner_of".into()))?;
-            Ok(TeamSource::WinnerOf(GameId::from(
-                u32::try_from(game_id).unwrap(),
-            )))
+            let game_id = row.required(columns.source_game_id, source_game_id)?;
+            Ok(TeamSource::WinnerOf(
+                row.game_id(columns.source_game_id, game_id)?,
+            ))
         }
         "loser_of" => {
-            let game_id = source_game_id
-                .ok_or_else(|| DbError::Generic("Missing source_game_id for loser_of".into()))?;
-            Ok(TeamSource::LoserOf(GameId::from(
-                u32::try_from(game_id).unwrap(),
-            )))
+            let game_id = row.required(columns.source_game_id, source_game_id)?;
+            Ok(TeamSource::LoserOf(
+                row.game_id(columns.source_game_id, game_id)?,
+            ))
         }
-        _ => Err(DbError::Generic(format!(
-            "Unknown source_type: {}",
-            source_type
-        ))),
+        _ => Err(row.invalid(
+            columns.source_type,
+            format!("Unknown source_type: {}", source_type),
+        )),
     }
 }
 
-/// Get playoff team sources from the database
+/// Decode the team sources of both sides of a playoff game
+pub(crate) fn decode_team_sources(
+    row: PlayoffTeamSourceRow,
+) -> Result<(GameId, (TeamSource, TeamSource)), DbError> {
+    let at = RowRef::new("playoff_team_sources", row.game_id);
+    let game_id = at.game_id("game_id", row.game_id)?;
+
+    let home_source = parse_team_source(
+        &at,
+        &HOME_SOURCE,
+        &row.home_source_type,
+        row.home_group_id.as_deref(),
+        row.home_outcome.as_deref(),
+        row.home_third_place_groups.as_deref(),
+        row.home_source_game_id,
+    )?;
+
+    let away_source = parse_team_source(
+        &at,
+        &AWAY_SOURCE,
+        &row.away_source_type,
+        row.away_group_id.as_deref(),
+        row.away_outcome.as_deref(),
+        row.away_third_place_groups.as_deref(),
+        row.away_source_game_id,
+    )?;
+
+    Ok((game_id, (home_source, away_source)))
+}
+
+/// Get the playoff team sources of a tournament
 pub async fn get_playoff_team_sources(
     pool: &SqlitePool,
+    tournament: TournamentId,
 ) -> Result<Vec<(GameId, (TeamSource, TeamSource))>, DbError> {
     let rows = sqlx::query_as::<_, PlayoffTeamSourceRow>(
-        "SELECT * FROM playoff_team_sources ORDER BY game_id",
+        "SELECT * FROM playoff_team_sources WHERE tournament_id = ? ORDER BY game_id",
     )
+    .bind(tournament)
     .fetch_all(pool)
     .await
     .map_err(DbError::Sqlx)?;
 
-    let team_sources: Result<Vec<_>, DbError> = rows
-        .into_iter()
-        .map(|row| {
-            let game_id = GameId::from(u32::try_from(row.game_id).unwrap());
-
-            let home_source = parse_team_source(
-                &row.home_source_type,
-                row.home_group_id.as_deref(),
-                row.home_outcome.as_deref(),
-                row.home_third_place_groups.as_deref(),
-                row.home_source_game_id,
-            )?;
-
-            let away_source = parse_team_source(
-                &row.away_source_type,
-                row.away_group_id.as_deref(),
-                row.away_outcome.as_deref(),
-                row.away_third_place_groups.as_deref(),
-                row.away_source_game_id,
-            )?;
-
-            Ok((game_id, (home_source, away_source)))
-        })
-        .collect();
-
-    team_sources
+    rows.into_iter().map(decode_team_sources).collect()
 }
 
-/// Insert playoff team sources into the database
+/// Column values of a team source, in the order of [`SourceColumns`]
+type SourceValues = (
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Option<i32>,
+);
+
+/// Insert playoff team sources into a tournament
 pub async fn insert_playoff_team_sources(
     pool: &SqlitePool,
+    tournament: TournamentId,
     team_sources: &[(GameId, (TeamSource, TeamSource))],
 ) -> Result<(), DbError> {
     for (game_id, (home_source, away_source)) in team_sources {
-        let game_id_i32 = i32::try_from(u32::from(*game_id)).unwrap();
+        let game_id_i32 = to_column("game_id", u32::from(*game_id))?;
 
         // Helper to extract fields from TeamSource
-        let extract_source = |source: &TeamSource| -> (
-            String,
-            Option<String>,
-            Option<String>,
-            Option<String>,
-            Option<i32>,
-        ) {
-            match source {
-                TeamSource::GroupOutcome(outcome) => match outcome {
-                    GroupOutcome::Winner(group_id) => (
-                        "group_outcome".to_string(),
-                        Some(String::from(char::from(*group_id))),
-                        Some("winner".to_string()),
+        let extract_source =
+            |source: &TeamSource, columns: &SourceColumns| -> Result<SourceValues, DbError> {
+                Ok(match source {
+                    TeamSource::GroupOutcome(outcome) => match outcome {
+                        GroupOutcome::Winner(group_id) => (
+                            "group_outcome".to_string(),
+                            Some(String::from(char::from(*group_id))),
+                            Some("winner".to_string()),
+                            None,
+                            None,
+                        ),
+                        GroupOutcome::RunnerUp(group_id) => (
+                            "group_outcome".to_string(),
+                            Some(String::from(char::from(*group_id))),
+                            Some("runner_up".to_string()),
+                            None,
+                            None,
+                        ),
+                        GroupOutcome::ThirdPlace(groups) => {
+                            let mut group_chars: Vec<String> = groups
+                                .iter()
+                                .map(|g| String::from(char::from(*g)))
+                                .collect();
+                            group_chars.sort();
+                            let groups_json = serde_json::to_string(&group_chars).unwrap();
+                            (
+                                "group_outcome".to_string(),
+                                None,
+                                Some("third_place".to_string()),
+                                Some(groups_json),
+                                None,
+                            )
+                        }
+                    },
+                    TeamSource::WinnerOf(game_id) => (
+                        "winner_of".to_string(),
+                        None,
                         None,
                         None,
+                        Some(to_column(columns.source_game_id, u32::from(*game_id))?),
                     ),
-                    GroupOutcome::RunnerUp(group_id) => (
-                        "group_outcome".to_string(),
-                        Some(String::from(char::from(*group_id))),
-                        Some("runner_up".to_string()),
+                    TeamSource::LoserOf(game_id) => (
+                        "loser_of".to_string(),
+                        None,
                         None,
                         None,
+                        Some(to_column(columns.source_game_id, u32::from(*game_id))?),
                     ),
-                    GroupOutcome::ThirdPlace(groups) => {
-                        let mut group_chars: Vec<String> = groups
-                            .iter()
-                            .map(|g| String::from(char::from(*g)))
-                            .collect();
-                        group_chars.sort();
-                        let groups_json = serde_json::to_string(&group_chars).unwrap();
-                        (
-                            "group_outcome".to_string(),
-                            None,
-                            Some("third_place".to_string()),
-                            Some(groups_json),
-                            None,
-                        )
-                    }
-                },
-                TeamSource::WinnerOf(game_id) => (
-                    "winner_of".to_string(),
-                    None,
-                    None,
-                    None,
-                    Some(i32::try_from(u32::from(*game_id)).unwrap()),
-                ),
-                TeamSource::LoserOf(game_id) => (
-                    "loser_of".to_string(),
-                    None,
-                    None,
-                    None,
-                    Some(i32::try_from(u32::from(*game_id)).unwrap()),
-                ),
-            }
-        };
+                })
+            };
 
         let (
             home_source_type,
@@ -178,22 +216,23 @@ pub async fn insert_playoff_team_sources(
             home_outcome,
             home_third_place_groups,
             home_source_game_id,
-        ) = extract_source(home_source);
+        ) = extract_source(home_source, &HOME_SOURCE)?;
         let (
             away_source_type,
             away_group_id,
             away_outcome,
             away_third_place_groups,
             away_source_game_id,
-        ) = extract_source(away_source);
+        ) = extract_source(away_source, &AWAY_SOURCE)?;
 
         sqlx::query(
             "INSERT INTO playoff_team_sources (
-                game_id,
+                tournament_id, game_id,
                 home_source_type, home_group_id, home_outcome, home_third_place_groups, home_source_game_id,
                 away_source_type, away_group_id, away_outcome, away_third_place_groups, away_source_game_id
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
         )
+        .bind(tournament)
         .bind(game_id_i32)
         .bind(home_source_type)
         .bind(home_group_id)
@@ -213,11 +252,16 @@ pub async fn insert_playoff_team_sources(
     Ok(())
 }
 
-/// Insert playoff game IDs into the database
-pub async fn insert_playoff_games(pool: &SqlitePool, game_ids: &[GameId]) -> Result<(), DbError> {
+/// Insert playoff game IDs into a tournament
+pub async fn insert_playoff_games(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    game_ids: &[GameId],
+) -> Result<(), DbError> {
     for game_id in game_ids {
-        sqlx::query("INSERT INTO playoff_games (id) VALUES (?)")
-            .bind(i32::try_from(u32::from(*game_id)).unwrap())
+        sqlx::query("INSERT INTO playoff_games (tournament_id, id) VALUES (?, ?)")
+            .bind(tournament)
+            .bind(to_column("id", u32::from(*game_id))?)
             .execute(pool)
             .await
             .map_err(DbError::Sqlx)?;
@@ -225,18 +269,269 @@ pub async fn insert_playoff_games(pool: &SqlitePool, game_ids: &[GameId]) -> Res
     Ok(())
 }
 
-/// Clear all playoff games from the database
-pub async fn clear_playoff_games(pool: &SqlitePool) -> Result<(), DbError> {
-    sqlx::query("DELETE FROM playoff_games")
+/// Clear all playoff games of a tournament
+pub async fn clear_playoff_games(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+) -> Result<(), DbError> {
+    sqlx::query("DELETE FROM playoff_games WHERE tournament_id = ?")
+        .bind(tournament)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    Ok(())
+}
+
+/// Clear all playoff team sources of a tournament
+pub async fn clear_playoff_team_sources(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+) -> Result<(), DbError> {
+    sqlx::query("DELETE FROM playoff_team_sources WHERE tournament_id = ?")
+        .bind(tournament)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    Ok(())
+}
+
+/// Get the actual playoff results of a tournament
+pub async fn get_playoff_results(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+) -> Result<BracketState, DbError> {
+    let rows = sqlx::query_as::<_, PlayoffResultRow>(
+        "SELECT * FROM playoff_results WHERE tournament_id = ?",
+    )
+    .bind(tournament)
+    .fetch_all(pool)
+    .await
+    .map_err(DbError::Sqlx)?;
+
+    bracket_state_from_rows(rows)
+}
+
+/// Record the result of a playoff game (replaces any previous result of the game)
+pub async fn insert_playoff_result(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    game_id: GameId,
+    result: &PlayoffResult,
+) -> Result<(), DbError> {
+    let (home_result, away_result) = result.score.regular_time_score();
+    let penalties = result.score.penalty_score();
+    sqlx::query(
+        "INSERT OR REPLACE INTO playoff_results (
+            tournament_id, game_id, home_team, away_team,
+            home_result, away_result, home_penalty, away_penalty
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(tournament)
+    .bind(to_column("game_id", u32::from(game_id))?)
+    .bind(to_column("home_team", result.home.0)?)
+    .bind(to_column("away_team", result.away.0)?)
+    .bind(to_column("home_result", u32::from(home_result))?)
+    .bind(to_column("away_result", u32::from(away_result))?)
+    .bind(
+        penalties
+            .map(|(home, _)| to_column("home_penalty", u32::from(home)))
+            .transpose()?,
+    )
+    .bind(
+        penalties
+            .map(|(_, away)| to_column("away_penalty", u32::from(away)))
+            .transpose()?,
+    )
+    .execute(pool)
+    .await
+    .map_err(DbError::Sqlx)?;
+    Ok(())
+}
+
+/// Clear the result of a playoff game
+pub async fn clear_playoff_result(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    game_id: GameId,
+) -> Result<(), DbError> {
+    sqlx::query("DELETE FROM playoff_results WHERE tournament_id = ? AND game_id = ?")
+        .bind(tournament)
+        .bind(to_column("game_id", u32::from(game_id))?)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    Ok(())
+}
+
+/// Clear all playoff results of a tournament
+pub async fn clear_playoff_results(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+) -> Result<(), DbError> {
+    sqlx::query("DELETE FROM playoff_results WHERE tournament_id = ?")
+        .bind(tournament)
         .execute(pool)
         .await
         .map_err(DbError::Sqlx)?;
     Ok(())
 }
 
-/// Clear all playoff team sources from the database
-pub async fn clear_playoff_team_sources(pool: &SqlitePool) -> Result<(), DbError> {
-    sqlx::query("DELETE FROM playoff_team_sources")
+fn bracket_state_from_rows<T>(rows: Vec<T>) -> Result<BracketState, DbError>
+where
+    (GameId, PlayoffResult): TryFrom<T, Error = DbError>,
+{
+    rows.into_iter()
+        .map(<(GameId, PlayoffResult)>::try_from)
+        .try_fold(BracketState::new(), |state, row| {
+            let (game_id, result) = row?;
+            Ok(state.play_game(game_id, result.home, result.away, result.score))
+        })
+}
+
+/// Get the predicted bracket for a specific player and optional bot
+///
+/// Unlike group predictions, human and bot brackets are never mixed:
+/// `None` only returns the player's own (non-bot) predictions.
+pub async fn get_playoff_preds(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    player_id: PlayerId,
+    bot_name: Option<&str>,
+) -> Result<BracketState, DbError> {
+    let player_id = i32::from(player_id);
+
+    let db_preds = if let Some(bot) = bot_name {
+        sqlx::query_as::<_, PlayoffPred>(
+            "SELECT * FROM playoff_preds WHERE tournament_id = ? AND player_id = ? AND bot_name = ?",
+        )
+        .bind(tournament)
+        .bind(player_id)
+        .bind(bot)
+        .fetch_all(pool)
+        .await
+        .map_err(DbError::Sqlx)?
+    } else {
+        sqlx::query_as::<_, PlayoffPred>(
+            "SELECT * FROM playoff_preds WHERE tournament_id = ? AND player_id = ? AND bot_name IS NULL",
+        )
+        .bind(tournament)
+        .bind(player_id)
+        .fetch_all(pool)
+        .await
+        .map_err(DbError::Sqlx)?
+    };
+
+    bracket_state_from_rows(db_preds)
+}
+
+/// Insert a predicted bracket for a player (replaces the existing one for that player+bot combination)
+///
+/// The old bracket is only replaced if every game of the new one can be stored, in a single
+/// transaction.
+pub async fn insert_playoff_preds(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    player_id: PlayerId,
+    bracket_state: &BracketState,
+    bot_name: Option<&str>,
+) -> Result<(), DbError> {
+    let player_id = i32::from(player_id);
+
+    let mut rows = Vec::new();
+    for (game_id, result) in bracket_state.played_games() {
+        let (home_result, away_result) = result.score.regular_time_score();
+        let penalties = result
+            .score
+            .penalty_score()
+            .map(|(home, away)| {
+                Ok::<_, DbError>((
+                    to_column("home_penalty", u32::from(home))?,
+                    to_column("away_penalty", u32::from(away))?,
+                ))
+            })
+            .transpose()?;
+        rows.push((
+            to_column("game_id", u32::from(*game_id))?,
+            to_column("home_team", result.home.0)?,
+            to_column("away_team", result.away.0)?,
+            to_column("home_result", u32::from(home_result))?,
+            to_column("away_result", u32::from(away_result))?,
+            penalties,
+        ));
+    }
+
+    let mut tx = pool.begin().await.map_err(DbError::Sqlx)?;
+
+    sqlx::query(
+        "DELETE FROM playoff_preds WHERE tournament_id = ? AND player_id = ? AND bot_name IS ?",
+    )
+    .bind(tournament)
+    .bind(player_id)
+    .bind(bot_name)
+    .execute(&mut *tx)
+    .await
+    .map_err(DbError::Sqlx)?;
+
+    for (game_id, home_team, away_team, home_result, away_result, penalties) in rows {
+        sqlx::query(
+            "INSERT INTO playoff_preds (
+                tournament_id, player_id, game_id, home_team, away_team,
+                home_result, away_result, home_penalty, away_penalty, bot_name
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(tournament)
+        .bind(player_id)
+        .bind(game_id)
+        .bind(home_team)
+        .bind(away_team)
+        .bind(home_result)
+        .bind(away_result)
+        .bind(penalties.map(|(home, _)| home))
+        .bind(penalties.map(|(_, away)| away))
+        .bind(bot_name)
+        .execute(&mut *tx)
+        .await
+        .map_err(DbError::Sqlx)?;
+    }
+
+    tx.commit().await.map_err(DbError::Sqlx)?;
+    Ok(())
+}
+
+/// Clear playoff predictions for a specific player and optional bot
+///
+/// With `None`, only the player's own (non-bot) predictions are cleared. Predictions of games which
+/// have kicked off at `now` are kept.
+pub async fn clear_player_playoff_preds(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    player_id: PlayerId,
+    bot_name: Option<&str>,
+    now: Date,
+) -> Result<(), DbError> {
+    let locked = get_locked_games(pool, tournament, now).await?;
+    let mut tx = pool.begin().await.map_err(DbError::Sqlx)?;
+    delete_unlocked_preds(
+        &mut tx,
+        "playoff_preds",
+        tournament,
+        player_id,
+        bot_name,
+        false,
+        &locked,
+    )
+    .await?;
+    tx.commit().await.map_err(DbError::Sqlx)?;
+    Ok(())
+}
+
+/// Clear all playoff predictions of a tournament (admin only)
+pub async fn clear_playoff_preds(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+) -> Result<(), DbError> {
+    sqlx::query("DELETE FROM playoff_preds WHERE tournament_id = ?")
+        .bind(tournament)
         .execute(pool)
         .await
         .map_err(DbError::Sqlx)?;
@@ -1,12 +1,20 @@
-//! Database connection pool and migrations
+//! Database connection pool
 
 use crate::DbError;
+use crate::migrations::run_migrations;
 use dotenv::dotenv;
 use sqlx::SqlitePool;
 use std::env;
 
-/// Create a SQLite connection pool and run migrations
+/// Create a SQLite connection pool and apply pending migrations
 pub async fn create_pool() -> Result<SqlitePool, DbError> {
+    let pool = connect_pool().await?;
+    run_migrations(&pool).await?;
+    Ok(pool)
+}
+
+/// Create a SQLite connection pool without applying migrations, e.g. to inspect them
+pub async fn connect_pool() -> Result<SqlitePool, DbError> {
     dotenv().ok();
     let database_url = env::var("DATABASE_URL").map_err(|_| DbError::DbUrlMissing)?;
 
@@ -24,21 +32,5 @@ pub async fn create_pool() -> Result<SqlitePool, DbError> {
         .await
         .map_err(DbError::Sqlx)?;
 
-    // Run migrations (idempotent due to IF NOT EXISTS)
-    sqlx::query(include_str!("../sqlx_migrations/001_create_tables.sql"))
-        .execute(&pool)
-        .await
-        .map_err(DbError::Sqlx)?;
-
-    sqlx::query(include_str!("../sqlx_migrations/002_playoff_tables.sql"))
-        .execute(&pool)
-        .await
-        .map_err(DbError::Sqlx)?;
-
-    sqlx::query(include_str!("../sqlx_migrations/003_auth_tables.sql"))
-        .execute(&pool)
-        .await
-        .map_err(DbError::Sqlx)?;
-
     Ok(pool)
 }
@@ -0,0 +1,225 @@
+use crate::DbError;
+use crate::decode::{RowRef, to_column};
+use crate::games::{get_group_game_ids, get_locked_games};
+use crate::models::TournamentId;
+use sqlx::{SqliteConnection, SqlitePool};
+use std::collections::HashSet;
+use wwc_core::Date;
+use wwc_core::game::GameId;
+use wwc_core::leaderboard::Competitor;
+use wwc_core::player::PlayerId;
+use wwc_core::pred_score::proba::{CompetitorProbPredictions, ProbForecast, ProbPrediction};
+use wwc_core::validation::PredReport;
+
+/// Decode a probabilistic prediction row
+pub(crate) fn decode_pred(
+    id: i32,
+    game_id: i32,
+    forecast: &str,
+) -> Result<ProbPrediction, DbError> {
+    let row = RowRef::new("proba_preds", id);
+    let forecast: ProbForecast = row.json("forecast", forecast)?;
+    Ok(ProbPrediction(row.game_id("game_id", game_id)?, forecast))
+}
+
+/// Get the probabilistic predictions of a specific bot
+pub async fn get_proba_preds(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    player_id: PlayerId,
+    bot_name: &str,
+) -> Result<Vec<ProbPrediction>, DbError> {
+    let rows = sqlx::query_as::<_, (i32, i32, String)>(
+        "SELECT id, game_id, forecast FROM proba_preds
+         WHERE tournament_id = ? AND player_id = ? AND bot_name = ?",
+    )
+    .bind(tournament)
+    .bind(i32::from(player_id))
+    .bind(bot_name)
+    .fetch_all(pool)
+    .await
+    .map_err(DbError::Sqlx)?;
+
+    rows.iter()
+        .map(|(id, game_id, forecast)| decode_pred(*id, *game_id, forecast))
+        .collect()
+}
+
+/// Get the probabilistic predictions of all bots in a tournament
+pub async fn get_all_proba_preds(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+) -> Result<Vec<CompetitorProbPredictions>, DbError> {
+    let rows = sqlx::query_as::<_, (i32, i32, String, i32, String)>(
+        "SELECT id, player_id, bot_name, game_id, forecast FROM proba_preds WHERE tournament_id = ?
+         ORDER BY player_id, bot_name, game_id",
+    )
+    .bind(tournament)
+    .fetch_all(pool)
+    .await
+    .map_err(DbError::Sqlx)?;
+
+    let mut all_preds: Vec<CompetitorProbPredictions> = Vec::new();
+    for (id, player_id, bot_name, game_id, forecast) in rows {
+        let pred = decode_pred(id, game_id, &forecast)?;
+        let competitor = Competitor::bot(PlayerId::from(player_id), bot_name);
+        match all_preds.last_mut() {
+            Some(last) if last.competitor == competitor => last.preds.push(pred),
+            _ => all_preds.push(CompetitorProbPredictions {
+                competitor,
+                preds: vec![pred],
+            }),
+        }
+    }
+    Ok(all_preds)
+}
+
+fn to_rows(preds: &[ProbPrediction]) -> Result<Vec<(i32, String)>, DbError> {
+    preds
+        .iter()
+        .map(|ProbPrediction(game_id, forecast)| {
+            let forecast = serde_json::to_string(forecast)
+                .map_err(|e| DbError::Generic(format!("Could not serialize forecast: {}", e)))?;
+            Ok((to_column("game_id", u32::from(*game_id))?, forecast))
+        })
+        .collect()
+}
+
+/// Delete the predictions of a bot, except those of the `kept` games
+async fn delete_proba_preds(
+    conn: &mut SqliteConnection,
+    tournament: TournamentId,
+    player_id: PlayerId,
+    bot_name: &str,
+    kept: &HashSet<GameId>,
+) -> Result<(), DbError> {
+    let stored: Vec<i32> = sqlx::query_scalar(
+        "SELECT game_id FROM proba_preds WHERE tournament_id = ? AND player_id = ? AND bot_name = ?",
+    )
+    .bind(tournament)
+    .bind(i32::from(player_id))
+    .bind(bot_name)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(DbError::Sqlx)?;
+    for game_id in stored {
+        let row = RowRef::new("proba_preds", format!("game {}", game_id));
+        if kept.contains(&row.game_id("game_id", game_id)?) {
+            continue;
+        }
+        sqlx::query(
+            "DELETE FROM proba_preds
+             WHERE tournament_id = ? AND player_id = ? AND bot_name = ? AND game_id = ?",
+        )
+        .bind(tournament)
+        .bind(i32::from(player_id))
+        .bind(bot_name)
+        .bind(game_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(DbError::Sqlx)?;
+    }
+    Ok(())
+}
+
+/// Upsert, the conflict target is the unique index of migration 017
+async fn write_proba_preds(
+    conn: &mut SqliteConnection,
+    tournament: TournamentId,
+    player_id: PlayerId,
+    bot_name: &str,
+    rows: Vec<(i32, String)>,
+) -> Result<(), DbError> {
+    for (game_id, forecast) in rows {
+        sqlx::query(
+            "INSERT INTO proba_preds (tournament_id, player_id, bot_name, game_id, forecast)
+             VALUES (?, ?, ?, ?, ?)
+             ON CONFLICT (tournament_id, player_id, game_id, IFNULL(bot_name, ''))
+             DO UPDATE SET forecast = excluded.forecast",
+        )
+        .bind(tournament)
+        .bind(i32::from(player_id))
+        .bind(bot_name)
+        .bind(game_id)
+        .bind(forecast)
+        .execute(&mut *conn)
+        .await
+        .map_err(DbError::Sqlx)?;
+    }
+    Ok(())
+}
+
+/// Insert the probabilistic predictions of a bot (replaces its previous predictions)
+///
+/// The previous predictions are only replaced if all new ones can be stored, in a single
+/// transaction. Of several predictions of the same game, the last one is stored.
+pub async fn insert_proba_preds(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    player_id: PlayerId,
+    bot_name: &str,
+    preds: &[ProbPrediction],
+) -> Result<(), DbError> {
+    let rows = to_rows(preds)?;
+
+    let mut tx = pool.begin().await.map_err(DbError::Sqlx)?;
+    delete_proba_preds(&mut tx, tournament, player_id, bot_name, &HashSet::new()).await?;
+    write_proba_preds(&mut tx, tournament, player_id, bot_name, rows).await?;
+    tx.commit().await.map_err(DbError::Sqlx)?;
+    Ok(())
+}
+
+/// Validate the probabilistic predictions of a bot against a tournament and store the accepted
+/// ones
+///
+/// Replaces the stored predictions of the bot, except for games which have kicked off at `now`:
+/// their stored predictions are kept as they are, so that forecasts can't be made or changed once
+/// the result is known.
+pub async fn insert_validated_proba_preds(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    player_id: PlayerId,
+    bot_name: &str,
+    preds: &[ProbPrediction],
+    now: Date,
+) -> Result<PredReport<ProbPrediction>, DbError> {
+    let games = get_group_game_ids(pool, tournament).await?;
+    let locked = get_locked_games(pool, tournament, now).await?;
+    let report = PredReport::new(preds, &games, &locked);
+    let accepted: Vec<ProbPrediction> = report.accepted().cloned().collect();
+    let rows = to_rows(&accepted)?;
+
+    let mut tx = pool.begin().await.map_err(DbError::Sqlx)?;
+    delete_proba_preds(&mut tx, tournament, player_id, bot_name, &locked).await?;
+    write_proba_preds(&mut tx, tournament, player_id, bot_name, rows).await?;
+    tx.commit().await.map_err(DbError::Sqlx)?;
+
+    Ok(report)
+}
+
+/// Clear the probabilistic predictions of a specific bot
+///
+/// Predictions of games which have kicked off at `now` are kept, they are scored.
+pub async fn clear_bot_proba_preds(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    player_id: PlayerId,
+    bot_name: &str,
+    now: Date,
+) -> Result<(), DbError> {
+    let locked = get_locked_games(pool, tournament, now).await?;
+    let mut tx = pool.begin().await.map_err(DbError::Sqlx)?;
+    delete_proba_preds(&mut tx, tournament, player_id, bot_name, &locked).await?;
+    tx.commit().await.map_err(DbError::Sqlx)?;
+    Ok(())
+}
+
+/// Clear all probabilistic predictions of a tournament
+pub async fn clear_proba_preds(pool: &SqlitePool, tournament: TournamentId) -> Result<(), DbError> {
+    sqlx::query("DELETE FROM proba_preds WHERE tournament_id = ?")
+        .bind(tournament)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    Ok(())
+}
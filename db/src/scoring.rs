@@ -0,0 +1,54 @@
+use crate::DbError;
+use crate::decode::RowRef;
+use crate::models::TournamentId;
+use sqlx::SqlitePool;
+use wwc_core::pred_score::scheme::ScoringConfig;
+
+/// Get the scoring config of a tournament, `None` if it has not been set
+pub async fn get_scoring_config(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+) -> Result<Option<ScoringConfig>, DbError> {
+    let config = sqlx::query_scalar::<_, String>(
+        "SELECT config FROM scoring_configs WHERE tournament_id = ?",
+    )
+    .bind(tournament)
+    .fetch_optional(pool)
+    .await
+    .map_err(DbError::Sqlx)?;
+
+    config
+        .map(|config| RowRef::new("scoring_configs", tournament).json("config", &config))
+        .transpose()
+}
+
+/// Insert the scoring config of a tournament (replaces any existing config)
+pub async fn insert_scoring_config(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    config: &ScoringConfig,
+) -> Result<(), DbError> {
+    let config = serde_json::to_string(config)
+        .map_err(|e| DbError::Generic(format!("Could not serialize scoring config: {}", e)))?;
+
+    sqlx::query("INSERT OR REPLACE INTO scoring_configs (tournament_id, config) VALUES (?, ?)")
+        .bind(tournament)
+        .bind(config)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    Ok(())
+}
+
+/// Clear the scoring config of a tournament
+pub async fn clear_scoring_config(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+) -> Result<(), DbError> {
+    sqlx::query("DELETE FROM scoring_configs WHERE tournament_id = ?")
+        .bind(tournament)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    Ok(())
+}
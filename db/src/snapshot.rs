@@ -0,0 +1,265 @@
+//! Database snapshots
+//!
+//! A snapshot is a JSON document with the rows of every table: tournaments, teams, games and
+//! results, group maps, playoff sources and results, players, users (with password hashes),
+//! bots, predictions and everything else in the schema. Rows are stored column by column with
+//! their SQLite value, so that importing an exported snapshot restores the database exactly.
+//!
+//! Snapshots record the schema version they were exported from and can only be imported into a
+//! database at the same version. Foreign keys are checked once all rows are inserted, a snapshot
+//! with dangling references is rejected without importing anything.
+
+use crate::DbError;
+use crate::migrations::MIGRATIONS;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Column, Row, SqliteConnection, SqlitePool, TypeInfo, ValueRef};
+use std::collections::BTreeMap;
+
+/// Version of the snapshot format, bumped on incompatible changes to [`Snapshot`]
+pub const SNAPSHOT_FORMAT: u32 = 1;
+
+/// Rows of one table, as column name to value
+pub type SnapshotRow = Map<String, Value>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub format: u32,
+    /// Latest migration applied to the exported database
+    pub schema_version: i64,
+    /// UTC, RFC 3339
+    pub exported_at: String,
+    /// Rows of every table, by table name
+    pub tables: BTreeMap<String, Vec<SnapshotRow>>,
+}
+
+impl Snapshot {
+    /// Number of rows of every table
+    pub fn row_counts(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.tables
+            .iter()
+            .map(|(table, rows)| (table.as_str(), rows.len()))
+    }
+}
+
+/// Latest schema version known to this crate
+fn latest_schema_version() -> i64 {
+    MIGRATIONS
+        .iter()
+        .map(|migration| migration.version)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Names of all data tables, i.e. all but the migration bookkeeping and SQLite's own tables
+async fn table_names(conn: &mut SqliteConnection) -> Result<Vec<String>, DbError> {
+    sqlx::query_scalar::<_, String>(
+        "SELECT name FROM sqlite_master
+         WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND name != 'schema_version'
+         ORDER BY name",
+    )
+    .fetch_all(conn)
+    .await
+    .map_err(DbError::Sqlx)
+}
+
+async fn column_names(conn: &mut SqliteConnection, table: &str) -> Result<Vec<String>, DbError> {
+    sqlx::query_scalar::<_, String>("SELECT name FROM pragma_table_info(?) ORDER BY cid")
+        .bind(table)
+        .fetch_all(conn)
+        .await
+        .map_err(DbError::Sqlx)
+}
+
+/// Column values of a row by their SQLite storage class
+fn row_to_json(table: &str, row: &SqliteRow) -> Result<SnapshotRow, DbError> {
+    row.columns()
+        .iter()
+        .map(|column| {
+            let i = column.ordinal();
+            let raw = row.try_get_raw(i).map_err(DbError::Sqlx)?;
+            let value = if raw.is_null() {
+                Value::Null
+            } else {
+                match raw.type_info().name() {
+                    "INTEGER" => Value::from(row.try_get_unchecked::<i64, _>(i)?),
+                    "REAL" => Number::from_f64(row.try_get_unchecked::<f64, _>(i)?)
+                        .map(Value::Number)
+                        .ok_or_else(|| {
+                            DbError::Snapshot(format!("{}.{} is not finite", table, column.name()))
+                        })?,
+                    "TEXT" => Value::from(row.try_get_unchecked::<String, _>(i)?),
+                    other => {
+                        return Err(DbError::Snapshot(format!(
+                            "{}.{} has unsupported type {}",
+                            table,
+                            column.name(),
+                            other
+                        )));
+                    }
+                }
+            };
+            Ok((column.name().to_string(), value))
+        })
+        .collect()
+}
+
+/// Export all rows of the database
+pub async fn export_snapshot(pool: &SqlitePool) -> Result<Snapshot, DbError> {
+    // A single transaction, so that the snapshot is consistent
+    let mut tx = pool.begin().await.map_err(DbError::Sqlx)?;
+    let schema_version =
+        sqlx::query_scalar::<_, i64>("SELECT IFNULL(MAX(version), 0) FROM schema_version")
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(DbError::Sqlx)?;
+    let exported_at =
+        sqlx::query_scalar::<_, String>("SELECT strftime('%Y-%m-%dT%H:%M:%SZ', 'now')")
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(DbError::Sqlx)?;
+
+    let mut tables = BTreeMap::new();
+    for table in table_names(&mut tx).await? {
+        // Ordered by primary key (rowid), so that exports of the same data are identical
+        let rows = sqlx::query(&format!("SELECT * FROM \"{}\" ORDER BY rowid", table))
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(DbError::Sqlx)?
+            .iter()
+            .map(|row| row_to_json(&table, row))
+            .collect::<Result<Vec<_>, _>>()?;
+        tables.insert(table, rows);
+    }
+    tx.commit().await.map_err(DbError::Sqlx)?;
+
+    Ok(Snapshot {
+        format: SNAPSHOT_FORMAT,
+        schema_version,
+        exported_at,
+        tables,
+    })
+}
+
+/// Import all rows of a snapshot
+///
+/// The database must be empty, unless `replace` is set, which deletes all existing rows first.
+/// Nothing is imported if the snapshot is invalid: of another format or schema version, with
+/// tables or columns the schema doesn't have, or with rows which break a foreign key.
+pub async fn import_snapshot(
+    pool: &SqlitePool,
+    snapshot: &Snapshot,
+    replace: bool,
+) -> Result<(), DbError> {
+    if snapshot.format != SNAPSHOT_FORMAT {
+        return Err(DbError::Snapshot(format!(
+            "unsupported snapshot format {}, expected {}",
+            snapshot.format, SNAPSHOT_FORMAT
+        )));
+    }
+    if snapshot.schema_version != latest_schema_version() {
+        return Err(DbError::Snapshot(format!(
+            "snapshot of schema version {}, the database is at version {}",
+            snapshot.schema_version,
+            latest_schema_version()
+        )));
+    }
+
+    let mut tx = pool.begin().await.map_err(DbError::Sqlx)?;
+    // Rows reference each other in any order, check the foreign keys after all are inserted
+    sqlx::query("PRAGMA defer_foreign_keys = ON")
+        .execute(&mut *tx)
+        .await
+        .map_err(DbError::Sqlx)?;
+
+    let tables = table_names(&mut tx).await?;
+    if let Some(unknown) = snapshot.tables.keys().find(|table| !tables.contains(table)) {
+        return Err(DbError::Snapshot(format!("unknown table '{}'", unknown)));
+    }
+
+    for table in &tables {
+        if replace {
+            sqlx::query(&format!("DELETE FROM \"{}\"", table))
+                .execute(&mut *tx)
+                .await
+                .map_err(DbError::Sqlx)?;
+        } else {
+            let count =
+                sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) FROM \"{}\"", table))
+                    .fetch_one(&mut *tx)
+                    .await
+                    .map_err(DbError::Sqlx)?;
+            if count > 0 {
+                return Err(DbError::Snapshot(format!(
+                    "table '{}' is not empty, import with replace to overwrite the database",
+                    table
+                )));
+            }
+        }
+    }
+
+    for (table, rows) in &snapshot.tables {
+        let columns = column_names(&mut tx, table).await?;
+        for row in rows {
+            if let Some(unknown) = row.keys().find(|column| !columns.contains(column)) {
+                return Err(DbError::Snapshot(format!(
+                    "unknown column '{}.{}'",
+                    table, unknown
+                )));
+            }
+            let sql = format!(
+                "INSERT INTO \"{}\" ({}) VALUES ({})",
+                table,
+                row.keys()
+                    .map(|column| format!("\"{}\"", column))
+                    .join(", "),
+                row.keys().map(|_| "?").join(", ")
+            );
+            let query = row.values().try_fold(sqlx::query(&sql), |query, value| {
+                Ok(match value {
+                    Value::Null => query.bind(None::<i64>),
+                    Value::Bool(b) => query.bind(*b),
+                    Value::Number(n) => match n.as_i64() {
+                        Some(i) => query.bind(i),
+                        None => query.bind(n.as_f64()),
+                    },
+                    Value::String(s) => query.bind(s.as_str()),
+                    Value::Array(_) | Value::Object(_) => {
+                        return Err(DbError::Snapshot(format!(
+                            "{} has a non-scalar value: {}",
+                            table, value
+                        )));
+                    }
+                })
+            })?;
+            query.execute(&mut *tx).await.map_err(DbError::Sqlx)?;
+        }
+    }
+
+    let violations = sqlx::query_as::<_, (String, Option<i64>, String)>(
+        "SELECT \"table\", rowid, parent FROM pragma_foreign_key_check",
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(DbError::Sqlx)?;
+    if !violations.is_empty() {
+        let examples = violations
+            .iter()
+            .take(5)
+            .map(|(table, rowid, parent)| match rowid {
+                Some(rowid) => format!("{} row {} -> {}", table, rowid, parent),
+                None => format!("{} -> {}", table, parent),
+            })
+            .join(", ");
+        return Err(DbError::Snapshot(format!(
+            "{} rows with dangling references: {}",
+            violations.len(),
+            examples
+        )));
+    }
+
+    tx.commit().await.map_err(DbError::Sqlx)?;
+    Ok(())
+}
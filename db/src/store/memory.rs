@@ -0,0 +1,1206 @@
+use super::Store;
+use crate::DbError;
+use crate::models::{
+    Bot, League, LeagueId, LeagueMember, LeagueRole, Tournament, TournamentId, User,
+};
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use wwc_core::Date;
+use wwc_core::bonus::{BonusAnswer, BonusPrediction, BonusQuestion, BonusQuestionId};
+use wwc_core::game::GameId;
+use wwc_core::group::GroupId;
+use wwc_core::group::game::{PlayedGroupGame, UnplayedGroupGame};
+use wwc_core::group::order::RuleSet;
+use wwc_core::leaderboard::{Competitor, CompetitorPredictions};
+use wwc_core::player::{
+    OutcomePrediction, PlayerId, PlayerPredictions, PredSubmission, Prediction,
+};
+use wwc_core::playoff::{BracketState, PlayoffResult, TeamSource};
+use wwc_core::pred_score::proba::{CompetitorProbPredictions, ProbPrediction};
+use wwc_core::pred_score::scheme::ScoringConfig;
+use wwc_core::validation::PredReport;
+
+fn competitor(player_id: PlayerId, bot_name: Option<&str>) -> Competitor {
+    Competitor {
+        player_id,
+        bot_name: bot_name.map(str::to_string),
+    }
+}
+
+/// Current time, in the format of SQLite's `CURRENT_TIMESTAMP`
+fn timestamp() -> String {
+    chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// All data of a single tournament
+#[derive(Default)]
+struct TournamentData {
+    config: Option<ScoringConfig>,
+    group_rules: RuleSet,
+    teams: Vec<wwc_core::Team>,
+    played_games: Vec<PlayedGroupGame>,
+    unplayed_games: Vec<UnplayedGroupGame>,
+    group_game_maps: Vec<(GameId, GroupId)>,
+    kickoffs: HashMap<GameId, Date>,
+    playoff_team_sources: Vec<(GameId, (TeamSource, TeamSource))>,
+    playoff_results: BracketState,
+    bonus_questions: BTreeMap<BonusQuestionId, BonusQuestion>,
+    preds: BTreeMap<Competitor, BTreeMap<GameId, Prediction>>,
+    pred_history: BTreeMap<Competitor, Vec<PredSubmission>>,
+    outcome_preds: BTreeMap<Competitor, BTreeMap<GameId, OutcomePrediction>>,
+    playoff_preds: BTreeMap<Competitor, BracketState>,
+    proba_preds: BTreeMap<Competitor, Vec<ProbPrediction>>,
+    bonus_preds: BTreeMap<Competitor, BTreeMap<BonusQuestionId, BonusPrediction>>,
+    jokers: BTreeMap<Competitor, BTreeSet<GameId>>,
+}
+
+impl TournamentData {
+    fn group_game_ids(&self) -> HashSet<GameId> {
+        self.played_games
+            .iter()
+            .map(|game| game.id)
+            .chain(self.unplayed_games.iter().map(|game| game.id))
+            .collect()
+    }
+
+    /// Games which are played or whose kickoff time has passed at `now`
+    fn locked_games(&self, now: Date) -> HashSet<GameId> {
+        self.played_games
+            .iter()
+            .map(|game| game.id)
+            .chain(
+                self.playoff_results
+                    .played_games()
+                    .map(|(game_id, _)| *game_id),
+            )
+            .chain(
+                self.kickoffs
+                    .iter()
+                    .filter(|(_, kickoff)| **kickoff <= now)
+                    .map(|(game_id, _)| *game_id),
+            )
+            .collect()
+    }
+}
+
+struct StoredLeague {
+    league: League,
+    config: Option<ScoringConfig>,
+    /// In order of joining
+    members: Vec<LeagueMember>,
+}
+
+#[derive(Default)]
+struct Data {
+    tournaments: Vec<Tournament>,
+    tournament_data: HashMap<TournamentId, TournamentData>,
+    players: BTreeMap<PlayerId, String>,
+    users: Vec<User>,
+    bots: Vec<Bot>,
+    leagues: Vec<StoredLeague>,
+    next_league_id: i32,
+}
+
+impl Data {
+    fn league(&self, league: LeagueId) -> Option<&StoredLeague> {
+        self.leagues
+            .iter()
+            .find(|stored| stored.league.id == league)
+    }
+
+    fn league_mut(&mut self, league: LeagueId) -> Result<&mut StoredLeague, DbError> {
+        self.leagues
+            .iter_mut()
+            .find(|stored| stored.league.id == league)
+            .ok_or_else(|| DbError::Generic(format!("No league {}", league)))
+    }
+
+    /// Fresh random invite code, unique among all leagues
+    fn new_invite_code(&self) -> String {
+        loop {
+            let code = format!(
+                "{:012x}",
+                RandomState::new().build_hasher().finish() & 0xffff_ffff_ffff
+            );
+            if !self
+                .leagues
+                .iter()
+                .any(|stored| stored.league.invite_code == code)
+            {
+                return code;
+            }
+        }
+    }
+}
+
+/// Store in memory, for tests without a database
+///
+/// Clones share the same data. Starts out empty, tournaments, teams, games and users are added
+/// with the methods of the same name as the crate's functions which add them to the database.
+#[derive(Clone, Default)]
+pub struct MemoryStore {
+    data: Arc<RwLock<Data>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        MemoryStore::default()
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Data> {
+        self.data.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Data> {
+        self.data.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Read the data of a tournament, which is empty if there is no such tournament
+    fn with<R>(&self, tournament: TournamentId, f: impl FnOnce(&TournamentData) -> R) -> R {
+        match self.read().tournament_data.get(&tournament) {
+            Some(data) => f(data),
+            None => f(&TournamentData::default()),
+        }
+    }
+
+    /// Change the data of a tournament, which must exist
+    fn with_mut<R>(
+        &self,
+        tournament: TournamentId,
+        f: impl FnOnce(&mut TournamentData) -> R,
+    ) -> Result<R, DbError> {
+        self.write()
+            .tournament_data
+            .get_mut(&tournament)
+            .map(f)
+            .ok_or_else(|| DbError::Generic(format!("No tournament {}", tournament)))
+    }
+
+    /// Create a tournament, or get the id of the existing tournament with the same name
+    pub fn insert_tournament(&self, name: &str) -> TournamentId {
+        let mut data = self.write();
+        if let Some(tournament) = data.tournaments.iter().find(|t| t.name == name) {
+            return tournament.id;
+        }
+        let id = TournamentId(data.tournaments.len() as i32 + 1);
+        data.tournaments.push(Tournament {
+            id,
+            name: name.to_string(),
+        });
+        data.tournament_data.insert(id, TournamentData::default());
+        id
+    }
+
+    /// Set the group rules of a tournament
+    pub fn set_group_rules(&self, tournament: TournamentId, rules: RuleSet) -> Result<(), DbError> {
+        self.with_mut(tournament, |data| data.group_rules = rules)
+    }
+
+    /// Insert the scoring config of a tournament (replaces any existing config)
+    pub fn insert_scoring_config(
+        &self,
+        tournament: TournamentId,
+        config: &ScoringConfig,
+    ) -> Result<(), DbError> {
+        self.with_mut(tournament, |data| data.config = Some(config.clone()))
+    }
+
+    /// Insert teams into a tournament
+    pub fn insert_teams(
+        &self,
+        tournament: TournamentId,
+        teams: &[wwc_core::Team],
+    ) -> Result<(), DbError> {
+        self.with_mut(tournament, |data| data.teams.extend_from_slice(teams))
+    }
+
+    /// Insert unplayed games into a tournament
+    pub fn insert_unplayed_games(
+        &self,
+        tournament: TournamentId,
+        games: &[UnplayedGroupGame],
+    ) -> Result<(), DbError> {
+        self.with_mut(tournament, |data| {
+            data.unplayed_games.extend_from_slice(games)
+        })
+    }
+
+    /// Insert played games into a tournament, with their cards
+    pub fn insert_played_games(
+        &self,
+        tournament: TournamentId,
+        games: &[PlayedGroupGame],
+    ) -> Result<(), DbError> {
+        self.with_mut(tournament, |data| {
+            data.played_games.extend_from_slice(games)
+        })
+    }
+
+    /// Insert group game mappings into a tournament
+    pub fn insert_group_game_mappings(
+        &self,
+        tournament: TournamentId,
+        group_mappings: &[(GroupId, GameId)],
+    ) -> Result<(), DbError> {
+        self.with_mut(tournament, |data| {
+            data.group_game_maps.extend(
+                group_mappings
+                    .iter()
+                    .map(|(group_id, game_id)| (*game_id, *group_id)),
+            )
+        })
+    }
+
+    /// Insert game kickoff times (replaces existing kickoffs of the same games)
+    pub fn insert_game_kickoffs(
+        &self,
+        tournament: TournamentId,
+        kickoffs: &[(GameId, Date)],
+    ) -> Result<(), DbError> {
+        self.with_mut(tournament, |data| {
+            data.kickoffs.extend(kickoffs.iter().copied())
+        })
+    }
+
+    /// Insert playoff team sources into a tournament
+    pub fn insert_playoff_team_sources(
+        &self,
+        tournament: TournamentId,
+        team_sources: &[(GameId, (TeamSource, TeamSource))],
+    ) -> Result<(), DbError> {
+        self.with_mut(tournament, |data| {
+            data.playoff_team_sources.extend_from_slice(team_sources)
+        })
+    }
+
+    /// Create a user, and the player with the same id if it doesn't exist
+    pub fn create_user(
+        &self,
+        player_id: i32,
+        username: &str,
+        password_hash: &str,
+        display_name: &str,
+    ) -> Result<(), DbError> {
+        let mut data = self.write();
+        if data
+            .users
+            .iter()
+            .any(|user| user.id == player_id || user.username == username)
+        {
+            return Err(DbError::Generic(format!(
+                "User {} '{}' already exists",
+                player_id, username
+            )));
+        }
+        data.players
+            .entry(PlayerId::from(player_id))
+            .or_insert_with(|| format!("Player {}", player_id));
+        data.users.push(User {
+            id: player_id,
+            username: username.to_string(),
+            password_hash: password_hash.to_string(),
+            display_name: display_name.to_string(),
+            created_at: timestamp(),
+        });
+        Ok(())
+    }
+
+    /// Create a bot for a user, returns the id of the bot
+    pub fn create_bot(
+        &self,
+        user_id: i32,
+        bot_name: &str,
+        bot_display_name: &str,
+    ) -> Result<i64, DbError> {
+        let mut data = self.write();
+        if !data.users.iter().any(|user| user.id == user_id) {
+            return Err(DbError::Generic(format!("No user {}", user_id)));
+        }
+        if data
+            .bots
+            .iter()
+            .any(|bot| bot.user_id == user_id && bot.bot_name == bot_name)
+        {
+            return Err(DbError::Generic(format!(
+                "User {} already has a bot '{}'",
+                user_id, bot_name
+            )));
+        }
+        let id = data.bots.len() as i32 + 1;
+        data.bots.push(Bot {
+            id,
+            user_id,
+            bot_name: bot_name.to_string(),
+            bot_display_name: bot_display_name.to_string(),
+            created_at: timestamp(),
+        });
+        Ok(i64::from(id))
+    }
+}
+
+impl Store for MemoryStore {
+    async fn get_tournaments(&self) -> Result<Vec<Tournament>, DbError> {
+        Ok(self.read().tournaments.clone())
+    }
+
+    async fn get_tournament_id(&self, name: &str) -> Result<Option<TournamentId>, DbError> {
+        Ok(self
+            .read()
+            .tournaments
+            .iter()
+            .find(|tournament| tournament.name == name)
+            .map(|tournament| tournament.id))
+    }
+
+    async fn get_scoring_config(
+        &self,
+        tournament: TournamentId,
+    ) -> Result<Option<ScoringConfig>, DbError> {
+        Ok(self.with(tournament, |data| data.config.clone()))
+    }
+
+    async fn get_group_rules(&self, tournament: TournamentId) -> Result<RuleSet, DbError> {
+        Ok(self.with(tournament, |data| data.group_rules))
+    }
+
+    async fn get_teams(&self, tournament: TournamentId) -> Result<Vec<wwc_core::Team>, DbError> {
+        Ok(self.with(tournament, |data| data.teams.clone()))
+    }
+
+    async fn get_group_games(
+        &self,
+        tournament: TournamentId,
+    ) -> Result<(Vec<PlayedGroupGame>, Vec<UnplayedGroupGame>), DbError> {
+        Ok(self.with(tournament, |data| {
+            (data.played_games.clone(), data.unplayed_games.clone())
+        }))
+    }
+
+    async fn get_group_game_maps(
+        &self,
+        tournament: TournamentId,
+    ) -> Result<Vec<(GameId, GroupId)>, DbError> {
+        Ok(self.with(tournament, |data| data.group_game_maps.clone()))
+    }
+
+    async fn get_locked_games(
+        &self,
+        tournament: TournamentId,
+        now: Date,
+    ) -> Result<HashSet<GameId>, DbError> {
+        Ok(self.with(tournament, |data| data.locked_games(now)))
+    }
+
+    async fn get_game_kickoffs(
+        &self,
+        tournament: TournamentId,
+    ) -> Result<Vec<(GameId, Date)>, DbError> {
+        // Ordered by game id, like the SQLite store
+        Ok(self.with(tournament, |data| {
+            let mut kickoffs: Vec<(GameId, Date)> = data
+                .kickoffs
+                .iter()
+                .map(|(game_id, kickoff)| (*game_id, *kickoff))
+                .collect();
+            kickoffs.sort_by_key(|(game_id, _)| *game_id);
+            kickoffs
+        }))
+    }
+
+    async fn insert_group_game_result(
+        &self,
+        tournament: TournamentId,
+        game: &PlayedGroupGame,
+    ) -> Result<(), DbError> {
+        self.with_mut(tournament, |data| {
+            if !data.group_game_ids().contains(&game.id) {
+                return Err(DbError::Generic(format!("No group game {}", game.id)));
+            }
+            data.played_games.retain(|played| played.id != game.id);
+            data.unplayed_games
+                .retain(|unplayed| unplayed.id != game.id);
+            data.played_games.push(*game);
+            Ok(())
+        })?
+    }
+
+    async fn clear_group_game_result(
+        &self,
+        tournament: TournamentId,
+        game_id: GameId,
+    ) -> Result<(), DbError> {
+        self.with_mut(tournament, |data| {
+            if !data.group_game_ids().contains(&game_id) {
+                return Err(DbError::Generic(format!("No group game {}", game_id)));
+            }
+            if let Some(index) = data.played_games.iter().position(|game| game.id == game_id) {
+                let game = data.played_games.remove(index);
+                data.unplayed_games.push(game.unplay());
+            }
+            Ok(())
+        })?
+    }
+
+    async fn get_playoff_team_sources(
+        &self,
+        tournament: TournamentId,
+    ) -> Result<Vec<(GameId, (TeamSource, TeamSource))>, DbError> {
+        Ok(self.with(tournament, |data| data.playoff_team_sources.clone()))
+    }
+
+    async fn get_playoff_results(&self, tournament: TournamentId) -> Result<BracketState, DbError> {
+        Ok(self.with(tournament, |data| data.playoff_results.clone()))
+    }
+
+    async fn insert_playoff_result(
+        &self,
+        tournament: TournamentId,
+        game_id: GameId,
+        result: &PlayoffResult,
+    ) -> Result<(), DbError> {
+        self.with_mut(tournament, |data| {
+            data.playoff_results =
+                data.playoff_results
+                    .play_game(game_id, result.home, result.away, result.score);
+        })
+    }
+
+    async fn clear_playoff_result(
+        &self,
+        tournament: TournamentId,
+        game_id: GameId,
+    ) -> Result<(), DbError> {
+        self.with_mut(tournament, |data| {
+            data.playoff_results = data.playoff_results.unplay_game(game_id);
+        })
+    }
+
+    async fn get_playoff_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+    ) -> Result<BracketState, DbError> {
+        let key = competitor(player_id, bot_name);
+        Ok(self.with(tournament, |data| {
+            data.playoff_preds.get(&key).cloned().unwrap_or_default()
+        }))
+    }
+
+    async fn insert_playoff_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bracket_state: &BracketState,
+        bot_name: Option<&str>,
+    ) -> Result<(), DbError> {
+        let key = competitor(player_id, bot_name);
+        self.with_mut(tournament, |data| {
+            data.playoff_preds.insert(key, bracket_state.clone());
+        })
+    }
+
+    async fn clear_player_playoff_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+        now: Date,
+    ) -> Result<(), DbError> {
+        let key = competitor(player_id, bot_name);
+        self.with_mut(tournament, |data| {
+            let locked = data.locked_games(now);
+            if let Some(state) = data.playoff_preds.get_mut(&key) {
+                let unlocked: Vec<GameId> = state
+                    .played_games()
+                    .map(|(game_id, _)| *game_id)
+                    .filter(|game_id| !locked.contains(game_id))
+                    .collect();
+                *state = unlocked
+                    .into_iter()
+                    .fold(state.clone(), |state, game_id| state.unplay_game(game_id));
+            }
+        })
+    }
+
+    async fn clear_playoff_preds(&self, tournament: TournamentId) -> Result<(), DbError> {
+        self.with_mut(tournament, |data| data.playoff_preds.clear())
+    }
+
+    async fn get_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+    ) -> Result<Vec<Prediction>, DbError> {
+        // Without a bot, all predictions of the player, like the SQLite store
+        Ok(self.with(tournament, |data| {
+            data.preds
+                .iter()
+                .filter(|(key, _)| {
+                    key.player_id == player_id
+                        && (bot_name.is_none() || key.bot_name.as_deref() == bot_name)
+                })
+                .flat_map(|(_, preds)| preds.values().copied())
+                .collect()
+        }))
+    }
+
+    async fn get_all_competitor_preds(
+        &self,
+        tournament: TournamentId,
+    ) -> Result<Vec<CompetitorPredictions>, DbError> {
+        Ok(self.with(tournament, |data| {
+            // Competitors with any stored prediction, ordered by player and bot
+            let competitors: BTreeSet<&Competitor> = data
+                .preds
+                .iter()
+                .filter(|(_, preds)| !preds.is_empty())
+                .map(|(key, _)| key)
+                .chain(
+                    data.playoff_preds
+                        .iter()
+                        .filter(|(_, preds)| preds.num_played() > 0)
+                        .map(|(key, _)| key),
+                )
+                .chain(
+                    data.bonus_preds
+                        .iter()
+                        .filter(|(_, preds)| !preds.is_empty())
+                        .map(|(key, _)| key),
+                )
+                .chain(
+                    data.jokers
+                        .iter()
+                        .filter(|(_, jokers)| !jokers.is_empty())
+                        .map(|(key, _)| key),
+                )
+                .chain(
+                    data.outcome_preds
+                        .iter()
+                        .filter(|(_, preds)| !preds.is_empty())
+                        .map(|(key, _)| key),
+                )
+                .collect();
+
+            competitors
+                .into_iter()
+                .map(|key| {
+                    let group = data
+                        .preds
+                        .get(key)
+                        .map(|preds| preds.values().copied().collect())
+                        .unwrap_or_default();
+                    let playoff = data.playoff_preds.get(key).cloned().unwrap_or_default();
+                    let outcomes = data
+                        .outcome_preds
+                        .get(key)
+                        .map(|preds| preds.values().copied().collect())
+                        .unwrap_or_default();
+                    let bonus = data
+                        .bonus_preds
+                        .get(key)
+                        .map(|preds| preds.values().cloned().collect())
+                        .unwrap_or_default();
+                    let jokers = data
+                        .jokers
+                        .get(key)
+                        .map(|jokers| jokers.iter().copied().collect())
+                        .unwrap_or_default();
+                    CompetitorPredictions::new(key.clone(), group, playoff)
+                        .with_outcomes(outcomes)
+                        .with_bonus(bonus)
+                        .with_jokers(jokers)
+                })
+                .collect()
+        }))
+    }
+
+    async fn insert_validated_preds(
+        &self,
+        tournament: TournamentId,
+        preds: &PlayerPredictions,
+        bot_name: Option<&str>,
+        now: Date,
+    ) -> Result<PredReport, DbError> {
+        let key = competitor(preds.id, bot_name);
+        self.with_mut(tournament, |data| {
+            let locked = data.locked_games(now);
+            let report = PredReport::new(preds.preds(), &data.group_game_ids(), &locked);
+
+            let kept: Vec<Prediction> = data
+                .preds
+                .get(&key)
+                .into_iter()
+                .flat_map(|stored| stored.values())
+                .filter(|Prediction(game_id, _)| locked.contains(game_id))
+                .copied()
+                .collect();
+            let stored: BTreeMap<GameId, Prediction> = report
+                .accepted()
+                .copied()
+                .chain(kept)
+                .map(|pred| (pred.0, pred))
+                .collect();
+
+            data.pred_history
+                .entry(key.clone())
+                .or_default()
+                .push(PredSubmission {
+                    submitted_at: now,
+                    preds: stored.values().copied().collect(),
+                });
+            data.preds.insert(key, stored);
+            report
+        })
+    }
+
+    async fn clear_player_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+        now: Date,
+    ) -> Result<(), DbError> {
+        // Without a bot, all predictions of the player, like the SQLite store. Predictions of
+        // locked games are kept, and recorded as a submission.
+        self.with_mut(tournament, |data| {
+            let locked = data.locked_games(now);
+            let keys: BTreeSet<Competitor> = data
+                .preds
+                .keys()
+                .chain(data.pred_history.keys())
+                .filter(|key| key.player_id == player_id)
+                .filter(|key| bot_name.is_none() || key.bot_name.as_deref() == bot_name)
+                .cloned()
+                .chain([competitor(player_id, bot_name)])
+                .collect();
+            for key in keys {
+                let preds = data.preds.entry(key.clone()).or_default();
+                preds.retain(|game_id, _| locked.contains(game_id));
+                let kept = preds.values().copied().collect();
+                data.pred_history
+                    .entry(key)
+                    .or_default()
+                    .push(PredSubmission {
+                        submitted_at: now,
+                        preds: kept,
+                    });
+            }
+        })
+    }
+    async fn clear_preds(&self, tournament: TournamentId, now: Date) -> Result<(), DbError> {
+        self.with_mut(tournament, |data| {
+            let keys: BTreeSet<Competitor> = data
+                .preds
+                .keys()
+                .chain(data.pred_history.keys())
+                .cloned()
+                .collect();
+            data.preds.clear();
+            for key in keys {
+                data.pred_history
+                    .entry(key)
+                    .or_default()
+                    .push(PredSubmission {
+                        submitted_at: now,
+                        preds: Vec::new(),
+                    });
+            }
+        })
+    }
+
+    async fn get_pred_history(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+    ) -> Result<Vec<PredSubmission>, DbError> {
+        let key = competitor(player_id, bot_name);
+        Ok(self.with(tournament, |data| {
+            data.pred_history.get(&key).cloned().unwrap_or_default()
+        }))
+    }
+
+    async fn get_outcome_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+    ) -> Result<Vec<OutcomePrediction>, DbError> {
+        let key = competitor(player_id, bot_name);
+        Ok(self.with(tournament, |data| {
+            data.outcome_preds
+                .get(&key)
+                .map(|preds| preds.values().copied().collect())
+                .unwrap_or_default()
+        }))
+    }
+
+    async fn insert_validated_outcome_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        preds: &[OutcomePrediction],
+        bot_name: Option<&str>,
+        now: Date,
+    ) -> Result<PredReport<OutcomePrediction>, DbError> {
+        let key = competitor(player_id, bot_name);
+        self.with_mut(tournament, |data| {
+            let locked = data.locked_games(now);
+            let report = PredReport::new(preds, &data.group_game_ids(), &locked);
+
+            let kept: Vec<OutcomePrediction> = data
+                .outcome_preds
+                .get(&key)
+                .into_iter()
+                .flat_map(|stored| stored.values())
+                .filter(|OutcomePrediction(game_id, _)| locked.contains(game_id))
+                .copied()
+                .collect();
+            let stored = report
+                .accepted()
+                .copied()
+                .chain(kept)
+                .map(|pred| (pred.0, pred))
+                .collect();
+            data.outcome_preds.insert(key, stored);
+            report
+        })
+    }
+
+    async fn clear_player_outcome_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+        now: Date,
+    ) -> Result<(), DbError> {
+        let key = competitor(player_id, bot_name);
+        self.with_mut(tournament, |data| {
+            let locked = data.locked_games(now);
+            if let Some(preds) = data.outcome_preds.get_mut(&key) {
+                preds.retain(|game_id, _| locked.contains(game_id));
+            }
+        })
+    }
+
+    async fn clear_outcome_preds(&self, tournament: TournamentId, _: Date) -> Result<(), DbError> {
+        // Without an outcome prediction history, there is no clearing to record
+        self.with_mut(tournament, |data| data.outcome_preds.clear())
+    }
+
+    async fn get_proba_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: &str,
+    ) -> Result<Vec<ProbPrediction>, DbError> {
+        let key = Competitor::bot(player_id, bot_name);
+        Ok(self.with(tournament, |data| {
+            data.proba_preds.get(&key).cloned().unwrap_or_default()
+        }))
+    }
+
+    async fn get_all_proba_preds(
+        &self,
+        tournament: TournamentId,
+    ) -> Result<Vec<CompetitorProbPredictions>, DbError> {
+        Ok(self.with(tournament, |data| {
+            data.proba_preds
+                .iter()
+                .filter(|(_, preds)| !preds.is_empty())
+                .map(|(key, preds)| CompetitorProbPredictions {
+                    competitor: key.clone(),
+                    preds: preds.clone(),
+                })
+                .collect()
+        }))
+    }
+
+    async fn insert_validated_proba_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: &str,
+        preds: &[ProbPrediction],
+        now: Date,
+    ) -> Result<PredReport<ProbPrediction>, DbError> {
+        let key = Competitor::bot(player_id, bot_name);
+        self.with_mut(tournament, |data| {
+            let locked = data.locked_games(now);
+            let report = PredReport::new(preds, &data.group_game_ids(), &locked);
+
+            let mut stored: Vec<ProbPrediction> = data
+                .proba_preds
+                .remove(&key)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|ProbPrediction(game_id, _)| locked.contains(game_id))
+                .chain(report.accepted().cloned())
+                .collect();
+            stored.sort_by_key(|ProbPrediction(game_id, _)| *game_id);
+            data.proba_preds.insert(key, stored);
+            report
+        })
+    }
+
+    async fn clear_bot_proba_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: &str,
+        now: Date,
+    ) -> Result<(), DbError> {
+        // Like the SQLite store, predictions of locked games are kept
+        let key = Competitor::bot(player_id, bot_name);
+        self.with_mut(tournament, |data| {
+            let locked = data.locked_games(now);
+            if let Some(preds) = data.proba_preds.get_mut(&key) {
+                preds.retain(|ProbPrediction(game_id, _)| locked.contains(game_id));
+            }
+        })
+    }
+
+    async fn clear_proba_preds(&self, tournament: TournamentId) -> Result<(), DbError> {
+        self.with_mut(tournament, |data| data.proba_preds.clear())
+    }
+
+    async fn get_bonus_questions(
+        &self,
+        tournament: TournamentId,
+    ) -> Result<Vec<BonusQuestion>, DbError> {
+        Ok(self.with(tournament, |data| {
+            data.bonus_questions.values().cloned().collect()
+        }))
+    }
+
+    async fn insert_bonus_question(
+        &self,
+        tournament: TournamentId,
+        question: &BonusQuestion,
+    ) -> Result<(), DbError> {
+        self.with_mut(tournament, |data| {
+            data.bonus_questions.insert(question.id, question.clone());
+        })
+    }
+
+    async fn resolve_bonus_question(
+        &self,
+        tournament: TournamentId,
+        id: BonusQuestionId,
+        solution: &[BonusAnswer],
+    ) -> Result<(), DbError> {
+        self.with_mut(tournament, |data| match data.bonus_questions.get_mut(&id) {
+            Some(question) => {
+                question.solution = Some(solution.to_vec());
+                Ok(())
+            }
+            None => Err(DbError::Generic(format!(
+                "No bonus question with id {}",
+                id
+            ))),
+        })?
+    }
+
+    async fn get_bonus_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+    ) -> Result<Vec<BonusPrediction>, DbError> {
+        let key = competitor(player_id, bot_name);
+        Ok(self.with(tournament, |data| {
+            data.bonus_preds
+                .get(&key)
+                .map(|preds| preds.values().cloned().collect())
+                .unwrap_or_default()
+        }))
+    }
+
+    async fn insert_bonus_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        preds: &[BonusPrediction],
+        bot_name: Option<&str>,
+    ) -> Result<(), DbError> {
+        let key = competitor(player_id, bot_name);
+        self.with_mut(tournament, |data| {
+            data.bonus_preds
+                .entry(key)
+                .or_default()
+                .extend(preds.iter().map(|pred| (pred.0, pred.clone())));
+        })
+    }
+
+    async fn clear_player_bonus_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+    ) -> Result<(), DbError> {
+        let key = competitor(player_id, bot_name);
+        self.with_mut(tournament, |data| {
+            data.bonus_preds.remove(&key);
+        })
+    }
+
+    async fn clear_bonus_preds(&self, tournament: TournamentId) -> Result<(), DbError> {
+        self.with_mut(tournament, |data| data.bonus_preds.clear())
+    }
+
+    async fn get_jokers(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+    ) -> Result<Vec<GameId>, DbError> {
+        let key = competitor(player_id, bot_name);
+        Ok(self.with(tournament, |data| {
+            data.jokers
+                .get(&key)
+                .map(|jokers| jokers.iter().copied().collect())
+                .unwrap_or_default()
+        }))
+    }
+
+    async fn insert_jokers(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        jokers: &[GameId],
+        bot_name: Option<&str>,
+    ) -> Result<(), DbError> {
+        let key = competitor(player_id, bot_name);
+        self.with_mut(tournament, |data| {
+            data.jokers.insert(key, jokers.iter().copied().collect());
+        })
+    }
+
+    async fn clear_player_jokers(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+    ) -> Result<(), DbError> {
+        let key = competitor(player_id, bot_name);
+        self.with_mut(tournament, |data| {
+            data.jokers.remove(&key);
+        })
+    }
+
+    async fn clear_jokers(&self, tournament: TournamentId) -> Result<(), DbError> {
+        self.with_mut(tournament, |data| data.jokers.clear())
+    }
+
+    async fn ensure_player(&self, player_id: PlayerId) -> Result<bool, DbError> {
+        let mut data = self.write();
+        if data.players.contains_key(&player_id) {
+            return Ok(false);
+        }
+        data.players
+            .insert(player_id, format!("Player {}", i32::from(player_id)));
+        Ok(true)
+    }
+
+    async fn get_user_by_username(&self, username: &str) -> Result<Option<User>, DbError> {
+        Ok(self
+            .read()
+            .users
+            .iter()
+            .find(|user| user.username == username)
+            .cloned())
+    }
+
+    async fn get_user_by_id(&self, user_id: i32) -> Result<Option<User>, DbError> {
+        Ok(self
+            .read()
+            .users
+            .iter()
+            .find(|user| user.id == user_id)
+            .cloned())
+    }
+
+    async fn get_all_display_names(&self) -> Result<Vec<(i32, Option<String>, String)>, DbError> {
+        let data = self.read();
+        let mut names: Vec<(i32, Option<String>, String)> = data
+            .users
+            .iter()
+            .map(|user| (user.id, None, user.display_name.clone()))
+            .chain(data.bots.iter().map(|bot| {
+                (
+                    bot.user_id,
+                    Some(bot.bot_name.clone()),
+                    bot.bot_display_name.clone(),
+                )
+            }))
+            .collect();
+        names.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+        Ok(names)
+    }
+
+    async fn insert_league(
+        &self,
+        tournament: TournamentId,
+        name: &str,
+        owner: PlayerId,
+    ) -> Result<League, DbError> {
+        let mut data = self.write();
+        if !data.tournament_data.contains_key(&tournament) {
+            return Err(DbError::Generic(format!("No tournament {}", tournament)));
+        }
+        data.next_league_id += 1;
+        let league = League {
+            id: LeagueId(data.next_league_id),
+            tournament_id: tournament,
+            name: name.to_string(),
+            invite_code: data.new_invite_code(),
+        };
+        data.leagues.push(StoredLeague {
+            league: league.clone(),
+            config: None,
+            members: vec![LeagueMember {
+                player_id: i32::from(owner),
+                role: LeagueRole::Owner,
+                joined_at: timestamp(),
+            }],
+        });
+        Ok(league)
+    }
+
+    async fn get_league(&self, league: LeagueId) -> Result<Option<League>, DbError> {
+        Ok(self
+            .read()
+            .league(league)
+            .map(|stored| stored.league.clone()))
+    }
+
+    async fn get_league_by_invite_code(
+        &self,
+        invite_code: &str,
+    ) -> Result<Option<League>, DbError> {
+        Ok(self
+            .read()
+            .leagues
+            .iter()
+            .find(|stored| stored.league.invite_code == invite_code)
+            .map(|stored| stored.league.clone()))
+    }
+
+    async fn get_player_leagues(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+    ) -> Result<Vec<League>, DbError> {
+        Ok(self
+            .read()
+            .leagues
+            .iter()
+            .filter(|stored| {
+                stored.league.tournament_id == tournament
+                    && stored
+                        .members
+                        .iter()
+                        .any(|member| member.player_id == i32::from(player_id))
+            })
+            .map(|stored| stored.league.clone())
+            .collect())
+    }
+
+    async fn reset_league_invite_code(&self, league: LeagueId) -> Result<String, DbError> {
+        let mut data = self.write();
+        let invite_code = data.new_invite_code();
+        data.league_mut(league)?.league.invite_code = invite_code.clone();
+        Ok(invite_code)
+    }
+
+    async fn delete_league(&self, league: LeagueId) -> Result<(), DbError> {
+        self.write()
+            .leagues
+            .retain(|stored| stored.league.id != league);
+        Ok(())
+    }
+
+    async fn get_league_members(&self, league: LeagueId) -> Result<Vec<LeagueMember>, DbError> {
+        let mut members = self
+            .read()
+            .league(league)
+            .map(|stored| stored.members.clone())
+            .unwrap_or_default();
+        // Owner first, the rest stay in order of joining
+        members.sort_by_key(|member| member.role != LeagueRole::Owner);
+        Ok(members)
+    }
+
+    async fn get_league_role(
+        &self,
+        league: LeagueId,
+        player_id: PlayerId,
+    ) -> Result<Option<LeagueRole>, DbError> {
+        Ok(self.read().league(league).and_then(|stored| {
+            stored
+                .members
+                .iter()
+                .find(|member| member.player_id == i32::from(player_id))
+                .map(|member| member.role)
+        }))
+    }
+
+    async fn insert_league_member(
+        &self,
+        league: LeagueId,
+        player_id: PlayerId,
+    ) -> Result<(), DbError> {
+        let mut data = self.write();
+        let stored = data.league_mut(league)?;
+        if !stored
+            .members
+            .iter()
+            .any(|member| member.player_id == i32::from(player_id))
+        {
+            stored.members.push(LeagueMember {
+                player_id: i32::from(player_id),
+                role: LeagueRole::Member,
+                joined_at: timestamp(),
+            });
+        }
+        Ok(())
+    }
+
+    async fn delete_league_member(
+        &self,
+        league: LeagueId,
+        player_id: PlayerId,
+    ) -> Result<(), DbError> {
+        if let Ok(stored) = self.write().league_mut(league) {
+            stored
+                .members
+                .retain(|member| member.player_id != i32::from(player_id));
+        }
+        Ok(())
+    }
+
+    async fn get_league_scoring_config(
+        &self,
+        league: LeagueId,
+    ) -> Result<Option<ScoringConfig>, DbError> {
+        Ok(self
+            .read()
+            .league(league)
+            .and_then(|stored| stored.config.clone()))
+    }
+
+    async fn set_league_scoring_config(
+        &self,
+        league: LeagueId,
+        config: Option<&ScoringConfig>,
+    ) -> Result<(), DbError> {
+        if let Ok(stored) = self.write().league_mut(league) {
+            stored.config = config.cloned();
+        }
+        Ok(())
+    }
+}
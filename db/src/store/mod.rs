@@ -0,0 +1,428 @@
+//! Storage backends
+//!
+//! [`Store`] is the storage the server works with: teams, games, groups, playoff data,
+//! predictions, leagues, users and bots of every tournament. [`SqliteStore`] keeps them in the
+//! SQLite database, delegating to the operations of this crate. [`MemoryStore`] keeps them in
+//! memory, for tests which should run without a database.
+//!
+//! Both backends behave the same, e.g. `None` as bot name means the player's own predictions
+//! or, where noted on the crate's functions, the player's and all of their bots' predictions.
+
+mod memory;
+mod sqlite;
+
+pub use memory::MemoryStore;
+pub use sqlite::SqliteStore;
+
+use crate::DbError;
+use crate::models::{League, LeagueId, LeagueMember, LeagueRole, Tournament, TournamentId, User};
+use std::collections::HashSet;
+use std::future::Future;
+use wwc_core::Date;
+use wwc_core::bonus::{BonusAnswer, BonusPrediction, BonusQuestion, BonusQuestionId};
+use wwc_core::game::GameId;
+use wwc_core::group::GroupId;
+use wwc_core::group::game::{PlayedGroupGame, UnplayedGroupGame};
+use wwc_core::group::order::RuleSet;
+use wwc_core::leaderboard::CompetitorPredictions;
+use wwc_core::player::{
+    OutcomePrediction, PlayerId, PlayerPredictions, PredSubmission, Prediction,
+};
+use wwc_core::playoff::{BracketState, PlayoffResult, TeamSource};
+use wwc_core::pred_score::proba::{CompetitorProbPredictions, ProbPrediction};
+use wwc_core::pred_score::scheme::ScoringConfig;
+use wwc_core::validation::PredReport;
+
+/// Storage of all tournament data
+///
+/// Every operation is documented on the crate function of the same name, which the SQLite store
+/// calls.
+pub trait Store: Clone + Send + Sync + 'static {
+    // Tournaments
+
+    fn get_tournaments(&self) -> impl Future<Output = Result<Vec<Tournament>, DbError>> + Send;
+
+    fn get_tournament_id(
+        &self,
+        name: &str,
+    ) -> impl Future<Output = Result<Option<TournamentId>, DbError>> + Send;
+
+    fn get_scoring_config(
+        &self,
+        tournament: TournamentId,
+    ) -> impl Future<Output = Result<Option<ScoringConfig>, DbError>> + Send;
+
+    fn get_group_rules(
+        &self,
+        tournament: TournamentId,
+    ) -> impl Future<Output = Result<RuleSet, DbError>> + Send;
+
+    // Teams, games and groups
+
+    fn get_teams(
+        &self,
+        tournament: TournamentId,
+    ) -> impl Future<Output = Result<Vec<wwc_core::Team>, DbError>> + Send;
+
+    #[allow(clippy::type_complexity)]
+    fn get_group_games(
+        &self,
+        tournament: TournamentId,
+    ) -> impl Future<Output = Result<(Vec<PlayedGroupGame>, Vec<UnplayedGroupGame>), DbError>> + Send;
+
+    fn get_group_game_maps(
+        &self,
+        tournament: TournamentId,
+    ) -> impl Future<Output = Result<Vec<(GameId, GroupId)>, DbError>> + Send;
+
+    fn get_locked_games(
+        &self,
+        tournament: TournamentId,
+        now: Date,
+    ) -> impl Future<Output = Result<HashSet<GameId>, DbError>> + Send;
+
+    fn get_game_kickoffs(
+        &self,
+        tournament: TournamentId,
+    ) -> impl Future<Output = Result<Vec<(GameId, Date)>, DbError>> + Send;
+
+    fn insert_group_game_result(
+        &self,
+        tournament: TournamentId,
+        game: &PlayedGroupGame,
+    ) -> impl Future<Output = Result<(), DbError>> + Send;
+
+    fn clear_group_game_result(
+        &self,
+        tournament: TournamentId,
+        game_id: GameId,
+    ) -> impl Future<Output = Result<(), DbError>> + Send;
+
+    // Playoff
+
+    #[allow(clippy::type_complexity)]
+    fn get_playoff_team_sources(
+        &self,
+        tournament: TournamentId,
+    ) -> impl Future<Output = Result<Vec<(GameId, (TeamSource, TeamSource))>, DbError>> + Send;
+
+    fn get_playoff_results(
+        &self,
+        tournament: TournamentId,
+    ) -> impl Future<Output = Result<BracketState, DbError>> + Send;
+
+    fn insert_playoff_result(
+        &self,
+        tournament: TournamentId,
+        game_id: GameId,
+        result: &PlayoffResult,
+    ) -> impl Future<Output = Result<(), DbError>> + Send;
+
+    fn clear_playoff_result(
+        &self,
+        tournament: TournamentId,
+        game_id: GameId,
+    ) -> impl Future<Output = Result<(), DbError>> + Send;
+
+    fn get_playoff_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+    ) -> impl Future<Output = Result<BracketState, DbError>> + Send;
+
+    fn insert_playoff_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bracket_state: &BracketState,
+        bot_name: Option<&str>,
+    ) -> impl Future<Output = Result<(), DbError>> + Send;
+
+    fn clear_player_playoff_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+        now: Date,
+    ) -> impl Future<Output = Result<(), DbError>> + Send;
+
+    fn clear_playoff_preds(
+        &self,
+        tournament: TournamentId,
+    ) -> impl Future<Output = Result<(), DbError>> + Send;
+
+    // Group predictions
+
+    fn get_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+    ) -> impl Future<Output = Result<Vec<Prediction>, DbError>> + Send;
+
+    fn get_all_competitor_preds(
+        &self,
+        tournament: TournamentId,
+    ) -> impl Future<Output = Result<Vec<CompetitorPredictions>, DbError>> + Send;
+
+    fn insert_validated_preds(
+        &self,
+        tournament: TournamentId,
+        preds: &PlayerPredictions,
+        bot_name: Option<&str>,
+        now: Date,
+    ) -> impl Future<Output = Result<PredReport, DbError>> + Send;
+
+    fn clear_player_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+        now: Date,
+    ) -> impl Future<Output = Result<(), DbError>> + Send;
+
+    fn clear_preds(
+        &self,
+        tournament: TournamentId,
+        now: Date,
+    ) -> impl Future<Output = Result<(), DbError>> + Send;
+
+    fn get_pred_history(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+    ) -> impl Future<Output = Result<Vec<PredSubmission>, DbError>> + Send;
+
+    // Outcome predictions
+
+    fn get_outcome_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+    ) -> impl Future<Output = Result<Vec<OutcomePrediction>, DbError>> + Send;
+
+    fn insert_validated_outcome_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        preds: &[OutcomePrediction],
+        bot_name: Option<&str>,
+        now: Date,
+    ) -> impl Future<Output = Result<PredReport<OutcomePrediction>, DbError>> + Send;
+
+    fn clear_player_outcome_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+        now: Date,
+    ) -> impl Future<Output = Result<(), DbError>> + Send;
+
+    fn clear_outcome_preds(
+        &self,
+        tournament: TournamentId,
+        now: Date,
+    ) -> impl Future<Output = Result<(), DbError>> + Send;
+
+    // Probabilistic predictions
+
+    fn get_proba_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: &str,
+    ) -> impl Future<Output = Result<Vec<ProbPrediction>, DbError>> + Send;
+
+    fn get_all_proba_preds(
+        &self,
+        tournament: TournamentId,
+    ) -> impl Future<Output = Result<Vec<CompetitorProbPredictions>, DbError>> + Send;
+
+    fn insert_validated_proba_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: &str,
+        preds: &[ProbPrediction],
+        now: Date,
+    ) -> impl Future<Output = Result<PredReport<ProbPrediction>, DbError>> + Send;
+
+    fn clear_bot_proba_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: &str,
+        now: Date,
+    ) -> impl Future<Output = Result<(), DbError>> + Send;
+
+    fn clear_proba_preds(
+        &self,
+        tournament: TournamentId,
+    ) -> impl Future<Output = Result<(), DbError>> + Send;
+
+    // Bonus questions and answers
+
+    fn get_bonus_questions(
+        &self,
+        tournament: TournamentId,
+    ) -> impl Future<Output = Result<Vec<BonusQuestion>, DbError>> + Send;
+
+    fn insert_bonus_question(
+        &self,
+        tournament: TournamentId,
+        question: &BonusQuestion,
+    ) -> impl Future<Output = Result<(), DbError>> + Send;
+
+    fn resolve_bonus_question(
+        &self,
+        tournament: TournamentId,
+        id: BonusQuestionId,
+        solution: &[BonusAnswer],
+    ) -> impl Future<Output = Result<(), DbError>> + Send;
+
+    fn get_bonus_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+    ) -> impl Future<Output = Result<Vec<BonusPrediction>, DbError>> + Send;
+
+    fn insert_bonus_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        preds: &[BonusPrediction],
+        bot_name: Option<&str>,
+    ) -> impl Future<Output = Result<(), DbError>> + Send;
+
+    fn clear_player_bonus_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+    ) -> impl Future<Output = Result<(), DbError>> + Send;
+
+    fn clear_bonus_preds(
+        &self,
+        tournament: TournamentId,
+    ) -> impl Future<Output = Result<(), DbError>> + Send;
+
+    // Jokers
+
+    fn get_jokers(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+    ) -> impl Future<Output = Result<Vec<GameId>, DbError>> + Send;
+
+    fn insert_jokers(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        jokers: &[GameId],
+        bot_name: Option<&str>,
+    ) -> impl Future<Output = Result<(), DbError>> + Send;
+
+    fn clear_player_jokers(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+    ) -> impl Future<Output = Result<(), DbError>> + Send;
+
+    fn clear_jokers(
+        &self,
+        tournament: TournamentId,
+    ) -> impl Future<Output = Result<(), DbError>> + Send;
+
+    // Players, users and bots
+
+    fn ensure_player(
+        &self,
+        player_id: PlayerId,
+    ) -> impl Future<Output = Result<bool, DbError>> + Send;
+
+    fn get_user_by_username(
+        &self,
+        username: &str,
+    ) -> impl Future<Output = Result<Option<User>, DbError>> + Send;
+
+    fn get_user_by_id(
+        &self,
+        user_id: i32,
+    ) -> impl Future<Output = Result<Option<User>, DbError>> + Send;
+
+    fn get_all_display_names(
+        &self,
+    ) -> impl Future<Output = Result<Vec<(i32, Option<String>, String)>, DbError>> + Send;
+
+    // Leagues
+
+    fn insert_league(
+        &self,
+        tournament: TournamentId,
+        name: &str,
+        owner: PlayerId,
+    ) -> impl Future<Output = Result<League, DbError>> + Send;
+
+    fn get_league(
+        &self,
+        league: LeagueId,
+    ) -> impl Future<Output = Result<Option<League>, DbError>> + Send;
+
+    fn get_league_by_invite_code(
+        &self,
+        invite_code: &str,
+    ) -> impl Future<Output = Result<Option<League>, DbError>> + Send;
+
+    fn get_player_leagues(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+    ) -> impl Future<Output = Result<Vec<League>, DbError>> + Send;
+
+    fn reset_league_invite_code(
+        &self,
+        league: LeagueId,
+    ) -> impl Future<Output = Result<String, DbError>> + Send;
+
+    fn delete_league(&self, league: LeagueId) -> impl Future<Output = Result<(), DbError>> + Send;
+
+    fn get_league_members(
+        &self,
+        league: LeagueId,
+    ) -> impl Future<Output = Result<Vec<LeagueMember>, DbError>> + Send;
+
+    fn get_league_role(
+        &self,
+        league: LeagueId,
+        player_id: PlayerId,
+    ) -> impl Future<Output = Result<Option<LeagueRole>, DbError>> + Send;
+
+    fn insert_league_member(
+        &self,
+        league: LeagueId,
+        player_id: PlayerId,
+    ) -> impl Future<Output = Result<(), DbError>> + Send;
+
+    fn delete_league_member(
+        &self,
+        league: LeagueId,
+        player_id: PlayerId,
+    ) -> impl Future<Output = Result<(), DbError>> + Send;
+
+    fn get_league_scoring_config(
+        &self,
+        league: LeagueId,
+    ) -> impl Future<Output = Result<Option<ScoringConfig>, DbError>> + Send;
+
+    fn set_league_scoring_config(
+        &self,
+        league: LeagueId,
+        config: Option<&ScoringConfig>,
+    ) -> impl Future<Output = Result<(), DbError>> + Send;
+}
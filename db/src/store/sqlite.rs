@@ -0,0 +1,483 @@
+use super::Store;
+use crate::DbError;
+use crate::models::{League, LeagueId, LeagueMember, LeagueRole, Tournament, TournamentId, User};
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+use wwc_core::Date;
+use wwc_core::bonus::{BonusAnswer, BonusPrediction, BonusQuestion, BonusQuestionId};
+use wwc_core::game::GameId;
+use wwc_core::group::GroupId;
+use wwc_core::group::game::{PlayedGroupGame, UnplayedGroupGame};
+use wwc_core::group::order::RuleSet;
+use wwc_core::leaderboard::CompetitorPredictions;
+use wwc_core::player::{
+    OutcomePrediction, PlayerId, PlayerPredictions, PredSubmission, Prediction,
+};
+use wwc_core::playoff::{BracketState, PlayoffResult, TeamSource};
+use wwc_core::pred_score::proba::{CompetitorProbPredictions, ProbPrediction};
+use wwc_core::pred_score::scheme::ScoringConfig;
+use wwc_core::validation::PredReport;
+
+/// Store in the SQLite database
+#[derive(Debug, Clone)]
+pub struct SqliteStore {
+    pool: SqlitePool,
+}
+
+impl SqliteStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        SqliteStore { pool }
+    }
+
+    /// The database pool, for operations which are not part of [`Store`]
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+}
+
+impl Store for SqliteStore {
+    async fn get_tournaments(&self) -> Result<Vec<Tournament>, DbError> {
+        crate::get_tournaments(&self.pool).await
+    }
+
+    async fn get_tournament_id(&self, name: &str) -> Result<Option<TournamentId>, DbError> {
+        crate::get_tournament_id(&self.pool, name).await
+    }
+
+    async fn get_scoring_config(
+        &self,
+        tournament: TournamentId,
+    ) -> Result<Option<ScoringConfig>, DbError> {
+        crate::get_scoring_config(&self.pool, tournament).await
+    }
+
+    async fn get_group_rules(&self, tournament: TournamentId) -> Result<RuleSet, DbError> {
+        crate::get_group_rules(&self.pool, tournament).await
+    }
+
+    async fn get_teams(&self, tournament: TournamentId) -> Result<Vec<wwc_core::Team>, DbError> {
+        crate::get_teams(&self.pool, tournament).await
+    }
+
+    async fn get_group_games(
+        &self,
+        tournament: TournamentId,
+    ) -> Result<(Vec<PlayedGroupGame>, Vec<UnplayedGroupGame>), DbError> {
+        crate::get_group_games(&self.pool, tournament).await
+    }
+
+    async fn get_group_game_maps(
+        &self,
+        tournament: TournamentId,
+    ) -> Result<Vec<(GameId, GroupId)>, DbError> {
+        crate::get_group_game_maps(&self.pool, tournament).await
+    }
+
+    async fn get_locked_games(
+        &self,
+        tournament: TournamentId,
+        now: Date,
+    ) -> Result<HashSet<GameId>, DbError> {
+        crate::get_locked_games(&self.pool, tournament, now).await
+    }
+
+    async fn get_game_kickoffs(
+        &self,
+        tournament: TournamentId,
+    ) -> Result<Vec<(GameId, Date)>, DbError> {
+        crate::get_game_kickoffs(&self.pool, tournament).await
+    }
+
+    async fn insert_group_game_result(
+        &self,
+        tournament: TournamentId,
+        game: &PlayedGroupGame,
+    ) -> Result<(), DbError> {
+        crate::insert_group_game_result(&self.pool, tournament, game).await
+    }
+
+    async fn clear_group_game_result(
+        &self,
+        tournament: TournamentId,
+        game_id: GameId,
+    ) -> Result<(), DbError> {
+        crate::clear_group_game_result(&self.pool, tournament, game_id).await
+    }
+
+    async fn get_playoff_team_sources(
+        &self,
+        tournament: TournamentId,
+    ) -> Result<Vec<(GameId, (TeamSource, TeamSource))>, DbError> {
+        crate::get_playoff_team_sources(&self.pool, tournament).await
+    }
+
+    async fn get_playoff_results(&self, tournament: TournamentId) -> Result<BracketState, DbError> {
+        crate::get_playoff_results(&self.pool, tournament).await
+    }
+
+    async fn insert_playoff_result(
+        &self,
+        tournament: TournamentId,
+        game_id: GameId,
+        result: &PlayoffResult,
+    ) -> Result<(), DbError> {
+        crate::insert_playoff_result(&self.pool, tournament, game_id, result).await
+    }
+
+    async fn clear_playoff_result(
+        &self,
+        tournament: TournamentId,
+        game_id: GameId,
+    ) -> Result<(), DbError> {
+        crate::clear_playoff_result(&self.pool, tournament, game_id).await
+    }
+
+    async fn get_playoff_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+    ) -> Result<BracketState, DbError> {
+        crate::get_playoff_preds(&self.pool, tournament, player_id, bot_name).await
+    }
+
+    async fn insert_playoff_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bracket_state: &BracketState,
+        bot_name: Option<&str>,
+    ) -> Result<(), DbError> {
+        crate::insert_playoff_preds(&self.pool, tournament, player_id, bracket_state, bot_name)
+            .await
+    }
+
+    async fn clear_player_playoff_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+        now: Date,
+    ) -> Result<(), DbError> {
+        crate::clear_player_playoff_preds(&self.pool, tournament, player_id, bot_name, now).await
+    }
+
+    async fn clear_playoff_preds(&self, tournament: TournamentId) -> Result<(), DbError> {
+        crate::clear_playoff_preds(&self.pool, tournament).await
+    }
+
+    async fn get_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+    ) -> Result<Vec<Prediction>, DbError> {
+        crate::get_preds(&self.pool, tournament, player_id, bot_name).await
+    }
+
+    async fn get_all_competitor_preds(
+        &self,
+        tournament: TournamentId,
+    ) -> Result<Vec<CompetitorPredictions>, DbError> {
+        crate::get_all_competitor_preds(&self.pool, tournament).await
+    }
+
+    async fn insert_validated_preds(
+        &self,
+        tournament: TournamentId,
+        preds: &PlayerPredictions,
+        bot_name: Option<&str>,
+        now: Date,
+    ) -> Result<PredReport, DbError> {
+        crate::insert_validated_preds(&self.pool, tournament, preds, bot_name, now).await
+    }
+
+    async fn clear_player_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+        now: Date,
+    ) -> Result<(), DbError> {
+        crate::clear_player_preds(&self.pool, tournament, player_id, bot_name, now).await
+    }
+
+    async fn clear_preds(&self, tournament: TournamentId, now: Date) -> Result<(), DbError> {
+        crate::clear_preds(&self.pool, tournament, now).await
+    }
+
+    async fn get_pred_history(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+    ) -> Result<Vec<PredSubmission>, DbError> {
+        crate::get_pred_history(&self.pool, tournament, player_id, bot_name).await
+    }
+
+    async fn get_outcome_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+    ) -> Result<Vec<OutcomePrediction>, DbError> {
+        crate::get_outcome_preds(&self.pool, tournament, player_id, bot_name).await
+    }
+
+    async fn insert_validated_outcome_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        preds: &[OutcomePrediction],
+        bot_name: Option<&str>,
+        now: Date,
+    ) -> Result<PredReport<OutcomePrediction>, DbError> {
+        crate::insert_validated_outcome_preds(
+            &self.pool, tournament, player_id, preds, bot_name, now,
+        )
+        .await
+    }
+
+    async fn clear_player_outcome_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+        now: Date,
+    ) -> Result<(), DbError> {
+        crate::clear_player_outcome_preds(&self.pool, tournament, player_id, bot_name, now).await
+    }
+
+    async fn clear_outcome_preds(
+        &self,
+        tournament: TournamentId,
+        now: Date,
+    ) -> Result<(), DbError> {
+        crate::clear_outcome_preds(&self.pool, tournament, now).await
+    }
+
+    async fn get_proba_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: &str,
+    ) -> Result<Vec<ProbPrediction>, DbError> {
+        crate::get_proba_preds(&self.pool, tournament, player_id, bot_name).await
+    }
+
+    async fn get_all_proba_preds(
+        &self,
+        tournament: TournamentId,
+    ) -> Result<Vec<CompetitorProbPredictions>, DbError> {
+        crate::get_all_proba_preds(&self.pool, tournament).await
+    }
+
+    async fn insert_validated_proba_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: &str,
+        preds: &[ProbPrediction],
+        now: Date,
+    ) -> Result<PredReport<ProbPrediction>, DbError> {
+        crate::insert_validated_proba_preds(&self.pool, tournament, player_id, bot_name, preds, now)
+            .await
+    }
+
+    async fn clear_bot_proba_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: &str,
+        now: Date,
+    ) -> Result<(), DbError> {
+        crate::clear_bot_proba_preds(&self.pool, tournament, player_id, bot_name, now).await
+    }
+
+    async fn clear_proba_preds(&self, tournament: TournamentId) -> Result<(), DbError> {
+        crate::clear_proba_preds(&self.pool, tournament).await
+    }
+
+    async fn get_bonus_questions(
+        &self,
+        tournament: TournamentId,
+    ) -> Result<Vec<BonusQuestion>, DbError> {
+        crate::get_bonus_questions(&self.pool, tournament).await
+    }
+
+    async fn insert_bonus_question(
+        &self,
+        tournament: TournamentId,
+        question: &BonusQuestion,
+    ) -> Result<(), DbError> {
+        crate::insert_bonus_question(&self.pool, tournament, question).await
+    }
+
+    async fn resolve_bonus_question(
+        &self,
+        tournament: TournamentId,
+        id: BonusQuestionId,
+        solution: &[BonusAnswer],
+    ) -> Result<(), DbError> {
+        crate::resolve_bonus_question(&self.pool, tournament, id, solution).await
+    }
+
+    async fn get_bonus_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+    ) -> Result<Vec<BonusPrediction>, DbError> {
+        crate::get_bonus_preds(&self.pool, tournament, player_id, bot_name).await
+    }
+
+    async fn insert_bonus_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        preds: &[BonusPrediction],
+        bot_name: Option<&str>,
+    ) -> Result<(), DbError> {
+        crate::insert_bonus_preds(&self.pool, tournament, player_id, preds, bot_name).await
+    }
+
+    async fn clear_player_bonus_preds(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+    ) -> Result<(), DbError> {
+        crate::clear_player_bonus_preds(&self.pool, tournament, player_id, bot_name).await
+    }
+
+    async fn clear_bonus_preds(&self, tournament: TournamentId) -> Result<(), DbError> {
+        crate::clear_bonus_preds(&self.pool, tournament).await
+    }
+
+    async fn get_jokers(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+    ) -> Result<Vec<GameId>, DbError> {
+        crate::get_jokers(&self.pool, tournament, player_id, bot_name).await
+    }
+
+    async fn insert_jokers(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        jokers: &[GameId],
+        bot_name: Option<&str>,
+    ) -> Result<(), DbError> {
+        crate::insert_jokers(&self.pool, tournament, player_id, jokers, bot_name).await
+    }
+
+    async fn clear_player_jokers(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+        bot_name: Option<&str>,
+    ) -> Result<(), DbError> {
+        crate::clear_player_jokers(&self.pool, tournament, player_id, bot_name).await
+    }
+
+    async fn clear_jokers(&self, tournament: TournamentId) -> Result<(), DbError> {
+        crate::clear_jokers(&self.pool, tournament).await
+    }
+
+    async fn ensure_player(&self, player_id: PlayerId) -> Result<bool, DbError> {
+        crate::ensure_player(&self.pool, player_id).await
+    }
+
+    async fn get_user_by_username(&self, username: &str) -> Result<Option<User>, DbError> {
+        crate::get_user_by_username(&self.pool, username).await
+    }
+
+    async fn get_user_by_id(&self, user_id: i32) -> Result<Option<User>, DbError> {
+        crate::get_user_by_id(&self.pool, user_id).await
+    }
+
+    async fn get_all_display_names(&self) -> Result<Vec<(i32, Option<String>, String)>, DbError> {
+        crate::get_all_display_names(&self.pool).await
+    }
+
+    async fn insert_league(
+        &self,
+        tournament: TournamentId,
+        name: &str,
+        owner: PlayerId,
+    ) -> Result<League, DbError> {
+        crate::insert_league(&self.pool, tournament, name, owner).await
+    }
+
+    async fn get_league(&self, league: LeagueId) -> Result<Option<League>, DbError> {
+        crate::get_league(&self.pool, league).await
+    }
+
+    async fn get_league_by_invite_code(
+        &self,
+        invite_code: &str,
+    ) -> Result<Option<League>, DbError> {
+        crate::get_league_by_invite_code(&self.pool, invite_code).await
+    }
+
+    async fn get_player_leagues(
+        &self,
+        tournament: TournamentId,
+        player_id: PlayerId,
+    ) -> Result<Vec<League>, DbError> {
+        crate::get_player_leagues(&self.pool, tournament, player_id).await
+    }
+
+    async fn reset_league_invite_code(&self, league: LeagueId) -> Result<String, DbError> {
+        crate::reset_league_invite_code(&self.pool, league).await
+    }
+
+    async fn delete_league(&self, league: LeagueId) -> Result<(), DbError> {
+        crate::delete_league(&self.pool, league).await
+    }
+
+    async fn get_league_members(&self, league: LeagueId) -> Result<Vec<LeagueMember>, DbError> {
+        crate::get_league_members(&self.pool, league).await
+    }
+
+    async fn get_league_role(
+        &self,
+        league: LeagueId,
+        player_id: PlayerId,
+    ) -> Result<Option<LeagueRole>, DbError> {
+        crate::get_league_role(&self.pool, league, player_id).await
+    }
+
+    async fn insert_league_member(
+        &self,
+        league: LeagueId,
+        player_id: PlayerId,
+    ) -> Result<(), DbError> {
+        crate::insert_league_member(&self.pool, league, player_id).await
+    }
+
+    async fn delete_league_member(
+        &self,
+        league: LeagueId,
+        player_id: PlayerId,
+    ) -> Result<(), DbError> {
+        crate::delete_league_member(&self.pool, league, player_id).await
+    }
+
+    async fn get_league_scoring_config(
+        &self,
+        league: LeagueId,
+    ) -> Result<Option<ScoringConfig>, DbError> {
+        crate::get_league_scoring_config(&self.pool, league).await
+    }
+
+    async fn set_league_scoring_config(
+        &self,
+        league: LeagueId,
+        config: Option<&ScoringConfig>,
+    ) -> Result<(), DbError> {
+        crate::set_league_scoring_config(&self.pool, league, config).await
+    }
+}
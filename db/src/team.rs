@@ -1,39 +1,53 @@
 //! Team operations
 
-use crate::{DbError, models::Team};
+use crate::DbError;
+use crate::decode::to_column;
+use crate::models::{Team, TournamentId};
 use sqlx::SqlitePool;
 
-/// Get all teams from the database
-pub async fn get_teams(pool: &SqlitePool) -> Result<Vec<wwc_core::Team>, DbError> {
-    let db_teams = sqlx::query_as::<_, Team>("SELECT * FROM teams")
+/// Get all teams of a tournament
+pub async fn get_teams(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+) -> Result<Vec<wwc_core::Team>, DbError> {
+    let db_teams = sqlx::query_as::<_, Team>("SELECT * FROM teams WHERE tournament_id = ?")
+        .bind(tournament)
         .fetch_all(pool)
         .await
         .map_err(DbError::Sqlx)?;
 
-    Ok(db_teams.into_iter().map(|team| team.into()).collect())
+    db_teams.into_iter().map(wwc_core::Team::try_from).collect()
 }
 
-/// Insert teams into the database
-pub async fn insert_teams(pool: &SqlitePool, teams: &[wwc_core::Team]) -> Result<(), DbError> {
+/// Insert teams into a tournament
+pub async fn insert_teams(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    teams: &[wwc_core::Team],
+) -> Result<(), DbError> {
     for team in teams {
         let name: &str = team.name.as_ref();
         let fifa_code: &str = team.fifa_code.as_ref();
-        sqlx::query("INSERT INTO teams (id, name, fifa_code, rank_) VALUES (?, ?, ?, ?)")
-            .bind(i32::try_from(u32::from(team.id)).unwrap())
-            .bind(name)
-            .bind(fifa_code)
-            .bind(i32::try_from(u32::from(team.rank)).unwrap())
-            .execute(pool)
-            .await
-            .map_err(DbError::Sqlx)?;
+        sqlx::query(
+            "INSERT INTO teams (tournament_id, id, name, fifa_code, rank_) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(tournament)
+        .bind(to_column("id", u32::from(team.id))?)
+        .bind(name)
+        .bind(fifa_code)
+        .bind(to_column("rank_", u32::from(team.rank))?)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
     }
 
     Ok(())
 }
 
-/// Clear all teams from the database
-pub async fn clear_teams(pool: &SqlitePool) -> Result<(), DbError> {
-    sqlx::query("DELETE FROM teams")
+/// Clear all teams of a tournament
+pub async fn clear_teams(pool: &SqlitePool, tournament: TournamentId) -> Result<(), DbError> {
+    sqlx::query("DELETE FROM teams WHERE tournament_id = ?")
+        .bind(tournament)
         .execute(pool)
         .await
         .map_err(DbError::Sqlx)?;
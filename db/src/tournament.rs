@@ -0,0 +1,118 @@
+//! Tournament operations
+//!
+//! Teams, games, groups, playoff sources, predictions and scoring configs belong to a tournament,
+//! the other operations take the [`TournamentId`] to work on.
+
+use crate::DbError;
+use crate::decode::RowRef;
+use crate::models::{Tournament, TournamentId};
+use sqlx::SqlitePool;
+use wwc_core::Date;
+use wwc_core::group::order::RuleSet;
+
+/// Get all tournaments, ordered by id
+pub async fn get_tournaments(pool: &SqlitePool) -> Result<Vec<Tournament>, DbError> {
+    let tournaments = sqlx::query_as::<_, Tournament>("SELECT * FROM tournaments ORDER BY id")
+        .fetch_all(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+
+    Ok(tournaments)
+}
+
+/// Get the id of a tournament by name, `None` if there is no such tournament
+pub async fn get_tournament_id(
+    pool: &SqlitePool,
+    name: &str,
+) -> Result<Option<TournamentId>, DbError> {
+    let id = sqlx::query_scalar::<_, TournamentId>("SELECT id FROM tournaments WHERE name = ?")
+        .bind(name)
+        .fetch_optional(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+
+    Ok(id)
+}
+
+/// Create a tournament, or get the id of the existing tournament with the same name
+pub async fn insert_tournament(pool: &SqlitePool, name: &str) -> Result<TournamentId, DbError> {
+    sqlx::query("INSERT OR IGNORE INTO tournaments (name) VALUES (?)")
+        .bind(name)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+
+    get_tournament_id(pool, name)
+        .await?
+        .ok_or_else(|| DbError::Generic(format!("Tournament '{}' was not created", name)))
+}
+
+fn rule_set_name(rules: RuleSet) -> &'static str {
+    match rules {
+        RuleSet::Fifa2018 => "fifa-2018",
+        RuleSet::Euro2020 => "euro-2020",
+    }
+}
+
+/// Get the group rules of a tournament
+pub async fn get_group_rules(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+) -> Result<RuleSet, DbError> {
+    let name = sqlx::query_scalar::<_, String>("SELECT group_rules FROM tournaments WHERE id = ?")
+        .bind(tournament)
+        .fetch_optional(pool)
+        .await
+        .map_err(DbError::Sqlx)?
+        .ok_or_else(|| DbError::Generic(format!("No tournament {}", tournament)))?;
+
+    match name.as_str() {
+        "fifa-2018" => Ok(RuleSet::Fifa2018),
+        "euro-2020" => Ok(RuleSet::Euro2020),
+        _ => Err(RowRef::new("tournaments", tournament)
+            .invalid("group_rules", format!("Unknown group rules: {}", name))),
+    }
+}
+
+/// Set the group rules of a tournament
+pub async fn set_group_rules(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+    rules: RuleSet,
+) -> Result<(), DbError> {
+    sqlx::query("UPDATE tournaments SET group_rules = ? WHERE id = ?")
+        .bind(rule_set_name(rules))
+        .bind(tournament)
+        .execute(pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+    Ok(())
+}
+
+/// Clear all data of a tournament, the tournament itself stays
+///
+/// The only operation deleting the (otherwise append-only) prediction history of a tournament.
+pub async fn clear_tournament_data(
+    pool: &SqlitePool,
+    tournament: TournamentId,
+) -> Result<(), DbError> {
+    // Child tables first to avoid foreign key constraints. Clearing the predictions records the
+    // clearing, which is deleted with the rest of the history.
+    let now = Date::from(chrono::Utc::now().fixed_offset());
+    crate::clear_leagues(pool, tournament).await?;
+    crate::clear_preds(pool, tournament, now).await?;
+    crate::clear_outcome_preds(pool, tournament, now).await?;
+    crate::history::clear_pred_history(pool, tournament).await?;
+    crate::clear_playoff_preds(pool, tournament).await?;
+    crate::clear_proba_preds(pool, tournament).await?;
+    crate::clear_bonus_questions(pool, tournament).await?;
+    crate::clear_jokers(pool, tournament).await?;
+    crate::clear_game_kickoffs(pool, tournament).await?;
+    crate::clear_playoff_results(pool, tournament).await?;
+    crate::clear_group_game_maps(pool, tournament).await?;
+    crate::clear_playoff_team_sources(pool, tournament).await?;
+    crate::clear_playoff_games(pool, tournament).await?;
+    crate::clear_games(pool, tournament).await?;
+    crate::clear_teams(pool, tournament).await?;
+    crate::clear_scoring_config(pool, tournament).await
+}
@@ -0,0 +1,129 @@
+#![allow(dead_code)]
+
+use sqlx::SqlitePool;
+use sqlx::sqlite::SqlitePoolOptions;
+use wwc_core::Date;
+use wwc_core::fair_play::FairPlayScore;
+use wwc_core::game::GoalCount;
+use wwc_core::group::GroupId;
+use wwc_core::group::game::{GroupGameScore, UnplayedGroupGame};
+use wwc_core::player::PlayerId;
+use wwc_core::team::{Team, TeamId, TeamRank};
+use wwc_db::{
+    MIGRATIONS, Step, TournamentId, ensure_player, insert_group_game_mappings, insert_played_games,
+    insert_teams, insert_tournament, insert_unplayed_games, run_migrations,
+};
+
+/// The player of [`tournament`]
+pub fn player() -> PlayerId {
+    PlayerId::from(1)
+}
+
+/// An empty in-memory database without migrations
+///
+/// Every connection opens its own in-memory database, so the pool keeps a single one.
+pub async fn empty_pool() -> SqlitePool {
+    SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap()
+}
+
+/// An in-memory database with all migrations applied
+pub async fn pool() -> SqlitePool {
+    let pool = empty_pool().await;
+    run_migrations(&pool).await.unwrap();
+    pool
+}
+
+/// An in-memory database migrated up to `version`, as by an older version of this crate
+pub async fn migrated_pool(version: i64) -> SqlitePool {
+    let pool = empty_pool().await;
+    sqlx::raw_sql(
+        "CREATE TABLE schema_version (
+           version INTEGER PRIMARY KEY NOT NULL,
+           name VARCHAR NOT NULL,
+           checksum VARCHAR NOT NULL,
+           applied_at DATETIME DEFAULT CURRENT_TIMESTAMP
+         )",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    for migration in MIGRATIONS.iter().filter(|m| m.version <= version) {
+        for step in migration.steps {
+            let sql = match step {
+                Step::Sql(sql) => sql.to_string(),
+                // Skipped like by the migrations, the scripts create the column
+                Step::AddColumn { table, column, .. } if has_column(&pool, table, column).await => {
+                    continue;
+                }
+                Step::AddColumn {
+                    table,
+                    column,
+                    definition,
+                } => format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            };
+            sqlx::raw_sql(&sql).execute(&pool).await.unwrap();
+        }
+        sqlx::query("INSERT INTO schema_version (version, name, checksum) VALUES (?, ?, ?)")
+            .bind(migration.version)
+            .bind(migration.name)
+            .bind(migration.checksum())
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+    pool
+}
+
+async fn has_column(pool: &SqlitePool, table: &str, column: &str) -> bool {
+    sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM pragma_table_info(?) WHERE name = ?")
+        .bind(table)
+        .bind(column)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+        > 0
+}
+
+pub fn score(home: u32, away: u32) -> GroupGameScore {
+    GroupGameScore::new(
+        GoalCount::try_from(home).unwrap(),
+        GoalCount::try_from(away).unwrap(),
+    )
+}
+
+/// A tournament with one group of four teams: game 1 is played 1-0, game 2 is not.
+/// Player 1 takes part.
+pub async fn tournament(pool: &SqlitePool) -> TournamentId {
+    let tournament = insert_tournament(pool, "test-cup").await.unwrap();
+    let teams: Vec<Team> = [(1, "Sweden", "SWE"), (2, "Denmark", "DEN")]
+        .into_iter()
+        .chain([(3, "Norway", "NOR"), (4, "Finland", "FIN")])
+        .map(|(id, name, code)| Team::try_new(TeamId(id), name, code, TeamRank(id)).unwrap())
+        .collect();
+    insert_teams(pool, tournament, &teams).await.unwrap();
+
+    let played = UnplayedGroupGame::try_new(1u32, 1u32, 2u32, Date::mock())
+        .unwrap()
+        .play(score(1, 0), FairPlayScore::default());
+    let unplayed = UnplayedGroupGame::try_new(2u32, 3u32, 4u32, Date::mock()).unwrap();
+    insert_played_games(pool, tournament, &[played])
+        .await
+        .unwrap();
+    insert_unplayed_games(pool, tournament, &[unplayed])
+        .await
+        .unwrap();
+    let group = GroupId::try_from('A').unwrap();
+    insert_group_game_mappings(
+        pool,
+        tournament,
+        &[(group, 1u32.into()), (group, 2u32.into())],
+    )
+    .await
+    .unwrap();
+    ensure_player(pool, player()).await.unwrap();
+    tournament
+}
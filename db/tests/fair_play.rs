@@ -0,0 +1,62 @@
+mod common;
+
+use wwc_core::Date;
+use wwc_core::fair_play::{FairPlay, FairPlayScore};
+use wwc_core::group::GroupId;
+use wwc_core::group::game::UnplayedGroupGame;
+use wwc_core::team::{Team, TeamId, TeamRank};
+use wwc_db::{
+    SqliteStore, get_group_games, insert_group_game_mappings, insert_played_games, insert_teams,
+    insert_tournament, load_groups, ranked_group_rules,
+};
+
+#[tokio::test]
+async fn stored_cards_decide_the_group_order() {
+    let pool = common::pool().await;
+    let tournament = insert_tournament(&pool, "fair-cup").await.unwrap();
+    let teams: Vec<Team> = [(1, "Sweden", "SWE"), (2, "Denmark", "DEN")]
+        .into_iter()
+        .chain([(3, "Norway", "NOR"), (4, "Finland", "FIN")])
+        .map(|(id, name, code)| Team::try_new(TeamId(id), name, code, TeamRank(id)).unwrap())
+        .collect();
+    insert_teams(&pool, tournament, &teams).await.unwrap();
+
+    // Every game is drawn 0-0, only the yellow card of Sweden in the first game separates it from
+    // the others
+    let yellow = FairPlayScore::new(FairPlay::new(1u32, 0, 0, 0), FairPlay::default());
+    let games: Vec<_> = [(1, 2), (3, 4), (1, 3), (2, 4), (1, 4), (2, 3)]
+        .into_iter()
+        .zip(1u32..)
+        .map(|((home, away), id)| {
+            let fair_play = if id == 1 {
+                yellow
+            } else {
+                FairPlayScore::default()
+            };
+            UnplayedGroupGame::try_new(id, home, away, Date::mock())
+                .unwrap()
+                .play(common::score(0, 0), fair_play)
+        })
+        .collect();
+    insert_played_games(&pool, tournament, &games)
+        .await
+        .unwrap();
+    let group = GroupId::try_from('A').unwrap();
+    let mappings: Vec<_> = games.iter().map(|game| (group, game.id)).collect();
+    insert_group_game_mappings(&pool, tournament, &mappings)
+        .await
+        .unwrap();
+
+    let (played, _) = get_group_games(&pool, tournament).await.unwrap();
+    let first = played.iter().find(|game| game.id == games[0].id).unwrap();
+    assert_eq!(first.fair_play(), yellow);
+
+    // Without the card Sweden, the best ranked team, would win the group
+    let store = SqliteStore::new(pool);
+    let groups = load_groups(&store, tournament).await.unwrap();
+    let (rules, _) = ranked_group_rules(&store, tournament, &groups)
+        .await
+        .unwrap();
+    let order: Vec<TeamId> = groups[&group].rank_teams(&rules).iter().copied().collect();
+    assert_eq!(order, [2, 3, 4, 1].map(TeamId));
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use std::collections::HashSet;
+use wwc_core::Date;
+use wwc_core::game::{GameId, GoalCount};
+use wwc_core::playoff::{PlayoffResult, PlayoffScore};
+use wwc_core::team::TeamId;
+use wwc_db::{
+    DbError, clear_group_game_result, clear_playoff_result, get_locked_games, insert_game_kickoffs,
+    insert_playoff_games, insert_playoff_result,
+};
+
+fn date(rfc3339: &str) -> Date {
+    Date::parse_rfc3339(rfc3339).unwrap()
+}
+
+#[tokio::test]
+async fn locked_games_have_kicked_off() {
+    let pool = common::pool().await;
+    let tournament = common::tournament(&pool).await;
+    let playoff_games = [3u32, 4].map(GameId::from);
+    insert_playoff_games(&pool, tournament, &playoff_games)
+        .await
+        .unwrap();
+    let score = PlayoffScore::regular_time(
+        GoalCount::try_from(2).unwrap(),
+        GoalCount::try_from(0).unwrap(),
+    )
+    .unwrap();
+    insert_playoff_result(
+        &pool,
+        tournament,
+        GameId::from(3),
+        &PlayoffResult::new(TeamId(1), TeamId(3), score),
+    )
+    .await
+    .unwrap();
+    let kickoff = date("2026-06-01T18:00:00+00:00");
+    insert_game_kickoffs(&pool, tournament, &[(GameId::from(2), kickoff)])
+        .await
+        .unwrap();
+
+    // Played group game 1 and playoff game 3 with a result
+    let before = date("2026-06-01T17:59:59+00:00");
+    let locked = get_locked_games(&pool, tournament, before).await.unwrap();
+    let expected: HashSet<GameId> = [1u32, 3].map(GameId::from).into();
+    assert_eq!(locked, expected);
+
+    // Group game 2 from its kickoff on
+    let locked = get_locked_games(&pool, tournament, kickoff).await.unwrap();
+    let expected: HashSet<GameId> = [1u32, 2, 3].map(GameId::from).into();
+    assert_eq!(locked, expected);
+}
+
+#[tokio::test]
+async fn out_of_range_game_ids_are_rejected() {
+    let pool = common::pool().await;
+    let tournament = common::tournament(&pool).await;
+    let too_large = GameId::from(u32::MAX);
+
+    let cleared = clear_group_game_result(&pool, tournament, too_large).await;
+    assert!(matches!(
+        cleared,
+        Err(DbError::OutOfRange { field: "id", .. })
+    ));
+    let cleared = clear_playoff_result(&pool, tournament, too_large).await;
+    assert!(matches!(
+        cleared,
+        Err(DbError::OutOfRange {
+            field: "game_id",
+            ..
+        })
+    ));
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use wwc_core::Date;
+use wwc_core::game::GameId;
+use wwc_core::group::game::{GroupGameOutcome, GroupGameScore};
+use wwc_core::player::{OutcomePrediction, PlayerPredictions, Prediction};
+use wwc_db::{
+    clear_player_outcome_preds, clear_player_preds, clear_preds, get_outcome_pred_history,
+    get_pred_history, get_preds_as_of, insert_validated_outcome_preds, insert_validated_preds,
+};
+
+fn date(rfc3339: &str) -> Date {
+    Date::parse_rfc3339(rfc3339).unwrap()
+}
+
+fn scores(preds: &[Prediction]) -> Vec<(GameId, GroupGameScore)> {
+    preds
+        .iter()
+        .map(|Prediction(id, score)| (*id, *score))
+        .collect()
+}
+
+#[tokio::test]
+async fn preds_as_of_follow_the_submissions() {
+    let pool = common::pool().await;
+    let tournament = common::tournament(&pool).await;
+    let player = common::player();
+    let game = GameId::from(2);
+
+    let first = date("2026-06-01T10:00:00+00:00");
+    let second = date("2026-06-01T12:00:00+00:00");
+    let cleared = date("2026-06-01T14:00:00+00:00");
+    for (at, score) in [(first, common::score(1, 1)), (second, common::score(2, 0))] {
+        let preds = PlayerPredictions::new(player, vec![Prediction(game, score)]);
+        insert_validated_preds(&pool, tournament, &preds, None, at)
+            .await
+            .unwrap();
+    }
+    clear_player_preds(&pool, tournament, player, None, cleared)
+        .await
+        .unwrap();
+
+    let as_of = async |at: &str| {
+        let preds = get_preds_as_of(&pool, tournament, player, None, date(at))
+            .await
+            .unwrap();
+        scores(&preds)
+    };
+    assert_eq!(as_of("2026-06-01T09:59:59+00:00").await, []);
+    assert_eq!(
+        as_of("2026-06-01T10:00:00+00:00").await,
+        [(game, common::score(1, 1))]
+    );
+    assert_eq!(
+        as_of("2026-06-01T11:00:00+00:00").await,
+        [(game, common::score(1, 1))]
+    );
+    assert_eq!(
+        as_of("2026-06-01T13:00:00+00:00").await,
+        [(game, common::score(2, 0))]
+    );
+    assert_eq!(as_of("2026-06-01T15:00:00+00:00").await, []);
+
+    // A bot has its own history
+    let bot = get_preds_as_of(&pool, tournament, player, Some("bot"), second)
+        .await
+        .unwrap();
+    assert!(bot.is_empty());
+}
+
+#[tokio::test]
+async fn outcome_preds_are_recorded_in_the_history() {
+    let pool = common::pool().await;
+    let tournament = common::tournament(&pool).await;
+    let player = common::player();
+    let submitted = date("2026-06-01T10:00:00+00:00");
+    let cleared = date("2026-06-01T12:00:00+00:00");
+
+    // Game 1 has been played, its prediction is rejected
+    let preds = [
+        OutcomePrediction(GameId::from(1), GroupGameOutcome::Draw),
+        OutcomePrediction(GameId::from(2), GroupGameOutcome::Win),
+    ];
+    insert_validated_outcome_preds(&pool, tournament, player, &preds, None, submitted)
+        .await
+        .unwrap();
+    clear_player_outcome_preds(&pool, tournament, player, None, cleared)
+        .await
+        .unwrap();
+
+    let history: Vec<(Date, Vec<(GameId, GroupGameOutcome)>)> =
+        get_outcome_pred_history(&pool, tournament, player, None)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|submission| {
+                let preds = submission
+                    .preds
+                    .iter()
+                    .map(|OutcomePrediction(id, outcome)| (*id, *outcome))
+                    .collect();
+                (submission.submitted_at, preds)
+            })
+            .collect();
+    assert_eq!(
+        history,
+        [
+            (submitted, vec![(GameId::from(2), GroupGameOutcome::Win)]),
+            (cleared, vec![]),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn clearing_all_preds_is_recorded() {
+    let pool = common::pool().await;
+    let tournament = common::tournament(&pool).await;
+    let player = common::player();
+    let submitted = date("2026-06-01T10:00:00+00:00");
+    let cleared = date("2026-06-01T12:00:00+00:00");
+    let preds = PlayerPredictions::new(
+        player,
+        vec![Prediction(GameId::from(2), common::score(1, 1))],
+    );
+    for bot_name in [None, Some("bot")] {
+        insert_validated_preds(&pool, tournament, &preds, bot_name, submitted)
+            .await
+            .unwrap();
+    }
+
+    clear_preds(&pool, tournament, cleared).await.unwrap();
+
+    for bot_name in [None, Some("bot")] {
+        let history = get_pred_history(&pool, tournament, player, bot_name)
+            .await
+            .unwrap();
+        let last = history.last().unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(last.submitted_at, cleared);
+        assert!(last.preds.is_empty());
+    }
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use sqlx::SqlitePool;
+use wwc_db::{DbError, MIGRATIONS, MigrationState, migration_status, run_migrations};
+
+/// The state of every migration, by version
+async fn states(pool: &SqlitePool) -> Vec<(i64, MigrationState)> {
+    migration_status(pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|status| (status.version, status.state))
+        .collect()
+}
+
+fn is_applied(state: &MigrationState) -> bool {
+    matches!(state, MigrationState::Applied { .. })
+}
+
+#[tokio::test]
+async fn status_of_a_new_database_is_read_only() {
+    let pool = common::empty_pool().await;
+
+    let states = states(&pool).await;
+    assert_eq!(states.len(), MIGRATIONS.len());
+    assert!(
+        states
+            .iter()
+            .all(|(_, state)| *state == MigrationState::Pending)
+    );
+    let tables: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sqlite_master")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(tables, 0);
+}
+
+#[tokio::test]
+async fn pending_migrations_are_applied() {
+    let pool = common::migrated_pool(10).await;
+
+    for (version, state) in states(&pool).await {
+        assert_eq!(is_applied(&state), version <= 10, "migration {}", version);
+    }
+
+    run_migrations(&pool).await.unwrap();
+    assert!(
+        states(&pool)
+            .await
+            .iter()
+            .all(|(_, state)| is_applied(state))
+    );
+}
+
+#[tokio::test]
+async fn changed_migrations_are_rejected() {
+    let pool = common::migrated_pool(10).await;
+    sqlx::query("UPDATE schema_version SET checksum = 'changed' WHERE version = 3")
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("INSERT INTO schema_version (version, name, checksum) VALUES (999, 'newer', '')")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let states = states(&pool).await;
+    assert!(matches!(
+        states.iter().find(|(version, _)| *version == 3),
+        Some((_, MigrationState::ChecksumMismatch { .. }))
+    ));
+    assert!(matches!(
+        states.last(),
+        Some((999, MigrationState::Unknown { .. }))
+    ));
+
+    // Nothing is applied
+    let result = run_migrations(&pool).await;
+    assert!(matches!(result, Err(DbError::Migration(_))));
+    let applied: i64 =
+        sqlx::query_scalar("SELECT MAX(version) FROM schema_version WHERE version < 999")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(applied, 10);
+}
+
+#[tokio::test]
+async fn existing_columns_are_not_added_again() {
+    // Migration 15 adds tournaments.group_rules, here added by hand before
+    let pool = common::migrated_pool(14).await;
+    sqlx::query(
+        "ALTER TABLE tournaments ADD COLUMN group_rules VARCHAR NOT NULL DEFAULT 'fifa-2018'",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    run_migrations(&pool).await.unwrap();
+    assert!(
+        states(&pool)
+            .await
+            .iter()
+            .all(|(_, state)| is_applied(state))
+    );
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use wwc_core::game::{GameId, GoalCount};
+use wwc_core::playoff::{BracketState, PlayoffScore};
+use wwc_core::team::TeamId;
+use wwc_db::{DbError, get_playoff_preds, insert_playoff_games, insert_playoff_preds};
+
+fn score(home: u32, away: u32) -> PlayoffScore {
+    PlayoffScore::regular_time(
+        GoalCount::try_from(home).unwrap(),
+        GoalCount::try_from(away).unwrap(),
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn failed_playoff_preds_keep_the_saved_bracket() {
+    let pool = common::pool().await;
+    let tournament = common::tournament(&pool).await;
+    let player = common::player();
+    insert_playoff_games(&pool, tournament, &[GameId::from(3), GameId::from(4)])
+        .await
+        .unwrap();
+
+    let saved = BracketState::new().play_game(GameId::from(3), TeamId(1), TeamId(2), score(2, 1));
+    insert_playoff_preds(&pool, tournament, player, &saved, None)
+        .await
+        .unwrap();
+
+    let too_large = saved.play_game(GameId::from(4), TeamId(3), TeamId(u32::MAX), score(1, 0));
+    let result = insert_playoff_preds(&pool, tournament, player, &too_large, None).await;
+    assert!(matches!(
+        result,
+        Err(DbError::OutOfRange {
+            field: "away_team",
+            ..
+        })
+    ));
+
+    let stored = get_playoff_preds(&pool, tournament, player, None)
+        .await
+        .unwrap();
+    assert_eq!(stored.num_played(), 1);
+    assert_eq!(
+        stored.result(GameId::from(3)),
+        saved.result(GameId::from(3))
+    );
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use wwc_core::Date;
+use wwc_core::game::GameId;
+use wwc_core::player::{PlayerPredictions, Prediction};
+use wwc_db::{clear_player_preds, insert_preds, insert_validated_preds, run_migrations};
+
+async fn pred_rows(pool: &sqlx::SqlitePool) -> Vec<(i32, i32, i32, Option<String>)> {
+    sqlx::query_as("SELECT id, game_id, home_result, bot_name FROM preds ORDER BY id")
+        .fetch_all(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn repeated_save_is_idempotent() {
+    let pool = common::pool().await;
+    let tournament = common::tournament(&pool).await;
+    let player = common::player();
+    let now = Date::parse_rfc3339("2026-06-01T10:00:00+00:00").unwrap();
+    let preds = PlayerPredictions::new(
+        player,
+        vec![Prediction(GameId::from(2), common::score(2, 1))],
+    );
+
+    insert_validated_preds(&pool, tournament, &preds, None, now)
+        .await
+        .unwrap();
+    let rows = pred_rows(&pool).await;
+    insert_validated_preds(&pool, tournament, &preds, None, now)
+        .await
+        .unwrap();
+
+    assert_eq!(rows.len(), 1);
+    assert_eq!(pred_rows(&pool).await, rows);
+}
+
+#[tokio::test]
+async fn clearing_keeps_the_preds_of_locked_games() {
+    let pool = common::pool().await;
+    let tournament = common::tournament(&pool).await;
+    let player = common::player();
+    let now = Date::parse_rfc3339("2026-06-01T10:00:00+00:00").unwrap();
+    // Saved before game 1 was played
+    let preds = PlayerPredictions::new(
+        player,
+        vec![
+            Prediction(GameId::from(1), common::score(1, 0)),
+            Prediction(GameId::from(2), common::score(2, 1)),
+        ],
+    );
+    for bot_name in [None, Some("bot")] {
+        insert_preds(&pool, tournament, &preds, bot_name)
+            .await
+            .unwrap();
+    }
+
+    // Without a bot, the predictions of the bot are cleared as well
+    clear_player_preds(&pool, tournament, player, None, now)
+        .await
+        .unwrap();
+    let kept: Vec<(i32, Option<String>)> = pred_rows(&pool)
+        .await
+        .into_iter()
+        .map(|(_, game_id, _, bot_name)| (game_id, bot_name))
+        .collect();
+    assert_eq!(kept, [(1, None), (1, Some("bot".to_string()))]);
+}
+
+#[tokio::test]
+async fn migration_011_removes_duplicate_preds() {
+    let pool = common::migrated_pool(10).await;
+    sqlx::raw_sql(
+        "INSERT INTO teams (id, name, fifa_code, rank_) VALUES (1, 'Sweden', 'SWE', 1);
+         INSERT INTO teams (id, name, fifa_code, rank_) VALUES (2, 'Denmark', 'DEN', 2);
+         INSERT INTO games (id, type_, home_team, away_team) VALUES (1, 'group', 1, 2);
+         INSERT INTO players (id, name) VALUES (1, 'Alice');
+         INSERT INTO preds (player_id, game_id, home_result, away_result, bot_name)
+         VALUES (1, 1, 1, 0, NULL), (1, 1, 2, 0, NULL), (1, 1, 3, 0, 'bot'), (1, 1, 4, 0, 'bot')",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    run_migrations(&pool).await.unwrap();
+
+    // The latest prediction of the player and of the bot are kept
+    assert_eq!(
+        pred_rows(&pool).await,
+        [(2, 1, 2, None), (4, 1, 4, Some("bot".to_string()))]
+    );
+}
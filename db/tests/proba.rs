@@ -0,0 +1,120 @@
+mod common;
+
+use wwc_core::Date;
+use wwc_core::game::GameId;
+use wwc_core::pred_score::proba::{OutcomeProbs, ProbForecast, ProbPrediction};
+use wwc_core::validation::PredStatus;
+use wwc_db::{
+    DbError, clear_bot_proba_preds, get_proba_preds, insert_proba_preds,
+    insert_validated_proba_preds,
+};
+
+#[tokio::test]
+async fn failed_proba_preds_keep_the_saved_ones() {
+    let pool = common::pool().await;
+    let tournament = common::tournament(&pool).await;
+    let player = common::player();
+    let forecast = ProbForecast::Outcome(OutcomeProbs::try_new(0.5, 0.3, 0.2).unwrap());
+
+    let saved = [ProbPrediction(GameId::from(2), forecast.clone())];
+    insert_proba_preds(&pool, tournament, player, "bot", &saved)
+        .await
+        .unwrap();
+
+    let too_large = [
+        ProbPrediction(GameId::from(1), forecast.clone()),
+        ProbPrediction(GameId::from(u32::MAX), forecast),
+    ];
+    let result = insert_proba_preds(&pool, tournament, player, "bot", &too_large).await;
+    assert!(matches!(
+        result,
+        Err(DbError::OutOfRange {
+            field: "game_id",
+            ..
+        })
+    ));
+
+    let stored = get_proba_preds(&pool, tournament, player, "bot")
+        .await
+        .unwrap();
+    assert_eq!(stored, saved);
+}
+
+fn forecast(home: f32) -> ProbForecast {
+    ProbForecast::Outcome(OutcomeProbs::try_new(home, 0.8 - home, 0.2).unwrap())
+}
+
+#[tokio::test]
+async fn locked_and_repeated_games_are_rejected() {
+    let pool = common::pool().await;
+    let tournament = common::tournament(&pool).await;
+    let player = common::player();
+    let now = Date::parse_rfc3339("2026-06-01T10:00:00+00:00").unwrap();
+
+    // Game 1 is played, its forecast stays once stored
+    let played = [ProbPrediction(GameId::from(1), forecast(0.1))];
+    insert_proba_preds(&pool, tournament, player, "bot", &played)
+        .await
+        .unwrap();
+
+    let preds = [
+        ProbPrediction(GameId::from(2), forecast(0.5)),
+        ProbPrediction(GameId::from(1), forecast(0.7)),
+        ProbPrediction(GameId::from(2), forecast(0.6)),
+        ProbPrediction(GameId::from(9), forecast(0.4)),
+    ];
+    let report = insert_validated_proba_preds(&pool, tournament, player, "bot", &preds, now)
+        .await
+        .unwrap();
+    let statuses: Vec<PredStatus> = report.checks.iter().map(|check| check.status).collect();
+    assert_eq!(
+        statuses,
+        [
+            PredStatus::Accepted,
+            PredStatus::RejectedLocked,
+            PredStatus::Duplicate,
+            PredStatus::RejectedUnknown,
+        ]
+    );
+
+    let mut stored = get_proba_preds(&pool, tournament, player, "bot")
+        .await
+        .unwrap();
+    stored.sort_by_key(|ProbPrediction(game_id, _)| *game_id);
+    assert_eq!(
+        stored,
+        [
+            played[0].clone(),
+            ProbPrediction(GameId::from(2), forecast(0.5))
+        ]
+    );
+
+    // Cleared, only the forecast of the played game is left
+    clear_bot_proba_preds(&pool, tournament, player, "bot", now)
+        .await
+        .unwrap();
+    let stored = get_proba_preds(&pool, tournament, player, "bot")
+        .await
+        .unwrap();
+    assert_eq!(stored, played);
+}
+
+#[tokio::test]
+async fn one_proba_pred_per_game() {
+    let pool = common::pool().await;
+    let tournament = common::tournament(&pool).await;
+    let player = common::player();
+
+    let preds = [
+        ProbPrediction(GameId::from(2), forecast(0.5)),
+        ProbPrediction(GameId::from(2), forecast(0.6)),
+    ];
+    insert_proba_preds(&pool, tournament, player, "bot", &preds)
+        .await
+        .unwrap();
+
+    let stored = get_proba_preds(&pool, tournament, player, "bot")
+        .await
+        .unwrap();
+    assert_eq!(stored, [ProbPrediction(GameId::from(2), forecast(0.6))]);
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use sqlx::SqlitePool;
+use wwc_core::Date;
+use wwc_core::game::GameId;
+use wwc_core::player::{PlayerPredictions, Prediction};
+use wwc_db::{DbError, Snapshot, export_snapshot, import_snapshot, insert_validated_preds};
+
+/// A tournament with predictions and their history
+async fn populated_pool() -> SqlitePool {
+    let pool = common::pool().await;
+    let tournament = common::tournament(&pool).await;
+    let now = Date::parse_rfc3339("2026-06-01T10:00:00+00:00").unwrap();
+    let preds = PlayerPredictions::new(
+        common::player(),
+        vec![Prediction(GameId::from(2), common::score(1, 2))],
+    );
+    for bot_name in [None, Some("bot")] {
+        insert_validated_preds(&pool, tournament, &preds, bot_name, now)
+            .await
+            .unwrap();
+    }
+    pool
+}
+
+fn row_count(snapshot: &Snapshot, table: &str) -> usize {
+    snapshot.tables.get(table).map_or(0, Vec::len)
+}
+
+#[tokio::test]
+async fn imported_snapshot_exports_the_same() {
+    let exported = export_snapshot(&populated_pool().await).await.unwrap();
+    assert_eq!(row_count(&exported, "preds"), 2);
+    assert_eq!(row_count(&exported, "pred_history"), 2);
+
+    let pool = common::pool().await;
+    import_snapshot(&pool, &exported, false).await.unwrap();
+    let reexported = export_snapshot(&pool).await.unwrap();
+    assert_eq!(reexported.schema_version, exported.schema_version);
+    assert_eq!(reexported.tables, exported.tables);
+}
+
+#[tokio::test]
+async fn dangling_references_are_rejected() {
+    let mut snapshot = export_snapshot(&populated_pool().await).await.unwrap();
+    // The predictions and submissions of the player are left without the player
+    snapshot.tables.remove("players");
+
+    let pool = common::pool().await;
+    let result = import_snapshot(&pool, &snapshot, false).await;
+    assert!(matches!(result, Err(DbError::Snapshot(reason)) if reason.contains("dangling")));
+
+    // Nothing is imported
+    let empty = export_snapshot(&pool).await.unwrap();
+    assert!(empty.row_counts().all(|(_, count)| count == 0));
+}
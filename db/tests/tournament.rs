@@ -0,0 +1,27 @@
+mod common;
+
+use wwc_core::group::order::RuleSet;
+use wwc_db::{get_group_rules, insert_tournament, set_group_rules};
+
+#[tokio::test]
+async fn group_rules_per_tournament() {
+    let pool = common::pool().await;
+    let fifa = insert_tournament(&pool, "fifa-2018").await.unwrap();
+    let euro = insert_tournament(&pool, "euro-2020").await.unwrap();
+    assert_eq!(
+        get_group_rules(&pool, fifa).await.unwrap(),
+        RuleSet::Fifa2018
+    );
+
+    set_group_rules(&pool, euro, RuleSet::Euro2020)
+        .await
+        .unwrap();
+    assert_eq!(
+        get_group_rules(&pool, euro).await.unwrap(),
+        RuleSet::Euro2020
+    );
+    assert_eq!(
+        get_group_rules(&pool, fifa).await.unwrap(),
+        RuleSet::Fifa2018
+    );
+}
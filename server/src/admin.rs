@@ -0,0 +1,407 @@
+use crate::AppError;
+use crate::cache::LeaderboardCache;
+use crate::live::Live;
+use crate::public::tournament_id;
+
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use serde::Deserialize;
+use tracing::info;
+use wwc_core::bonus::{BonusAnswer, BonusQuestion, BonusQuestionId};
+use wwc_core::error::WwcError;
+use wwc_core::fair_play::FairPlayScore;
+use wwc_core::game::{GameId, GoalCount};
+use wwc_core::group::game::{GroupGameScore, UnplayedGroupGame};
+use wwc_core::group::order::{Rules, Tiebreaker};
+use wwc_core::group::{GroupId, GroupOutcome, Groups};
+use wwc_core::live::LiveEvent;
+use wwc_core::playoff::bracket::EdgeType;
+use wwc_core::playoff::{BracketState, BracketStructure, PlayoffResult, PlayoffScore, TeamSource};
+use wwc_core::team::TeamId;
+use wwc_db::{Store, TournamentId, load_groups, ranked_group_rules};
+
+/// Add or replace a bonus question
+///
+/// Resolved questions are scored and can no longer be replaced.
+pub(crate) async fn add_bonus_question<S: Store>(
+    State(store): State<S>,
+    Extension(live): Extension<Live>,
+    Extension(cache): Extension<LeaderboardCache>,
+    Path(tournament): Path<String>,
+    Json(question): Json<BonusQuestion>,
+) -> Result<StatusCode, AppError> {
+    let tournament = tournament_id(&store, &tournament).await?;
+    if store
+        .get_bonus_questions(tournament)
+        .await?
+        .iter()
+        .any(|existing| existing.id == question.id && existing.solution.is_some())
+    {
+        return Err(AppError::Generic(format!(
+            "Bonus question {} is resolved and can no longer be replaced",
+            question.id
+        )));
+    }
+
+    store.insert_bonus_question(tournament, &question).await?;
+    cache.invalidate(tournament);
+    live.publish(tournament, LiveEvent::Leaderboard);
+
+    info!("Added bonus question {}: {}", question.id, question.text);
+    Ok(StatusCode::OK)
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ResolveBonusRequest {
+    id: BonusQuestionId,
+    /// Accepted answers
+    solution: Vec<BonusAnswer>,
+}
+
+/// Resolve a bonus question with its accepted answers
+///
+/// The answers must have the type of the question. Resolving again replaces the solution.
+pub(crate) async fn resolve_bonus_question<S: Store>(
+    State(store): State<S>,
+    Extension(live): Extension<Live>,
+    Extension(cache): Extension<LeaderboardCache>,
+    Path(tournament): Path<String>,
+    Json(request): Json<ResolveBonusRequest>,
+) -> Result<StatusCode, AppError> {
+    let tournament = tournament_id(&store, &tournament).await?;
+    let question = store
+        .get_bonus_questions(tournament)
+        .await?
+        .into_iter()
+        .find(|question| question.id == request.id)
+        .ok_or_else(|| AppError::Generic(format!("No bonus question with id {}", request.id)))?;
+    for answer in &request.solution {
+        question
+            .validate_answer(answer)
+            .map_err(wwc_core::error::WwcError::from)?;
+    }
+
+    store
+        .resolve_bonus_question(tournament, request.id, &request.solution)
+        .await?;
+    cache.invalidate(tournament);
+    live.publish(tournament, LiveEvent::Leaderboard);
+
+    info!(
+        "Resolved bonus question {} with {} accepted answers",
+        request.id,
+        request.solution.len()
+    );
+    Ok(StatusCode::OK)
+}
+
+fn goals(goals: u32) -> Result<GoalCount, AppError> {
+    Ok(GoalCount::try_from(goals).map_err(WwcError::from)?)
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct GroupResultRequest {
+    game_id: GameId,
+    home_goals: u32,
+    away_goals: u32,
+    /// Cards of both teams, none if left out
+    #[serde(default)]
+    fair_play: FairPlayScore,
+}
+
+/// Record or correct the result of a group game, with the cards of both teams
+///
+/// Rejected once a playoff game with a team from the game's group is played, since the result
+/// may change which teams advanced.
+pub(crate) async fn save_group_result<S: Store>(
+    State(store): State<S>,
+    Extension(live): Extension<Live>,
+    Extension(cache): Extension<LeaderboardCache>,
+    Path(tournament): Path<String>,
+    Json(request): Json<GroupResultRequest>,
+) -> Result<StatusCode, AppError> {
+    let tournament = tournament_id(&store, &tournament).await?;
+    let game = group_game(&store, tournament, request.game_id).await?;
+    check_group_open(&store, tournament, request.game_id).await?;
+
+    let score = GroupGameScore::new(goals(request.home_goals)?, goals(request.away_goals)?);
+    store
+        .insert_group_game_result(tournament, &game.play(score, request.fair_play))
+        .await?;
+    let event = LiveEvent::GroupResult {
+        game_id: request.game_id,
+    };
+    cache.invalidate(tournament);
+    live.result_changed(&store, tournament, event).await;
+
+    info!(
+        "Recorded result {} of group game {}",
+        score, request.game_id
+    );
+    Ok(StatusCode::OK)
+}
+
+/// Unset the result of a group game, with its cards
+pub(crate) async fn clear_group_result<S: Store>(
+    State(store): State<S>,
+    Extension(live): Extension<Live>,
+    Extension(cache): Extension<LeaderboardCache>,
+    Path((tournament, game_id)): Path<(String, u32)>,
+) -> Result<StatusCode, AppError> {
+    let tournament = tournament_id(&store, &tournament).await?;
+    let game_id = GameId::from(game_id);
+    check_group_open(&store, tournament, game_id).await?;
+    store.clear_group_game_result(tournament, game_id).await?;
+    cache.invalidate(tournament);
+    live.result_changed(&store, tournament, LiveEvent::GroupResult { game_id })
+        .await;
+
+    info!("Cleared result of group game {}", game_id);
+    Ok(StatusCode::OK)
+}
+
+/// Group game of a tournament, as if unplayed
+async fn group_game<S: Store>(
+    store: &S,
+    tournament: TournamentId,
+    game_id: GameId,
+) -> Result<UnplayedGroupGame, AppError> {
+    let (played, unplayed) = store.get_group_games(tournament).await?;
+    played
+        .into_iter()
+        .map(|game| game.unplay())
+        .chain(unplayed)
+        .find(|game| game.id == game_id)
+        .ok_or_else(|| AppError::Generic(format!("No group game {}", game_id)))
+}
+
+/// Errors if a played playoff game has a team from the group of the game
+async fn check_group_open<S: Store>(
+    store: &S,
+    tournament: TournamentId,
+    game_id: GameId,
+) -> Result<(), AppError> {
+    let Some(group) = store
+        .get_group_game_maps(tournament)
+        .await?
+        .into_iter()
+        .find_map(|(id, group)| (id == game_id).then_some(group))
+    else {
+        return Ok(());
+    };
+    let results = store.get_playoff_results(tournament).await?;
+    let sources = store.get_playoff_team_sources(tournament).await?;
+    match sources.iter().find(|(id, (home, away))| {
+        results.is_played(*id) && (from_group(home, group) || from_group(away, group))
+    }) {
+        Some((id, _)) => Err(AppError::Generic(format!(
+            "Playoff game {} has a team from group {}, clear its result first",
+            id, group
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn from_group(source: &TeamSource, group: GroupId) -> bool {
+    match source {
+        TeamSource::GroupOutcome(GroupOutcome::Winner(id) | GroupOutcome::RunnerUp(id)) => {
+            *id == group
+        }
+        TeamSource::GroupOutcome(GroupOutcome::ThirdPlace(ids)) => ids.contains(&group),
+        TeamSource::WinnerOf(_) | TeamSource::LoserOf(_) => false,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct PlayoffResultRequest {
+    game_id: GameId,
+    home: TeamId,
+    away: TeamId,
+    /// Goals after extra time
+    home_goals: u32,
+    away_goals: u32,
+    /// Penalty shootout, only if the game was drawn after extra time
+    home_penalty: Option<u32>,
+    away_penalty: Option<u32>,
+}
+
+/// Record or correct the result of a playoff game
+///
+/// The teams must be the game's participants, resolved from the final group standings and the
+/// results of earlier playoff games. A correction which changes the winner is rejected while a
+/// game the teams went on to is played.
+pub(crate) async fn save_playoff_result<S: Store>(
+    State(store): State<S>,
+    Extension(live): Extension<Live>,
+    Extension(cache): Extension<LeaderboardCache>,
+    Path(tournament): Path<String>,
+    Json(request): Json<PlayoffResultRequest>,
+) -> Result<StatusCode, AppError> {
+    let tournament = tournament_id(&store, &tournament).await?;
+    let score = PlayoffScore::try_new(
+        goals(request.home_goals)?,
+        goals(request.away_goals)?,
+        request.home_penalty.map(goals).transpose()?,
+        request.away_penalty.map(goals).transpose()?,
+    )
+    .map_err(WwcError::from)?;
+    let result = PlayoffResult::new(request.home, request.away, score);
+
+    let playoff = Playoff::load(&store, tournament).await?;
+    let (home, away) = participants(&store, tournament, &playoff, request.game_id).await?;
+    if (home, away) != (result.home, result.away) {
+        return Err(AppError::Generic(format!(
+            "Game {} is between teams {} and {}",
+            request.game_id, home, away
+        )));
+    }
+    if let Some(previous) = playoff.results.result(request.game_id)
+        && previous.winner() != result.winner()
+    {
+        playoff.check_unplayed_dependents(request.game_id)?;
+    }
+
+    store
+        .insert_playoff_result(tournament, request.game_id, &result)
+        .await?;
+    let event = LiveEvent::PlayoffResult {
+        game_id: request.game_id,
+    };
+    cache.invalidate(tournament);
+    live.result_changed(&store, tournament, event).await;
+
+    info!(
+        "Recorded result of playoff game {}: {} won",
+        request.game_id,
+        result.winner()
+    );
+    Ok(StatusCode::OK)
+}
+
+/// Unset the result of a playoff game
+///
+/// Rejected while a game its teams went on to is played.
+pub(crate) async fn clear_playoff_result<S: Store>(
+    State(store): State<S>,
+    Extension(live): Extension<Live>,
+    Extension(cache): Extension<LeaderboardCache>,
+    Path((tournament, game_id)): Path<(String, u32)>,
+) -> Result<StatusCode, AppError> {
+    let tournament = tournament_id(&store, &tournament).await?;
+    let game_id = GameId::from(game_id);
+    Playoff::load(&store, tournament)
+        .await?
+        .check_unplayed_dependents(game_id)?;
+    store.clear_playoff_result(tournament, game_id).await?;
+    cache.invalidate(tournament);
+    live.result_changed(&store, tournament, LiveEvent::PlayoffResult { game_id })
+        .await;
+
+    info!("Cleared result of playoff game {}", game_id);
+    Ok(StatusCode::OK)
+}
+
+/// Teams of a playoff game, see [`Playoff::participants`]
+///
+/// The group rules are not `Send`, so they are only built after the last await.
+async fn participants<S: Store>(
+    store: &S,
+    tournament: TournamentId,
+    playoff: &Playoff,
+    game_id: GameId,
+) -> Result<(TeamId, TeamId), AppError> {
+    let groups = load_groups(store, tournament).await?;
+    let (rules, _) = ranked_group_rules(store, tournament, &groups).await?;
+    playoff.participants(game_id, &groups, &rules)
+}
+
+/// The playoff of a tournament, with its results
+struct Playoff {
+    sources: Vec<(GameId, (TeamSource, TeamSource))>,
+    bracket: BracketStructure,
+    results: BracketState,
+}
+
+impl Playoff {
+    async fn load<S: Store>(store: &S, tournament: TournamentId) -> Result<Self, AppError> {
+        let sources = store.get_playoff_team_sources(tournament).await?;
+        if sources.is_empty() {
+            return Err(AppError::Generic(
+                "The tournament has no playoff".to_string(),
+            ));
+        }
+        let bracket = BracketStructure::from_team_sources(&sources).map_err(WwcError::from)?;
+        let results = store.get_playoff_results(tournament).await?;
+        Ok(Playoff {
+            sources,
+            bracket,
+            results,
+        })
+    }
+
+    /// Teams of a game, from the group standings and the results of earlier games
+    ///
+    /// Errors if a team is not known yet, i.e. its group is not finished or its previous game
+    /// is not played.
+    fn participants<T: Tiebreaker>(
+        &self,
+        game_id: GameId,
+        groups: &Groups,
+        rules: &Rules<T>,
+    ) -> Result<(TeamId, TeamId), AppError> {
+        let (home, away) = self
+            .sources
+            .iter()
+            .find_map(|(id, sources)| (*id == game_id).then_some(sources))
+            .ok_or_else(|| AppError::Generic(format!("No playoff game {}", game_id)))?;
+        let team = |source: &TeamSource| {
+            if let TeamSource::GroupOutcome(outcome) = source {
+                check_groups_finished(outcome, groups)?;
+            }
+            self.bracket
+                .resolve_team(source.clone(), &self.results, groups, rules)
+                .ok_or_else(|| {
+                    AppError::Generic(format!(
+                        "Team '{}' of game {} is not known yet",
+                        source, game_id
+                    ))
+                })
+        };
+        Ok((team(home)?, team(away)?))
+    }
+
+    /// Errors if a game which gets the winner or loser of the game is played
+    fn check_unplayed_dependents(&self, game_id: GameId) -> Result<(), AppError> {
+        let played = [EdgeType::Winner, EdgeType::Loser]
+            .into_iter()
+            .flat_map(|edge| self.bracket.dependents(game_id, edge))
+            .find(|dependent| self.results.is_played(*dependent));
+        match played {
+            Some(dependent) => Err(AppError::Generic(format!(
+                "Game {} has a team from game {}, clear its result first",
+                dependent, game_id
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Errors unless all groups of the outcome exist and all their games are played
+fn check_groups_finished(outcome: &GroupOutcome, groups: &Groups) -> Result<(), AppError> {
+    let group_ids = match outcome {
+        GroupOutcome::Winner(id) | GroupOutcome::RunnerUp(id) => vec![*id],
+        GroupOutcome::ThirdPlace(ids) => ids.iter().copied().collect(),
+    };
+    for id in group_ids {
+        let group = groups
+            .get(&id)
+            .ok_or_else(|| AppError::Generic(format!("No group {}", id)))?;
+        if group.unplayed_games().next().is_some() {
+            return Err(AppError::Generic(format!("Group {} is not finished", id)));
+        }
+    }
+    Ok(())
+}
@@ -9,9 +9,9 @@ use axum::{
 };
 use jsonwebtoken::{DecodingKey, Validation, decode};
 use serde::{Deserialize, Serialize};
-use sqlx::SqlitePool;
 use std::env;
 use tracing::info;
+use wwc_db::Store;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
@@ -41,12 +41,13 @@ pub(crate) struct LoginResponse {
 }
 
 /// Login endpoint
-pub(crate) async fn login(
-    State(pool): State<SqlitePool>,
+pub(crate) async fn login<S: Store>(
+    State(store): State<S>,
     Json(login_req): Json<LoginRequest>,
 ) -> Result<Json<LoginResponse>, AppError> {
     // Get user by username
-    let user = wwc_db::get_user_by_username(&pool, &login_req.username)
+    let user = store
+        .get_user_by_username(&login_req.username)
         .await?
         .ok_or_else(|| AppError::Generic("Invalid username or password".to_string()))?;
 
@@ -104,21 +105,41 @@ fn generate_jwt_token(player_id: i32, bot_name: Option<String>) -> String {
     .expect("Failed to generate JWT token")
 }
 
-/// Admin middleware - checks for ADMIN_SECRET in Authorization header
-pub async fn admin_auth_middleware(req: Request, next: Next) -> Result<Response, StatusCode> {
+/// Secret of the admin routes, expected as the bearer token of their requests
+///
+/// Without a secret, the admin routes are unavailable.
+#[derive(Clone)]
+pub struct AdminSecret(Option<String>);
+
+impl AdminSecret {
+    pub fn new(secret: impl Into<String>) -> Self {
+        AdminSecret(Some(secret.into()).filter(|secret| !secret.is_empty()))
+    }
+
+    /// The secret in the ADMIN_SECRET environment variable, if set
+    pub fn from_env() -> Self {
+        let secret = env::var("ADMIN_SECRET").unwrap_or_else(|_| {
+            eprintln!("Warning: ADMIN_SECRET not set");
+            String::new()
+        });
+        AdminSecret::new(secret)
+    }
+}
+
+/// Admin middleware - checks for the admin secret in Authorization header
+pub async fn admin_auth_middleware(
+    State(admin_secret): State<AdminSecret>,
+    req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
     let auth_header = req
         .headers()
         .get(header::AUTHORIZATION)
         .and_then(|h| h.to_str().ok());
 
-    let admin_secret = env::var("ADMIN_SECRET").unwrap_or_else(|_| {
-        eprintln!("Warning: ADMIN_SECRET not set");
-        String::new()
-    });
-
-    if admin_secret.is_empty() {
+    let Some(admin_secret) = admin_secret.0 else {
         return Err(StatusCode::INTERNAL_SERVER_ERROR);
-    }
+    };
 
     match auth_header {
         Some(header) if header == format!("Bearer {}", admin_secret) => Ok(next.run(req).await),
@@ -0,0 +1,70 @@
+//! Cache of computed leaderboards
+//!
+//! Scoring all competitors is repeated for every request otherwise. The leaderboards of a
+//! tournament are dropped whenever its results or predictions change.
+use crate::public::LeaderboardQuery;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use wwc_core::leaderboard::Leaderboard;
+use wwc_db::TournamentId;
+
+#[derive(Debug, Default)]
+struct TournamentEntry {
+    /// Increased on every invalidation
+    generation: u64,
+    leaderboards: HashMap<LeaderboardQuery, Arc<Leaderboard>>,
+}
+
+/// Leaderboards per tournament and query, shared by all handlers
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LeaderboardCache {
+    entries: Arc<Mutex<HashMap<TournamentId, TournamentEntry>>>,
+}
+
+impl LeaderboardCache {
+    fn entries(&self) -> MutexGuard<'_, HashMap<TournamentId, TournamentEntry>> {
+        // Every update leaves the map consistent, even if a holder of the lock panicked
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// The cached leaderboard, or else the generation to insert the computed one with
+    pub(crate) fn get(
+        &self,
+        tournament: TournamentId,
+        query: LeaderboardQuery,
+    ) -> Result<Arc<Leaderboard>, u64> {
+        let entries = self.entries();
+        let entry = entries.get(&tournament);
+        entry
+            .and_then(|entry| entry.leaderboards.get(&query).cloned())
+            .ok_or_else(|| entry.map_or(0, |entry| entry.generation))
+    }
+
+    /// Cache a leaderboard computed at `generation`
+    ///
+    /// Ignored if the tournament was invalidated since, the leaderboard may be outdated.
+    pub(crate) fn insert(
+        &self,
+        tournament: TournamentId,
+        generation: u64,
+        query: LeaderboardQuery,
+        leaderboard: Arc<Leaderboard>,
+    ) {
+        let mut entries = self.entries();
+        let entry = entries.entry(tournament).or_default();
+        if entry.generation == generation {
+            entry.leaderboards.insert(query, leaderboard);
+        }
+    }
+
+    /// Drop the leaderboards of a tournament
+    pub(crate) fn invalidate(&self, tournament: TournamentId) {
+        let mut entries = self.entries();
+        let entry = entries.entry(tournament).or_default();
+        entry.generation += 1;
+        entry.leaderboards.clear();
+    }
+}
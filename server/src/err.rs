@@ -10,8 +10,6 @@ pub(crate) enum AppError {
     Db(#[from] wwc_db::DbError),
     #[error("WWC core error: {0}")]
     Wwc(#[from] WwcError),
-    #[error("SQLx error: {0}")]
-    Sqlx(#[from] sqlx::Error),
     #[error("{0}")]
     Generic(String),
 }
@@ -24,9 +22,12 @@ impl IntoResponse for AppError {
         }
 
         let (status, error_message) = match self {
+            // Ids from the client which cannot be stored
+            AppError::Db(e @ wwc_db::DbError::OutOfRange { .. }) => {
+                (StatusCode::BAD_REQUEST, e.to_string())
+            }
             AppError::Db(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
             AppError::Wwc(e) => (StatusCode::BAD_REQUEST, e.to_string()),
-            AppError::Sqlx(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
             AppError::Generic(msg) => (StatusCode::BAD_REQUEST, msg),
         };
 
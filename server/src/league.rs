@@ -0,0 +1,348 @@
+use crate::AppError;
+
+use crate::auth::AuthUser;
+use crate::public::{LeaderboardQuery, ensure_player_exists, leaderboard_history, tournament_id};
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tracing::info;
+use wwc_core::leaderboard::history::LeaderboardHistory;
+use wwc_core::player::PlayerId;
+use wwc_core::pred_score::scheme::ScoringConfig;
+use wwc_db::Store;
+use wwc_db::{League, LeagueId, LeagueRole};
+
+/// A league as seen by one of its members
+#[derive(Debug, Serialize)]
+pub(crate) struct LeagueInfo {
+    id: i32,
+    name: String,
+    invite_code: String,
+    role: LeagueRole,
+}
+
+impl LeagueInfo {
+    fn new(league: League, role: LeagueRole) -> Self {
+        LeagueInfo {
+            id: league.id.0,
+            name: league.name,
+            invite_code: league.invite_code,
+            role,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct LeagueMemberInfo {
+    player_id: i32,
+    role: LeagueRole,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct CreateLeagueRequest {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct JoinLeagueRequest {
+    invite_code: String,
+}
+
+/// Look up a league which the authenticated player is a member of, with the player's role
+async fn member_league<S: Store>(
+    store: &S,
+    league_id: i32,
+    auth_user: &AuthUser,
+) -> Result<(League, LeagueRole), AppError> {
+    let league = store
+        .get_league(LeagueId(league_id))
+        .await?
+        .ok_or_else(|| AppError::Generic(format!("No league {}", league_id)))?;
+    let role = store
+        .get_league_role(league.id, PlayerId::from(auth_user.player_id))
+        .await?
+        .ok_or_else(|| AppError::Generic(format!("Not a member of league {}", league_id)))?;
+    Ok((league, role))
+}
+
+/// Look up a league which the authenticated player owns
+async fn owned_league<S: Store>(
+    store: &S,
+    league_id: i32,
+    auth_user: &AuthUser,
+) -> Result<League, AppError> {
+    match member_league(store, league_id, auth_user).await? {
+        (league, LeagueRole::Owner) => Ok(league),
+        (_, LeagueRole::Member) => Err(AppError::Generic(format!(
+            "Only the owner can manage league {}",
+            league_id
+        ))),
+    }
+}
+
+/// The league's scoring config, or else the tournament's
+async fn league_scoring_config<S: Store>(
+    store: &S,
+    league: &League,
+) -> Result<ScoringConfig, AppError> {
+    match store.get_league_scoring_config(league.id).await? {
+        Some(config) => Ok(config),
+        None => store
+            .get_scoring_config(league.tournament_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::Generic(format!("No scoring config for league {}", league.id))
+            }),
+    }
+}
+
+/// Create a league in a tournament, owned by the authenticated player
+pub(crate) async fn create_league<S: Store>(
+    State(store): State<S>,
+    Path(tournament): Path<String>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<CreateLeagueRequest>,
+) -> Result<Json<LeagueInfo>, AppError> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(AppError::Generic("League name cannot be empty".to_string()));
+    }
+
+    let tournament = tournament_id(&store, &tournament).await?;
+    let player_id = PlayerId::from(auth_user.player_id);
+    ensure_player_exists(&store, player_id).await?;
+    let league = store.insert_league(tournament, name, player_id).await?;
+
+    info!(
+        "Player {} created league {} '{}'",
+        player_id, league.id, league.name
+    );
+    Ok(Json(LeagueInfo::new(league, LeagueRole::Owner)))
+}
+
+/// Join the league with an invite code
+///
+/// Joining a league again is a no-op, the player keeps their role.
+pub(crate) async fn join_league<S: Store>(
+    State(store): State<S>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<JoinLeagueRequest>,
+) -> Result<Json<LeagueInfo>, AppError> {
+    let league = store
+        .get_league_by_invite_code(request.invite_code.trim())
+        .await?
+        .ok_or_else(|| AppError::Generic("Invalid invite code".to_string()))?;
+
+    let player_id = PlayerId::from(auth_user.player_id);
+    ensure_player_exists(&store, player_id).await?;
+    store.insert_league_member(league.id, player_id).await?;
+    let role = store
+        .get_league_role(league.id, player_id)
+        .await?
+        .unwrap_or(LeagueRole::Member);
+
+    info!("Player {} joined league {}", player_id, league.id);
+    Ok(Json(LeagueInfo::new(league, role)))
+}
+
+/// Get the leagues of a tournament which the authenticated player is a member of
+pub(crate) async fn get_my_leagues<S: Store>(
+    State(store): State<S>,
+    Path(tournament): Path<String>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<Vec<LeagueInfo>>, AppError> {
+    let tournament = tournament_id(&store, &tournament).await?;
+    let player_id = PlayerId::from(auth_user.player_id);
+    let mut leagues = Vec::new();
+    for league in store.get_player_leagues(tournament, player_id).await? {
+        let role = store
+            .get_league_role(league.id, player_id)
+            .await?
+            .unwrap_or(LeagueRole::Member);
+        leagues.push(LeagueInfo::new(league, role));
+    }
+
+    info!(
+        "Retrieved {} leagues for player {}",
+        leagues.len(),
+        player_id
+    );
+    Ok(Json(leagues))
+}
+
+/// Get the members of a league (members only)
+pub(crate) async fn get_league_members<S: Store>(
+    State(store): State<S>,
+    Path(league_id): Path<i32>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<Vec<LeagueMemberInfo>>, AppError> {
+    let (league, _) = member_league(&store, league_id, &auth_user).await?;
+    let members: Vec<LeagueMemberInfo> = store
+        .get_league_members(league.id)
+        .await?
+        .into_iter()
+        .map(|member| LeagueMemberInfo {
+            player_id: member.player_id,
+            role: member.role,
+        })
+        .collect();
+
+    info!(
+        "Retrieved {} members of league {}",
+        members.len(),
+        league.id
+    );
+    Ok(Json(members))
+}
+
+/// Leave a league, the owner deletes the league instead
+pub(crate) async fn leave_league<S: Store>(
+    State(store): State<S>,
+    Path(league_id): Path<i32>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<StatusCode, AppError> {
+    let (league, role) = member_league(&store, league_id, &auth_user).await?;
+    if role == LeagueRole::Owner {
+        return Err(AppError::Generic(
+            "The owner cannot leave the league, delete it instead".to_string(),
+        ));
+    }
+    let player_id = PlayerId::from(auth_user.player_id);
+    store.delete_league_member(league.id, player_id).await?;
+
+    info!("Player {} left league {}", player_id, league.id);
+    Ok(StatusCode::OK)
+}
+
+/// Remove a member from a league (owner only)
+pub(crate) async fn remove_league_member<S: Store>(
+    State(store): State<S>,
+    Path((league_id, player_id)): Path<(i32, i32)>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<StatusCode, AppError> {
+    let league = owned_league(&store, league_id, &auth_user).await?;
+    if player_id == auth_user.player_id {
+        return Err(AppError::Generic(
+            "The owner cannot be removed from the league".to_string(),
+        ));
+    }
+    store
+        .delete_league_member(league.id, PlayerId::from(player_id))
+        .await?;
+
+    info!("Removed player {} from league {}", player_id, league.id);
+    Ok(StatusCode::OK)
+}
+
+/// Replace the invite code of a league (owner only), returns the new code
+pub(crate) async fn reset_league_invite_code<S: Store>(
+    State(store): State<S>,
+    Path(league_id): Path<i32>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<String>, AppError> {
+    let league = owned_league(&store, league_id, &auth_user).await?;
+    let invite_code = store.reset_league_invite_code(league.id).await?;
+
+    info!("Reset the invite code of league {}", league.id);
+    Ok(Json(invite_code))
+}
+
+/// Delete a league (owner only), the members' predictions are kept
+pub(crate) async fn delete_league<S: Store>(
+    State(store): State<S>,
+    Path(league_id): Path<i32>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<StatusCode, AppError> {
+    let league = owned_league(&store, league_id, &auth_user).await?;
+    store.delete_league(league.id).await?;
+
+    info!("Deleted league {} '{}'", league.id, league.name);
+    Ok(StatusCode::OK)
+}
+
+/// Get the scoring config of a league (members only), the tournament's unless the league has one
+pub(crate) async fn get_league_scoring_config<S: Store>(
+    State(store): State<S>,
+    Path(league_id): Path<i32>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ScoringConfig>, AppError> {
+    let (league, _) = member_league(&store, league_id, &auth_user).await?;
+    let config = league_scoring_config(&store, &league).await?;
+
+    info!("Retrieved scoring config for league {}", league.id);
+    Ok(Json(config))
+}
+
+/// Set the scoring config of a league (owner only), `null` to use the tournament's config
+///
+/// Predictions are shared with the tournament and its other leagues, so the league's config must
+/// keep the tournament's prediction mode and joker rules, only the points may differ.
+pub(crate) async fn save_league_scoring_config<S: Store>(
+    State(store): State<S>,
+    Path(league_id): Path<i32>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(config): Json<Option<ScoringConfig>>,
+) -> Result<StatusCode, AppError> {
+    let league = owned_league(&store, league_id, &auth_user).await?;
+    if let Some(config) = &config {
+        let tournament_config = store
+            .get_scoring_config(league.tournament_id)
+            .await?
+            .unwrap_or_else(|| ScoringConfig::new(config.scheme));
+        if config.mode != tournament_config.mode {
+            return Err(AppError::Generic(format!(
+                "League prediction mode must be the tournament's ({:?})",
+                tournament_config.mode
+            )));
+        }
+        if config.jokers != tournament_config.jokers {
+            return Err(AppError::Generic(
+                "League joker rules must be the tournament's".to_string(),
+            ));
+        }
+    }
+    store
+        .set_league_scoring_config(league.id, config.as_ref())
+        .await?;
+
+    info!("Saved scoring config for league {}", league.id);
+    Ok(StatusCode::OK)
+}
+
+/// Get the leaderboard history of a league (members only)
+///
+/// Like the tournament's leaderboard history, but with the league members (and their bots) as
+/// the only competitors, scored with the league's scoring config.
+pub(crate) async fn get_league_leaderboard_history<S: Store>(
+    State(store): State<S>,
+    Path(league_id): Path<i32>,
+    Query(query): Query<LeaderboardQuery>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<LeaderboardHistory>, AppError> {
+    let (league, _) = member_league(&store, league_id, &auth_user).await?;
+    let config = league_scoring_config(&store, &league).await?;
+    let members: HashSet<PlayerId> = store
+        .get_league_members(league.id)
+        .await?
+        .into_iter()
+        .map(|member| PlayerId::from(member.player_id))
+        .collect();
+    let preds = store
+        .get_all_competitor_preds(league.tournament_id)
+        .await?
+        .into_iter()
+        .filter(|preds| members.contains(&preds.competitor.player_id))
+        .collect();
+    let history = leaderboard_history(&store, league.tournament_id, &config, preds, query).await?;
+
+    info!(
+        "Computed leaderboard history for league {} over {} games",
+        league.id,
+        history.len()
+    );
+    Ok(Json(history))
+}
@@ -0,0 +1,245 @@
+//! Live updates, pushed to clients as Server-Sent Events
+//!
+//! Every event gets an id, increasing over the lifetime of the server. The latest events are kept,
+//! so that a client which reconnects with the id of the last event it received (the
+//! `Last-Event-ID` header, or the `last_event_id` query parameter) gets the events it missed.
+//! If they are no longer kept, e.g. after a restart, the client gets a [`LiveEvent::Reset`]
+//! instead and should reload everything.
+use crate::AppError;
+use crate::public::tournament_id;
+
+use axum::{
+    Extension,
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures::{Stream, StreamExt, stream};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{error, warn};
+use wwc_core::Date;
+use wwc_core::game::GameId;
+use wwc_core::live::LiveEvent;
+use wwc_db::{DbError, Store, TournamentId};
+
+/// Number of latest events kept for clients which resume
+const REPLAY_LEN: usize = 256;
+/// Interval between the checks for games which have kicked off
+const LOCK_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// An event of a tournament, with its id
+#[derive(Debug, Clone)]
+struct Published {
+    id: u64,
+    tournament: TournamentId,
+    event: LiveEvent,
+}
+
+#[derive(Debug)]
+struct LiveState {
+    next_id: u64,
+    replay: VecDeque<Published>,
+    /// Games with locked predictions, per tournament, as of the last check
+    locked: HashMap<TournamentId, HashSet<GameId>>,
+}
+
+/// Publishes the live events of all tournaments, shared by all handlers
+#[derive(Debug, Clone)]
+pub(crate) struct Live {
+    sender: broadcast::Sender<Published>,
+    state: Arc<Mutex<LiveState>>,
+}
+
+impl Live {
+    /// Ids start from the current time in milliseconds, so that ids from before a restart are
+    /// lower than all ids after it
+    pub(crate) fn new() -> Self {
+        let start = u64::try_from(chrono::Utc::now().timestamp_millis()).unwrap_or_default();
+        let (sender, _) = broadcast::channel(REPLAY_LEN);
+        Live {
+            sender,
+            state: Arc::new(Mutex::new(LiveState {
+                next_id: start,
+                replay: VecDeque::with_capacity(REPLAY_LEN),
+                locked: HashMap::new(),
+            })),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, LiveState> {
+        // The state stays consistent even if a holder of the lock panicked
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub(crate) fn publish(&self, tournament: TournamentId, event: LiveEvent) {
+        let mut state = self.state();
+        let published = Published {
+            id: state.next_id,
+            tournament,
+            event,
+        };
+        state.next_id += 1;
+        if state.replay.len() == REPLAY_LEN {
+            state.replay.pop_front();
+        }
+        state.replay.push_back(published.clone());
+        // Sent while holding the lock, so that subscribers neither miss nor repeat events.
+        // Fails only if no client is connected.
+        let _ = self.sender.send(published);
+    }
+
+    /// Events of a tournament after `last_id`, and the receiver of all later events
+    ///
+    /// Only a reset if some events after `last_id` are no longer kept.
+    fn subscribe(
+        &self,
+        tournament: TournamentId,
+        last_id: Option<u64>,
+    ) -> (Vec<Published>, broadcast::Receiver<Published>) {
+        let state = self.state();
+        let receiver = self.sender.subscribe();
+        let Some(last_id) = last_id else {
+            return (Vec::new(), receiver);
+        };
+
+        let oldest = state.replay.front().map_or(state.next_id, |event| event.id);
+        if last_id + 1 < oldest || last_id >= state.next_id {
+            let reset = Published {
+                id: state.next_id - 1,
+                tournament,
+                event: LiveEvent::Reset,
+            };
+            return (vec![reset], receiver);
+        }
+        let missed = state
+            .replay
+            .iter()
+            .filter(|event| event.id > last_id && event.tournament == tournament)
+            .cloned()
+            .collect();
+        (missed, receiver)
+    }
+
+    /// Publish the games whose predictions were locked since the last check
+    ///
+    /// Games are locked once they have kicked off or have a result. Unlocked games, e.g. after a
+    /// result is cleared, are published again once they lock again.
+    pub(crate) async fn check_locks<S: Store>(
+        &self,
+        store: &S,
+        tournament: TournamentId,
+    ) -> Result<(), DbError> {
+        let now = Date::from(chrono::Utc::now().fixed_offset());
+        let locked = store.get_locked_games(tournament, now).await?;
+        let mut newly_locked: Vec<GameId> = {
+            let mut state = self.state();
+            let previous = state.locked.insert(tournament, locked.clone());
+            locked
+                .into_iter()
+                .filter(|game_id| previous.as_ref().is_none_or(|ids| !ids.contains(game_id)))
+                .collect()
+        };
+        if !newly_locked.is_empty() {
+            newly_locked.sort();
+            self.publish(
+                tournament,
+                LiveEvent::PredsLocked {
+                    game_ids: newly_locked,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Publish a changed result, which changes the leaderboard and may lock the game
+    ///
+    /// The result is already stored, so a failed lock check is only logged.
+    pub(crate) async fn result_changed<S: Store>(
+        &self,
+        store: &S,
+        tournament: TournamentId,
+        event: LiveEvent,
+    ) {
+        self.publish(tournament, event);
+        self.publish(tournament, LiveEvent::Leaderboard);
+        if let Err(e) = self.check_locks(store, tournament).await {
+            warn!("Locked games not checked: {}", e);
+        }
+    }
+}
+
+/// Check all tournaments for newly locked games, until the server stops
+pub(crate) async fn watch_locks<S: Store>(store: S, live: Live) {
+    let mut interval = tokio::time::interval(LOCK_CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        let tournaments = match store.get_tournaments().await {
+            Ok(tournaments) => tournaments,
+            Err(e) => {
+                error!("Tournaments not loaded for the lock check: {}", e);
+                continue;
+            }
+        };
+        for tournament in tournaments {
+            if let Err(e) = live.check_locks(&store, tournament.id).await {
+                error!("Locked games of {} not checked: {}", tournament.name, e);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ResumeQuery {
+    /// Alternative to the `Last-Event-ID` header, for clients which open a new connection
+    last_event_id: Option<u64>,
+}
+
+/// Stream the live events of a tournament
+///
+/// Resumes after the event id in the `Last-Event-ID` header or the `last_event_id` query
+/// parameter, if given.
+pub(crate) async fn live_events<S: Store>(
+    State(store): State<S>,
+    Extension(live): Extension<Live>,
+    Path(tournament): Path<String>,
+    Query(query): Query<ResumeQuery>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, AppError> {
+    let tournament = tournament_id(&store, &tournament).await?;
+    let last_id = headers
+        .get("last-event-id")
+        .and_then(|id| id.to_str().ok()?.parse().ok())
+        .or(query.last_event_id);
+
+    let (missed, receiver) = live.subscribe(tournament, last_id);
+    let later = stream::unfold(receiver, move |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) if event.tournament == tournament => {
+                    return Some((sse_event(&event), receiver));
+                }
+                Ok(_) => {}
+                // Sent without an id, the client resumes from the event before
+                Err(RecvError::Lagged(_)) => {
+                    return Some((Event::default().json_data(LiveEvent::Reset), receiver));
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    let events = stream::iter(missed.iter().map(sse_event).collect::<Vec<_>>()).chain(later);
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+fn sse_event(published: &Published) -> Result<Event, axum::Error> {
+    Event::default()
+        .id(published.id.to_string())
+        .json_data(&published.event)
+}
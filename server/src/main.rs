@@ -1,24 +1,51 @@
 mod admin;
 mod auth;
+mod cache;
 mod err;
+mod league;
+mod live;
 mod public;
 mod user;
 
+#[cfg(test)]
+mod tests;
+
 use err::AppError;
 
-use crate::public::{get_display_names, get_groups, get_playoff_team_sources, get_teams};
-use crate::user::{clear_my_preds, get_current_user, get_preds, save_preds};
+use crate::admin::{
+    add_bonus_question, clear_group_result, clear_playoff_result, resolve_bonus_question,
+    save_group_result, save_playoff_result,
+};
+use crate::cache::LeaderboardCache;
+use crate::league::{
+    create_league, delete_league, get_league_leaderboard_history, get_league_members,
+    get_league_scoring_config, get_my_leagues, join_league, leave_league, remove_league_member,
+    reset_league_invite_code, save_league_scoring_config,
+};
+use crate::live::{Live, live_events, watch_locks};
+use crate::public::{
+    get_bonus_questions, get_crowd_preds, get_display_names, get_groups, get_leaderboard,
+    get_leaderboard_history, get_playoff_team_sources, get_proba_scores, get_score_breakdown,
+    get_scoring_config, get_teams, get_tournaments, tournament_id,
+};
+use crate::user::{
+    clear_my_preds, get_bonus_preds, get_current_user, get_jokers, get_outcome_preds,
+    get_playoff_preds, get_pred_history, get_preds, get_proba_preds, save_bonus_preds, save_jokers,
+    save_outcome_preds, save_playoff_preds, save_preds, save_proba_preds,
+};
 use axum::{
-    Router,
-    extract::State,
+    Extension, Router,
+    extract::{Path, State},
     http::StatusCode,
     middleware,
-    routing::{get, post, put},
+    routing::{delete, get, post, put},
 };
-use sqlx::SqlitePool;
 use std::net::SocketAddr;
 use tower_http::cors::{Any, CorsLayer};
 use tracing::info;
+use wwc_core::Date;
+use wwc_core::live::LiveEvent;
+use wwc_db::{SqliteStore, Store};
 
 #[tokio::main]
 async fn main() {
@@ -30,29 +57,165 @@ async fn main() {
         .await
         .expect("Failed to create database pool");
 
-    // Build our application with routes
+    let store = SqliteStore::new(pool);
+    let live = Live::new();
+    tokio::spawn(watch_locks(store.clone(), live.clone()));
+    let app = app(store, live, auth::AdminSecret::from_env());
+
+    // Run the server
+    let addr = SocketAddr::from(([127, 0, 0, 1], 8000));
+    info!("Server listening on {}", addr);
+
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .expect("Failed to bind to address");
+
+    axum::serve(listener, app).await.expect("Server error");
+}
+
+/// Build the application with all routes, on top of a store
+///
+/// The server runs on the SQLite store, tests on the in-memory store. Handlers publish live
+/// events through `live`, admin routes require `admin_secret`.
+fn app<S: Store>(store: S, live: Live, admin_secret: auth::AdminSecret) -> Router {
+    // Tournament data is requested by tournament name, e.g. /get_groups/fifa-2018
     // Public routes (no auth required)
     let public_routes = Router::new()
-        .route("/get_teams", get(get_teams))
-        .route("/get_groups", get(get_groups))
-        .route("/get_playoff_team_sources", get(get_playoff_team_sources))
-        .route("/get_display_names", get(get_display_names))
-        .route("/login", post(auth::login));
+        .route("/get_tournaments", get(get_tournaments::<S>))
+        .route("/get_teams/:tournament", get(get_teams::<S>))
+        .route("/get_groups/:tournament", get(get_groups::<S>))
+        .route(
+            "/get_playoff_team_sources/:tournament",
+            get(get_playoff_team_sources::<S>),
+        )
+        .route("/get_display_names", get(get_display_names::<S>))
+        .route(
+            "/get_scoring_config/:tournament",
+            get(get_scoring_config::<S>),
+        )
+        .route("/get_leaderboard/:tournament", get(get_leaderboard::<S>))
+        .route(
+            "/get_score_breakdown/:tournament/:player_id",
+            get(get_score_breakdown::<S>),
+        )
+        .route(
+            "/get_leaderboard_history/:tournament",
+            get(get_leaderboard_history::<S>),
+        )
+        .route("/get_proba_scores/:tournament", get(get_proba_scores::<S>))
+        .route(
+            "/get_bonus_questions/:tournament",
+            get(get_bonus_questions::<S>),
+        )
+        .route("/get_crowd_preds/:tournament", get(get_crowd_preds::<S>))
+        // Server-Sent Events, see `live`
+        .route("/events/:tournament", get(live_events::<S>))
+        .route("/login", post(auth::login::<S>));
 
     // User-authenticated routes (requires JWT token)
     let user_routes = Router::new()
-        .route("/get_preds/:player_id", get(get_preds))
-        .route("/save_preds", put(save_preds))
-        .route("/clear_my_preds", get(clear_my_preds))
-        .route("/me", get(get_current_user))
+        .route("/get_preds/:tournament/:player_id", get(get_preds::<S>))
+        .route("/save_preds/:tournament", put(save_preds::<S>))
+        .route(
+            "/get_pred_history/:tournament/:player_id",
+            get(get_pred_history::<S>),
+        )
+        .route(
+            "/get_playoff_preds/:tournament/:player_id",
+            get(get_playoff_preds::<S>),
+        )
+        .route(
+            "/save_playoff_preds/:tournament",
+            put(save_playoff_preds::<S>),
+        )
+        .route(
+            "/get_proba_preds/:tournament/:player_id",
+            get(get_proba_preds::<S>),
+        )
+        .route("/save_proba_preds/:tournament", put(save_proba_preds::<S>))
+        .route(
+            "/get_bonus_preds/:tournament/:player_id",
+            get(get_bonus_preds::<S>),
+        )
+        .route("/save_bonus_preds/:tournament", put(save_bonus_preds::<S>))
+        .route(
+            "/get_outcome_preds/:tournament/:player_id",
+            get(get_outcome_preds::<S>),
+        )
+        .route(
+            "/save_outcome_preds/:tournament",
+            put(save_outcome_preds::<S>),
+        )
+        .route("/get_jokers/:tournament/:player_id", get(get_jokers::<S>))
+        .route("/save_jokers/:tournament", put(save_jokers::<S>))
+        .route("/clear_my_preds/:tournament", get(clear_my_preds::<S>))
+        .route("/me", get(get_current_user::<S>))
+        // Private leagues, requested by league id, e.g. /get_league_members/3
+        .route("/create_league/:tournament", put(create_league::<S>))
+        .route("/join_league", put(join_league::<S>))
+        .route("/get_my_leagues/:tournament", get(get_my_leagues::<S>))
+        .route(
+            "/get_league_members/:league_id",
+            get(get_league_members::<S>),
+        )
+        .route("/leave_league/:league_id", post(leave_league::<S>))
+        .route(
+            "/remove_league_member/:league_id/:player_id",
+            delete(remove_league_member::<S>),
+        )
+        .route(
+            "/reset_league_invite_code/:league_id",
+            post(reset_league_invite_code::<S>),
+        )
+        .route("/delete_league/:league_id", delete(delete_league::<S>))
+        .route(
+            "/get_league_scoring_config/:league_id",
+            get(get_league_scoring_config::<S>),
+        )
+        .route(
+            "/save_league_scoring_config/:league_id",
+            put(save_league_scoring_config::<S>),
+        )
+        .route(
+            "/get_league_leaderboard_history/:league_id",
+            get(get_league_leaderboard_history::<S>),
+        )
         .route_layer(middleware::from_fn(auth::user_auth_middleware));
 
-    // Admin-only routes (requires ADMIN_SECRET)
+    // Admin-only routes (requires the admin secret)
     let admin_routes = Router::new()
-        .route("/clear_preds", get(clear_preds))
-        .route_layer(middleware::from_fn(auth::admin_auth_middleware));
+        .route("/clear_preds/:tournament", get(clear_preds::<S>))
+        .route(
+            "/add_bonus_question/:tournament",
+            put(add_bonus_question::<S>),
+        )
+        .route(
+            "/resolve_bonus_question/:tournament",
+            put(resolve_bonus_question::<S>),
+        )
+        // Results, recorded again to correct them
+        .route(
+            "/save_group_result/:tournament",
+            put(save_group_result::<S>),
+        )
+        .route(
+            "/clear_group_result/:tournament/:game_id",
+            delete(clear_group_result::<S>),
+        )
+        .route(
+            "/save_playoff_result/:tournament",
+            put(save_playoff_result::<S>),
+        )
+        .route(
+            "/clear_playoff_result/:tournament/:game_id",
+            delete(clear_playoff_result::<S>),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            admin_secret,
+            auth::admin_auth_middleware,
+        ));
 
-    let app = Router::new()
+    Router::new()
         .merge(public_routes)
         .merge(user_routes)
         .merge(admin_routes)
@@ -64,25 +227,33 @@ async fn main() {
                     axum::http::header::CONTENT_TYPE,
                     axum::http::header::AUTHORIZATION,
                     axum::http::header::ACCEPT,
+                    axum::http::header::HeaderName::from_static("last-event-id"),
                 ]),
         )
-        .with_state(pool);
-
-    // Run the server
-    let addr = SocketAddr::from(([127, 0, 0, 1], 8000));
-    info!("Server listening on {}", addr);
-
-    let listener = tokio::net::TcpListener::bind(addr)
-        .await
-        .expect("Failed to bind to address");
-
-    axum::serve(listener, app).await.expect("Server error");
+        .layer(Extension(live))
+        .layer(Extension(LeaderboardCache::default()))
+        .with_state(store)
 }
 
-/// Clear all predictions (admin only)
-async fn clear_preds(State(pool): State<SqlitePool>) -> Result<StatusCode, AppError> {
-    wwc_db::clear_preds(&pool).await?;
+/// Clear all predictions of a tournament (admin only)
+async fn clear_preds<S: Store>(
+    State(store): State<S>,
+    Extension(live): Extension<Live>,
+    Extension(cache): Extension<LeaderboardCache>,
+    Path(tournament): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let id = tournament_id(&store, &tournament).await?;
+    // Recorded in the prediction history, like players clearing their own predictions
+    let now = Date::from(chrono::Utc::now().fixed_offset());
+    store.clear_preds(id, now).await?;
+    store.clear_playoff_preds(id).await?;
+    store.clear_proba_preds(id).await?;
+    store.clear_bonus_preds(id).await?;
+    store.clear_outcome_preds(id, now).await?;
+    store.clear_jokers(id).await?;
+    cache.invalidate(id);
+    live.publish(id, LiveEvent::Leaderboard);
 
-    info!("All predictions cleared");
+    info!("All predictions of {} cleared", tournament);
     Ok(StatusCode::OK)
 }
@@ -1,20 +1,78 @@
 use crate::AppError;
+use crate::cache::LeaderboardCache;
 
-use axum::{Json, extract::State};
-use itertools::Itertools;
-use sqlx::SqlitePool;
-use std::collections::{BTreeMap, HashMap};
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+};
+use serde::Deserialize;
+use std::sync::Arc;
 use tracing::info;
-use wwc_core::error::WwcError;
+use wwc_core::Date;
+use wwc_core::bonus::BonusQuestion;
+use wwc_core::crowd::{CrowdMethod, crowd_predictions};
 use wwc_core::game::GameId;
-use wwc_core::group::{Group, GroupId, Groups, game::PlayedGroupGame, game::UnplayedGroupGame};
-use wwc_core::player::PlayerId;
+use wwc_core::group::Groups;
+use wwc_core::leaderboard::history::LeaderboardHistory;
+use wwc_core::leaderboard::{
+    Competitor, CompetitorFilter, CompetitorPredictions, Leaderboard, LeaderboardRow,
+    LeaderboardRules,
+};
+use wwc_core::player::{PlayerId, Prediction};
 use wwc_core::playoff::TeamSource;
+use wwc_core::pred_score::playoff::SimplePlayoffPredScoreFn;
+use wwc_core::pred_score::proba::{ProbScoreRow, proba_table};
+use wwc_core::pred_score::scheme::ScoringConfig;
 use wwc_core::team::Teams;
+use wwc_db::TournamentId;
+use wwc_db::{Store, TournamentScoring, load_groups};
+
+/// Get the names of all tournaments
+pub(crate) async fn get_tournaments<S: Store>(
+    State(store): State<S>,
+) -> Result<Json<Vec<String>>, AppError> {
+    let names: Vec<String> = store
+        .get_tournaments()
+        .await?
+        .into_iter()
+        .map(|tournament| tournament.name)
+        .collect();
+
+    info!("Retrieved {} tournaments", names.len());
+    Ok(Json(names))
+}
+
+/// Look up a tournament by the name in a route
+pub(crate) async fn tournament_id<S: Store>(
+    store: &S,
+    tournament: &str,
+) -> Result<TournamentId, AppError> {
+    store
+        .get_tournament_id(tournament)
+        .await?
+        .ok_or_else(|| AppError::Generic(format!("No tournament '{}'", tournament)))
+}
+
+/// Get the scoring config of a tournament, which must have one
+pub(crate) async fn scoring_config<S: Store>(
+    store: &S,
+    tournament: &str,
+) -> Result<(TournamentId, ScoringConfig), AppError> {
+    let id = tournament_id(store, tournament).await?;
+    let config = store.get_scoring_config(id).await?.ok_or_else(|| {
+        AppError::Generic(format!("No scoring config for tournament '{}'", tournament))
+    })?;
+    Ok((id, config))
+}
 
 /// Get teams
-pub(crate) async fn get_teams(State(pool): State<SqlitePool>) -> Result<Json<Teams>, AppError> {
-    let teams: Teams = wwc_db::get_teams(&pool)
+pub(crate) async fn get_teams<S: Store>(
+    State(store): State<S>,
+    Path(tournament): Path<String>,
+) -> Result<Json<Teams>, AppError> {
+    let tournament = tournament_id(&store, &tournament).await?;
+    let teams: Teams = store
+        .get_teams(tournament)
         .await?
         .into_iter()
         .map(|x| (x.id, x))
@@ -28,90 +86,264 @@ pub(crate) async fn get_teams(State(pool): State<SqlitePool>) -> Result<Json<Tea
 ///
 /// Returns the team sources for all playoff games.
 /// This is used to build the BracketStructure on the client.
-pub(crate) async fn get_playoff_team_sources(
-    State(pool): State<SqlitePool>,
+pub(crate) async fn get_playoff_team_sources<S: Store>(
+    State(store): State<S>,
+    Path(tournament): Path<String>,
 ) -> Result<Json<Vec<(GameId, (TeamSource, TeamSource))>>, AppError> {
-    let team_sources = wwc_db::get_playoff_team_sources(&pool).await?;
+    let tournament = tournament_id(&store, &tournament).await?;
+    let team_sources = store.get_playoff_team_sources(tournament).await?;
 
     info!("Retrieved {} playoff team sources", team_sources.len());
     Ok(Json(team_sources))
 }
 
+/// Get the scoring config of a tournament
+///
+/// All clients score predictions with this config, so that scores are identical everywhere.
+pub(crate) async fn get_scoring_config<S: Store>(
+    State(store): State<S>,
+    Path(tournament): Path<String>,
+) -> Result<Json<ScoringConfig>, AppError> {
+    let (_, config) = scoring_config(&store, &tournament).await?;
+
+    info!("Retrieved scoring config for {}", tournament);
+    Ok(Json(config))
+}
+
 /// Get groups
 ///
 /// Loads group games and a GameId: GroupId map from the db.
 /// The games (played and unplayed) are then mapped to prospective groups.
 /// The final groups are validated (with a fallible constructor) and collected together.
-pub(crate) async fn get_groups(State(pool): State<SqlitePool>) -> Result<Json<Groups>, AppError> {
-    let (played_games, unplayed_games) = wwc_db::get_group_games(&pool).await?;
-
-    let game_group_map = wwc_db::get_group_game_maps(&pool)
-        .await?
-        .into_iter()
-        .collect::<HashMap<GameId, GroupId>>();
-
-    let empty_groups = game_group_map
-        .iter()
-        .map(|(_game_id, group_id)| group_id)
-        .unique();
-
-    let groups_played =
-        played_games.into_iter().fold(
-            empty_groups
-                .clone()
-                .map(|group_id| (*group_id, Vec::new()))
-                .collect::<BTreeMap<GroupId, Vec<PlayedGroupGame>>>(),
-            |mut acc, game| {
-                let entry = acc
-                    .entry(*game_group_map.get(&game.id).unwrap_or_else(|| {
-                        panic!("game group map discrepancy: no id: {:?}", game.id)
-                    }))
-                    .or_insert_with(Vec::new);
-                entry.push(game);
-                acc
-            },
-        );
-
-    let groups_unplayed =
-        unplayed_games.into_iter().fold(
-            empty_groups
-                .clone()
-                .map(|group_id| (*group_id, Vec::new()))
-                .collect::<BTreeMap<GroupId, Vec<UnplayedGroupGame>>>(),
-            |mut acc, game| {
-                let entry = acc
-                    .entry(*game_group_map.get(&game.id).unwrap_or_else(|| {
-                        panic!("game group map discrepancy: no id: {:?}", game.id)
-                    }))
-                    .or_insert_with(Vec::new);
-                entry.push(game);
-                acc
-            },
-        );
-
-    let groups: Result<Groups, WwcError> = groups_played
-        .into_iter()
-        .zip(groups_unplayed.into_iter())
-        .map(
-            |((group_id_played, played), (group_id_unplayed, unplayed))| {
-                assert!(group_id_played == group_id_unplayed);
-                Group::try_new(unplayed, played)
-                    .map(|group| (group_id_played, group))
-                    .map_err(WwcError::from)
-            },
-        )
-        .collect();
-
-    let groups = groups?;
+/// Played games carry the cards of both teams, which the fair play tiebreaker uses.
+pub(crate) async fn get_groups<S: Store>(
+    State(store): State<S>,
+    Path(tournament): Path<String>,
+) -> Result<Json<Groups>, AppError> {
+    let tournament = tournament_id(&store, &tournament).await?;
+    let groups = load_groups(&store, tournament).await?;
     info!("Retrieved {} groups", groups.len());
     Ok(Json(groups))
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+pub(crate) struct LeaderboardQuery {
+    #[serde(default)]
+    filter: CompetitorFilter,
+    /// Rank the crowd as a baseline
+    crowd: Option<CrowdMethod>,
+}
+
+/// Get the leaderboard history of a tournament
+///
+/// Standings of all competitors after each played game, scored with the tournament's
+/// scoring config. Optionally restricted to humans or bots with `?filter=humans|bots`, and with
+/// the crowd of those competitors as a baseline with e.g. `?crowd=modal_score`. Predicted group
+/// standings are scored if the config awards points for them.
+pub(crate) async fn get_leaderboard_history<S: Store>(
+    State(store): State<S>,
+    Path(tournament): Path<String>,
+    Query(query): Query<LeaderboardQuery>,
+) -> Result<Json<LeaderboardHistory>, AppError> {
+    let (id, config) = scoring_config(&store, &tournament).await?;
+    let preds = store.get_all_competitor_preds(id).await?;
+    let history = leaderboard_history(&store, id, &config, preds, query).await?;
+
+    info!(
+        "Computed leaderboard history for {} over {} games",
+        tournament,
+        history.len()
+    );
+    Ok(Json(history))
+}
+
+/// Get the current leaderboard of a tournament
+///
+/// Ranks, points per stage and per game of all competitors, scored with the tournament's scoring
+/// config. Takes the same query parameters as the leaderboard history. Leaderboards are cached
+/// until the results or predictions of the tournament change.
+pub(crate) async fn get_leaderboard<S: Store>(
+    State(store): State<S>,
+    Extension(cache): Extension<LeaderboardCache>,
+    Path(tournament): Path<String>,
+    Query(query): Query<LeaderboardQuery>,
+) -> Result<Json<Leaderboard>, AppError> {
+    let (id, config) = scoring_config(&store, &tournament).await?;
+    let leaderboard = cached_leaderboard(&store, &cache, id, &config, query).await?;
+
+    info!(
+        "Retrieved leaderboard of {} with {} competitors",
+        tournament,
+        leaderboard.len()
+    );
+    Ok(Json(Leaderboard::clone(&leaderboard)))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct BreakdownQuery {
+    /// Bot of the player, the player's own predictions if left out
+    bot: Option<String>,
+}
+
+/// Get the score breakdown of a player or one of the player's bots (`?bot=name`)
+///
+/// The competitor's row of the full leaderboard: rank, points per stage and the points of every
+/// scored game.
+pub(crate) async fn get_score_breakdown<S: Store>(
+    State(store): State<S>,
+    Extension(cache): Extension<LeaderboardCache>,
+    Path((tournament, player_id)): Path<(String, i32)>,
+    Query(query): Query<BreakdownQuery>,
+) -> Result<Json<LeaderboardRow>, AppError> {
+    let (id, config) = scoring_config(&store, &tournament).await?;
+    let leaderboard =
+        cached_leaderboard(&store, &cache, id, &config, LeaderboardQuery::default()).await?;
+    let competitor = Competitor {
+        player_id: PlayerId::from(player_id),
+        bot_name: query.bot,
+    };
+    let row = leaderboard.row(&competitor).cloned().ok_or_else(|| {
+        AppError::Generic(format!(
+            "No predictions of player {} (bot: {:?}) in '{}'",
+            player_id, competitor.bot_name, tournament
+        ))
+    })?;
+
+    info!(
+        "Retrieved score breakdown of player {} (bot: {:?}) over {} games",
+        player_id,
+        competitor.bot_name,
+        row.games.len()
+    );
+    Ok(Json(row))
+}
+
+/// The leaderboard of a tournament from the cache, computed if it is not cached
+async fn cached_leaderboard<S: Store>(
+    store: &S,
+    cache: &LeaderboardCache,
+    id: TournamentId,
+    config: &ScoringConfig,
+    query: LeaderboardQuery,
+) -> Result<Arc<Leaderboard>, AppError> {
+    match cache.get(id, query) {
+        Ok(leaderboard) => Ok(leaderboard),
+        Err(generation) => {
+            let preds = store.get_all_competitor_preds(id).await?;
+            let computed = Arc::new(leaderboard(store, id, config, preds, query).await?);
+            cache.insert(id, generation, query, Arc::clone(&computed));
+            Ok(computed)
+        }
+    }
+}
+
+/// Standings of the competitors after each played game of a tournament, scored with the config
+pub(crate) async fn leaderboard_history<S: Store>(
+    store: &S,
+    id: TournamentId,
+    config: &ScoringConfig,
+    preds: Vec<CompetitorPredictions>,
+    query: LeaderboardQuery,
+) -> Result<LeaderboardHistory, AppError> {
+    let scoring = TournamentScoring::load(store, id, config, preds).await?;
+    Ok(leaderboard_rules(config, &query).history(scoring.preds(), &scoring.truth()))
+}
+
+/// Current standings of the competitors of a tournament, scored with the config
+pub(crate) async fn leaderboard<S: Store>(
+    store: &S,
+    id: TournamentId,
+    config: &ScoringConfig,
+    preds: Vec<CompetitorPredictions>,
+    query: LeaderboardQuery,
+) -> Result<Leaderboard, AppError> {
+    let scoring = TournamentScoring::load(store, id, config, preds).await?;
+    Ok(leaderboard_rules(config, &query).leaderboard(scoring.preds(), &scoring.truth()))
+}
+
+fn leaderboard_rules(
+    config: &ScoringConfig,
+    query: &LeaderboardQuery,
+) -> LeaderboardRules<ScoringConfig, SimplePlayoffPredScoreFn> {
+    let rules = LeaderboardRules::from_config(config).with_filter(query.filter);
+    match query.crowd {
+        Some(method) => rules.with_crowd(method),
+        None => rules,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct CrowdQuery {
+    #[serde(default)]
+    method: CrowdMethod,
+    #[serde(default)]
+    filter: CompetitorFilter,
+}
+
+/// Get the crowd's predictions, aggregated from all competitors' predictions
+///
+/// Only games which have kicked off are included, so the crowd can't be copied.
+/// The aggregation is chosen with `?method=modal_score|median_goals|outcome_majority`.
+pub(crate) async fn get_crowd_preds<S: Store>(
+    State(store): State<S>,
+    Path(tournament): Path<String>,
+    Query(query): Query<CrowdQuery>,
+) -> Result<Json<Vec<Prediction>>, AppError> {
+    let tournament = tournament_id(&store, &tournament).await?;
+    let now = Date::from(chrono::Utc::now().fixed_offset());
+    let locked = store.get_locked_games(tournament, now).await?;
+    let preds = store.get_all_competitor_preds(tournament).await?;
+
+    let crowd_preds = crowd_predictions(
+        preds
+            .iter()
+            .filter(|preds| query.filter.includes(&preds.competitor))
+            .flat_map(|preds| &preds.group)
+            .filter(|Prediction(game_id, _)| locked.contains(game_id)),
+        query.method,
+    );
+
+    info!(
+        "Aggregated {} crowd predictions ({:?})",
+        crowd_preds.len(),
+        query.method
+    );
+    Ok(Json(crowd_preds))
+}
+
+/// Get all bonus questions, including the solutions of resolved questions
+pub(crate) async fn get_bonus_questions<S: Store>(
+    State(store): State<S>,
+    Path(tournament): Path<String>,
+) -> Result<Json<Vec<BonusQuestion>>, AppError> {
+    let tournament = tournament_id(&store, &tournament).await?;
+    let questions = store.get_bonus_questions(tournament).await?;
+
+    info!("Retrieved {} bonus questions", questions.len());
+    Ok(Json(questions))
+}
+
+/// Get the proper scores (Brier, log loss, RPS) of all bots with probabilistic predictions
+///
+/// Best (lowest) mean RPS first. Only played group games are scored.
+pub(crate) async fn get_proba_scores<S: Store>(
+    State(store): State<S>,
+    Path(tournament): Path<String>,
+) -> Result<Json<Vec<ProbScoreRow>>, AppError> {
+    let tournament = tournament_id(&store, &tournament).await?;
+    let groups = load_groups(&store, tournament).await?;
+    let preds = store.get_all_proba_preds(tournament).await?;
+    let table = proba_table(&preds, &groups);
+
+    info!("Computed proper scores for {} bots", table.len());
+    Ok(Json(table))
+}
+
 /// Get all display names
-pub(crate) async fn get_display_names(
-    State(pool): State<SqlitePool>,
+pub(crate) async fn get_display_names<S: Store>(
+    State(store): State<S>,
 ) -> Result<Json<Vec<(i32, Option<String>, String)>>, AppError> {
-    let display_names = wwc_db::get_all_display_names(&pool).await?;
+    let display_names = store.get_all_display_names().await?;
 
     info!("Retrieved {} display names", display_names.len());
     Ok(Json(display_names))
@@ -119,26 +351,12 @@ pub(crate) async fn get_display_names(
 
 /// Helper function to ensure a player exists in the database
 /// Creates a default player with the given ID if it doesn't exist
-pub(crate) async fn ensure_player_exists(
-    pool: &SqlitePool,
+pub(crate) async fn ensure_player_exists<S: Store>(
+    store: &S,
     player_id: PlayerId,
 ) -> Result<(), AppError> {
-    // Check if player exists
-    let exists = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM players WHERE id = ?")
-        .bind(i32::from(player_id))
-        .fetch_one(pool)
-        .await?;
-
-    // If player doesn't exist, create them
-    if exists == 0 {
-        let player_name = format!("Player {}", i32::from(player_id));
-        sqlx::query("INSERT INTO players (id, name) VALUES (?, ?)")
-            .bind(i32::from(player_id))
-            .bind(player_name)
-            .execute(pool)
-            .await?;
+    if store.ensure_player(player_id).await? {
         info!("Auto-created player with id {}", player_id);
     }
-
     Ok(())
 }
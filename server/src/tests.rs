@@ -0,0 +1,797 @@
+//! Handler tests, on the in-memory store
+
+use crate::app;
+use crate::auth::AdminSecret;
+use crate::live::Live;
+use axum::Router;
+use axum::body::{Body, BodyDataStream, to_bytes};
+use axum::http::{Method, Request, StatusCode, header};
+use futures::StreamExt;
+use serde_json::{Value, json};
+use tower::ServiceExt;
+use wwc_core::Date;
+use wwc_core::fair_play::FairPlayScore;
+use wwc_core::game::GoalCount;
+use wwc_core::group::game::{GroupGameScore, UnplayedGroupGame};
+use wwc_core::group::{GroupId, GroupOutcome};
+use wwc_core::player::{PlayerId, PlayerPredictions, Prediction};
+use wwc_core::playoff::TeamSource;
+use wwc_core::pred_score::joker::JokerRules;
+use wwc_core::pred_score::scheme::{ScoringConfig, ScoringScheme};
+use wwc_core::team::{Team, TeamId, TeamRank};
+use wwc_core::validation::PredReport;
+use wwc_db::{MemoryStore, Store};
+
+const TOURNAMENT: &str = "test-cup";
+const PASSWORD: &str = "hunter2";
+const ADMIN_SECRET: &str = "admin-secret";
+
+fn score(home: u32, away: u32) -> GroupGameScore {
+    GroupGameScore::new(
+        GoalCount::try_from(home).unwrap(),
+        GoalCount::try_from(away).unwrap(),
+    )
+}
+
+/// A tournament with one group of four teams: game 1 is played, game 2 is not.
+/// Users 1 (alice) and 2 (bob) have the same password.
+fn store() -> MemoryStore {
+    let store = MemoryStore::new();
+    let tournament = store.insert_tournament(TOURNAMENT);
+    let teams: Vec<Team> = [(1, "Sweden", "SWE"), (2, "Denmark", "DEN")]
+        .into_iter()
+        .chain([(3, "Norway", "NOR"), (4, "Finland", "FIN")])
+        .map(|(id, name, code)| Team::try_new(TeamId(id), name, code, TeamRank(id)).unwrap())
+        .collect();
+    store.insert_teams(tournament, &teams).unwrap();
+
+    let played = UnplayedGroupGame::try_new(1u32, 1u32, 2u32, Date::mock())
+        .unwrap()
+        .play(score(1, 0), FairPlayScore::default());
+    let unplayed = UnplayedGroupGame::try_new(2u32, 3u32, 4u32, Date::mock()).unwrap();
+    store.insert_played_games(tournament, &[played]).unwrap();
+    store
+        .insert_unplayed_games(tournament, &[unplayed])
+        .unwrap();
+    let group = GroupId::try_from('A').unwrap();
+    store
+        .insert_group_game_mappings(tournament, &[(group, 1u32.into()), (group, 2u32.into())])
+        .unwrap();
+    store
+        .insert_scoring_config(tournament, &ScoringConfig::new(ScoringScheme::kicktipp()))
+        .unwrap();
+
+    let password_hash = bcrypt::hash(PASSWORD, 4).unwrap();
+    store
+        .create_user(1, "alice", &password_hash, "Alice")
+        .unwrap();
+    store.create_user(2, "bob", &password_hash, "Bob").unwrap();
+    store
+}
+
+fn request(method: Method, uri: &str, token: Option<&str>, body: Option<Value>) -> Request<Body> {
+    let mut request = Request::builder().method(method).uri(uri);
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    match body {
+        Some(body) => request
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string())),
+        None => request.body(Body::empty()),
+    }
+    .unwrap()
+}
+
+/// Send a request to the app, returns the status and the JSON body (`null` if there is none)
+async fn send(
+    store: &MemoryStore,
+    method: Method,
+    uri: &str,
+    token: Option<&str>,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    call(
+        &app(store.clone(), Live::new(), AdminSecret::new(ADMIN_SECRET)),
+        request(method, uri, token, body),
+    )
+    .await
+}
+
+/// Send a request to an app, which keeps its state between requests
+async fn call(app: &Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+    (status, body)
+}
+
+/// Next Server-Sent Event of a stream, returns its id and its JSON data
+async fn next_event(events: &mut BodyDataStream) -> (Option<String>, Value) {
+    let mut text = String::new();
+    while !text.ends_with("\n\n") {
+        let chunk = events.next().await.unwrap().unwrap();
+        text.push_str(std::str::from_utf8(&chunk).unwrap());
+    }
+    let field = |name: &str| {
+        text.lines()
+            .find_map(|line| line.strip_prefix(name))
+            .map(|value| value.trim().to_string())
+    };
+    let data = serde_json::from_str(&field("data:").unwrap()).unwrap();
+    (field("id:"), data)
+}
+
+async fn login(store: &MemoryStore, username: &str) -> String {
+    let (status, body) = send(
+        store,
+        Method::POST,
+        "/login",
+        None,
+        Some(json!({"username": username, "password": PASSWORD})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    body["token"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn get_tournaments_and_groups() {
+    let store = store();
+
+    let (status, body) = send(&store, Method::GET, "/get_tournaments", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!([TOURNAMENT]));
+
+    let (status, body) = send(&store, Method::GET, "/get_groups/test-cup", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let groups = body.as_object().unwrap();
+    assert_eq!(groups.len(), 1);
+    assert!(groups.contains_key("A"));
+}
+
+#[tokio::test]
+async fn unknown_tournament_is_bad_request() {
+    let store = store();
+    let (status, body) = send(&store, Method::GET, "/get_teams/no-cup", None, None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "No tournament 'no-cup'");
+}
+
+#[tokio::test]
+async fn login_rejects_wrong_password() {
+    let store = store();
+    let (status, _) = send(
+        &store,
+        Method::POST,
+        "/login",
+        None,
+        Some(json!({"username": "alice", "password": "wrong"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn user_routes_require_token() {
+    let store = store();
+    let (status, _) = send(&store, Method::GET, "/me", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let token = login(&store, "alice").await;
+    let (status, body) = send(&store, Method::GET, "/me", Some(&token), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["display_name"], "Alice");
+}
+
+#[tokio::test]
+async fn save_preds_stores_open_games_only() {
+    let store = store();
+    let token = login(&store, "alice").await;
+    let preds = PlayerPredictions::new(
+        PlayerId::from(1),
+        vec![
+            Prediction(1u32.into(), score(0, 0)),
+            Prediction(2u32.into(), score(2, 1)),
+        ],
+    );
+
+    let (status, body) = send(
+        &store,
+        Method::PUT,
+        "/save_preds/test-cup",
+        Some(&token),
+        Some(serde_json::to_value(&preds).unwrap()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let report: PredReport = serde_json::from_value(body).unwrap();
+    assert_eq!(report.checks.len(), 2);
+    assert_eq!(report.accepted().count(), 1);
+
+    let (status, body) = send(
+        &store,
+        Method::GET,
+        "/get_preds/test-cup/1",
+        Some(&token),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let stored: Vec<Prediction> = serde_json::from_value(body).unwrap();
+    assert_eq!(stored.len(), 1);
+    assert_eq!(u32::from(stored[0].0), 2);
+
+    // Other players' predictions are off limits
+    let (status, _) = send(
+        &store,
+        Method::GET,
+        "/get_preds/test-cup/2",
+        Some(&token),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn join_league_with_invite_code() {
+    let store = store();
+    let alice = login(&store, "alice").await;
+    let bob = login(&store, "bob").await;
+
+    let (status, league) = send(
+        &store,
+        Method::PUT,
+        "/create_league/test-cup",
+        Some(&alice),
+        Some(json!({"name": "Office"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(league["role"], "owner");
+
+    let (status, joined) = send(
+        &store,
+        Method::PUT,
+        "/join_league",
+        Some(&bob),
+        Some(json!({"invite_code": league["invite_code"]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(joined["id"], league["id"]);
+    assert_eq!(joined["role"], "member");
+
+    let uri = format!("/get_league_members/{}", league["id"]);
+    let (status, members) = send(&store, Method::GET, &uri, Some(&bob), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        members,
+        json!([
+            {"player_id": 1, "role": "owner"},
+            {"player_id": 2, "role": "member"},
+        ])
+    );
+
+    // Only the owner manages the league
+    let uri = format!("/delete_league/{}", league["id"]);
+    let (status, _) = send(&store, Method::DELETE, &uri, Some(&bob), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let uri = format!("/reset_league_invite_code/{}", league["id"]);
+    let (status, _) = send(&store, Method::POST, &uri, Some(&bob), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, code) = send(&store, Method::POST, &uri, Some(&alice), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_ne!(code, league["invite_code"]);
+
+    // Changes are never made by a GET
+    let uri = format!("/leave_league/{}", league["id"]);
+    let (status, _) = send(&store, Method::GET, &uri, Some(&bob), None).await;
+    assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+    let (status, _) = send(&store, Method::POST, &uri, Some(&bob), None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = send(
+        &store,
+        Method::PUT,
+        "/join_league",
+        Some(&bob),
+        Some(json!({"invite_code": code})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let uri = format!("/remove_league_member/{}/2", league["id"]);
+    let (status, _) = send(&store, Method::DELETE, &uri, Some(&alice), None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let uri = format!("/delete_league/{}", league["id"]);
+    let (status, _) = send(&store, Method::DELETE, &uri, Some(&alice), None).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn league_access_is_checked() {
+    let store = store();
+    let alice = login(&store, "alice").await;
+    let bob = login(&store, "bob").await;
+    let (_, league) = send(
+        &store,
+        Method::PUT,
+        "/create_league/test-cup",
+        Some(&alice),
+        Some(json!({"name": "Office"})),
+    )
+    .await;
+    let join = |invite_code| {
+        send(
+            &store,
+            Method::PUT,
+            "/join_league",
+            Some(&bob),
+            Some(json!({ "invite_code": invite_code })),
+        )
+    };
+
+    let (status, body) = join(json!("wrong-code")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "Invalid invite code");
+
+    // Only members see the league's leaderboard
+    let uri = format!("/get_league_leaderboard_history/{}", league["id"]);
+    let (status, body) = send(&store, Method::GET, &uri, Some(&bob), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["error"],
+        format!("Not a member of league {}", league["id"])
+    );
+    let (status, _) = join(league["invite_code"].clone()).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(&store, Method::GET, &uri, Some(&bob), None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Only the owner kicks members and deletes the league
+    let owner_only = format!("Only the owner can manage league {}", league["id"]);
+    let uri = format!("/remove_league_member/{}/1", league["id"]);
+    let (status, body) = send(&store, Method::DELETE, &uri, Some(&bob), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], owner_only);
+    let uri = format!("/delete_league/{}", league["id"]);
+    let (status, body) = send(&store, Method::DELETE, &uri, Some(&bob), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], owner_only);
+
+    let uri = format!("/get_league_members/{}", league["id"]);
+    let (status, members) = send(&store, Method::GET, &uri, Some(&alice), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(members.as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn group_result_entry() {
+    let store = store();
+    let admin = Some(ADMIN_SECRET);
+    let result = json!({"game_id": 2, "home_goals": 2, "away_goals": 0});
+
+    let (status, _) = send(
+        &store,
+        Method::PUT,
+        "/save_group_result/test-cup",
+        None,
+        Some(result.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let uri = "/save_group_result/test-cup";
+    let (status, _) = send(&store, Method::PUT, uri, admin, Some(result)).await;
+    assert_eq!(status, StatusCode::OK);
+    // Corrected, with cards
+    let corrected = json!({
+        "game_id": 2,
+        "home_goals": 2,
+        "away_goals": 1,
+        "fair_play": {
+            "home": {"yellow": 2, "indirect_red": 0, "direct_red": 0, "yellow_and_direct": 0},
+            "away": {"yellow": 0, "indirect_red": 0, "direct_red": 1, "yellow_and_direct": 0},
+        },
+    });
+    let (status, _) = send(&store, Method::PUT, uri, admin, Some(corrected)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let tournament = store.insert_tournament(TOURNAMENT);
+    let (played, unplayed) = store.get_group_games(tournament).await.unwrap();
+    assert!(unplayed.is_empty());
+    let game = played.iter().find(|game| u32::from(game.id) == 2).unwrap();
+    assert_eq!(game.score, score(2, 1));
+    assert_eq!(u32::from(game.fair_play().away.direct_red()), 1);
+
+    let unknown = json!({"game_id": 9, "home_goals": 0, "away_goals": 0});
+    let (status, body) = send(&store, Method::PUT, uri, admin, Some(unknown)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "No group game 9");
+
+    let uri = "/clear_group_result/test-cup/2";
+    let (status, _) = send(&store, Method::GET, uri, admin, None).await;
+    assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+    let (status, _) = send(&store, Method::DELETE, uri, admin, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (played, _) = store.get_group_games(tournament).await.unwrap();
+    assert_eq!(played.len(), 1);
+}
+
+#[tokio::test]
+async fn playoff_result_needs_resolved_teams() {
+    let store = store();
+    let tournament = store.insert_tournament(TOURNAMENT);
+    let group = GroupId::try_from('A').unwrap();
+    let final_sources = (
+        TeamSource::GroupOutcome(GroupOutcome::Winner(group)),
+        TeamSource::GroupOutcome(GroupOutcome::RunnerUp(group)),
+    );
+    store
+        .insert_playoff_team_sources(tournament, &[(3u32.into(), final_sources)])
+        .unwrap();
+    let admin = Some(ADMIN_SECRET);
+    let save = "/save_playoff_result/test-cup";
+    let result = |home: u32, away: u32, penalties: Option<(u32, u32)>| {
+        json!({
+            "game_id": 3,
+            "home": home,
+            "away": away,
+            "home_goals": 1,
+            "away_goals": 1,
+            "home_penalty": penalties.map(|(home, _)| home),
+            "away_penalty": penalties.map(|(_, away)| away),
+        })
+    };
+
+    let (status, body) = send(
+        &store,
+        Method::PUT,
+        save,
+        admin,
+        Some(result(3, 1, Some((4, 3)))),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "Group A is not finished");
+
+    // Team 3 wins the group on goal difference, ahead of team 1
+    let group_result = json!({"game_id": 2, "home_goals": 2, "away_goals": 0});
+    let uri = "/save_group_result/test-cup";
+    let (status, _) = send(&store, Method::PUT, uri, admin, Some(group_result)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = send(
+        &store,
+        Method::PUT,
+        save,
+        admin,
+        Some(result(1, 3, Some((4, 3)))),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "Game 3 is between teams 3 and 1");
+    // A draw needs a penalty shootout
+    let (status, _) = send(&store, Method::PUT, save, admin, Some(result(3, 1, None))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(
+        &store,
+        Method::PUT,
+        save,
+        admin,
+        Some(result(3, 1, Some((4, 3)))),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let results = store.get_playoff_results(tournament).await.unwrap();
+    assert_eq!(results.result(3u32.into()).unwrap().winner(), TeamId(3));
+
+    // The group decided the teams of the played final
+    let uri = "/clear_group_result/test-cup/2";
+    let (status, _) = send(&store, Method::DELETE, uri, admin, None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = send(
+        &store,
+        Method::DELETE,
+        "/clear_playoff_result/test-cup/3",
+        admin,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(&store, Method::DELETE, uri, admin, None).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn playoff_preds_follow_the_bracket() {
+    let store = store();
+    let tournament = store.insert_tournament(TOURNAMENT);
+    let group = GroupId::try_from('A').unwrap();
+    let team_sources = [
+        (
+            3u32.into(),
+            (
+                TeamSource::GroupOutcome(GroupOutcome::Winner(group)),
+                TeamSource::GroupOutcome(GroupOutcome::RunnerUp(group)),
+            ),
+        ),
+        (
+            4u32.into(),
+            (
+                TeamSource::WinnerOf(3u32.into()),
+                TeamSource::LoserOf(3u32.into()),
+            ),
+        ),
+    ];
+    store
+        .insert_playoff_team_sources(tournament, &team_sources)
+        .unwrap();
+    let token = login(&store, "alice").await;
+    let save = "/save_playoff_preds/test-cup";
+    let result = |home: u32, away: u32| {
+        json!({
+            "home": home,
+            "away": away,
+            "score": {"home": 2, "away": 1, "home_penalty": null, "away_penalty": null},
+        })
+    };
+    let bracket = |game_3: Value, game_4: Value| json!({"results": {"3": game_3, "4": game_4}});
+
+    // Team 2 lost game 3
+    let wrong = bracket(result(1, 2), result(2, 1));
+    let (status, _) = send(&store, Method::PUT, save, Some(&token), Some(wrong)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let saved = bracket(result(1, 2), result(1, 2));
+    let (status, _) = send(&store, Method::PUT, save, Some(&token), Some(saved)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Game 3 has kicked off, game 4 is still open
+    store
+        .insert_game_kickoffs(tournament, &[(3u32.into(), Date::mock())])
+        .unwrap();
+    let changed = bracket(result(2, 1), result(2, 1));
+    let (status, body) = send(&store, Method::PUT, save, Some(&token), Some(changed)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "Playoff game 3 has kicked off");
+    let (status, _) = send(
+        &store,
+        Method::PUT,
+        save,
+        Some(&token),
+        Some(json!({"results": {"3": result(1, 2)}})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let preds = store
+        .get_playoff_preds(tournament, PlayerId::from(1), None)
+        .await
+        .unwrap();
+    assert_eq!(preds.num_played(), 1);
+}
+
+#[tokio::test]
+async fn clearing_preds_keeps_locked_jokers() {
+    let store = store();
+    let tournament = store.insert_tournament(TOURNAMENT);
+    let jokers = JokerRules {
+        group: 1,
+        ..JokerRules::default()
+    };
+    store
+        .insert_scoring_config(
+            tournament,
+            &ScoringConfig::new(ScoringScheme::kicktipp()).with_jokers(jokers),
+        )
+        .unwrap();
+    let token = login(&store, "alice").await;
+    let save = "/save_jokers/test-cup";
+
+    // Picked before game 1 was played
+    store
+        .insert_jokers(tournament, PlayerId::from(1), &[1u32.into()], None)
+        .await
+        .unwrap();
+    let uri = "/clear_my_preds/test-cup";
+    let (status, _) = send(&store, Method::GET, uri, Some(&token), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let kept = store
+        .get_jokers(tournament, PlayerId::from(1), None)
+        .await
+        .unwrap();
+    assert_eq!(kept, vec![1u32.into()]);
+
+    let (status, body) = send(&store, Method::PUT, save, Some(&token), Some(json!([2]))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["error"],
+        "Joker error: Game 1 has kicked off, its joker is locked"
+    );
+    let (status, _) = send(&store, Method::PUT, save, Some(&token), Some(json!([1]))).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn live_events_resume() {
+    let store = store();
+    let live = Live::new();
+    let events = |last_id: Option<&str>| {
+        let mut request = request(Method::GET, "/events/test-cup", None, None);
+        if let Some(last_id) = last_id {
+            request
+                .headers_mut()
+                .insert("last-event-id", last_id.parse().unwrap());
+        }
+        app(store.clone(), live.clone(), AdminSecret::new(ADMIN_SECRET)).oneshot(request)
+    };
+    let mut stream = events(None).await.unwrap().into_body().into_data_stream();
+
+    let result = json!({"game_id": 2, "home_goals": 2, "away_goals": 0});
+    let save = request(
+        Method::PUT,
+        "/save_group_result/test-cup",
+        Some(ADMIN_SECRET),
+        Some(result),
+    );
+    let response = app(store.clone(), live.clone(), AdminSecret::new(ADMIN_SECRET))
+        .oneshot(save)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (first_id, event) = next_event(&mut stream).await;
+    assert_eq!(event, json!({"kind": "group_result", "game_id": 2}));
+    assert_eq!(
+        next_event(&mut stream).await.1,
+        json!({"kind": "leaderboard"})
+    );
+    // Both games of the fixture have a result
+    let locked = json!({"kind": "preds_locked", "game_ids": [1, 2]});
+    assert_eq!(next_event(&mut stream).await.1, locked);
+
+    // Missed events are sent again
+    let mut resumed = events(first_id.as_deref())
+        .await
+        .unwrap()
+        .into_body()
+        .into_data_stream();
+    assert_eq!(
+        next_event(&mut resumed).await.1,
+        json!({"kind": "leaderboard"})
+    );
+    assert_eq!(next_event(&mut resumed).await.1, locked);
+
+    // Events from before the oldest kept event cannot be resumed
+    let mut reset = events(Some("1"))
+        .await
+        .unwrap()
+        .into_body()
+        .into_data_stream();
+    assert_eq!(next_event(&mut reset).await.1, json!({"kind": "reset"}));
+}
+
+#[tokio::test]
+async fn resolved_bonus_questions_are_not_replaced() {
+    let store = store();
+    let live = Live::new();
+    let app = app(store.clone(), live.clone(), AdminSecret::new(ADMIN_SECRET));
+    let events = request(Method::GET, "/events/test-cup", None, None);
+    let mut stream = app
+        .clone()
+        .oneshot(events)
+        .await
+        .unwrap()
+        .into_body()
+        .into_data_stream();
+    let add = |text: &str| {
+        let question = json!({
+            "id": 1,
+            "text": text,
+            "kind": {"type": "team_pick", "points": 5.0},
+            "deadline": "2026-06-01T18:00:00+00:00",
+            "solution": null,
+        });
+        request(
+            Method::PUT,
+            "/add_bonus_question/test-cup",
+            Some(ADMIN_SECRET),
+            Some(question),
+        )
+    };
+
+    // Replaced while open, the leaderboard is updated
+    assert_eq!(call(&app, add("Winner?")).await.0, StatusCode::OK);
+    assert_eq!(
+        next_event(&mut stream).await.1,
+        json!({"kind": "leaderboard"})
+    );
+    assert_eq!(call(&app, add("Champion?")).await.0, StatusCode::OK);
+    assert_eq!(
+        next_event(&mut stream).await.1,
+        json!({"kind": "leaderboard"})
+    );
+
+    let solution = json!({"id": 1, "solution": [{"type": "team", "value": 1}]});
+    let resolve = request(
+        Method::PUT,
+        "/resolve_bonus_question/test-cup",
+        Some(ADMIN_SECRET),
+        Some(solution),
+    );
+    assert_eq!(call(&app, resolve).await.0, StatusCode::OK);
+
+    let (status, body) = call(&app, add("Runner up?")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["error"],
+        "Bonus question 1 is resolved and can no longer be replaced"
+    );
+    let tournament = store.insert_tournament(TOURNAMENT);
+    let questions = store.get_bonus_questions(tournament).await.unwrap();
+    assert_eq!(questions[0].text, "Champion?");
+    assert!(questions[0].solution.is_some());
+}
+
+#[tokio::test]
+async fn leaderboard_is_cached_until_results_change() {
+    let store = store();
+    let app = app(store.clone(), Live::new(), AdminSecret::new(ADMIN_SECRET));
+    let token = login(&store, "alice").await;
+    let preds = PlayerPredictions::new(
+        PlayerId::from(1),
+        vec![Prediction(2u32.into(), score(2, 0))],
+    );
+    let save = request(
+        Method::PUT,
+        "/save_preds/test-cup",
+        Some(&token),
+        Some(serde_json::to_value(&preds).unwrap()),
+    );
+    assert_eq!(call(&app, save).await.0, StatusCode::OK);
+
+    let get_leaderboard = || request(Method::GET, "/get_leaderboard/test-cup", None, None);
+    let (status, before) = call(&app, get_leaderboard()).await;
+    assert_eq!(status, StatusCode::OK);
+    let rows = before["rows"].as_array().unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["competitor"]["player_id"], 1);
+    assert!(rows[0]["games"].as_array().unwrap().is_empty());
+
+    // Changed behind the server's back, the cached leaderboard is returned
+    let tournament = store.insert_tournament(TOURNAMENT);
+    let game = UnplayedGroupGame::try_new(2u32, 3u32, 4u32, Date::mock())
+        .unwrap()
+        .play(score(2, 0), FairPlayScore::default());
+    store
+        .insert_group_game_result(tournament, &game)
+        .await
+        .unwrap();
+    assert_eq!(call(&app, get_leaderboard()).await.1, before);
+
+    // Recorded through the server, the leaderboard is computed again
+    let result = json!({"game_id": 2, "home_goals": 2, "away_goals": 0});
+    let save = request(
+        Method::PUT,
+        "/save_group_result/test-cup",
+        Some(ADMIN_SECRET),
+        Some(result),
+    );
+    assert_eq!(call(&app, save).await.0, StatusCode::OK);
+    let (_, after) = call(&app, get_leaderboard()).await;
+    assert_ne!(after, before);
+
+    let breakdown = request(Method::GET, "/get_score_breakdown/test-cup/1", None, None);
+    let (status, body) = call(&app, breakdown).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, after["rows"][0]);
+    assert_eq!(body["games"][0]["game_id"], 2);
+    assert_eq!(body["games"][0]["exact"], true);
+
+    // Bob has no predictions
+    let breakdown = request(Method::GET, "/get_score_breakdown/test-cup/2", None, None);
+    assert_eq!(call(&app, breakdown).await.0, StatusCode::BAD_REQUEST);
+}
@@ -1,16 +1,27 @@
 use crate::AppError;
 
 use crate::auth::AuthUser;
-use crate::public::ensure_player_exists;
+use crate::cache::LeaderboardCache;
+use crate::public::{ensure_player_exists, scoring_config, tournament_id};
 use axum::{
     Extension, Json,
     extract::{Path, Query, State},
     http::StatusCode,
 };
 use serde::{Deserialize, Serialize};
-use sqlx::SqlitePool;
 use tracing::info;
-use wwc_core::player::{PlayerId, PlayerPredictions, Prediction};
+use wwc_core::Date;
+use wwc_core::bonus::{BonusError, BonusPrediction};
+use wwc_core::error::WwcError;
+use wwc_core::game::GameId;
+use wwc_core::player::{
+    OutcomePrediction, PlayerId, PlayerPredictions, PredSubmission, Prediction, PredictionMode,
+};
+use wwc_core::playoff::{BracketState, BracketStructure};
+use wwc_core::pred_score::joker::game_stages;
+use wwc_core::pred_score::proba::ProbPrediction;
+use wwc_core::validation::PredReport;
+use wwc_db::{Store, load_groups};
 
 /// Query parameters for get_preds
 #[derive(Deserialize)]
@@ -19,9 +30,9 @@ pub(crate) struct GetPredsQuery {
 }
 
 /// Get predictions for a player (authenticated)
-pub(crate) async fn get_preds(
-    State(pool): State<SqlitePool>,
-    Path(player_id): Path<i32>,
+pub(crate) async fn get_preds<S: Store>(
+    State(store): State<S>,
+    Path((tournament, player_id)): Path<(String, i32)>,
     Query(query): Query<GetPredsQuery>,
     Extension(auth_user): Extension<AuthUser>,
 ) -> Result<Json<Vec<Prediction>>, AppError> {
@@ -32,8 +43,11 @@ pub(crate) async fn get_preds(
         ));
     }
 
+    let tournament = tournament_id(&store, &tournament).await?;
     let bot_name = query.bot.as_deref();
-    let preds = wwc_db::get_preds(&pool, PlayerId::from(player_id), bot_name).await?;
+    let preds = store
+        .get_preds(tournament, PlayerId::from(player_id), bot_name)
+        .await?;
 
     info!(
         "Retrieved {} predictions for player {} (bot: {:?})",
@@ -44,12 +58,48 @@ pub(crate) async fn get_preds(
     Ok(Json(preds))
 }
 
+/// Get the prediction history of a player (authenticated)
+///
+/// Every submission of the player (or bot with `?bot=`), oldest first, with the stored group
+/// predictions right after it. A submission without predictions means they were cleared.
+pub(crate) async fn get_pred_history<S: Store>(
+    State(store): State<S>,
+    Path((tournament, player_id)): Path<(String, i32)>,
+    Query(query): Query<GetPredsQuery>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<Vec<PredSubmission>>, AppError> {
+    if player_id != auth_user.player_id {
+        return Err(AppError::Generic(
+            "Cannot access predictions for a different player".to_string(),
+        ));
+    }
+
+    let tournament = tournament_id(&store, &tournament).await?;
+    let bot_name = query.bot.as_deref();
+    let history = store
+        .get_pred_history(tournament, PlayerId::from(player_id), bot_name)
+        .await?;
+
+    info!(
+        "Retrieved {} prediction submissions for player {} (bot: {:?})",
+        history.len(),
+        player_id,
+        bot_name
+    );
+    Ok(Json(history))
+}
+
 /// Save predictions
-pub(crate) async fn save_preds(
-    State(pool): State<SqlitePool>,
+///
+/// Predictions are validated against the current tournament, only accepted predictions are
+/// stored. The response reports the status of every submitted prediction.
+pub(crate) async fn save_preds<S: Store>(
+    State(store): State<S>,
+    Extension(cache): Extension<LeaderboardCache>,
+    Path(tournament): Path<String>,
     Extension(auth_user): Extension<AuthUser>,
     Json(player_preds): Json<PlayerPredictions>,
-) -> Result<StatusCode, AppError> {
+) -> Result<Json<PredReport>, AppError> {
     // Verify the player_id in the predictions matches the authenticated user's player_id
     if i32::from(player_preds.id) != auth_user.player_id {
         return Err(AppError::Generic(
@@ -63,24 +113,476 @@ pub(crate) async fn save_preds(
     );
 
     // Ensure player exists (auto-create if needed)
-    ensure_player_exists(&pool, player_preds.id).await?;
+    ensure_player_exists(&store, player_preds.id).await?;
+
+    let tournament = tournament_id(&store, &tournament).await?;
+    let now = Date::from(chrono::Utc::now().fixed_offset());
+    let report = store
+        .insert_validated_preds(
+            tournament,
+            &player_preds,
+            auth_user.bot_name.as_deref(),
+            now,
+        )
+        .await?;
+    cache.invalidate(tournament);
+
+    info!(
+        "Stored {} of {} predictions for player {} (bot: {:?})",
+        report.accepted().count(),
+        report.checks.len(),
+        player_preds.id,
+        auth_user.bot_name
+    );
+    Ok(Json(report))
+}
 
-    wwc_db::insert_preds(&pool, &player_preds, auth_user.bot_name.as_deref()).await?;
+/// Get predicted playoff bracket for a player (authenticated)
+pub(crate) async fn get_playoff_preds<S: Store>(
+    State(store): State<S>,
+    Path((tournament, player_id)): Path<(String, i32)>,
+    Query(query): Query<GetPredsQuery>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<BracketState>, AppError> {
+    if player_id != auth_user.player_id {
+        return Err(AppError::Generic(
+            "Cannot access predictions for a different player".to_string(),
+        ));
+    }
+
+    let tournament = tournament_id(&store, &tournament).await?;
+    let bot_name = query.bot.as_deref();
+    let bracket_state = store
+        .get_playoff_preds(tournament, PlayerId::from(player_id), bot_name)
+        .await?;
+
+    info!(
+        "Retrieved {} playoff predictions for player {} (bot: {:?})",
+        bracket_state.num_played(),
+        player_id,
+        bot_name
+    );
+    Ok(Json(bracket_state))
+}
+
+/// Save predicted playoff bracket
+///
+/// The bracket is stored for the authenticated player (and bot, if any).
+pub(crate) async fn save_playoff_preds<S: Store>(
+    State(store): State<S>,
+    Extension(cache): Extension<LeaderboardCache>,
+    Path(tournament): Path<String>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(bracket_state): Json<BracketState>,
+) -> Result<StatusCode, AppError> {
+    let player_id = PlayerId::from(auth_user.player_id);
+    let bot_name = auth_user.bot_name.as_deref();
+    let tournament = tournament_id(&store, &tournament).await?;
+
+    let team_sources = store.get_playoff_team_sources(tournament).await?;
+    if team_sources.is_empty() {
+        return Err(AppError::Generic("Tournament has no playoff".to_string()));
+    }
+    let bracket = BracketStructure::from_team_sources(&team_sources).map_err(WwcError::from)?;
+    bracket
+        .validate_state(&bracket_state)
+        .map_err(WwcError::from)?;
+
+    // Predictions of games which have kicked off must stay as they are
+    let now = Date::from(chrono::Utc::now().fixed_offset());
+    let locked = store.get_locked_games(tournament, now).await?;
+    let previous = store
+        .get_playoff_preds(tournament, player_id, bot_name)
+        .await?;
+    if let Some(game_id) = bracket.all_game_ids().find(|game_id| {
+        locked.contains(game_id) && bracket_state.result(*game_id) != previous.result(*game_id)
+    }) {
+        return Err(AppError::Generic(format!(
+            "Playoff game {} has kicked off",
+            game_id
+        )));
+    }
+
+    info!(
+        "Saving {} playoff predictions for player {} (bot: {:?})",
+        bracket_state.num_played(),
+        player_id,
+        bot_name
+    );
+
+    ensure_player_exists(&store, player_id).await?;
+
+    store
+        .insert_playoff_preds(tournament, player_id, &bracket_state, bot_name)
+        .await?;
+    cache.invalidate(tournament);
 
     Ok(StatusCode::OK)
 }
 
-/// Clear my predictions (authenticated user)
-pub(crate) async fn clear_my_preds(
-    State(pool): State<SqlitePool>,
+/// Get probabilistic predictions for one of the player's bots (authenticated)
+pub(crate) async fn get_proba_preds<S: Store>(
+    State(store): State<S>,
+    Path((tournament, player_id)): Path<(String, i32)>,
+    Query(query): Query<GetPredsQuery>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<Vec<ProbPrediction>>, AppError> {
+    if player_id != auth_user.player_id {
+        return Err(AppError::Generic(
+            "Cannot access predictions for a different player".to_string(),
+        ));
+    }
+
+    let tournament = tournament_id(&store, &tournament).await?;
+    let bot_name = query
+        .bot
+        .ok_or_else(|| AppError::Generic("Probabilistic predictions require a bot".to_string()))?;
+    let preds = store
+        .get_proba_preds(tournament, PlayerId::from(player_id), &bot_name)
+        .await?;
+
+    info!(
+        "Retrieved {} probabilistic predictions for player {} (bot: {})",
+        preds.len(),
+        player_id,
+        bot_name
+    );
+    Ok(Json(preds))
+}
+
+/// Save probabilistic predictions
+///
+/// Only bots submit probabilistic predictions, so the token must belong to a bot.
+/// Every forecast is validated (probabilities in [0, 1], summing to 1) before anything is stored.
+/// Games are checked like score predictions: forecasts of unknown or kicked off games and repeated
+/// games are not stored, see the returned report.
+pub(crate) async fn save_proba_preds<S: Store>(
+    State(store): State<S>,
+    Path(tournament): Path<String>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(preds): Json<Vec<ProbPrediction>>,
+) -> Result<Json<PredReport<ProbPrediction>>, AppError> {
+    let bot_name = auth_user
+        .bot_name
+        .ok_or_else(|| AppError::Generic("Probabilistic predictions require a bot".to_string()))?;
+    let player_id = PlayerId::from(auth_user.player_id);
+
+    for ProbPrediction(_, forecast) in &preds {
+        forecast.validate().map_err(WwcError::from)?;
+    }
+
+    info!(
+        "Saving {} probabilistic predictions for player {} (bot: {})",
+        preds.len(),
+        player_id,
+        bot_name
+    );
+
+    ensure_player_exists(&store, player_id).await?;
+
+    let tournament = tournament_id(&store, &tournament).await?;
+    let now = Date::from(chrono::Utc::now().fixed_offset());
+    let report = store
+        .insert_validated_proba_preds(tournament, player_id, &bot_name, &preds, now)
+        .await?;
+
+    info!(
+        "Stored {} of {} probabilistic predictions for player {} (bot: {})",
+        report.accepted().count(),
+        report.checks.len(),
+        player_id,
+        bot_name
+    );
+    Ok(Json(report))
+}
+
+/// Get bonus answers for a player (authenticated)
+pub(crate) async fn get_bonus_preds<S: Store>(
+    State(store): State<S>,
+    Path((tournament, player_id)): Path<(String, i32)>,
+    Query(query): Query<GetPredsQuery>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<Vec<BonusPrediction>>, AppError> {
+    if player_id != auth_user.player_id {
+        return Err(AppError::Generic(
+            "Cannot access predictions for a different player".to_string(),
+        ));
+    }
+
+    let tournament = tournament_id(&store, &tournament).await?;
+    let bot_name = query.bot.as_deref();
+    let preds = store
+        .get_bonus_preds(tournament, PlayerId::from(player_id), bot_name)
+        .await?;
+
+    info!(
+        "Retrieved {} bonus answers for player {} (bot: {:?})",
+        preds.len(),
+        player_id,
+        bot_name
+    );
+    Ok(Json(preds))
+}
+
+/// Save bonus answers
+///
+/// Every answer must be for an open question and have the question's type, otherwise nothing is
+/// stored. Answers to questions which are not part of the request are kept.
+pub(crate) async fn save_bonus_preds<S: Store>(
+    State(store): State<S>,
+    Extension(cache): Extension<LeaderboardCache>,
+    Path(tournament): Path<String>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(preds): Json<Vec<BonusPrediction>>,
+) -> Result<StatusCode, AppError> {
+    let player_id = PlayerId::from(auth_user.player_id);
+    let tournament = tournament_id(&store, &tournament).await?;
+    let questions = store.get_bonus_questions(tournament).await?;
+    let now = Date::from(chrono::Utc::now().fixed_offset());
+
+    for BonusPrediction(question_id, answer) in &preds {
+        let question = questions
+            .iter()
+            .find(|question| question.id == *question_id)
+            .ok_or(BonusError::UnknownQuestion(*question_id))
+            .map_err(WwcError::from)?;
+        question
+            .validate_submission(answer, now)
+            .map_err(WwcError::from)?;
+    }
+
+    info!(
+        "Saving {} bonus answers for player {} (bot: {:?})",
+        preds.len(),
+        player_id,
+        auth_user.bot_name
+    );
+
+    ensure_player_exists(&store, player_id).await?;
+
+    store
+        .insert_bonus_preds(tournament, player_id, &preds, auth_user.bot_name.as_deref())
+        .await?;
+    cache.invalidate(tournament);
+
+    Ok(StatusCode::OK)
+}
+
+/// Get outcome-only (1X2) predictions for a player (authenticated)
+pub(crate) async fn get_outcome_preds<S: Store>(
+    State(store): State<S>,
+    Path((tournament, player_id)): Path<(String, i32)>,
+    Query(query): Query<GetPredsQuery>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<Vec<OutcomePrediction>>, AppError> {
+    if player_id != auth_user.player_id {
+        return Err(AppError::Generic(
+            "Cannot access predictions for a different player".to_string(),
+        ));
+    }
+
+    let tournament = tournament_id(&store, &tournament).await?;
+    let bot_name = query.bot.as_deref();
+    let preds = store
+        .get_outcome_preds(tournament, PlayerId::from(player_id), bot_name)
+        .await?;
+
+    info!(
+        "Retrieved {} outcome predictions for player {} (bot: {:?})",
+        preds.len(),
+        player_id,
+        bot_name
+    );
+    Ok(Json(preds))
+}
+
+/// Save outcome-only (1X2) predictions
+///
+/// Only for tournaments in outcome prediction mode. Predictions are validated like score
+/// predictions, see [`save_preds`].
+pub(crate) async fn save_outcome_preds<S: Store>(
+    State(store): State<S>,
+    Extension(cache): Extension<LeaderboardCache>,
+    Path(tournament): Path<String>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(preds): Json<Vec<OutcomePrediction>>,
+) -> Result<Json<PredReport<OutcomePrediction>>, AppError> {
+    let player_id = PlayerId::from(auth_user.player_id);
+    let (id, config) = scoring_config(&store, &tournament).await?;
+    if config.mode != PredictionMode::Outcome {
+        return Err(AppError::Generic(format!(
+            "Tournament '{}' does not use outcome predictions",
+            tournament
+        )));
+    }
+
+    ensure_player_exists(&store, player_id).await?;
+
+    let now = Date::from(chrono::Utc::now().fixed_offset());
+    let report = store
+        .insert_validated_outcome_preds(id, player_id, &preds, auth_user.bot_name.as_deref(), now)
+        .await?;
+    cache.invalidate(id);
+
+    info!(
+        "Stored {} of {} outcome predictions for player {} (bot: {:?})",
+        report.accepted().count(),
+        report.checks.len(),
+        player_id,
+        auth_user.bot_name
+    );
+    Ok(Json(report))
+}
+
+/// Get the jokers of a player (authenticated)
+pub(crate) async fn get_jokers<S: Store>(
+    State(store): State<S>,
+    Path((tournament, player_id)): Path<(String, i32)>,
+    Query(query): Query<GetPredsQuery>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<Vec<GameId>>, AppError> {
+    if player_id != auth_user.player_id {
+        return Err(AppError::Generic(
+            "Cannot access predictions for a different player".to_string(),
+        ));
+    }
+
+    let tournament = tournament_id(&store, &tournament).await?;
+    let bot_name = query.bot.as_deref();
+    let jokers = store
+        .get_jokers(tournament, PlayerId::from(player_id), bot_name)
+        .await?;
+
+    info!(
+        "Retrieved {} jokers for player {} (bot: {:?})",
+        jokers.len(),
+        player_id,
+        bot_name
+    );
+    Ok(Json(jokers))
+}
+
+/// Save the joker selection, replacing the previous one
+///
+/// The selection is validated against the tournament's joker rules: at most the configured
+/// number of jokers per stage, and no joker added to or removed from a game which has kicked
+/// off. A game has kicked off once it is played or its kickoff time has passed.
+pub(crate) async fn save_jokers<S: Store>(
+    State(store): State<S>,
+    Extension(cache): Extension<LeaderboardCache>,
+    Path(tournament): Path<String>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(jokers): Json<Vec<GameId>>,
+) -> Result<StatusCode, AppError> {
+    let player_id = PlayerId::from(auth_user.player_id);
+    let bot_name = auth_user.bot_name.as_deref();
+    let (tournament, config) = scoring_config(&store, &tournament).await?;
+    let groups = load_groups(&store, tournament).await?;
+    let team_sources = store.get_playoff_team_sources(tournament).await?;
+    let bracket = if team_sources.is_empty() {
+        None
+    } else {
+        Some(BracketStructure::from_team_sources(&team_sources).map_err(WwcError::from)?)
+    };
+    let stages = game_stages(&groups, bracket.as_ref());
+
+    let now = Date::from(chrono::Utc::now().fixed_offset());
+    let locked = store.get_locked_games(tournament, now).await?;
+    let previous = store.get_jokers(tournament, player_id, bot_name).await?;
+
+    config
+        .jokers
+        .validate(&jokers, &previous, &stages, &locked)
+        .map_err(WwcError::from)?;
+
+    info!(
+        "Saving {} jokers for player {} (bot: {:?})",
+        jokers.len(),
+        player_id,
+        bot_name
+    );
+
+    ensure_player_exists(&store, player_id).await?;
+
+    store
+        .insert_jokers(tournament, player_id, &jokers, bot_name)
+        .await?;
+    cache.invalidate(tournament);
+
+    Ok(StatusCode::OK)
+}
+
+/// Clear my predictions in a tournament (authenticated user)
+pub(crate) async fn clear_my_preds<S: Store>(
+    State(store): State<S>,
+    Extension(cache): Extension<LeaderboardCache>,
+    Path(tournament): Path<String>,
     Extension(auth_user): Extension<AuthUser>,
 ) -> Result<StatusCode, AppError> {
-    wwc_db::clear_player_preds(
-        &pool,
-        PlayerId::from(auth_user.player_id),
-        auth_user.bot_name.as_deref(),
-    )
-    .await?;
+    let tournament = tournament_id(&store, &tournament).await?;
+    // Predictions and jokers of games which have kicked off stay, they can no longer be changed
+    let now = Date::from(chrono::Utc::now().fixed_offset());
+    store
+        .clear_player_preds(
+            tournament,
+            PlayerId::from(auth_user.player_id),
+            auth_user.bot_name.as_deref(),
+            now,
+        )
+        .await?;
+    store
+        .clear_player_playoff_preds(
+            tournament,
+            PlayerId::from(auth_user.player_id),
+            auth_user.bot_name.as_deref(),
+            now,
+        )
+        .await?;
+    store
+        .clear_player_bonus_preds(
+            tournament,
+            PlayerId::from(auth_user.player_id),
+            auth_user.bot_name.as_deref(),
+        )
+        .await?;
+    store
+        .clear_player_outcome_preds(
+            tournament,
+            PlayerId::from(auth_user.player_id),
+            auth_user.bot_name.as_deref(),
+            now,
+        )
+        .await?;
+    let locked = store.get_locked_games(tournament, now).await?;
+    let kept: Vec<GameId> = store
+        .get_jokers(
+            tournament,
+            PlayerId::from(auth_user.player_id),
+            auth_user.bot_name.as_deref(),
+        )
+        .await?
+        .into_iter()
+        .filter(|game_id| locked.contains(game_id))
+        .collect();
+    store
+        .insert_jokers(
+            tournament,
+            PlayerId::from(auth_user.player_id),
+            &kept,
+            auth_user.bot_name.as_deref(),
+        )
+        .await?;
+    if let Some(bot_name) = auth_user.bot_name.as_deref() {
+        store
+            .clear_bot_proba_preds(
+                tournament,
+                PlayerId::from(auth_user.player_id),
+                bot_name,
+                now,
+            )
+            .await?;
+    }
+    cache.invalidate(tournament);
 
     info!(
         "Cleared predictions for player {} (bot: {:?})",
@@ -97,12 +599,13 @@ pub(crate) struct MeResponse {
     bot_name: Option<String>,
 }
 
-pub(crate) async fn get_current_user(
-    State(pool): State<SqlitePool>,
+pub(crate) async fn get_current_user<S: Store>(
+    State(store): State<S>,
     Extension(auth_user): Extension<AuthUser>,
 ) -> Result<Json<MeResponse>, AppError> {
     // Get user from database
-    let user = wwc_db::get_user_by_id(&pool, auth_user.player_id)
+    let user = store
+        .get_user_by_id(auth_user.player_id)
         .await?
         .ok_or_else(|| AppError::Generic("User not found".to_string()))?;
 
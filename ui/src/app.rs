@@ -1,5 +1,8 @@
 use crate::auth::{AuthState, ProtectedRoute, load_auth_from_storage};
-use crate::pages::{login::LoginPage, predictions_view::PredictionsView};
+use crate::pages::{
+    leaderboard_view::LeaderboardView, login::LoginPage, predictions_view::PredictionsView,
+};
+use crate::tournament::{SelectedTournament, load_tournament_from_storage};
 use leptos::prelude::*;
 use leptos_router::{
     StaticSegment,
@@ -12,6 +15,11 @@ pub fn App() -> impl IntoView {
     let auth_state = RwSignal::new(AuthState::Loading);
     provide_context(auth_state);
 
+    // Tournament selection survives reloads, falling back to the default tournament
+    provide_context(SelectedTournament(RwSignal::new(
+        load_tournament_from_storage(),
+    )));
+
     // Check localStorage for existing auth on mount
     Effect::new(move |_| {
         if let Some(stored_auth) = load_auth_from_storage() {
@@ -28,6 +36,7 @@ pub fn App() -> impl IntoView {
         <Router>
             <Routes fallback=|| "Page not found">
                 <Route path=StaticSegment("/login") view=LoginPage/>
+                <Route path=StaticSegment("/leaderboard") view=LeaderboardView/>
                 <Route
                     path=StaticSegment("/app")
                     view=|| {
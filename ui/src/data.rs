@@ -2,8 +2,11 @@ use crate::UiError;
 use gloo_net::http::Request;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use wwc_core::player::{PlayerId, Prediction};
-use wwc_core::playoff::TeamSource;
+use wwc_core::leaderboard::history::LeaderboardHistory;
+use wwc_core::player::{OutcomePrediction, PlayerId, Prediction};
+use wwc_core::playoff::{BracketState, TeamSource};
+use wwc_core::pred_score::scheme::ScoringConfig;
+use wwc_core::validation::PredReport;
 use wwc_core::{
     game::GameId,
     group::{GroupId, Groups},
@@ -11,7 +14,9 @@ use wwc_core::{
     team::Teams,
 };
 
-const SERVER_IP: &str = "http://localhost:8000";
+pub(crate) const SERVER_IP: &str = "http://localhost:8000";
+/// Tournament selected until the user picks another one
+pub(crate) const DEFAULT_TOURNAMENT: &str = "fifa-2018";
 
 #[derive(Serialize)]
 struct LoginRequest {
@@ -77,19 +82,102 @@ pub(crate) async fn login(username: &str, password: &str) -> Result<(String, i32
 }
 
 pub(crate) async fn get_preds(
+    tournament: &str,
     player_id: PlayerId,
     token: &str,
 ) -> Result<Vec<Prediction>, UiError> {
-    let response = Request::get(&format!("{}/{}/{}", SERVER_IP, "get_preds", player_id))
+    let response = Request::get(&format!(
+        "{}/{}/{}/{}",
+        SERVER_IP, "get_preds", tournament, player_id
+    ))
+    .header("Authorization", &format!("Bearer {}", token))
+    .send()
+    .await?;
+    Ok(response.json().await?)
+}
+
+/// Save group predictions, returns the server's per-prediction validation report
+pub(crate) async fn save_preds(
+    tournament: &str,
+    preds: PlayerPredictions,
+    token: &str,
+) -> Result<PredReport, UiError> {
+    let url = format!("{}/{}/{}", SERVER_IP, "save_preds", tournament);
+    let json_body = serde_json::to_string(&preds)?;
+    let response = Request::put(&url)
+        .header("Content-Type", "application/json")
         .header("Authorization", &format!("Bearer {}", token))
+        .body(json_body)?
         .send()
         .await?;
+    if response.ok() {
+        Ok(response.json().await?)
+    } else {
+        let error_response: ErrorResponse = response.json().await?;
+        Err(UiError::Server(error_response.error))
+    }
+}
+
+pub(crate) async fn get_outcome_preds(
+    tournament: &str,
+    player_id: PlayerId,
+    token: &str,
+) -> Result<Vec<OutcomePrediction>, UiError> {
+    let response = Request::get(&format!(
+        "{}/{}/{}/{}",
+        SERVER_IP, "get_outcome_preds", tournament, player_id
+    ))
+    .header("Authorization", &format!("Bearer {}", token))
+    .send()
+    .await?;
     Ok(response.json().await?)
 }
 
-pub(crate) async fn save_preds(preds: PlayerPredictions, token: &str) -> Result<(), UiError> {
-    let url = format!("{}/{}", SERVER_IP, "save_preds");
+/// Save outcome-only (1X2) predictions, rejected by the server unless the tournament is in outcome
+/// mode
+pub(crate) async fn save_outcome_preds(
+    tournament: &str,
+    preds: Vec<OutcomePrediction>,
+    token: &str,
+) -> Result<PredReport<OutcomePrediction>, UiError> {
+    let url = format!("{}/{}/{}", SERVER_IP, "save_outcome_preds", tournament);
     let json_body = serde_json::to_string(&preds)?;
+    let response = Request::put(&url)
+        .header("Content-Type", "application/json")
+        .header("Authorization", &format!("Bearer {}", token))
+        .body(json_body)?
+        .send()
+        .await?;
+    if response.ok() {
+        Ok(response.json().await?)
+    } else {
+        let error_response: ErrorResponse = response.json().await?;
+        Err(UiError::Server(error_response.error))
+    }
+}
+
+pub(crate) async fn get_playoff_preds(
+    tournament: &str,
+    player_id: PlayerId,
+    token: &str,
+) -> Result<BracketState, UiError> {
+    let response = Request::get(&format!(
+        "{}/{}/{}/{}",
+        SERVER_IP, "get_playoff_preds", tournament, player_id
+    ))
+    .header("Authorization", &format!("Bearer {}", token))
+    .send()
+    .await?;
+    Ok(response.json().await?)
+}
+
+pub(crate) async fn save_playoff_preds(
+    tournament: &str,
+    bracket_state: BracketState,
+    token: &str,
+) -> Result<(), UiError> {
+    let url = format!("{}/{}/{}", SERVER_IP, "save_playoff_preds", tournament);
+    let json_body = serde_json::to_string(&bracket_state)?;
     Request::put(&url)
         .header("Content-Type", "application/json")
         .header("Authorization", &format!("Bearer {}", token))
@@ -99,31 +187,90 @@ pub(crate) async fn save_preds(preds: PlayerPredictions, token: &str) -> Result<
     Ok(())
 }
 
-pub(crate) async fn clear_my_preds(token: &str) -> Result<(), UiError> {
-    Request::get(&format!("{}/{}", SERVER_IP, "clear_my_preds"))
+pub(crate) async fn get_jokers(
+    tournament: &str,
+    player_id: PlayerId,
+    token: &str,
+) -> Result<Vec<GameId>, UiError> {
+    let response = Request::get(&format!(
+        "{}/{}/{}/{}",
+        SERVER_IP, "get_jokers", tournament, player_id
+    ))
+    .header("Authorization", &format!("Bearer {}", token))
+    .send()
+    .await?;
+    Ok(response.json().await?)
+}
+
+/// Save the joker selection, rejected by the server if it breaks the tournament's joker rules
+pub(crate) async fn save_jokers(
+    tournament: &str,
+    jokers: Vec<GameId>,
+    token: &str,
+) -> Result<(), UiError> {
+    let url = format!("{}/{}/{}", SERVER_IP, "save_jokers", tournament);
+    let json_body = serde_json::to_string(&jokers)?;
+    let response = Request::put(&url)
+        .header("Content-Type", "application/json")
         .header("Authorization", &format!("Bearer {}", token))
+        .body(json_body)?
         .send()
         .await?;
+    if response.ok() {
+        Ok(())
+    } else {
+        let error_response: ErrorResponse = response.json().await?;
+        Err(UiError::Server(error_response.error))
+    }
+}
+
+pub(crate) async fn clear_my_preds(tournament: &str, token: &str) -> Result<(), UiError> {
+    Request::get(&format!(
+        "{}/{}/{}",
+        SERVER_IP, "clear_my_preds", tournament
+    ))
+    .header("Authorization", &format!("Bearer {}", token))
+    .send()
+    .await?;
     Ok(())
 }
 
-pub(crate) async fn clear_preds(admin_secret: &str) -> Result<(), UiError> {
-    Request::get(&format!("{}/{}", SERVER_IP, "clear_preds"))
+pub(crate) async fn clear_preds(tournament: &str, admin_secret: &str) -> Result<(), UiError> {
+    Request::get(&format!("{}/{}/{}", SERVER_IP, "clear_preds", tournament))
         .header("Authorization", &format!("Bearer {}", admin_secret))
         .send()
         .await?;
     Ok(())
 }
 
-pub(crate) async fn get_teams() -> Result<Teams, UiError> {
-    let response = Request::get(&format!("{}/{}", SERVER_IP, "get_teams"))
+pub(crate) async fn get_scoring_config(tournament: &str) -> Result<ScoringConfig, UiError> {
+    let url = format!("{}/{}/{}", SERVER_IP, "get_scoring_config", tournament);
+    let response = Request::get(&url).send().await?;
+    if response.ok() {
+        Ok(response.json().await?)
+    } else {
+        let error_response: ErrorResponse = response.json().await?;
+        Err(UiError::Server(error_response.error))
+    }
+}
+
+/// Fetch the names of all tournaments in the database
+pub(crate) async fn get_tournaments() -> Result<Vec<String>, UiError> {
+    let response = Request::get(&format!("{}/{}", SERVER_IP, "get_tournaments"))
+        .send()
+        .await?;
+    response.json().await.map_err(Into::into)
+}
+
+pub(crate) async fn get_teams(tournament: &str) -> Result<Teams, UiError> {
+    let response = Request::get(&format!("{}/{}/{}", SERVER_IP, "get_teams", tournament))
         .send()
         .await?;
     Ok(response.json().await?)
 }
 
-pub(crate) async fn get_groups() -> Result<Groups, UiError> {
-    let response = Request::get(&format!("{}/{}", SERVER_IP, "get_groups"))
+pub(crate) async fn get_groups(tournament: &str) -> Result<Groups, UiError> {
+    let response = Request::get(&format!("{}/{}/{}", SERVER_IP, "get_groups", tournament))
         .send()
         .await?;
     response.json().await.map_err(Into::into)
@@ -131,11 +278,12 @@ pub(crate) async fn get_groups() -> Result<Groups, UiError> {
 
 /// Fetches all group games and unplays them to simulate settings at betting time.
 pub(crate) async fn get_groups_played_with_preds(
+    tournament: &str,
     player_id: PlayerId,
     token: &str,
 ) -> Result<Groups, UiError> {
-    let preds = get_preds(player_id, token).await?;
-    let mut groups = get_groups().await?;
+    let preds = get_preds(tournament, player_id, token).await?;
+    let mut groups = get_groups(tournament).await?;
     groups.iter_mut().for_each(|(_, group)| {
         // TODO: How to remove this allocation?
         let tmp = group.clone();
@@ -158,8 +306,8 @@ pub(crate) async fn get_groups_played_with_preds(
 }
 
 /// Fetches all group games and unplays them to simulate settings at betting time.
-pub(crate) async fn get_groups_as_unplayed() -> Result<Groups, UiError> {
-    let mut groups = get_groups().await?;
+pub(crate) async fn get_groups_as_unplayed(tournament: &str) -> Result<Groups, UiError> {
+    let mut groups = get_groups(tournament).await?;
     groups.iter_mut().for_each(|(_, group)| {
         // TODO: How to remove this allocation?
         let tmp = group.clone();
@@ -170,10 +318,40 @@ pub(crate) async fn get_groups_as_unplayed() -> Result<Groups, UiError> {
 }
 
 /// Fetch playoff team sources from server
-pub(crate) async fn get_playoff_team_sources()
--> Result<Vec<(GameId, (TeamSource, TeamSource))>, UiError> {
-    let response = Request::get(&format!("{}/{}", SERVER_IP, "get_playoff_team_sources"))
+pub(crate) async fn get_playoff_team_sources(
+    tournament: &str,
+) -> Result<Vec<(GameId, (TeamSource, TeamSource))>, UiError> {
+    let response = Request::get(&format!(
+        "{}/{}/{}",
+        SERVER_IP, "get_playoff_team_sources", tournament
+    ))
+    .send()
+    .await?;
+    response.json().await.map_err(Into::into)
+}
+
+/// Fetch all display names as (player_id, bot_name, display_name)
+pub(crate) async fn get_display_names() -> Result<Vec<(i32, Option<String>, String)>, UiError> {
+    let response = Request::get(&format!("{}/{}", SERVER_IP, "get_display_names"))
         .send()
         .await?;
     response.json().await.map_err(Into::into)
 }
+
+/// Fetch the standings after each played game of a tournament, with the crowd as a baseline
+pub(crate) async fn get_leaderboard_history(
+    tournament: &str,
+) -> Result<LeaderboardHistory, UiError> {
+    let response = Request::get(&format!(
+        "{}/{}/{}?crowd=modal_score",
+        SERVER_IP, "get_leaderboard_history", tournament
+    ))
+    .send()
+    .await?;
+    if response.ok() {
+        Ok(response.json().await?)
+    } else {
+        let error_response: ErrorResponse = response.json().await?;
+        Err(UiError::Server(error_response.error))
+    }
+}
@@ -1,10 +1,12 @@
 use crate::team::format_team_flag;
 use leptos::ev;
 use leptos::prelude::*;
+use std::collections::{HashMap, HashSet};
 use wwc_core::game::GameId;
 use wwc_core::game::GoalCount;
 use wwc_core::group::GroupId;
-use wwc_core::group::game::{GroupGameScore, PlayedGroupGame, UnplayedGroupGame};
+use wwc_core::group::game::{GroupGameOutcome, GroupGameScore, PlayedGroupGame, UnplayedGroupGame};
+use wwc_core::player::PredictionMode;
 use wwc_core::team::Teams;
 
 #[derive(Debug, Clone, Copy)]
@@ -24,6 +26,74 @@ impl ScoreInput {
     }
 }
 
+/// Games picked as jokers, provided as context by views which support jokers
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct JokerPicks(pub(crate) RwSignal<HashSet<GameId>>);
+
+/// Joker toggle of a game, empty if the view does not support jokers
+#[component]
+fn JokerToggle(game_id: GameId) -> impl IntoView {
+    use_context::<JokerPicks>().map(|JokerPicks(jokers)| {
+        let is_joker = move || jokers.with(|jokers| jokers.contains(&game_id));
+        let toggle = move |_| {
+            jokers.update(|jokers| {
+                if !jokers.remove(&game_id) {
+                    jokers.insert(game_id);
+                }
+            })
+        };
+        view! {
+            <button
+                class="joker-toggle"
+                class:joker-active=is_joker
+                title="Joker: multiplied points"
+                on:click=toggle
+            >
+                "\u{00D7}2"
+            </button>
+        }
+    })
+}
+
+/// Outcome-only (1X2) picks, provided as context by views which support the outcome mode
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct OutcomePicks {
+    pub(crate) mode: RwSignal<PredictionMode>,
+    pub(crate) picks: RwSignal<HashMap<GameId, GroupGameOutcome>>,
+}
+
+/// Home win / draw / away win buttons, clicking the picked outcome again removes the pick
+#[component]
+fn OutcomeInput(
+    game_id: GameId,
+    picks: RwSignal<HashMap<GameId, GroupGameOutcome>>,
+) -> impl IntoView {
+    let button = move |outcome: GroupGameOutcome, label: &'static str| {
+        let is_picked = move || picks.with(|picks| picks.get(&game_id) == Some(&outcome));
+        let pick = move |_| {
+            picks.update(|picks| {
+                if picks.get(&game_id) == Some(&outcome) {
+                    picks.remove(&game_id);
+                } else {
+                    picks.insert(game_id, outcome);
+                }
+            })
+        };
+        view! {
+            <button class="outcome-button" class:outcome-picked=is_picked on:click=pick>
+                {label}
+            </button>
+        }
+    };
+
+    view! {
+        <td class="outcome-input-container">
+            {button(GroupGameOutcome::Win, "1")} {button(GroupGameOutcome::Draw, "X")}
+            {button(GroupGameOutcome::Lose, "2")}
+        </td>
+    }
+}
+
 #[component]
 pub fn PlayedGameView(
     game: PlayedGroupGame,
@@ -52,6 +122,7 @@ pub fn PlayedGameView(
             </td>
             <td>{away_team.fifa_code.to_string()}</td>
             <td><span class={away_flag}></span></td>
+            <td><JokerToggle game_id=game_id/></td>
         </tr>
     }
 }
@@ -106,10 +177,14 @@ pub fn UnplayedGameView(
     let home_flag = format_team_flag(&home_team);
     let away_flag = format_team_flag(&away_team);
 
-    view! {
-        <tr class="played_game">
-            <td>{home_team.fifa_code.to_string()}</td>
-            <td><span class={home_flag}></span></td>
+    // The mode is tracked by the parent view, which re-renders the games when it changes
+    let outcome_picks = use_context::<OutcomePicks>()
+        .filter(|picks| picks.mode.get_untracked() == PredictionMode::Outcome);
+    let prediction_cell = match outcome_picks {
+        Some(OutcomePicks { picks, .. }) => {
+            view! { <OutcomeInput game_id=game_id picks=picks/> }.into_any()
+        }
+        None => view! {
             <td class="score-input-container">
                 <input
                     node_ref=home_input_ref
@@ -129,8 +204,18 @@ pub fn UnplayedGameView(
                     on:keydown=on_away_keydown
                 />
             </td>
+        }
+        .into_any(),
+    };
+
+    view! {
+        <tr class="played_game">
+            <td>{home_team.fifa_code.to_string()}</td>
+            <td><span class={home_flag}></span></td>
+            {prediction_cell}
             <td>{away_team.fifa_code.to_string()}</td>
             <td><span class={away_flag}></span></td>
+            <td><JokerToggle game_id=game_id/></td>
         </tr>
     }
 }
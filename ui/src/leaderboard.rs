@@ -0,0 +1,173 @@
+use leptos::prelude::*;
+use std::collections::HashMap;
+use wwc_core::crowd::CROWD_NAME;
+use wwc_core::leaderboard::Competitor;
+use wwc_core::leaderboard::history::LeaderboardHistory;
+
+const WIDTH: f32 = 800.0;
+const HEIGHT: f32 = 400.0;
+const MARGIN: f32 = 40.0;
+const COLORS: [&str; 8] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#17becf",
+];
+
+/// Quantity plotted against games played
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ChartMetric {
+    Points,
+    Rank,
+}
+
+/// A single competitor's line in the chart
+#[derive(Debug, Clone)]
+struct ChartLine {
+    name: String,
+    color: &'static str,
+    /// (step, value) pairs
+    values: Vec<(usize, f32)>,
+}
+
+fn chart_lines(
+    history: &LeaderboardHistory,
+    names: &HashMap<Competitor, String>,
+    metric: ChartMetric,
+) -> Vec<ChartLine> {
+    let competitors: Vec<&Competitor> = history
+        .steps()
+        .last()
+        .map(|step| step.standings.iter().map(|s| &s.competitor).collect())
+        .unwrap_or_default();
+    competitors
+        .into_iter()
+        .enumerate()
+        .map(|(idx, competitor)| {
+            let values = history
+                .steps()
+                .enumerate()
+                .filter_map(|(step, hist_step)| {
+                    hist_step
+                        .standings
+                        .iter()
+                        .find(|s| &s.competitor == competitor)
+                        .map(|s| match metric {
+                            ChartMetric::Points => (step, f32::from(s.total)),
+                            ChartMetric::Rank => (step, s.rank as f32),
+                        })
+                })
+                .collect();
+            ChartLine {
+                name: display_name(competitor, names),
+                color: COLORS[idx % COLORS.len()],
+                values,
+            }
+        })
+        .collect()
+}
+
+fn display_name(competitor: &Competitor, names: &HashMap<Competitor, String>) -> String {
+    if competitor.is_crowd() {
+        return CROWD_NAME.to_string();
+    }
+    names
+        .get(competitor)
+        .cloned()
+        .unwrap_or_else(|| match &competitor.bot_name {
+            Some(bot_name) => format!("Player {} ({})", competitor.player_id, bot_name),
+            None => format!("Player {}", competitor.player_id),
+        })
+}
+
+/// SVG polyline points, ranks are drawn with rank 1 at the top
+fn polyline(
+    values: &[(usize, f32)],
+    num_steps: usize,
+    max_value: f32,
+    metric: ChartMetric,
+) -> String {
+    let x_scale = (WIDTH - 2.0 * MARGIN) / (num_steps.max(2) - 1) as f32;
+    let y_scale = (HEIGHT - 2.0 * MARGIN) / max_value.max(1.0);
+    values
+        .iter()
+        .map(|(step, value)| {
+            let x = MARGIN + *step as f32 * x_scale;
+            let y = match metric {
+                ChartMetric::Points => HEIGHT - MARGIN - value * y_scale,
+                ChartMetric::Rank => MARGIN + (value - 1.0) * y_scale,
+            };
+            format!("{:.1},{:.1}", x, y)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Line chart of every competitor's points or rank after each played game
+#[component]
+pub fn LeaderboardChart(
+    history: LeaderboardHistory,
+    names: HashMap<Competitor, String>,
+    metric: ChartMetric,
+) -> impl IntoView {
+    let lines = chart_lines(&history, &names, metric);
+    let num_steps = history.len();
+    let max_value = lines
+        .iter()
+        .flat_map(|line| line.values.iter().map(|(_, value)| *value))
+        .fold(0.0, f32::max);
+    let max_value = match metric {
+        ChartMetric::Points => max_value,
+        // Ranks start at 1, so the last rank is max_value - 1 steps below the top
+        ChartMetric::Rank => max_value - 1.0,
+    };
+    let title = match metric {
+        ChartMetric::Points => "Points",
+        ChartMetric::Rank => "Rank",
+    };
+
+    view! {
+        <div class="leaderboard-chart">
+            <h3>{title}</h3>
+            <svg
+                viewBox=format!("0 0 {} {}", WIDTH, HEIGHT)
+                width=WIDTH.to_string()
+                height=HEIGHT.to_string()
+            >
+                <line
+                    class="leaderboard-chart__axis"
+                    x1=MARGIN.to_string()
+                    y1=(HEIGHT - MARGIN).to_string()
+                    x2=(WIDTH - MARGIN).to_string()
+                    y2=(HEIGHT - MARGIN).to_string()
+                />
+                <line
+                    class="leaderboard-chart__axis"
+                    x1=MARGIN.to_string()
+                    y1=MARGIN.to_string()
+                    x2=MARGIN.to_string()
+                    y2=(HEIGHT - MARGIN).to_string()
+                />
+                {lines
+                    .iter()
+                    .map(|line| {
+                        view! {
+                            <polyline
+                                class="leaderboard-chart__line"
+                                stroke=line.color
+                                points=polyline(&line.values, num_steps, max_value, metric)
+                            />
+                        }
+                    })
+                    .collect_view()}
+            </svg>
+            <ul class="leaderboard-chart__legend">
+                {lines
+                    .into_iter()
+                    .map(|line| {
+                        view! {
+                            <li style=format!("color: {}", line.color)>{line.name}</li>
+                        }
+                    })
+                    .collect_view()}
+            </ul>
+        </div>
+    }
+}
@@ -5,11 +5,14 @@ mod auth;
 mod data;
 mod group;
 mod group_game;
+mod leaderboard;
+mod live;
 mod pages;
 mod playoff;
 mod playoff_game;
 mod table;
 mod team;
+mod tournament;
 
 use leptos::prelude::*;
 use thiserror::Error;
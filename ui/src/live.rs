@@ -0,0 +1,86 @@
+//! Live updates pushed by the server, as Server-Sent Events
+use crate::data::SERVER_IP;
+use futures::StreamExt;
+use futures::channel::oneshot;
+use futures::future::{self, Either};
+use gloo_net::eventsource::futures::EventSource;
+use leptos::task::spawn_local;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::console;
+use wwc_core::live::LiveEvent;
+
+/// Delay before a closed connection is opened again
+const RECONNECT_DELAY_MS: i32 = 5000;
+
+/// Subscription to the live events of a tournament, ends when dropped
+pub(crate) struct LiveSubscription {
+    _stop: oneshot::Sender<()>,
+}
+
+/// Call `on_event` for every live event of a tournament
+///
+/// The browser reconnects on its own after a dropped connection and resumes after the last
+/// received event. If the connection is closed instead, e.g. while the server restarts, it is
+/// opened again after a delay and resumes likewise.
+pub(crate) fn subscribe_live(
+    tournament: String,
+    on_event: impl Fn(LiveEvent) + 'static,
+) -> LiveSubscription {
+    let (stop_sender, mut stop) = oneshot::channel::<()>();
+    spawn_local(async move {
+        let mut last_id: Option<String> = None;
+        loop {
+            let url = match &last_id {
+                Some(id) => format!("{}/events/{}?last_event_id={}", SERVER_IP, tournament, id),
+                None => format!("{}/events/{}", SERVER_IP, tournament),
+            };
+            let connection = EventSource::new(&url).and_then(|mut source| {
+                let messages = source.subscribe("message")?;
+                Ok((source, messages))
+            });
+            match connection {
+                // The source is closed when dropped, after the messages end
+                Ok((_source, messages)) => {
+                    let mut messages = messages.take_until(&mut stop);
+                    while let Some(Ok((_, message))) = messages.next().await {
+                        let id = message.last_event_id();
+                        if !id.is_empty() {
+                            last_id = Some(id);
+                        }
+                        let data = message.data().as_string().unwrap_or_default();
+                        match serde_json::from_str::<LiveEvent>(&data) {
+                            Ok(event) => on_event(event),
+                            Err(e) => {
+                                console::error_1(&format!("Invalid live event: {}", e).into())
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    console::error_1(&format!("Error opening live events: {}", e).into());
+                }
+            }
+
+            // Unsubscribed, or else closed by the server
+            if !matches!(stop.try_recv(), Ok(None)) {
+                return;
+            }
+            console::warn_1(&"Live events closed, reconnecting".into());
+            if let Either::Right(_) =
+                future::select(Box::pin(sleep(RECONNECT_DELAY_MS)), &mut stop).await
+            {
+                return;
+            }
+        }
+    });
+    LiveSubscription { _stop: stop_sender }
+}
+
+async fn sleep(millis: i32) {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        if let Some(window) = web_sys::window() {
+            let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, millis);
+        }
+    });
+    let _ = JsFuture::from(promise).await;
+}
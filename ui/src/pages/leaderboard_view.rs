@@ -0,0 +1,104 @@
+use crate::data::{get_display_names, get_leaderboard_history};
+use crate::leaderboard::{ChartMetric, LeaderboardChart};
+use crate::live::subscribe_live;
+use crate::tournament::{SelectedTournament, TournamentPicker};
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use std::collections::HashMap;
+use web_sys::console;
+use wwc_core::leaderboard::Competitor;
+use wwc_core::leaderboard::history::LeaderboardHistory;
+use wwc_core::live::LiveEvent;
+use wwc_core::player::PlayerId;
+
+#[component]
+pub fn LeaderboardView() -> impl IntoView {
+    let (history, set_history) = signal(LeaderboardHistory::default());
+    // Notified to fetch the history again when the scores change
+    let reload = Trigger::new();
+    let (names, set_names) = signal(HashMap::<Competitor, String>::new());
+    let SelectedTournament(tournament) = expect_context::<SelectedTournament>();
+
+    // Fetch leaderboard history on mount, whenever another tournament is selected and on reload
+    Effect::new(move |_| {
+        let tournament = tournament.get();
+        reload.track();
+        spawn_local(async move {
+            console::log_1(&format!("Fetching leaderboard history of {}", tournament).into());
+            match get_leaderboard_history(&tournament).await {
+                Ok(fetched_history) => {
+                    console::log_1(
+                        &format!("Fetched history over {} games", fetched_history.len()).into(),
+                    );
+                    set_history.set(fetched_history);
+                }
+                Err(e) => {
+                    console::error_1(&format!("Error fetching leaderboard history: {}", e).into());
+                }
+            }
+        });
+    });
+
+    // Live updates of the selected tournament, resubscribed whenever another tournament is selected
+    Effect::new(move |_| {
+        let subscription = subscribe_live(tournament.get(), move |event| {
+            if matches!(event, LiveEvent::Leaderboard | LiveEvent::Reset) {
+                reload.notify();
+            }
+        });
+        on_cleanup(move || drop(subscription));
+    });
+
+    // Fetch display names on mount (run once)
+    Effect::new(move |_| {
+        spawn_local(async move {
+            match get_display_names().await {
+                Ok(display_names) => {
+                    let fetched_names = display_names
+                        .into_iter()
+                        .map(|(player_id, bot_name, name)| {
+                            let competitor = Competitor {
+                                player_id: PlayerId::from(player_id),
+                                bot_name,
+                            };
+                            (competitor, name)
+                        })
+                        .collect();
+                    set_names.set(fetched_names);
+                }
+                Err(e) => {
+                    console::error_1(&format!("Error fetching display names: {}", e).into());
+                }
+            }
+        });
+    });
+
+    view! {
+        <div>
+            <header class="header">
+                <h1>"Leaderboard"</h1>
+                <TournamentPicker/>
+            </header>
+            {move || {
+                let current_history = history.get();
+                if current_history.is_empty() {
+                    view! { <p>"No games played yet"</p> }.into_any()
+                } else {
+                    view! {
+                        <LeaderboardChart
+                            history=current_history.clone()
+                            names=names.get()
+                            metric=ChartMetric::Points
+                        />
+                        <LeaderboardChart
+                            history=current_history
+                            names=names.get()
+                            metric=ChartMetric::Rank
+                        />
+                    }
+                        .into_any()
+                }
+            }}
+        </div>
+    }
+}
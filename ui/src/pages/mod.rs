@@ -1,2 +1,3 @@
+pub mod leaderboard_view;
 pub mod login;
 pub mod predictions_view;
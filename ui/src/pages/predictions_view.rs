@@ -1,26 +1,33 @@
 use crate::auth::{AuthState, logout};
 use crate::data::{
-    clear_my_preds, get_groups_played_with_preds, get_me, get_playoff_team_sources, get_teams,
-    save_preds,
+    clear_my_preds, get_groups_played_with_preds, get_jokers, get_me, get_outcome_preds,
+    get_playoff_preds, get_playoff_team_sources, get_preds, get_scoring_config, get_teams,
+    save_jokers, save_outcome_preds, save_playoff_preds, save_preds,
 };
 use crate::group::view_group_play;
-use crate::group_game::ScoreInput;
+use crate::group_game::{JokerPicks, OutcomePicks, ScoreInput};
+use crate::live::subscribe_live;
 use crate::playoff::PlayoffBracketView;
 use crate::playoff_game::PlayoffScoreInput;
+use crate::tournament::{SelectedTournament, TournamentPicker};
 use leptos::prelude::*;
 use leptos::task::spawn_local;
+use std::collections::{HashMap, HashSet};
 use web_sys::console;
 use wwc_core::{
     game::GameId,
     group::{GroupId, Groups, order::fifa_2018_rules},
-    player::{PlayerPredictions, Prediction},
+    live::LiveEvent,
+    player::{OutcomePrediction, PlayerId, PlayerPredictions, Prediction, PredictionMode},
     playoff::{BracketState, BracketStructure, TeamSource},
     team::Teams,
+    validation::{PredReport, PredStatus},
 };
 
 #[component]
 pub fn PredictionsView() -> impl IntoView {
     let auth_state = expect_context::<RwSignal<AuthState>>();
+    let SelectedTournament(tournament) = expect_context::<SelectedTournament>();
 
     // Display name is local to this component - just cosmetic data from server
     let (display_name, set_display_name) = signal(Option::<String>::None);
@@ -36,8 +43,18 @@ pub fn PredictionsView() -> impl IntoView {
     // Reactive signals for model state
     let groups = RwSignal::new(Groups::new());
     let bracket_state = RwSignal::new(BracketState::new());
-    // Teams and team_sources are read-only, we only need to set them once on load,
-    // therefore it is better for reactivity to have separate read/write accessors.
+    // Notified to fetch the saved predictions again, after missed live events
+    let reload = Trigger::new();
+    let jokers = RwSignal::new(HashSet::<GameId>::new());
+    provide_context(JokerPicks(jokers));
+    let outcome_mode = RwSignal::new(PredictionMode::default());
+    let outcome_picks = RwSignal::new(HashMap::new());
+    provide_context(OutcomePicks {
+        mode: outcome_mode,
+        picks: outcome_picks,
+    });
+    // Teams and team_sources are read-only, we only need to set them on load and when switching
+    // tournaments, therefore it is better for reactivity to have separate read/write accessors.
     let (teams, set_teams) = signal(Teams::new());
     let (team_sources, set_team_sources) = signal(Vec::<(GameId, (TeamSource, TeamSource))>::new());
 
@@ -60,11 +77,12 @@ pub fn PredictionsView() -> impl IntoView {
         }
     });
 
-    // Fetch teams on mount (run once)
+    // Fetch teams on mount and whenever another tournament is selected
     Effect::new(move |_| {
+        let tournament = tournament.get();
         spawn_local(async move {
             console::log_1(&"Fetching teams".into());
-            match get_teams().await {
+            match get_teams(&tournament).await {
                 Ok(fetched_teams) => {
                     console::log_1(&format!("Fetched {} teams", fetched_teams.len()).into());
                     set_teams.set(fetched_teams);
@@ -76,13 +94,15 @@ pub fn PredictionsView() -> impl IntoView {
         });
     });
 
-    // Fetch groups on mount (run once)
+    // Fetch groups on mount, whenever another tournament is selected and on reload
     Effect::new(move |_| {
+        let tournament = tournament.get();
+        reload.track();
         if let Some(token) = auth_token.get_untracked() {
             let token_clone = token.clone();
             spawn_local(async move {
                 console::log_1(&"Fetching groups".into());
-                match get_groups_played_with_preds(player_id, &token_clone).await {
+                match get_groups_played_with_preds(&tournament, player_id, &token_clone).await {
                     Ok(fetched_groups) => {
                         console::log_1(&format!("Fetched {} groups", fetched_groups.len()).into());
                         groups.set(fetched_groups);
@@ -95,11 +115,97 @@ pub fn PredictionsView() -> impl IntoView {
         }
     });
 
-    // Fetch playoff team sources on mount (run once)
+    // Fetch saved playoff predictions on mount, whenever another tournament is selected and on
+    // reload
+    Effect::new(move |_| {
+        let tournament = tournament.get();
+        reload.track();
+        if let Some(token) = auth_token.get_untracked() {
+            let token_clone = token.clone();
+            spawn_local(async move {
+                console::log_1(&"Fetching playoff preds".into());
+                match get_playoff_preds(&tournament, player_id, &token_clone).await {
+                    Ok(fetched_state) => {
+                        console::log_1(
+                            &format!("Fetched {} playoff preds", fetched_state.num_played()).into(),
+                        );
+                        bracket_state.set(fetched_state);
+                    }
+                    Err(e) => {
+                        console::error_1(&format!("Error fetching playoff preds: {}", e).into());
+                    }
+                }
+            });
+        }
+    });
+
+    // Fetch saved jokers on mount and whenever another tournament is selected
+    Effect::new(move |_| {
+        let tournament = tournament.get();
+        if let Some(token) = auth_token.get_untracked() {
+            let token_clone = token.clone();
+            spawn_local(async move {
+                match get_jokers(&tournament, player_id, &token_clone).await {
+                    Ok(fetched_jokers) => {
+                        console::log_1(&format!("Fetched {} jokers", fetched_jokers.len()).into());
+                        jokers.set(fetched_jokers.into_iter().collect());
+                    }
+                    Err(e) => {
+                        console::error_1(&format!("Error fetching jokers: {}", e).into());
+                    }
+                }
+            });
+        }
+    });
+
+    // Fetch the prediction mode on mount and whenever another tournament is selected
     Effect::new(move |_| {
+        let tournament = tournament.get();
+        spawn_local(async move {
+            match get_scoring_config(&tournament).await {
+                Ok(config) => {
+                    console::log_1(&format!("Prediction mode: {:?}", config.mode).into());
+                    outcome_mode.set(config.mode);
+                }
+                Err(e) => {
+                    console::error_1(&format!("Error fetching scoring config: {}", e).into());
+                }
+            }
+        });
+    });
+
+    // Fetch saved outcome predictions on mount and whenever another tournament is selected
+    Effect::new(move |_| {
+        let tournament = tournament.get();
+        if let Some(token) = auth_token.get_untracked() {
+            let token_clone = token.clone();
+            spawn_local(async move {
+                match get_outcome_preds(&tournament, player_id, &token_clone).await {
+                    Ok(fetched_preds) => {
+                        console::log_1(
+                            &format!("Fetched {} outcome preds", fetched_preds.len()).into(),
+                        );
+                        outcome_picks.set(
+                            fetched_preds
+                                .into_iter()
+                                .map(|OutcomePrediction(game_id, outcome)| (game_id, outcome))
+                                .collect(),
+                        );
+                    }
+                    Err(e) => {
+                        console::error_1(&format!("Error fetching outcome preds: {}", e).into());
+                    }
+                }
+            });
+        }
+    });
+
+    // Fetch playoff team sources on mount and whenever another tournament is selected
+    Effect::new(move |_| {
+        let tournament = tournament.get();
         spawn_local(async move {
             console::log_1(&"Fetching playoff team sources".into());
-            match get_playoff_team_sources().await {
+            match get_playoff_team_sources(&tournament).await {
                 Ok(sources) => {
                     console::log_1(&format!("Fetched {} playoff games", sources.len()).into());
                     set_team_sources.set(sources);
@@ -111,6 +217,47 @@ pub fn PredictionsView() -> impl IntoView {
         });
     });
 
+    // Live updates of the selected tournament, resubscribed whenever another tournament is selected
+    Effect::new(move |_| {
+        let tournament = tournament.get();
+        let subscription = subscribe_live(tournament.clone(), move |event| {
+            let game_ids = match event {
+                LiveEvent::PredsLocked { game_ids } => {
+                    console::log_1(
+                        &format!("Predictions of {} games locked", game_ids.len()).into(),
+                    );
+                    game_ids
+                }
+                // The game is shown with the saved prediction again, whether its result was
+                // recorded, corrected or cleared
+                LiveEvent::GroupResult { game_id } | LiveEvent::PlayoffResult { game_id } => {
+                    console::log_1(&format!("Result of game {} changed", game_id).into());
+                    vec![game_id]
+                }
+                LiveEvent::Reset => {
+                    reload.notify();
+                    return;
+                }
+                LiveEvent::Leaderboard => return,
+            };
+            if let Some(token) = auth_token.get_untracked() {
+                let tournament = tournament.clone();
+                spawn_local(async move {
+                    reload_saved(
+                        &tournament,
+                        player_id,
+                        &token,
+                        groups,
+                        bracket_state,
+                        game_ids,
+                    )
+                    .await
+                });
+            }
+        });
+        on_cleanup(move || drop(subscription));
+    });
+
     // Action to play a game
     let play_game = move |input: ScoreInput| {
         console::log_1(
@@ -165,6 +312,14 @@ pub fn PredictionsView() -> impl IntoView {
     let save_preds_action = move |_| {
         let current_groups = groups.get();
         let current_bracket_state = bracket_state.get();
+        let current_jokers: Vec<GameId> = jokers.get().into_iter().collect();
+        let current_mode = outcome_mode.get();
+        let current_outcomes: Vec<OutcomePrediction> = outcome_picks
+            .get()
+            .into_iter()
+            .map(|(game_id, outcome)| OutcomePrediction(game_id, outcome))
+            .collect();
+        let tournament = tournament.get_untracked();
         if let Some(token) = auth_token.get() {
             spawn_local(async move {
                 console::log_1(&"Saving preds".into());
@@ -176,23 +331,51 @@ pub fn PredictionsView() -> impl IntoView {
                     .map(|game| Prediction::from(*game))
                     .collect();
 
-                // TODO: Add playoff predictions once Prediction type supports playoff scores
-                // For now, playoff predictions are only stored in UI state
                 console::log_1(
                     &format!(
-                        "Saving {} group predictions (playoff predictions not yet persisted)",
-                        all_predictions.len()
+                        "Saving {} group predictions and {} playoff predictions",
+                        all_predictions.len(),
+                        current_bracket_state.num_played()
                     )
                     .into(),
                 );
 
-                let player_preds = PlayerPredictions::new(player_id, all_predictions);
-                match save_preds(player_preds, &token).await {
+                if current_mode == PredictionMode::Outcome {
+                    match save_outcome_preds(&tournament, current_outcomes, &token).await {
+                        Ok(report) if report.is_clean() => {
+                            console::log_1(&"Outcome preds saved successfully".into());
+                        }
+                        Ok(report) => warn_rejected(&report),
+                        Err(e) => {
+                            console::error_1(&format!("Error saving outcome preds: {}", e).into());
+                        }
+                    }
+                } else {
+                    let player_preds = PlayerPredictions::new(player_id, all_predictions);
+                    match save_preds(&tournament, player_preds, &token).await {
+                        Ok(report) if report.is_clean() => {
+                            console::log_1(&"Preds saved successfully".into());
+                        }
+                        Ok(report) => warn_rejected(&report),
+                        Err(e) => {
+                            console::error_1(&format!("Error saving preds: {}", e).into());
+                        }
+                    }
+                }
+                match save_playoff_preds(&tournament, current_bracket_state, &token).await {
                     Ok(_) => {
-                        console::log_1(&"Preds saved successfully".into());
+                        console::log_1(&"Playoff preds saved successfully".into());
                     }
                     Err(e) => {
-                        console::error_1(&format!("Error saving preds: {}", e).into());
+                        console::error_1(&format!("Error saving playoff preds: {}", e).into());
+                    }
+                }
+                match save_jokers(&tournament, current_jokers, &token).await {
+                    Ok(_) => {
+                        console::log_1(&"Jokers saved successfully".into());
+                    }
+                    Err(e) => {
+                        console::error_1(&format!("Error saving jokers: {}", e).into());
                     }
                 }
             });
@@ -215,9 +398,16 @@ pub fn PredictionsView() -> impl IntoView {
         // Clear playoff predictions
         bracket_state.set(BracketState::new());
 
+        // Clear jokers
+        jokers.set(HashSet::new());
+
+        // Clear outcome predictions
+        outcome_picks.set(HashMap::new());
+
+        let tournament = tournament.get_untracked();
         if let Some(token) = auth_token.get() {
             spawn_local(async move {
-                match clear_my_preds(&token).await {
+                match clear_my_preds(&tournament, &token).await {
                     Ok(_) => {
                         console::log_1(&"Preds cleared successfully".into());
                     }
@@ -248,6 +438,7 @@ pub fn PredictionsView() -> impl IntoView {
         <div>
             <header class="header">
                 <h1>"Wednesday world cup"</h1>
+                <TournamentPicker/>
                 <div class="user-info">
                     {move || {
                         if let Some(name) = display_name.get() {
@@ -263,7 +454,11 @@ pub fn PredictionsView() -> impl IntoView {
             <button on:click=save_preds_action>"Save preds"</button>
             <br/>
             <button on:click=clear_preds_action>"Clear preds"</button>
-            {move || view_group_play(groups.get(), teams.get(), play_game, unplay_game)}
+            {move || {
+                // Unplayed games render score or outcome inputs depending on the mode
+                outcome_mode.track();
+                view_group_play(groups.get(), teams.get(), play_game, unplay_game)
+            }}
             {move || {
                 let current_groups = groups.get();
                 let has_groups = !current_groups.is_empty();
@@ -289,3 +484,65 @@ pub fn PredictionsView() -> impl IntoView {
         </div>
     }
 }
+
+/// Log the predictions which the server did not save
+fn warn_rejected<P: std::fmt::Display>(report: &PredReport<P>) {
+    report
+        .checks
+        .iter()
+        .filter(|check| check.status != PredStatus::Accepted)
+        .for_each(|check| {
+            console::warn_1(&format!("Pred not saved: {} ({:?})", check.pred, check.status).into())
+        });
+}
+
+/// Set the predictions of these games back to the saved ones
+///
+/// Used for games which are locked or have a new result, since changes of their predictions can
+/// no longer be saved.
+async fn reload_saved(
+    tournament: &str,
+    player_id: PlayerId,
+    token: &str,
+    groups: RwSignal<Groups>,
+    bracket_state: RwSignal<BracketState>,
+    game_ids: Vec<GameId>,
+) {
+    let reloaded: HashSet<GameId> = game_ids.into_iter().collect();
+    match get_preds(tournament, player_id, token).await {
+        Ok(saved) => groups.update(|groups| {
+            groups.iter_mut().for_each(|(_, group)| {
+                let edited: Vec<GameId> = group
+                    .played_games()
+                    .map(|game| game.id)
+                    .filter(|game_id| reloaded.contains(game_id))
+                    .collect();
+                edited
+                    .into_iter()
+                    .for_each(|game_id| group.unplay_game(game_id));
+                saved
+                    .iter()
+                    .filter(|Prediction(game_id, _)| reloaded.contains(game_id))
+                    .for_each(|Prediction(game_id, score)| {
+                        if group.unplayed_games().any(|game| game.id == *game_id) {
+                            group.play_game(*game_id, *score);
+                        }
+                    });
+            });
+        }),
+        Err(e) => console::error_1(&format!("Error fetching preds: {}", e).into()),
+    }
+    match get_playoff_preds(tournament, player_id, token).await {
+        Ok(saved) => bracket_state.update(|state| {
+            *state = reloaded.iter().fold(state.clone(), |state, game_id| {
+                match saved.result(*game_id) {
+                    Some(result) => {
+                        state.play_game(*game_id, result.home, result.away, result.score)
+                    }
+                    None => state.unplay_game(*game_id),
+                }
+            });
+        }),
+        Err(e) => console::error_1(&format!("Error fetching playoff preds: {}", e).into()),
+    }
+}
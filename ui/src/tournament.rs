@@ -0,0 +1,69 @@
+use crate::data::{DEFAULT_TOURNAMENT, get_tournaments};
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use web_sys::{console, window};
+
+const STORAGE_KEY: &str = "wwc_tournament";
+
+// Name of the tournament all pages fetch their data for, provided as context by the app
+#[derive(Clone, Copy)]
+pub struct SelectedTournament(pub RwSignal<String>);
+
+// Load the last selected tournament from localStorage
+pub fn load_tournament_from_storage() -> String {
+    window()
+        .and_then(|w| w.local_storage().ok().flatten())
+        .and_then(|storage| storage.get_item(STORAGE_KEY).ok().flatten())
+        .unwrap_or_else(|| DEFAULT_TOURNAMENT.to_string())
+}
+
+// Save the selected tournament to localStorage
+fn save_tournament_to_storage(tournament: &str) {
+    if let Some(storage) = window().and_then(|w| w.local_storage().ok()).flatten() {
+        let _ = storage.set_item(STORAGE_KEY, tournament);
+    }
+}
+
+// Dropdown of all tournaments in the database, switching the selected one
+#[component]
+pub fn TournamentPicker() -> impl IntoView {
+    let SelectedTournament(selected) = expect_context::<SelectedTournament>();
+    let (tournaments, set_tournaments) = signal(Vec::<String>::new());
+
+    // Fetch tournaments on mount (run once)
+    Effect::new(move |_| {
+        spawn_local(async move {
+            match get_tournaments().await {
+                Ok(fetched) => set_tournaments.set(fetched),
+                Err(e) => {
+                    console::error_1(&format!("Error fetching tournaments: {}", e).into());
+                }
+            }
+        });
+    });
+
+    let on_change = move |ev| {
+        let tournament = event_target_value(&ev);
+        save_tournament_to_storage(&tournament);
+        selected.set(tournament);
+    };
+
+    view! {
+        <select class="tournament-picker" on:change=on_change>
+            {move || {
+                tournaments
+                    .get()
+                    .into_iter()
+                    .map(|name| {
+                        let is_selected = name == selected.get();
+                        view! {
+                            <option value=name.clone() selected=is_selected>
+                                {name.clone()}
+                            </option>
+                        }
+                    })
+                    .collect_view()
+            }}
+        </select>
+    }
+}